@@ -8,6 +8,51 @@ mod m20230902_025217_create_permission_user;
 mod m20230902_025247_create_permission_role;
 mod m20230902_025255_create_role_user;
 mod m20230902_025309_create_tokens;
+mod m20261019_000001_add_resource_to_role_user;
+mod m20261019_000002_create_policies;
+mod m20261019_000003_create_organizations;
+mod m20261019_000004_create_organization_user;
+mod m20261019_000005_create_organization_role;
+mod m20261019_000006_create_organization_permission;
+mod m20261019_000007_add_organization_to_tokens;
+mod m20261019_000008_create_groups;
+mod m20261019_000009_create_group_user;
+mod m20261019_000010_create_group_role;
+mod m20261019_000011_create_group_permission;
+mod m20261019_000012_add_window_to_grants;
+mod m20261019_000013_create_role_owner;
+mod m20261019_000014_create_access_requests;
+mod m20261019_000015_create_access_request_histories;
+mod m20261019_000016_create_access_review_campaigns;
+mod m20261019_000017_create_access_review_items;
+mod m20261019_000018_create_role_eligibilities;
+mod m20261019_000019_create_role_elevations;
+mod m20261019_000020_add_impersonator_to_tokens;
+mod m20261019_000021_create_personal_access_tokens;
+mod m20261019_000022_create_service_accounts;
+mod m20261019_000023_create_oauth_clients;
+mod m20261019_000024_create_oauth_authorization_codes;
+mod m20261019_000025_create_oauth_refresh_tokens;
+mod m20261019_000026_add_oauth_client_to_tokens;
+mod m20261019_000027_add_oidc_to_oauth;
+mod m20261019_000028_create_identity_providers;
+mod m20261019_000029_create_external_identities;
+mod m20261019_000030_create_identity_provider_role_mappings;
+mod m20261019_000031_create_federated_logins;
+mod m20261019_000032_create_ldap_users;
+mod m20261019_000033_create_ldap_group_mappings;
+mod m20261019_000034_add_saml_to_identity_providers;
+mod m20261019_000035_create_outbox_events;
+mod m20261019_000036_create_webhooks;
+mod m20261019_000037_create_webhook_deliveries;
+mod m20261019_000038_create_audit_logs;
+mod m20261019_000039_add_chain_to_audit_logs;
+mod m20261019_000040_create_audit_chain;
+mod m20261019_000041_create_audit_checkpoints;
+mod m20261019_000042_create_password_histories;
+mod m20261019_000043_add_password_changed_at_to_users;
+mod m20261019_000044_add_algorithm_to_identity_providers;
+mod m20261019_000045_add_organization_to_personal_access_tokens_and_service_accounts;
 
 pub struct Migrator;
 
@@ -23,6 +68,51 @@ impl MigratorTrait for Migrator {
             Box::new(m20230902_025247_create_permission_role::Migration),
             Box::new(m20230902_025255_create_role_user::Migration),
             Box::new(m20230902_025309_create_tokens::Migration),
+            Box::new(m20261019_000001_add_resource_to_role_user::Migration),
+            Box::new(m20261019_000002_create_policies::Migration),
+            Box::new(m20261019_000003_create_organizations::Migration),
+            Box::new(m20261019_000004_create_organization_user::Migration),
+            Box::new(m20261019_000005_create_organization_role::Migration),
+            Box::new(m20261019_000006_create_organization_permission::Migration),
+            Box::new(m20261019_000007_add_organization_to_tokens::Migration),
+            Box::new(m20261019_000008_create_groups::Migration),
+            Box::new(m20261019_000009_create_group_user::Migration),
+            Box::new(m20261019_000010_create_group_role::Migration),
+            Box::new(m20261019_000011_create_group_permission::Migration),
+            Box::new(m20261019_000012_add_window_to_grants::Migration),
+            Box::new(m20261019_000013_create_role_owner::Migration),
+            Box::new(m20261019_000014_create_access_requests::Migration),
+            Box::new(m20261019_000015_create_access_request_histories::Migration),
+            Box::new(m20261019_000016_create_access_review_campaigns::Migration),
+            Box::new(m20261019_000017_create_access_review_items::Migration),
+            Box::new(m20261019_000018_create_role_eligibilities::Migration),
+            Box::new(m20261019_000019_create_role_elevations::Migration),
+            Box::new(m20261019_000020_add_impersonator_to_tokens::Migration),
+            Box::new(m20261019_000021_create_personal_access_tokens::Migration),
+            Box::new(m20261019_000022_create_service_accounts::Migration),
+            Box::new(m20261019_000023_create_oauth_clients::Migration),
+            Box::new(m20261019_000024_create_oauth_authorization_codes::Migration),
+            Box::new(m20261019_000025_create_oauth_refresh_tokens::Migration),
+            Box::new(m20261019_000026_add_oauth_client_to_tokens::Migration),
+            Box::new(m20261019_000027_add_oidc_to_oauth::Migration),
+            Box::new(m20261019_000028_create_identity_providers::Migration),
+            Box::new(m20261019_000029_create_external_identities::Migration),
+            Box::new(m20261019_000030_create_identity_provider_role_mappings::Migration),
+            Box::new(m20261019_000031_create_federated_logins::Migration),
+            Box::new(m20261019_000032_create_ldap_users::Migration),
+            Box::new(m20261019_000033_create_ldap_group_mappings::Migration),
+            Box::new(m20261019_000034_add_saml_to_identity_providers::Migration),
+            Box::new(m20261019_000035_create_outbox_events::Migration),
+            Box::new(m20261019_000036_create_webhooks::Migration),
+            Box::new(m20261019_000037_create_webhook_deliveries::Migration),
+            Box::new(m20261019_000038_create_audit_logs::Migration),
+            Box::new(m20261019_000039_add_chain_to_audit_logs::Migration),
+            Box::new(m20261019_000040_create_audit_chain::Migration),
+            Box::new(m20261019_000041_create_audit_checkpoints::Migration),
+            Box::new(m20261019_000042_create_password_histories::Migration),
+            Box::new(m20261019_000043_add_password_changed_at_to_users::Migration),
+            Box::new(m20261019_000044_add_algorithm_to_identity_providers::Migration),
+            Box::new(m20261019_000045_add_organization_to_personal_access_tokens_and_service_accounts::Migration),
         ]
     }
 }
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE role_user ADD COLUMN resource_type VARCHAR(255) NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE role_user ADD COLUMN resource_id VARCHAR(255) NULL DEFAULT NULL")
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(RoleUser::Table)
+                    .add_column(
+                        ColumnDef::new(RoleUser::ResourceType)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(RoleUser::ResourceId)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(RoleUser::Table)
+                .name("idx_role_user_resource")
+                .col(RoleUser::ResourceType)
+                .col(RoleUser::ResourceId)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(
+            Index::drop()
+                .table(RoleUser::Table)
+                .name("idx_role_user_resource")
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(RoleUser::Table)
+                .drop_column(RoleUser::ResourceType)
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(RoleUser::Table)
+                .drop_column(RoleUser::ResourceId)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RoleUser {
+    #[sea_orm(iden = "role_user")]
+    Table,
+    ResourceType,
+    ResourceId,
+}
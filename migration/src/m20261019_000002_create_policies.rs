@@ -0,0 +1,149 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230902_024928_create_permissions::Permission, m20230902_025106_create_roles::Role};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS policies (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        name VARCHAR(255) NOT NULL,
+                        effect VARCHAR(8) NOT NULL,
+                        permission_id VARCHAR(36) NOT NULL,
+                        role_id VARCHAR(36) NULL DEFAULT NULL,
+                        resource_type VARCHAR(255) NULL DEFAULT NULL,
+                        conditions TEXT NOT NULL,
+                        dry_run BOOLEAN NOT NULL DEFAULT FALSE,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (permission_id) REFERENCES permissions (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(Policy::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Policy::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(Policy::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Policy::Effect)
+                            .string_len(8)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Policy::PermissionId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Policy::RoleId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(Policy::ResourceType)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(Policy::Conditions)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Policy::DryRun)
+                            .boolean()
+                            .not_null()
+                            .default(false)
+                    )
+                    .col(
+                        ColumnDef::new(Policy::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(Policy::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_policies_permission_id")
+                    .from(Policy::Table, Policy::PermissionId)
+                    .to(Permission::Table, Permission::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_policies_role_id")
+                    .from(Policy::Table, Policy::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(Policy::Table)
+                .name("idx_policies_permission_id")
+                .col(Policy::PermissionId)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(Policy::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Policy {
+    #[sea_orm(iden = "policies")]
+    Table,
+    Id,
+    Name,
+    Effect,
+    PermissionId,
+    RoleId,
+    ResourceType,
+    Conditions,
+    DryRun,
+    CreatedAt,
+    UpdatedAt,
+}
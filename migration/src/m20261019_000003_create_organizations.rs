@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS organizations (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        code VARCHAR(255) NOT NULL UNIQUE,
+                        name VARCHAR(255) NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(Organization::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Organization::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(Organization::Code)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(Organization::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Organization::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(Organization::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(Organization::Table)
+                .name("idx_organizations_code")
+                .col(Organization::Code)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(Organization::Table)
+                .name("idx_organizations_name")
+                .col(Organization::Name)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Organization::Table).take()).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Organization {
+    #[sea_orm(iden = "organizations")]
+    Table,
+    Id,
+    Code,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
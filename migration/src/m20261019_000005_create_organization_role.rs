@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000003_create_organizations::Organization, m20230902_025106_create_roles::Role};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS organization_role (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        organization_id VARCHAR(36) NOT NULL,
+                        role_id VARCHAR(36) NOT NULL,
+                        FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(OrganizationRole::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrganizationRole::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationRole::OrganizationId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationRole::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_organization_role_organization_id")
+                    .from(OrganizationRole::Table, OrganizationRole::OrganizationId)
+                    .to(Organization::Table, Organization::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_organization_role_role_id")
+                    .from(OrganizationRole::Table, OrganizationRole::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(OrganizationRole::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrganizationRole {
+    #[sea_orm(iden = "organization_role")]
+    Table,
+    Id,
+    OrganizationId,
+    RoleId,
+}
@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000003_create_organizations::Organization, m20230902_024928_create_permissions::Permission};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS organization_permission (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        organization_id VARCHAR(36) NOT NULL,
+                        permission_id VARCHAR(36) NOT NULL,
+                        FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE,
+                        FOREIGN KEY (permission_id) REFERENCES permissions (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(OrganizationPermission::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrganizationPermission::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationPermission::OrganizationId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationPermission::PermissionId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_organization_permission_organization_id")
+                    .from(OrganizationPermission::Table, OrganizationPermission::OrganizationId)
+                    .to(Organization::Table, Organization::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_organization_permission_permission_id")
+                    .from(OrganizationPermission::Table, OrganizationPermission::PermissionId)
+                    .to(Permission::Table, Permission::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(OrganizationPermission::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OrganizationPermission {
+    #[sea_orm(iden = "organization_permission")]
+    Table,
+    Id,
+    OrganizationId,
+    PermissionId,
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261019_000003_create_organizations::Organization;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE tokens ADD COLUMN organization_id VARCHAR(36) NULL DEFAULT NULL
+                        REFERENCES organizations (id) ON DELETE SET NULL"
+                )
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(Token::Table)
+                    .add_column(
+                        ColumnDef::new(Token::OrganizationId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_tokens_organization_id")
+                    .from(Token::Table, Token::OrganizationId)
+                    .to(Organization::Table, Organization::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Token::Table)
+                .drop_column(Token::OrganizationId)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Token {
+    #[sea_orm(iden = "tokens")]
+    Table,
+    OrganizationId,
+}
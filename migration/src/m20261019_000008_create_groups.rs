@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS groups (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        code VARCHAR(255) NOT NULL UNIQUE,
+                        name VARCHAR(255) NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(Group::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Group::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(Group::Code)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(Group::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Group::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(Group::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(Group::Table)
+                .name("idx_groups_code")
+                .col(Group::Code)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(Group::Table)
+                .name("idx_groups_name")
+                .col(Group::Name)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Group::Table).take()).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum Group {
+    #[sea_orm(iden = "groups")]
+    Table,
+    Id,
+    Code,
+    Name,
+    CreatedAt,
+    UpdatedAt,
+}
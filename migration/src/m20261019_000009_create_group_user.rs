@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000008_create_groups::Group, m20230902_024725_create_users::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS group_user (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        group_id VARCHAR(36) NOT NULL,
+                        user_id VARCHAR(36) NOT NULL,
+                        FOREIGN KEY (group_id) REFERENCES groups (id) ON DELETE CASCADE,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(GroupUser::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupUser::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(GroupUser::GroupId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(GroupUser::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_group_user_group_id")
+                    .from(GroupUser::Table, GroupUser::GroupId)
+                    .to(Group::Table, Group::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_group_user_user_id")
+                    .from(GroupUser::Table, GroupUser::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(GroupUser::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupUser {
+    #[sea_orm(iden = "group_user")]
+    Table,
+    Id,
+    GroupId,
+    UserId,
+}
@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000008_create_groups::Group, m20230902_025106_create_roles::Role};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS group_role (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        group_id VARCHAR(36) NOT NULL,
+                        role_id VARCHAR(36) NOT NULL,
+                        FOREIGN KEY (group_id) REFERENCES groups (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(GroupRole::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GroupRole::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(GroupRole::GroupId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(GroupRole::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_group_role_group_id")
+                    .from(GroupRole::Table, GroupRole::GroupId)
+                    .to(Group::Table, Group::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_group_role_role_id")
+                    .from(GroupRole::Table, GroupRole::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(GroupRole::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GroupRole {
+    #[sea_orm(iden = "group_role")]
+    Table,
+    Id,
+    GroupId,
+    RoleId,
+}
@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        for table in ["role_user", "permission_user"] {
+            if !is_postgres {
+                for column in ["starts_at", "expires_at"] {
+                    manager.get_connection()
+                        .execute_unprepared(&format!(
+                            "ALTER TABLE {} ADD COLUMN {} TIMESTAMP NULL DEFAULT NULL", table, column,
+                        ))
+                        .await?;
+                }
+            } else {
+                manager.alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .add_column(
+                            ColumnDef::new(Grant::StartsAt)
+                                .timestamp()
+                                .null()
+                                .default(None as Option<String>)
+                        )
+                        .add_column(
+                            ColumnDef::new(Grant::ExpiresAt)
+                                .timestamp()
+                                .null()
+                                .default(None as Option<String>)
+                        )
+                        .to_owned()
+                ).await?;
+            }
+
+            manager.create_index(
+                Index::create()
+                    .table(Alias::new(table))
+                    .name(&format!("idx_{}_expires_at", table))
+                    .col(Grant::ExpiresAt)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for table in ["role_user", "permission_user"] {
+            manager.drop_index(
+                Index::drop()
+                    .table(Alias::new(table))
+                    .name(&format!("idx_{}_expires_at", table))
+                    .to_owned()
+            ).await?;
+
+            for column in [Grant::StartsAt, Grant::ExpiresAt] {
+                manager.alter_table(
+                    Table::alter()
+                        .table(Alias::new(table))
+                        .drop_column(column)
+                        .to_owned()
+                ).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Grant {
+    StartsAt,
+    ExpiresAt,
+}
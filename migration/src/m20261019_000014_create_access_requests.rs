@@ -0,0 +1,168 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230902_024725_create_users::User, m20230902_025106_create_roles::Role, m20230902_024928_create_permissions::Permission};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS access_requests (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        user_id VARCHAR(36) NOT NULL,
+                        role_id VARCHAR(36) NULL DEFAULT NULL,
+                        permission_id VARCHAR(36) NULL DEFAULT NULL,
+                        justification TEXT NOT NULL,
+                        status VARCHAR(16) NOT NULL,
+                        starts_at TIMESTAMP NULL DEFAULT NULL,
+                        expires_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+                        FOREIGN KEY (permission_id) REFERENCES permissions (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AccessRequest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessRequest::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::RoleId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::PermissionId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::Justification)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::Status)
+                            .string_len(16)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::StartsAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::ExpiresAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_requests_user_id")
+                    .from(AccessRequest::Table, AccessRequest::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_requests_role_id")
+                    .from(AccessRequest::Table, AccessRequest::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_requests_permission_id")
+                    .from(AccessRequest::Table, AccessRequest::PermissionId)
+                    .to(Permission::Table, Permission::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AccessRequest::Table)
+                .name("idx_access_requests_user_id")
+                .col(AccessRequest::UserId)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(AccessRequest::Table)
+                .name("idx_access_requests_status")
+                .col(AccessRequest::Status)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(AccessRequest::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccessRequest {
+    #[sea_orm(iden = "access_requests")]
+    Table,
+    Id,
+    UserId,
+    RoleId,
+    PermissionId,
+    Justification,
+    Status,
+    StartsAt,
+    ExpiresAt,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,118 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000014_create_access_requests::AccessRequest, m20230902_024725_create_users::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS access_request_histories (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        access_request_id VARCHAR(36) NOT NULL,
+                        actor_id VARCHAR(36) NOT NULL,
+                        action VARCHAR(16) NOT NULL,
+                        comment TEXT NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (access_request_id) REFERENCES access_requests (id) ON DELETE CASCADE,
+                        FOREIGN KEY (actor_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AccessRequestHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessRequestHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequestHistory::AccessRequestId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequestHistory::ActorId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequestHistory::Action)
+                            .string_len(16)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequestHistory::Comment)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequestHistory::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_request_histories_access_request_id")
+                    .from(AccessRequestHistory::Table, AccessRequestHistory::AccessRequestId)
+                    .to(AccessRequest::Table, AccessRequest::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_request_histories_actor_id")
+                    .from(AccessRequestHistory::Table, AccessRequestHistory::ActorId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AccessRequestHistory::Table)
+                .name("idx_access_request_histories_access_request_id")
+                .col(AccessRequestHistory::AccessRequestId)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(AccessRequestHistory::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccessRequestHistory {
+    #[sea_orm(iden = "access_request_histories")]
+    Table,
+    Id,
+    AccessRequestId,
+    ActorId,
+    Action,
+    Comment,
+    CreatedAt,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230902_024725_create_users::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS access_review_campaigns (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        name VARCHAR(255) NOT NULL,
+                        status VARCHAR(16) NOT NULL,
+                        created_by VARCHAR(36) NOT NULL,
+                        closed_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (created_by) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AccessReviewCampaign::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::Status)
+                            .string_len(16)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::CreatedBy)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::ClosedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewCampaign::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_review_campaigns_created_by")
+                    .from(AccessReviewCampaign::Table, AccessReviewCampaign::CreatedBy)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AccessReviewCampaign::Table)
+                .name("idx_access_review_campaigns_status")
+                .col(AccessReviewCampaign::Status)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(AccessReviewCampaign::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccessReviewCampaign {
+    #[sea_orm(iden = "access_review_campaigns")]
+    Table,
+    Id,
+    Name,
+    Status,
+    CreatedBy,
+    ClosedAt,
+    CreatedAt,
+    UpdatedAt,
+}
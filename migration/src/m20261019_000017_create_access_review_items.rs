@@ -0,0 +1,203 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000016_create_access_review_campaigns::AccessReviewCampaign, m20230902_024725_create_users::User, m20230902_025106_create_roles::Role, m20230902_024928_create_permissions::Permission};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS access_review_items (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        campaign_id VARCHAR(36) NOT NULL,
+                        user_id VARCHAR(36) NOT NULL,
+                        role_id VARCHAR(36) NULL DEFAULT NULL,
+                        permission_id VARCHAR(36) NULL DEFAULT NULL,
+                        assignment_id VARCHAR(36) NOT NULL,
+                        resource_type VARCHAR(255) NULL DEFAULT NULL,
+                        resource_id VARCHAR(255) NULL DEFAULT NULL,
+                        decision VARCHAR(16) NOT NULL,
+                        reviewer_id VARCHAR(36) NULL DEFAULT NULL,
+                        comment TEXT NULL DEFAULT NULL,
+                        decided_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (campaign_id) REFERENCES access_review_campaigns (id) ON DELETE CASCADE,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+                        FOREIGN KEY (permission_id) REFERENCES permissions (id) ON DELETE CASCADE,
+                        FOREIGN KEY (reviewer_id) REFERENCES users (id) ON DELETE SET NULL
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AccessReviewItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessReviewItem::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::CampaignId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::RoleId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::PermissionId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::AssignmentId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::ResourceType)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::ResourceId)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::Decision)
+                            .string_len(16)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::ReviewerId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::Comment)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::DecidedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AccessReviewItem::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_review_items_campaign_id")
+                    .from(AccessReviewItem::Table, AccessReviewItem::CampaignId)
+                    .to(AccessReviewCampaign::Table, AccessReviewCampaign::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_review_items_user_id")
+                    .from(AccessReviewItem::Table, AccessReviewItem::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_review_items_role_id")
+                    .from(AccessReviewItem::Table, AccessReviewItem::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_review_items_permission_id")
+                    .from(AccessReviewItem::Table, AccessReviewItem::PermissionId)
+                    .to(Permission::Table, Permission::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_access_review_items_reviewer_id")
+                    .from(AccessReviewItem::Table, AccessReviewItem::ReviewerId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AccessReviewItem::Table)
+                .name("idx_access_review_items_campaign_id")
+                .col(AccessReviewItem::CampaignId)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(AccessReviewItem::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AccessReviewItem {
+    #[sea_orm(iden = "access_review_items")]
+    Table,
+    Id,
+    CampaignId,
+    UserId,
+    RoleId,
+    PermissionId,
+    AssignmentId,
+    ResourceType,
+    ResourceId,
+    Decision,
+    ReviewerId,
+    Comment,
+    DecidedAt,
+    CreatedAt,
+}
@@ -0,0 +1,145 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230902_025106_create_roles::Role, m20230902_024725_create_users::User, m20261019_000008_create_groups::Group};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS role_eligibilities (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        role_id VARCHAR(36) NOT NULL,
+                        user_id VARCHAR(36) NULL DEFAULT NULL,
+                        group_id VARCHAR(36) NULL DEFAULT NULL,
+                        max_minutes INTEGER NOT NULL,
+                        require_reauthentication BOOLEAN NOT NULL DEFAULT FALSE,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                        FOREIGN KEY (group_id) REFERENCES groups (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(RoleEligibility::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoleEligibility::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::UserId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::GroupId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::MaxMinutes)
+                            .integer()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::RequireReauthentication)
+                            .boolean()
+                            .not_null()
+                            .default(false)
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(RoleEligibility::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_eligibilities_role_id")
+                    .from(RoleEligibility::Table, RoleEligibility::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_eligibilities_user_id")
+                    .from(RoleEligibility::Table, RoleEligibility::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_eligibilities_group_id")
+                    .from(RoleEligibility::Table, RoleEligibility::GroupId)
+                    .to(Group::Table, Group::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(RoleEligibility::Table)
+                .name("idx_role_eligibilities_role_id")
+                .col(RoleEligibility::RoleId)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(RoleEligibility::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum RoleEligibility {
+    #[sea_orm(iden = "role_eligibilities")]
+    Table,
+    Id,
+    RoleId,
+    UserId,
+    GroupId,
+    MaxMinutes,
+    RequireReauthentication,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,176 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230902_024725_create_users::User, m20230902_025106_create_roles::Role, m20261019_000018_create_role_eligibilities::RoleEligibility};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS role_elevations (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        user_id VARCHAR(36) NOT NULL,
+                        role_id VARCHAR(36) NOT NULL,
+                        eligibility_id VARCHAR(36) NULL DEFAULT NULL,
+                        reason TEXT NOT NULL,
+                        starts_at TIMESTAMP NOT NULL,
+                        expires_at TIMESTAMP NOT NULL,
+                        ended_at TIMESTAMP NULL DEFAULT NULL,
+                        ended_by VARCHAR(36) NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE,
+                        FOREIGN KEY (eligibility_id) REFERENCES role_eligibilities (id) ON DELETE SET NULL,
+                        FOREIGN KEY (ended_by) REFERENCES users (id) ON DELETE SET NULL
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(RoleElevation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoleElevation::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::EligibilityId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::Reason)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::StartsAt)
+                            .timestamp()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::ExpiresAt)
+                            .timestamp()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::EndedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::EndedBy)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(RoleElevation::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_elevations_user_id")
+                    .from(RoleElevation::Table, RoleElevation::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_elevations_role_id")
+                    .from(RoleElevation::Table, RoleElevation::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_elevations_eligibility_id")
+                    .from(RoleElevation::Table, RoleElevation::EligibilityId)
+                    .to(RoleEligibility::Table, RoleEligibility::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_role_elevations_ended_by")
+                    .from(RoleElevation::Table, RoleElevation::EndedBy)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(RoleElevation::Table)
+                .name("idx_role_elevations_user_id")
+                .col(RoleElevation::UserId)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(RoleElevation::Table)
+                .name("idx_role_elevations_expires_at")
+                .col(RoleElevation::ExpiresAt)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(RoleElevation::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum RoleElevation {
+    #[sea_orm(iden = "role_elevations")]
+    Table,
+    Id,
+    UserId,
+    RoleId,
+    EligibilityId,
+    Reason,
+    StartsAt,
+    ExpiresAt,
+    EndedAt,
+    EndedBy,
+    CreatedAt,
+}
@@ -0,0 +1,123 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230902_024725_create_users::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS personal_access_tokens (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        user_id VARCHAR(36) NOT NULL,
+                        name VARCHAR(255) NOT NULL,
+                        secret VARCHAR(255) NOT NULL,
+                        scopes TEXT NOT NULL,
+                        expires_at TIMESTAMP NULL DEFAULT NULL,
+                        last_used_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(PersonalAccessToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::Secret)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::Scopes)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::ExpiresAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::LastUsedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(PersonalAccessToken::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_personal_access_tokens_user_id")
+                    .from(PersonalAccessToken::Table, PersonalAccessToken::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(PersonalAccessToken::Table)
+                .name("idx_personal_access_tokens_user_id")
+                .col(PersonalAccessToken::UserId)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(PersonalAccessToken::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum PersonalAccessToken {
+    #[sea_orm(iden = "personal_access_tokens")]
+    Table,
+    Id,
+    UserId,
+    Name,
+    Secret,
+    Scopes,
+    ExpiresAt,
+    LastUsedAt,
+    CreatedAt,
+}
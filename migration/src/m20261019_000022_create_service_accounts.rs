@@ -0,0 +1,125 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230902_024725_create_users::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS service_accounts (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        user_id VARCHAR(36) NOT NULL,
+                        code VARCHAR(255) NOT NULL UNIQUE,
+                        description TEXT NULL DEFAULT NULL,
+                        client_id VARCHAR(255) NOT NULL UNIQUE,
+                        client_secret VARCHAR(255) NOT NULL,
+                        api_key VARCHAR(255) NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(ServiceAccount::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ServiceAccount::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::Code)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::Description)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::ClientId)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::ClientSecret)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::ApiKey)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(ServiceAccount::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_service_accounts_user_id")
+                    .from(ServiceAccount::Table, ServiceAccount::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(ServiceAccount::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ServiceAccount {
+    #[sea_orm(iden = "service_accounts")]
+    Table,
+    Id,
+    UserId,
+    Code,
+    Description,
+    ClientId,
+    ClientSecret,
+    ApiKey,
+    CreatedAt,
+    UpdatedAt,
+}
@@ -0,0 +1,138 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000023_create_oauth_clients::OauthClient, m20230902_024725_create_users::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS oauth_authorization_codes (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        oauth_client_id VARCHAR(36) NOT NULL,
+                        user_id VARCHAR(36) NOT NULL,
+                        redirect_uri TEXT NOT NULL,
+                        scopes TEXT NOT NULL,
+                        code_challenge VARCHAR(255) NOT NULL,
+                        code_challenge_method VARCHAR(10) NOT NULL,
+                        expires_at TIMESTAMP NOT NULL,
+                        used_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (oauth_client_id) REFERENCES oauth_clients (id) ON DELETE CASCADE,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(OauthAuthorizationCode::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::OauthClientId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::RedirectUri)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::Scopes)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::CodeChallenge)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::CodeChallengeMethod)
+                            .string_len(10)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::ExpiresAt)
+                            .timestamp()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::UsedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(OauthAuthorizationCode::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_oauth_authorization_codes_oauth_client_id")
+                    .from(OauthAuthorizationCode::Table, OauthAuthorizationCode::OauthClientId)
+                    .to(OauthClient::Table, OauthClient::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_oauth_authorization_codes_user_id")
+                    .from(OauthAuthorizationCode::Table, OauthAuthorizationCode::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(OauthAuthorizationCode::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum OauthAuthorizationCode {
+    #[sea_orm(iden = "oauth_authorization_codes")]
+    Table,
+    Id,
+    OauthClientId,
+    UserId,
+    RedirectUri,
+    Scopes,
+    CodeChallenge,
+    CodeChallengeMethod,
+    ExpiresAt,
+    UsedAt,
+    CreatedAt,
+}
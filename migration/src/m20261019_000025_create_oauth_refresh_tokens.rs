@@ -0,0 +1,117 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000023_create_oauth_clients::OauthClient, m20230902_024725_create_users::User};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS oauth_refresh_tokens (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        oauth_client_id VARCHAR(36) NOT NULL,
+                        user_id VARCHAR(36) NOT NULL,
+                        scopes TEXT NOT NULL,
+                        expires_at TIMESTAMP NOT NULL,
+                        revoked_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (oauth_client_id) REFERENCES oauth_clients (id) ON DELETE CASCADE,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(OauthRefreshToken::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::OauthClientId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::Scopes)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::ExpiresAt)
+                            .timestamp()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::RevokedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(OauthRefreshToken::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_oauth_refresh_tokens_oauth_client_id")
+                    .from(OauthRefreshToken::Table, OauthRefreshToken::OauthClientId)
+                    .to(OauthClient::Table, OauthClient::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_oauth_refresh_tokens_user_id")
+                    .from(OauthRefreshToken::Table, OauthRefreshToken::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(OauthRefreshToken::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum OauthRefreshToken {
+    #[sea_orm(iden = "oauth_refresh_tokens")]
+    Table,
+    Id,
+    OauthClientId,
+    UserId,
+    Scopes,
+    ExpiresAt,
+    RevokedAt,
+    CreatedAt,
+}
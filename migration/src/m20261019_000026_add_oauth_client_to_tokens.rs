@@ -0,0 +1,74 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261019_000023_create_oauth_clients::OauthClient;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "ALTER TABLE tokens ADD COLUMN oauth_client_id VARCHAR(36) NULL DEFAULT NULL
+                        REFERENCES oauth_clients (id) ON DELETE CASCADE"
+                )
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE tokens ADD COLUMN scopes TEXT NULL DEFAULT NULL")
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(Token::Table)
+                    .add_column(
+                        ColumnDef::new(Token::OauthClientId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(Token::Scopes)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_tokens_oauth_client_id")
+                    .from(Token::Table, Token::OauthClientId)
+                    .to(OauthClient::Table, OauthClient::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Token::Table)
+                .drop_column(Token::OauthClientId)
+                .drop_column(Token::Scopes)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Token {
+    #[sea_orm(iden = "tokens")]
+    Table,
+    OauthClientId,
+    Scopes,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE oauth_clients ADD COLUMN post_logout_redirect_uris TEXT NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE oauth_authorization_codes ADD COLUMN nonce TEXT NULL DEFAULT NULL")
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(OauthClient::Table)
+                    .add_column(
+                        ColumnDef::new(OauthClient::PostLogoutRedirectUris)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+
+            manager.alter_table(
+                Table::alter()
+                    .table(OauthAuthorizationCode::Table)
+                    .add_column(
+                        ColumnDef::new(OauthAuthorizationCode::Nonce)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(OauthClient::Table)
+                .drop_column(OauthClient::PostLogoutRedirectUris)
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(OauthAuthorizationCode::Table)
+                .drop_column(OauthAuthorizationCode::Nonce)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OauthClient {
+    #[sea_orm(iden = "oauth_clients")]
+    Table,
+    PostLogoutRedirectUris,
+}
+
+#[derive(DeriveIden)]
+enum OauthAuthorizationCode {
+    #[sea_orm(iden = "oauth_authorization_codes")]
+    Table,
+    Nonce,
+}
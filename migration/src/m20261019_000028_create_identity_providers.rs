@@ -0,0 +1,127 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS identity_providers (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        code VARCHAR(255) NOT NULL UNIQUE,
+                        name VARCHAR(255) NOT NULL,
+                        issuer VARCHAR(255) NOT NULL,
+                        client_id VARCHAR(255) NOT NULL,
+                        client_secret VARCHAR(255) NULL DEFAULT NULL,
+                        scopes VARCHAR(255) NOT NULL,
+                        jit_provisioning BOOLEAN NOT NULL DEFAULT TRUE,
+                        enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(IdentityProvider::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IdentityProvider::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::Code)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::Issuer)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::ClientId)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::ClientSecret)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::Scopes)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::JitProvisioning)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProvider::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(IdentityProvider::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum IdentityProvider {
+    #[sea_orm(iden = "identity_providers")]
+    Table,
+    Id,
+    Code,
+    Name,
+    Issuer,
+    ClientId,
+    ClientSecret,
+    Scopes,
+    JitProvisioning,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
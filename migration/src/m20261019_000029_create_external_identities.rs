@@ -0,0 +1,135 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230902_024725_create_users::User, m20261019_000028_create_identity_providers::IdentityProvider};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS external_identities (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        user_id VARCHAR(36) NOT NULL,
+                        provider_id VARCHAR(36) NOT NULL,
+                        issuer VARCHAR(255) NOT NULL,
+                        subject VARCHAR(255) NOT NULL,
+                        email VARCHAR(255) NULL DEFAULT NULL,
+                        last_login_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE,
+                        FOREIGN KEY (provider_id) REFERENCES identity_providers (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(ExternalIdentity::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ExternalIdentity::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::ProviderId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::Issuer)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::Subject)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::Email)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::LastLoginAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(ExternalIdentity::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_external_identities_user_id")
+                    .from(ExternalIdentity::Table, ExternalIdentity::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_external_identities_provider_id")
+                    .from(ExternalIdentity::Table, ExternalIdentity::ProviderId)
+                    .to(IdentityProvider::Table, IdentityProvider::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(ExternalIdentity::Table)
+                .name("uniq_external_identities_issuer_subject")
+                .col(ExternalIdentity::Issuer)
+                .col(ExternalIdentity::Subject)
+                .unique()
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(ExternalIdentity::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum ExternalIdentity {
+    #[sea_orm(iden = "external_identities")]
+    Table,
+    Id,
+    UserId,
+    ProviderId,
+    Issuer,
+    Subject,
+    Email,
+    LastLoginAt,
+    CreatedAt,
+}
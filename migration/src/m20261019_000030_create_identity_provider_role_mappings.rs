@@ -0,0 +1,109 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20261019_000028_create_identity_providers::IdentityProvider, m20230902_025106_create_roles::Role};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS identity_provider_role_mappings (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        provider_id VARCHAR(36) NOT NULL,
+                        claim VARCHAR(255) NOT NULL,
+                        value VARCHAR(255) NOT NULL,
+                        role_id VARCHAR(36) NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (provider_id) REFERENCES identity_providers (id) ON DELETE CASCADE,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(IdentityProviderRoleMapping::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(IdentityProviderRoleMapping::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviderRoleMapping::ProviderId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviderRoleMapping::Claim)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviderRoleMapping::Value)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviderRoleMapping::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(IdentityProviderRoleMapping::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_identity_provider_role_mappings_provider_id")
+                    .from(IdentityProviderRoleMapping::Table, IdentityProviderRoleMapping::ProviderId)
+                    .to(IdentityProvider::Table, IdentityProvider::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_identity_provider_role_mappings_role_id")
+                    .from(IdentityProviderRoleMapping::Table, IdentityProviderRoleMapping::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(IdentityProviderRoleMapping::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum IdentityProviderRoleMapping {
+    #[sea_orm(iden = "identity_provider_role_mappings")]
+    Table,
+    Id,
+    ProviderId,
+    Claim,
+    Value,
+    RoleId,
+    CreatedAt,
+}
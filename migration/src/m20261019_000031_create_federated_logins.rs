@@ -0,0 +1,99 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261019_000028_create_identity_providers::IdentityProvider;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS federated_logins (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        provider_id VARCHAR(36) NOT NULL,
+                        nonce VARCHAR(255) NOT NULL,
+                        code_verifier VARCHAR(255) NOT NULL,
+                        expires_at TIMESTAMP NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (provider_id) REFERENCES identity_providers (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(FederatedLogin::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FederatedLogin::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(FederatedLogin::ProviderId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(FederatedLogin::Nonce)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(FederatedLogin::CodeVerifier)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(FederatedLogin::ExpiresAt)
+                            .timestamp()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(FederatedLogin::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_federated_logins_provider_id")
+                    .from(FederatedLogin::Table, FederatedLogin::ProviderId)
+                    .to(IdentityProvider::Table, IdentityProvider::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(FederatedLogin::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum FederatedLogin {
+    #[sea_orm(iden = "federated_logins")]
+    Table,
+    Id,
+    ProviderId,
+    Nonce,
+    CodeVerifier,
+    ExpiresAt,
+    CreatedAt,
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230902_024725_create_users::User;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS ldap_users (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        user_id VARCHAR(36) NOT NULL,
+                        dn TEXT NOT NULL,
+                        synced_at TIMESTAMP NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(LdapUser::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LdapUser::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(LdapUser::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(LdapUser::Dn)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(LdapUser::SyncedAt)
+                            .timestamp()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(LdapUser::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_ldap_users_user_id")
+                    .from(LdapUser::Table, LdapUser::UserId)
+                    .to(User::Table, User::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(LdapUser::Table)
+                .name("uniq_ldap_users_user_id")
+                .col(LdapUser::UserId)
+                .unique()
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(LdapUser::Table)
+                .name("uniq_ldap_users_dn")
+                .col(LdapUser::Dn)
+                .unique()
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(LdapUser::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum LdapUser {
+    #[sea_orm(iden = "ldap_users")]
+    Table,
+    Id,
+    UserId,
+    Dn,
+    SyncedAt,
+    CreatedAt,
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230902_025106_create_roles::Role;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS ldap_group_mappings (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        group_dn TEXT NOT NULL,
+                        role_id VARCHAR(36) NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(LdapGroupMapping::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LdapGroupMapping::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(LdapGroupMapping::GroupDn)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(LdapGroupMapping::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(LdapGroupMapping::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_ldap_group_mappings_role_id")
+                    .from(LdapGroupMapping::Table, LdapGroupMapping::RoleId)
+                    .to(Role::Table, Role::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(LdapGroupMapping::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum LdapGroupMapping {
+    #[sea_orm(iden = "ldap_group_mappings")]
+    Table,
+    Id,
+    GroupDn,
+    RoleId,
+    CreatedAt,
+}
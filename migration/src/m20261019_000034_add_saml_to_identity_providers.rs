@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN protocol VARCHAR(255) NOT NULL DEFAULT 'oidc'")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN sso_url TEXT NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN certificate TEXT NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN email_attribute VARCHAR(255) NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN name_attribute VARCHAR(255) NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN username_attribute VARCHAR(255) NULL DEFAULT NULL")
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(IdentityProvider::Table)
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::Protocol)
+                            .string()
+                            .not_null()
+                            .default("oidc")
+                    )
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::SsoUrl)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::Certificate)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::EmailAttribute)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::NameAttribute)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::UsernameAttribute)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(IdentityProvider::Table)
+                .drop_column(IdentityProvider::Protocol)
+                .drop_column(IdentityProvider::SsoUrl)
+                .drop_column(IdentityProvider::Certificate)
+                .drop_column(IdentityProvider::EmailAttribute)
+                .drop_column(IdentityProvider::NameAttribute)
+                .drop_column(IdentityProvider::UsernameAttribute)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IdentityProvider {
+    #[sea_orm(iden = "identity_providers")]
+    Table,
+    Protocol,
+    SsoUrl,
+    Certificate,
+    EmailAttribute,
+    NameAttribute,
+    UsernameAttribute,
+}
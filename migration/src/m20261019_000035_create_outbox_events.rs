@@ -0,0 +1,114 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS outbox_events (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        name VARCHAR(255) NOT NULL,
+                        version INTEGER NOT NULL DEFAULT 1,
+                        payload TEXT NOT NULL,
+                        attempts INTEGER NOT NULL DEFAULT 0,
+                        last_error TEXT NULL DEFAULT NULL,
+                        published_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(OutboxEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OutboxEvent::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::Version)
+                            .integer()
+                            .not_null()
+                            .default(1)
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::Payload)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0)
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::LastError)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::PublishedAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvent::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(OutboxEvent::Table)
+                .name("idx_outbox_events_published_at_created_at")
+                .col(OutboxEvent::PublishedAt)
+                .col(OutboxEvent::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(OutboxEvent::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum OutboxEvent {
+    #[sea_orm(iden = "outbox_events")]
+    Table,
+    Id,
+    Name,
+    Version,
+    Payload,
+    Attempts,
+    LastError,
+    PublishedAt,
+    CreatedAt,
+}
@@ -0,0 +1,180 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261019_000036_create_webhooks::Webhook;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        webhook_id VARCHAR(36) NOT NULL,
+                        event_id VARCHAR(36) NOT NULL,
+                        event VARCHAR(255) NOT NULL,
+                        payload TEXT NOT NULL,
+                        status VARCHAR(16) NOT NULL,
+                        attempts INTEGER NOT NULL DEFAULT 0,
+                        response_status INTEGER NULL DEFAULT NULL,
+                        response_body TEXT NULL DEFAULT NULL,
+                        last_error TEXT NULL DEFAULT NULL,
+                        next_attempt_at TIMESTAMP NULL DEFAULT NULL,
+                        delivered_at TIMESTAMP NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                        FOREIGN KEY (webhook_id) REFERENCES webhooks (id) ON DELETE CASCADE
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::WebhookId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::EventId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Event)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Payload)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Status)
+                            .string_len(16)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::ResponseStatus)
+                            .integer()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::ResponseBody)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::LastError)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::NextAttemptAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::DeliveredAt)
+                            .timestamp()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_webhook_deliveries_webhook_id")
+                    .from(WebhookDelivery::Table, WebhookDelivery::WebhookId)
+                    .to(Webhook::Table, Webhook::Id)
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(WebhookDelivery::Table)
+                .name("idx_webhook_deliveries_status_next_attempt_at")
+                .col(WebhookDelivery::Status)
+                .col(WebhookDelivery::NextAttemptAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(WebhookDelivery::Table)
+                .name("idx_webhook_deliveries_webhook_id_created_at")
+                .col(WebhookDelivery::WebhookId)
+                .col(WebhookDelivery::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(WebhookDelivery::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum WebhookDelivery {
+    #[sea_orm(iden = "webhook_deliveries")]
+    Table,
+    Id,
+    WebhookId,
+    EventId,
+    Event,
+    Payload,
+    Status,
+    Attempts,
+    ResponseStatus,
+    ResponseBody,
+    LastError,
+    NextAttemptAt,
+    DeliveredAt,
+    CreatedAt,
+    UpdatedAt,
+}
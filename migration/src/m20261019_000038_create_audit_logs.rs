@@ -0,0 +1,222 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS audit_logs (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        actor_id VARCHAR(36) NULL DEFAULT NULL,
+                        impersonator_id VARCHAR(36) NULL DEFAULT NULL,
+                        action VARCHAR(255) NOT NULL,
+                        target_type VARCHAR(64) NULL DEFAULT NULL,
+                        target_id VARCHAR(64) NULL DEFAULT NULL,
+                        old_values TEXT NULL DEFAULT NULL,
+                        new_values TEXT NULL DEFAULT NULL,
+                        ip VARCHAR(64) NULL DEFAULT NULL,
+                        user_agent VARCHAR(512) NULL DEFAULT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditLog::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::ActorId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::ImpersonatorId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::Action)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::TargetType)
+                            .string_len(64)
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::TargetId)
+                            .string_len(64)
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::OldValues)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::NewValues)
+                            .text()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::Ip)
+                            .string_len(64)
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::UserAgent)
+                            .string_len(512)
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .col(
+                        ColumnDef::new(AuditLog::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AuditLog::Table)
+                .name("idx_audit_logs_created_at")
+                .col(AuditLog::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(AuditLog::Table)
+                .name("idx_audit_logs_actor_id_created_at")
+                .col(AuditLog::ActorId)
+                .col(AuditLog::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(AuditLog::Table)
+                .name("idx_audit_logs_target_type_target_id")
+                .col(AuditLog::TargetType)
+                .col(AuditLog::TargetId)
+                .to_owned()
+        ).await?;
+
+        manager.create_index(
+            Index::create()
+                .table(AuditLog::Table)
+                .name("idx_audit_logs_action_created_at")
+                .col(AuditLog::Action)
+                .col(AuditLog::CreatedAt)
+                .to_owned()
+        ).await?;
+
+        // the trail is append only, corrections are recorded as new entries
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TRIGGER IF NOT EXISTS audit_logs_no_update BEFORE UPDATE ON audit_logs
+                    BEGIN
+                        SELECT RAISE(ABORT, 'audit_logs is append only');
+                    END"
+                )
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TRIGGER IF NOT EXISTS audit_logs_no_delete BEFORE DELETE ON audit_logs
+                    BEGIN
+                        SELECT RAISE(ABORT, 'audit_logs is append only');
+                    END"
+                )
+                .await?;
+        } else {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE OR REPLACE FUNCTION audit_logs_append_only() RETURNS trigger AS $$
+                    BEGIN
+                        RAISE EXCEPTION 'audit_logs is append only';
+                    END;
+                    $$ LANGUAGE plpgsql"
+                )
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TRIGGER audit_logs_append_only
+                    BEFORE UPDATE OR DELETE ON audit_logs
+                    FOR EACH ROW EXECUTE FUNCTION audit_logs_append_only()"
+                )
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TRIGGER audit_logs_no_truncate
+                    BEFORE TRUNCATE ON audit_logs
+                    FOR EACH STATEMENT EXECUTE FUNCTION audit_logs_append_only()"
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+        manager.drop_table(
+            Table::drop().table(AuditLog::Table).to_owned()
+        ).await?;
+
+        if url.starts_with("postgres://") {
+            manager.get_connection()
+                .execute_unprepared("DROP FUNCTION IF EXISTS audit_logs_append_only()")
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AuditLog {
+    #[sea_orm(iden = "audit_logs")]
+    Table,
+    Id,
+    ActorId,
+    ImpersonatorId,
+    Action,
+    TargetType,
+    TargetId,
+    OldValues,
+    NewValues,
+    Ip,
+    UserAgent,
+    CreatedAt,
+}
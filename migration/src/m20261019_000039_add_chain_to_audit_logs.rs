@@ -0,0 +1,87 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        // entries written before the chain existed keep null columns
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE audit_logs ADD COLUMN sequence BIGINT NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE audit_logs ADD COLUMN previous_hash VARCHAR(64) NULL DEFAULT NULL")
+                .await?;
+
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE audit_logs ADD COLUMN hash VARCHAR(64) NULL DEFAULT NULL")
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(AuditLog::Table)
+                    .add_column(
+                        ColumnDef::new(AuditLog::Sequence)
+                            .big_integer()
+                            .null()
+                            .default(None as Option<i64>)
+                    )
+                    .add_column(
+                        ColumnDef::new(AuditLog::PreviousHash)
+                            .string_len(64)
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .add_column(
+                        ColumnDef::new(AuditLog::Hash)
+                            .string_len(64)
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AuditLog::Table)
+                .name("idx_audit_logs_sequence")
+                .col(AuditLog::Sequence)
+                .unique()
+                .to_owned()
+        ).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(
+            Index::drop()
+                .table(AuditLog::Table)
+                .name("idx_audit_logs_sequence")
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(AuditLog::Table)
+                .drop_column(AuditLog::Sequence)
+                .drop_column(AuditLog::PreviousHash)
+                .drop_column(AuditLog::Hash)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditLog {
+    #[sea_orm(iden = "audit_logs")]
+    Table,
+    Sequence,
+    PreviousHash,
+    Hash,
+}
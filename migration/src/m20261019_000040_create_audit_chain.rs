@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS audit_chain (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        sequence BIGINT NOT NULL,
+                        hash VARCHAR(64) NOT NULL,
+                        updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AuditChain::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditChain::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AuditChain::Sequence)
+                            .big_integer()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditChain::Hash)
+                            .string_len(64)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditChain::UpdatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        // single head row, appends lock it so concurrent entries can't fork
+        // the chain, the genesis hash is all zeros
+        manager.get_connection()
+            .execute_unprepared(&format!(
+                "INSERT INTO audit_chain (id, sequence, hash) VALUES ('00000000-0000-0000-0000-000000000000', 0, '{}')",
+                "0".repeat(64),
+            ))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(AuditChain::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AuditChain {
+    #[sea_orm(iden = "audit_chain")]
+    Table,
+    Id,
+    Sequence,
+    Hash,
+    UpdatedAt,
+}
@@ -0,0 +1,95 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared(
+                    "CREATE TABLE IF NOT EXISTS audit_checkpoints (
+                        id VARCHAR(36) NOT NULL PRIMARY KEY,
+                        sequence BIGINT NOT NULL,
+                        hash VARCHAR(64) NOT NULL,
+                        key_id VARCHAR(255) NOT NULL,
+                        signature TEXT NOT NULL,
+                        created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+                    )"
+                )
+                .await?;
+        } else {
+            manager.create_table(
+                Table::create()
+                    .table(AuditCheckpoint::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AuditCheckpoint::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT uuid_generate_v4()"),
+                    )
+                    .col(
+                        ColumnDef::new(AuditCheckpoint::Sequence)
+                            .big_integer()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditCheckpoint::Hash)
+                            .string_len(64)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditCheckpoint::KeyId)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditCheckpoint::Signature)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AuditCheckpoint::CreatedAt)
+                            .timestamp()
+                            .not_null()
+                            .extra("DEFAULT NOW()")
+                    )
+                    .to_owned(),
+            ).await?;
+        }
+
+        manager.create_index(
+            Index::create()
+                .table(AuditCheckpoint::Table)
+                .name("idx_audit_checkpoints_sequence")
+                .col(AuditCheckpoint::Sequence)
+                .to_owned()
+        ).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(
+            Table::drop().table(AuditCheckpoint::Table).to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+pub enum AuditCheckpoint {
+    #[sea_orm(iden = "audit_checkpoints")]
+    Table,
+    Id,
+    Sequence,
+    Hash,
+    KeyId,
+    Signature,
+    CreatedAt,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            manager.get_connection()
+                .execute_unprepared("ALTER TABLE identity_providers ADD COLUMN algorithm VARCHAR(255) NULL DEFAULT NULL")
+                .await?;
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(IdentityProvider::Table)
+                    .add_column(
+                        ColumnDef::new(IdentityProvider::Algorithm)
+                            .string()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(IdentityProvider::Table)
+                .drop_column(IdentityProvider::Algorithm)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IdentityProvider {
+    #[sea_orm(iden = "identity_providers")]
+    Table,
+    Algorithm,
+}
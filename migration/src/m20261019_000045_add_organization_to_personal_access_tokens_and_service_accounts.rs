@@ -0,0 +1,100 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20261019_000003_create_organizations::Organization;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let is_postgres = url.starts_with("postgres://");
+
+        if !is_postgres {
+            for table in ["personal_access_tokens", "service_accounts"] {
+                manager.get_connection()
+                    .execute_unprepared(&format!(
+                        "ALTER TABLE {} ADD COLUMN organization_id VARCHAR(36) NULL DEFAULT NULL
+                            REFERENCES organizations (id) ON DELETE SET NULL",
+                        table,
+                    ))
+                    .await?;
+            }
+        } else {
+            manager.alter_table(
+                Table::alter()
+                    .table(PersonalAccessToken::Table)
+                    .add_column(
+                        ColumnDef::new(PersonalAccessToken::OrganizationId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_personal_access_tokens_organization_id")
+                    .from(PersonalAccessToken::Table, PersonalAccessToken::OrganizationId)
+                    .to(Organization::Table, Organization::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned()
+            ).await?;
+
+            manager.alter_table(
+                Table::alter()
+                    .table(ServiceAccount::Table)
+                    .add_column(
+                        ColumnDef::new(ServiceAccount::OrganizationId)
+                            .uuid()
+                            .null()
+                            .default(None as Option<String>)
+                    )
+                    .to_owned()
+            ).await?;
+
+            manager.create_foreign_key(
+                ForeignKey::create()
+                    .name("fk_service_accounts_organization_id")
+                    .from(ServiceAccount::Table, ServiceAccount::OrganizationId)
+                    .to(Organization::Table, Organization::Id)
+                    .on_delete(ForeignKeyAction::SetNull)
+                    .to_owned()
+            ).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(PersonalAccessToken::Table)
+                .drop_column(PersonalAccessToken::OrganizationId)
+                .to_owned()
+        ).await?;
+
+        manager.alter_table(
+            Table::alter()
+                .table(ServiceAccount::Table)
+                .drop_column(ServiceAccount::OrganizationId)
+                .to_owned()
+        ).await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PersonalAccessToken {
+    #[sea_orm(iden = "personal_access_tokens")]
+    Table,
+    OrganizationId,
+}
+
+#[derive(DeriveIden)]
+enum ServiceAccount {
+    #[sea_orm(iden = "service_accounts")]
+    Table,
+    OrganizationId,
+}
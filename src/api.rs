@@ -11,6 +11,16 @@ use crate::requests;
 use crate::requests::user::UserOrderByColumn;
 use crate::requests::permission::PermissionOrderByColumn;
 use crate::requests::role::RoleOrderByColumn;
+use crate::requests::organization::OrganizationOrderByColumn;
+use crate::requests::group::GroupOrderByColumn;
+use crate::requests::policy::PolicyOrderByColumn;
+use crate::requests::access_request::AccessRequestOrderByColumn;
+use crate::requests::access_review::AccessReviewOrderByColumn;
+use crate::requests::service_account::ServiceAccountOrderByColumn;
+use crate::requests::oauth_client::OauthClientOrderByColumn;
+use crate::requests::identity_provider::IdentityProviderOrderByColumn;
+use crate::requests::webhook::{WebhookDeliveryOrderByColumn, WebhookOrderByColumn};
+use crate::requests::audit::{AuditCheckpointOrderByColumn, AuditOrderByColumn};
 use crate::responses;
 
 #[derive(OpenApi)]
@@ -30,11 +40,34 @@ use crate::responses;
         (name = "Master User"),
         (name = "Permission"),
         (name = "Role"),
+        (name = "Organization"),
+        (name = "Group"),
+        (name = "Policy"),
+        (name = "Authorization"),
+        (name = "Access Request"),
+        (name = "Access Review"),
+        (name = "Elevation"),
+        (name = "Personal Access Token"),
+        (name = "Service Account"),
+        (name = "OAuth"),
+        (name = "OAuth Client"),
+        (name = "OpenID Connect"),
+        (name = "Identity Provider"),
+        (name = "LDAP"),
+        (name = "SCIM"),
+        (name = "Webhook"),
+        (name = "Audit"),
     ),
     paths(
         controllers::auth::login,
+        controllers::federation::start,
+        controllers::federation::callback,
+        controllers::saml::metadata,
+        controllers::saml::acs,
         controllers::auth::authenticate,
         controllers::auth::logout,
+        controllers::password::policy,
+        controllers::password::expired,
 
         controllers::user::paginate,
         controllers::user::store,
@@ -44,6 +77,7 @@ use crate::responses;
         controllers::user::delete,
         controllers::user::sync_permissions,
         controllers::user::sync_roles,
+        controllers::user::impersonate,
 
         controllers::permission::paginate,
         controllers::permission::store,
@@ -56,6 +90,128 @@ use crate::responses;
         controllers::role::show,
         controllers::role::update,
         controllers::role::delete,
+        controllers::role::sync_owners,
+
+        controllers::organization::paginate,
+        controllers::organization::store,
+        controllers::organization::show,
+        controllers::organization::update,
+        controllers::organization::delete,
+        controllers::organization::sync_users,
+        controllers::organization::switch,
+
+        controllers::group::paginate,
+        controllers::group::store,
+        controllers::group::show,
+        controllers::group::update,
+        controllers::group::delete,
+        controllers::group::sync_users,
+        controllers::group::sync_roles,
+        controllers::group::sync_permissions,
+
+        controllers::policy::paginate,
+        controllers::policy::store,
+        controllers::policy::show,
+        controllers::policy::update,
+        controllers::policy::delete,
+
+        controllers::authorization::authorize,
+
+        controllers::access_request::paginate,
+        controllers::access_request::store,
+        controllers::access_request::show,
+        controllers::access_request::approve,
+        controllers::access_request::reject,
+        controllers::access_request::cancel,
+
+        controllers::access_review::paginate,
+        controllers::access_review::store,
+        controllers::access_review::show,
+        controllers::access_review::export,
+        controllers::access_review::decide,
+        controllers::access_review::close,
+
+        controllers::elevation::eligibilities,
+        controllers::elevation::store_eligibility,
+        controllers::elevation::delete_eligibility,
+        controllers::elevation::history,
+        controllers::elevation::activate,
+        controllers::elevation::end,
+
+        controllers::personal_access_token::all,
+        controllers::personal_access_token::store,
+        controllers::personal_access_token::delete,
+
+        controllers::service_account::paginate,
+        controllers::service_account::store,
+        controllers::service_account::show,
+        controllers::service_account::update,
+        controllers::service_account::delete,
+        controllers::service_account::rotate_secret,
+        controllers::service_account::rotate_api_key,
+        controllers::service_account::sync_roles,
+        controllers::service_account::sync_permissions,
+
+        controllers::oauth::authorize,
+        controllers::oauth::consent,
+        controllers::oauth::token,
+
+        controllers::oauth_client::paginate,
+        controllers::oauth_client::store,
+        controllers::oauth_client::show,
+        controllers::oauth_client::update,
+        controllers::oauth_client::delete,
+        controllers::oauth_client::rotate_secret,
+
+        controllers::oidc::configuration,
+        controllers::oidc::jwks,
+        controllers::oidc::userinfo,
+        controllers::oidc::logout,
+
+        controllers::identity_provider::paginate,
+        controllers::identity_provider::store,
+        controllers::identity_provider::show,
+        controllers::identity_provider::update,
+        controllers::identity_provider::delete,
+        controllers::identity_provider::mappings,
+        controllers::identity_provider::store_mapping,
+        controllers::identity_provider::delete_mapping,
+        controllers::identity_provider::identities,
+        controllers::identity_provider::link,
+        controllers::identity_provider::unlink,
+
+        controllers::ldap::mappings,
+        controllers::ldap::store_mapping,
+        controllers::ldap::delete_mapping,
+
+        controllers::scim::users,
+        controllers::scim::user,
+        controllers::scim::store_user,
+        controllers::scim::replace_user,
+        controllers::scim::patch_user,
+        controllers::scim::delete_user,
+        controllers::scim::groups,
+        controllers::scim::group,
+        controllers::scim::store_group,
+        controllers::scim::replace_group,
+        controllers::scim::patch_group,
+        controllers::scim::delete_group,
+        controllers::scim::service_provider_config,
+        controllers::scim::schemas,
+        controllers::scim::resource_types,
+
+        controllers::webhook::paginate,
+        controllers::webhook::store,
+        controllers::webhook::show,
+        controllers::webhook::update,
+        controllers::webhook::delete,
+        controllers::webhook::deliveries,
+        controllers::webhook::redeliver,
+
+        controllers::audit::paginate,
+        controllers::audit::verify,
+        controllers::audit::checkpoints,
+        controllers::audit::jwks,
     ),
     components(
         schemas(requests::auth::Login),
@@ -69,20 +225,153 @@ use crate::responses;
         schemas(requests::permission::PermissionOrderByColumn),
         schemas(requests::permission::PermissionStoreRequest),
         schemas(requests::permission::PermissionUpdateRequest),
+        schemas(requests::permission::PermissionAssignment),
         schemas(requests::permission::PermissionBulkRequest),
 
         schemas(requests::role::RoleOrderByColumn),
         schemas(requests::role::RoleStoreRequest),
         schemas(requests::role::RoleUpdateRequest),
+        schemas(requests::role::RoleAssignment),
         schemas(requests::role::RoleBulkRequest),
+        schemas(requests::role::RoleOwnerBulkRequest),
+
+        schemas(requests::organization::OrganizationOrderByColumn),
+        schemas(requests::organization::OrganizationStoreRequest),
+        schemas(requests::organization::OrganizationUpdateRequest),
+        schemas(requests::organization::OrganizationUserBulkRequest),
+
+        schemas(requests::group::GroupOrderByColumn),
+        schemas(requests::group::GroupStoreRequest),
+        schemas(requests::group::GroupUpdateRequest),
+        schemas(requests::group::GroupUserBulkRequest),
+
+        schemas(requests::policy::PolicyOrderByColumn),
+        schemas(requests::policy::PolicyEffect),
+        schemas(requests::policy::PolicyOperator),
+        schemas(requests::policy::PolicyCondition),
+        schemas(requests::policy::PolicyStoreRequest),
+
+        schemas(requests::authorization::AuthorizationSubject),
+        schemas(requests::authorization::AuthorizationResource),
+        schemas(requests::authorization::AuthorizationCheck),
+        schemas(requests::authorization::AuthorizationRequest),
+
+        schemas(requests::access_request::AccessRequestOrderByColumn),
+        schemas(requests::access_request::AccessRequestStoreRequest),
+        schemas(requests::access_request::AccessRequestDecisionRequest),
+
+        schemas(requests::access_review::AccessReviewOrderByColumn),
+        schemas(requests::access_review::AccessReviewStoreRequest),
+        schemas(requests::access_review::AccessReviewDecision),
+        schemas(requests::access_review::AccessReviewItemDecisionRequest),
+
+        schemas(requests::elevation::RoleEligibilityStoreRequest),
+        schemas(requests::elevation::ElevationRequest),
+
+        schemas(requests::personal_access_token::PersonalAccessTokenStoreRequest),
+
+        schemas(requests::service_account::ServiceAccountOrderByColumn),
+        schemas(requests::service_account::ServiceAccountStoreRequest),
+        schemas(requests::service_account::ServiceAccountUpdateRequest),
+
+        schemas(requests::oauth::TokenRequest),
+        schemas(requests::oauth::AuthorizeRequest),
+        schemas(requests::oauth::ConsentRequest),
+
+        schemas(requests::oauth_client::OauthClientOrderByColumn),
+        schemas(requests::oauth_client::OauthClientStoreRequest),
+        schemas(requests::oauth_client::OauthClientUpdateRequest),
+
+        schemas(requests::oidc::LogoutRequest),
+
+        schemas(requests::identity_provider::IdentityProviderOrderByColumn),
+        schemas(requests::identity_provider::IdentityProviderProtocol),
+        schemas(requests::identity_provider::IdentityProviderStoreRequest),
+        schemas(requests::identity_provider::IdentityProviderUpdateRequest),
+        schemas(requests::identity_provider::RoleMappingStoreRequest),
+        schemas(requests::identity_provider::ExternalIdentityLinkRequest),
+        schemas(requests::identity_provider::FederatedCallbackRequest),
+        schemas(requests::identity_provider::SamlResponseRequest),
+
+        schemas(requests::ldap::LdapGroupMappingStoreRequest),
+
+        schemas(requests::scim::ScimListRequest),
+        schemas(requests::scim::ScimName),
+        schemas(requests::scim::ScimEmail),
+        schemas(requests::scim::ScimUserRequest),
+        schemas(requests::scim::ScimMember),
+        schemas(requests::scim::ScimGroupRequest),
+        schemas(requests::scim::ScimPatchOperation),
+        schemas(requests::scim::ScimPatchRequest),
+
+        schemas(requests::webhook::WebhookOrderByColumn),
+        schemas(requests::webhook::WebhookDeliveryOrderByColumn),
+        schemas(requests::webhook::WebhookStoreRequest),
+        schemas(requests::webhook::WebhookUpdateRequest),
+
+        schemas(requests::audit::AuditOrderByColumn),
+        schemas(requests::audit::AuditFilterRequest),
+        schemas(requests::audit::AuditCheckpointOrderByColumn),
+        schemas(requests::password::ExpiredPasswordRequest),
 
         schemas(responses::user::UserOAS),
         schemas(responses::permission::PermissionOAS),
         schemas(responses::role::RoleOAS),
+        schemas(responses::organization::OrganizationOAS),
+        schemas(responses::group::GroupOAS),
+        schemas(responses::policy::PolicyOAS),
+        schemas(responses::policy::ConditionEvaluation),
+        schemas(responses::policy::PolicyEvaluation),
+        schemas(responses::authorization::AuthorizationDecision),
+        schemas(responses::authorization::Authorized),
+        schemas(responses::auth::ScopedRole),
+        schemas(responses::auth::Impersonated),
+        schemas(responses::access_request::AccessRequestOAS),
+        schemas(responses::access_request::AccessRequestHistoryOAS),
+        schemas(responses::access_review::AccessReviewOAS),
+        schemas(responses::access_review::AccessReviewItemOAS),
+        schemas(responses::elevation::RoleEligibilityOAS),
+        schemas(responses::elevation::RoleElevationOAS),
+        schemas(responses::personal_access_token::PersonalAccessTokenOAS),
+        schemas(responses::personal_access_token::PersonalAccessTokenCreated),
+        schemas(responses::service_account::ServiceAccountOAS),
+        schemas(responses::service_account::ServiceAccountCredentials),
+        schemas(responses::oauth::TokenResponse),
+        schemas(responses::oauth_client::OauthClientOAS),
+        schemas(responses::oauth_client::OauthClientCredentials),
+        schemas(responses::identity_provider::IdentityProviderOAS),
+        schemas(responses::identity_provider::RoleMappingOAS),
+        schemas(responses::identity_provider::ExternalIdentityOAS),
+        schemas(responses::ldap::LdapGroupMappingOAS),
+        schemas(responses::scim::ScimMeta),
+        schemas(responses::scim::ScimUserOAS),
+        schemas(responses::scim::ScimGroupOAS),
+        schemas(responses::scim::ScimUserList),
+        schemas(responses::scim::ScimGroupList),
+        schemas(responses::webhook::WebhookOAS),
+        schemas(responses::webhook::WebhookSecret),
+        schemas(responses::webhook_delivery::WebhookDeliveryOAS),
+        schemas(responses::audit::AuditOAS),
+        schemas(responses::password::PasswordPolicyOAS),
+        schemas(responses::audit::AuditBreak),
+        schemas(responses::audit::AuditVerification),
+        schemas(responses::audit_checkpoint::AuditCheckpointOAS),
 
         schemas(PaginationRequest<UserOrderByColumn>),
         schemas(PaginationRequest<PermissionOrderByColumn>),
         schemas(PaginationRequest<RoleOrderByColumn>),
+        schemas(PaginationRequest<OrganizationOrderByColumn>),
+        schemas(PaginationRequest<GroupOrderByColumn>),
+        schemas(PaginationRequest<PolicyOrderByColumn>),
+        schemas(PaginationRequest<AccessRequestOrderByColumn>),
+        schemas(PaginationRequest<AccessReviewOrderByColumn>),
+        schemas(PaginationRequest<ServiceAccountOrderByColumn>),
+        schemas(PaginationRequest<OauthClientOrderByColumn>),
+        schemas(PaginationRequest<IdentityProviderOrderByColumn>),
+        schemas(PaginationRequest<WebhookOrderByColumn>),
+        schemas(PaginationRequest<WebhookDeliveryOrderByColumn>),
+        schemas(PaginationRequest<AuditOrderByColumn>),
+        schemas(PaginationRequest<AuditCheckpointOrderByColumn>),
     ),
 )]
 pub struct Doc;
@@ -0,0 +1,466 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use ldap3::{LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::models::{users, Id};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::backends::{Backend, Failure};
+use crate::{dao, events};
+
+/// Result code of a bind with a wrong password
+const INVALID_CREDENTIALS: u32 = 49;
+
+/// Directory bind, the user entry is looked up with the service account
+/// and the password is checked by binding as that entry
+#[derive(Clone, Debug)]
+pub struct Ldap {
+    url: String,
+    starttls: bool,
+    bind_dn: Option<String>,
+    bind_password: Option<String>,
+    base_dn: String,
+    user_filter: String,
+    username_attribute: String,
+    email_attribute: String,
+    name_attribute: String,
+    group_attribute: String,
+}
+
+fn env(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|value| !value.trim().is_empty())
+}
+
+fn attribute(entry: &SearchEntry, name: &str) -> Vec<String> {
+    entry.attrs.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, values)| values.clone())
+        .unwrap_or_default()
+}
+
+fn first(entry: &SearchEntry, name: &str) -> Option<String> {
+    attribute(entry, name).into_iter()
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Distinguished names compare without case and spacing around separators
+fn normalize(dn: &str) -> String {
+    dn.split(',')
+        .map(|rdn| rdn.split('=').map(str::trim).collect::<Vec<&str>>().join("="))
+        .collect::<Vec<String>>()
+        .join(",")
+        .to_lowercase()
+}
+
+impl Ldap {
+    /// `LDAP_URL` and `LDAP_BASE_DN` are required, the filter gets `{0}`
+    /// replaced with the escaped email or username
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            url: env("LDAP_URL")?,
+            starttls: env("LDAP_STARTTLS").is_some_and(|value| value.eq("true")),
+            bind_dn: env("LDAP_BIND_DN"),
+            bind_password: env("LDAP_BIND_PASSWORD"),
+            base_dn: env("LDAP_BASE_DN")?,
+            user_filter: env("LDAP_USER_FILTER").unwrap_or("(|(uid={0})(mail={0}))".to_string()),
+            username_attribute: env("LDAP_USERNAME_ATTRIBUTE").unwrap_or("uid".to_string()),
+            email_attribute: env("LDAP_EMAIL_ATTRIBUTE").unwrap_or("mail".to_string()),
+            name_attribute: env("LDAP_NAME_ATTRIBUTE").unwrap_or("cn".to_string()),
+            group_attribute: env("LDAP_GROUP_ATTRIBUTE").unwrap_or("memberOf".to_string()),
+        })
+    }
+
+    async fn connect(&self) -> Result<ldap3::Ldap, LdapError> {
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(Duration::from_secs(5))
+            .set_starttls(self.starttls);
+
+        let (conn, ldap) = LdapConnAsync::with_settings(settings, &self.url).await?;
+
+        ldap3::drive!(conn);
+
+        Ok(ldap)
+    }
+
+    /// Bind as the entry the identifier resolves to, then refresh its local
+    /// record and mapped roles
+    async fn verify<D: Directory>(
+        &self,
+        directory: &D,
+        db: &DatabaseConnection,
+        identifier: &str,
+        password: &str,
+    ) -> Result<users::Model, Failure> {
+        // an empty password is an unauthenticated bind, which servers
+        // report as a success
+        if password.is_empty() {
+            return Err(Failure::Rejected)
+        }
+
+        let entry = match directory.search(identifier).await {
+            Err(e) => return Err(unavailable(e)),
+            Ok(None) => return Err(Failure::Unknown),
+            Ok(Some(entry)) => entry,
+        };
+
+        match directory.bind(&entry.dn, password).await {
+            Err(e) => return Err(unavailable(e)),
+            Ok(false) => return Err(Failure::Rejected),
+            Ok(true) => {},
+        }
+
+        let user = self.shadow(db, &entry).await?;
+
+        self.sync(db, &user, &entry).await.map_err(unavailable)?;
+
+        Ok(user)
+    }
+
+    /// Create or refresh the local record standing in for the directory
+    /// entry, its password is random since it never signs in locally
+    async fn shadow(&self, db: &DatabaseConnection, entry: &SearchEntry) -> Result<users::Model, Failure> {
+        let username = first(entry, &self.username_attribute)
+            .map(|username| username.to_lowercase());
+        let email = first(entry, &self.email_attribute)
+            .map(|email| email.to_lowercase());
+
+        let (username, email) = match (username, email) {
+            (Some(username), Some(email)) => (username, email),
+            _ => return Err(Failure::Conflict(format!(
+                "Directory entry has no {} or {}",
+                self.username_attribute,
+                self.email_attribute,
+            ))),
+        };
+
+        let name = first(entry, &self.name_attribute).unwrap_or(username.clone());
+
+        if let Some(shadow) = dao::ldap::find_by_dn(db, normalize(&entry.dn)).await {
+            let mut user = match dao::user::find(db, shadow.user_id.clone()).await {
+                None => return Err(Failure::Unknown),
+                Some(user) if user.deleted_at.is_some() => return Err(Failure::Conflict(
+                    "Account has been deleted, ask an administrator to restore it".to_string()
+                )),
+                Some(user) => user,
+            };
+
+            if !user.email.eq(&email) && dao::user::email_exist_except(db, &user.id, &email).await {
+                log::error!(backends::ldap::shadow, "{} email {} belongs to another user", entry.dn, email);
+            } else {
+                user.email = email;
+            }
+
+            user.name = name;
+            user = dao::user::update(db, &user).await.map_err(unavailable)?;
+            dao::ldap::touch(db, &shadow).await.map_err(unavailable)?;
+
+            return Ok(user)
+        }
+
+        if dao::user::email_exist(db, &email).await {
+            return Err(Failure::Conflict(
+                "Email already belongs to a local account, ask an administrator to remove it".to_string()
+            ))
+        }
+
+        let preferred = username;
+        let mut username = preferred.clone();
+
+        while dao::user::username_exist(db, &username).await {
+            username = format!("{}-{}", preferred, &secret()[..6].to_lowercase());
+        }
+
+        let id = Uuid::new_v4();
+        let user = async {
+            let txn = db.begin().await?;
+            let user = dao::user::store(&txn, users::Model {
+                id: id.into(),
+                name,
+                email,
+                username,
+                email_verified_at: Some(time::now()),
+                password: hash::make(id, secret()).to_string(),
+                profile_photo_id: None,
+                created_at: time::now(),
+                updated_at: time::now(),
+                deleted_at: None,
+            }).await?;
+
+            dao::ldap::link(&txn, &user, normalize(&entry.dn)).await?;
+            events::emit(&txn, events::USER_CREATED, json!({
+                "id": user.id,
+                "name": user.name,
+                "email": user.email,
+                "username": user.username,
+                "backend": "ldap",
+            })).await?;
+            txn.commit().await?;
+
+            Ok::<_, DbErr>(user)
+        };
+
+        user.await.map_err(unavailable)
+    }
+
+    /// Give the user the roles mapped to the groups the entry belongs to
+    /// and take away mapped roles of groups it has left
+    async fn sync(&self, db: &DatabaseConnection, user: &users::Model, entry: &SearchEntry) -> Result<(), DbErr> {
+        let groups = attribute(entry, &self.group_attribute).iter()
+            .map(|group| normalize(group))
+            .collect::<Vec<String>>();
+
+        let mappings = dao::ldap::mappings(db).await?;
+        let managed = mappings.iter()
+            .map(|mapping| mapping.role_id.clone())
+            .collect::<Vec<Id>>();
+        let granted = mappings.iter()
+            .filter(|mapping| groups.contains(&normalize(&mapping.group_dn)))
+            .map(|mapping| mapping.role_id.clone())
+            .collect::<Vec<Id>>();
+
+        dao::ldap::sync_roles(db, user, managed, granted).await
+    }
+}
+
+/// Lookups and binds against the directory, tests stand in a directory
+/// of their own
+#[async_trait(?Send)]
+trait Directory {
+    /// Find the single entry matching the identifier, none when the
+    /// directory doesn't know it or the filter is ambiguous
+    async fn search(&self, identifier: &str) -> Result<Option<SearchEntry>, LdapError>;
+
+    /// Whether the password binds as the entry
+    async fn bind(&self, dn: &str, password: &str) -> Result<bool, LdapError>;
+}
+
+#[async_trait(?Send)]
+impl Directory for Ldap {
+    async fn search(&self, identifier: &str) -> Result<Option<SearchEntry>, LdapError> {
+        let mut ldap = self.connect().await?;
+
+        if let (Some(dn), Some(password)) = (&self.bind_dn, &self.bind_password) {
+            ldap.simple_bind(dn, password).await?.success()?;
+        }
+
+        let filter = self.user_filter.replace("{0}", &ldap3::ldap_escape(identifier));
+        let attributes = vec![
+            self.username_attribute.as_str(),
+            self.email_attribute.as_str(),
+            self.name_attribute.as_str(),
+            self.group_attribute.as_str(),
+        ];
+
+        let (entries, _) = ldap.search(&self.base_dn, Scope::Subtree, &filter, attributes)
+            .await?
+            .success()?;
+
+        ldap.unbind().await?;
+
+        if entries.len() > 1 {
+            log::error!(backends::ldap::search, "{} matches {} entries", filter, entries.len());
+
+            return Ok(None)
+        }
+
+        Ok(entries.into_iter().next().map(SearchEntry::construct))
+    }
+
+    async fn bind(&self, dn: &str, password: &str) -> Result<bool, LdapError> {
+        let mut ldap = self.connect().await?;
+        let result = ldap.simple_bind(dn, password).await?;
+
+        ldap.unbind().await?;
+
+        match result.rc {
+            0 => Ok(true),
+            INVALID_CREDENTIALS => Ok(false),
+            _ => result.success().map(|_| true),
+        }
+    }
+}
+
+fn secret() -> String {
+    base58::to_string(Uuid::new_v4().as_bytes())
+}
+
+fn unavailable<E: ToString>(e: E) -> Failure {
+    Failure::Unavailable(e.to_string())
+}
+
+#[async_trait(?Send)]
+impl Backend for Ldap {
+    fn name(&self) -> &'static str {
+        "ldap"
+    }
+
+    async fn authenticate(
+        &self,
+        db: &DatabaseConnection,
+        identifier: &str,
+        password: &str,
+    ) -> Result<users::Model, Failure> {
+        self.verify(self, db, identifier, password).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter};
+
+    use super::*;
+    use crate::models::{ldap_group_mappings, ldap_users, outbox_events, role_user, webhooks};
+    use crate::testing;
+
+    const ADMINS: &str = "cn=admins,ou=groups,dc=nightmare,dc=local";
+
+    /// Directory held in memory, entries by dn with their password
+    #[derive(Default)]
+    struct MockDirectory {
+        entries: HashMap<String, (String, SearchEntry)>,
+    }
+
+    impl MockDirectory {
+        fn with(mut self, uid: &str, password: &str, groups: &[&str]) -> Self {
+            let dn = format!("uid={},ou=people,dc=nightmare,dc=local", uid);
+            let entry = SearchEntry {
+                dn: dn.clone(),
+                attrs: HashMap::from([
+                    ("uid".to_string(), vec![uid.to_string()]),
+                    ("mail".to_string(), vec![format!("{}@nightmare.local", uid)]),
+                    ("cn".to_string(), vec![uid.to_uppercase()]),
+                    ("memberOf".to_string(), groups.iter().map(|group| group.to_string()).collect()),
+                ]),
+                bin_attrs: HashMap::new(),
+            };
+
+            self.entries.insert(dn, (password.to_string(), entry));
+            self
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Directory for MockDirectory {
+        async fn search(&self, identifier: &str) -> Result<Option<SearchEntry>, LdapError> {
+            Ok(self.entries.values()
+                .map(|(_, entry)| entry)
+                .find(|entry| attribute(entry, "uid").contains(&identifier.to_string()) || attribute(entry, "mail").contains(&identifier.to_string()))
+                .cloned())
+        }
+
+        async fn bind(&self, dn: &str, password: &str) -> Result<bool, LdapError> {
+            Ok(self.entries.get(dn).is_some_and(|(expected, _)| expected.eq(password)))
+        }
+    }
+
+    fn ldap() -> Ldap {
+        Ldap {
+            url: "ldap://mock".to_string(),
+            starttls: false,
+            bind_dn: None,
+            bind_password: None,
+            base_dn: "dc=nightmare,dc=local".to_string(),
+            user_filter: "(|(uid={0})(mail={0}))".to_string(),
+            username_attribute: "uid".to_string(),
+            email_attribute: "mail".to_string(),
+            name_attribute: "cn".to_string(),
+            group_attribute: "memberOf".to_string(),
+        }
+    }
+
+    async fn database() -> DatabaseConnection {
+        testing::database(vec![
+            testing::table(users::Entity),
+            testing::table(ldap_users::Entity),
+            testing::table(ldap_group_mappings::Entity),
+            testing::table(role_user::Entity),
+            testing::table(outbox_events::Entity),
+            testing::table(webhooks::Entity),
+        ]).await
+    }
+
+    async fn roles(db: &DatabaseConnection, user: &users::Model) -> Vec<Id> {
+        role_user::Entity::find()
+            .filter(role_user::Column::UserId.eq(user.id.clone()))
+            .all(db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|role| role.role_id)
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn first_bind_provisions_a_shadow_user() {
+        let db = database().await;
+        let directory = MockDirectory::default().with("alice", "secret", &[]);
+        let user = ldap().verify(&directory, &db, "alice", "secret").await.unwrap();
+
+        assert_eq!(user.username, "alice");
+        assert_eq!(user.email, "alice@nightmare.local");
+        assert!(dao::ldap::find_by_dn(&db, "uid=alice,ou=people,dc=nightmare,dc=local").await.is_some());
+
+        let again = ldap().verify(&directory, &db, "alice@nightmare.local", "secret").await.unwrap();
+
+        assert_eq!(again.id, user.id);
+    }
+
+    #[actix_web::test]
+    async fn wrong_and_empty_passwords_are_rejected() {
+        let db = database().await;
+        let directory = MockDirectory::default().with("alice", "secret", &[]);
+
+        assert!(matches!(ldap().verify(&directory, &db, "alice", "wrong").await, Err(Failure::Rejected)));
+        assert!(matches!(ldap().verify(&directory, &db, "alice", "").await, Err(Failure::Rejected)));
+    }
+
+    #[actix_web::test]
+    async fn entries_missing_from_the_directory_are_unknown() {
+        let db = database().await;
+        let directory = MockDirectory::default().with("alice", "secret", &[]);
+
+        assert!(matches!(ldap().verify(&directory, &db, "bob", "secret").await, Err(Failure::Unknown)));
+    }
+
+    #[actix_web::test]
+    async fn deleted_shadow_users_are_refused() {
+        let db = database().await;
+        let directory = MockDirectory::default().with("alice", "secret", &[]);
+        let mut user = ldap().verify(&directory, &db, "alice", "secret").await.unwrap();
+
+        user.deleted_at = Some(time::now());
+        dao::user::update(&db, &user).await.unwrap();
+
+        assert!(matches!(ldap().verify(&directory, &db, "alice", "secret").await, Err(Failure::Conflict(_))));
+    }
+
+    #[actix_web::test]
+    async fn group_roles_follow_the_directory() {
+        let db = database().await;
+        let role: Id = Uuid::new_v4().into();
+
+        ldap_group_mappings::ActiveModel::from(ldap_group_mappings::Model {
+            id: Uuid::new_v4().into(),
+            group_dn: ADMINS.to_string(),
+            role_id: role.clone(),
+            created_at: time::now(),
+        }).insert(&db).await.unwrap();
+
+        let member = MockDirectory::default().with("alice", "secret", &[ADMINS]);
+        let user = ldap().verify(&member, &db, "alice", "secret").await.unwrap();
+
+        assert_eq!(roles(&db, &user).await, vec![role]);
+
+        let former = MockDirectory::default().with("alice", "secret", &[]);
+
+        ldap().verify(&former, &db, "alice", "secret").await.unwrap();
+
+        assert!(roles(&db, &user).await.is_empty());
+    }
+}
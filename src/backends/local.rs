@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+use nightmare_common::hash;
+use nightmare_common::hash::Hash;
+use nightmare_common::models::users;
+use sea_orm::DatabaseConnection;
+
+use crate::backends::{Backend, Failure};
+use crate::dao;
+
+/// Password hashes stored in the users table
+pub struct Local;
+
+#[async_trait(?Send)]
+impl Backend for Local {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn authenticate(
+        &self,
+        db: &DatabaseConnection,
+        identifier: &str,
+        password: &str,
+    ) -> Result<users::Model, Failure> {
+        // deleted accounts are gone as far as signing in is concerned
+        let user = match dao::user::find_by_email_or_username(db, identifier).await {
+            Some(user) if user.deleted_at.is_none() => user,
+            _ => return Err(Failure::Unknown),
+        };
+
+        let hashed = Hash::from(user.password.clone());
+
+        if !hash::verify(hashed, user.id.clone(), password.to_string()) {
+            return Err(Failure::Rejected)
+        }
+
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nightmare_common::time;
+
+    use super::*;
+    use crate::testing;
+
+    async fn database() -> DatabaseConnection {
+        testing::database(vec![testing::table(users::Entity)]).await
+    }
+
+    #[actix_web::test]
+    async fn accepts_the_password_by_username_or_email() {
+        let db = database().await;
+        let user = dao::user::store(&db, testing::user("alice", "secret")).await.unwrap();
+
+        assert_eq!(Local.authenticate(&db, "alice", "secret").await.unwrap().id, user.id);
+        assert_eq!(Local.authenticate(&db, "alice@nightmare.local", "secret").await.unwrap().id, user.id);
+    }
+
+    #[actix_web::test]
+    async fn rejects_a_wrong_password() {
+        let db = database().await;
+
+        dao::user::store(&db, testing::user("alice", "secret")).await.unwrap();
+
+        assert!(matches!(Local.authenticate(&db, "alice", "wrong").await, Err(Failure::Rejected)));
+    }
+
+    #[actix_web::test]
+    async fn doesnt_know_missing_or_deleted_users() {
+        let db = database().await;
+        let mut user = testing::user("alice", "secret");
+
+        user.deleted_at = Some(time::now());
+        dao::user::store(&db, user).await.unwrap();
+
+        assert!(matches!(Local.authenticate(&db, "bob", "secret").await, Err(Failure::Unknown)));
+        assert!(matches!(Local.authenticate(&db, "alice", "secret").await, Err(Failure::Unknown)));
+    }
+}
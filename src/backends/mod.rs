@@ -0,0 +1,188 @@
+use async_trait::async_trait;
+use nightmare_common::log;
+use nightmare_common::models::users;
+use sea_orm::DatabaseConnection;
+
+pub mod ldap;
+pub mod local;
+
+#[derive(Clone, Debug)]
+pub enum Failure {
+    /// The backend doesn't know the user, the next backend is tried
+    Unknown,
+    /// The user is known but the password doesn't match
+    Rejected,
+    /// The user can't be signed in through this backend
+    Conflict(String),
+    /// The backend couldn't be reached
+    Unavailable(String),
+}
+
+/// Something that can tell whether a password belongs to a user, a
+/// successful check resolves to the local user record
+#[async_trait(?Send)]
+pub trait Backend {
+    fn name(&self) -> &'static str;
+
+    async fn authenticate(
+        &self,
+        db: &DatabaseConnection,
+        identifier: &str,
+        password: &str,
+    ) -> Result<users::Model, Failure>;
+}
+
+/// Backends listed in `AUTH_BACKENDS` in the order they're tried, only
+/// the local password check when it isn't set
+pub fn configured() -> Vec<Box<dyn Backend>> {
+    std::env::var("AUTH_BACKENDS")
+        .unwrap_or("local".to_string())
+        .split(',')
+        .filter_map(|name| match name.trim() {
+            "local" => Some(Box::new(local::Local) as Box<dyn Backend>),
+            "ldap" => match ldap::Ldap::from_env() {
+                None => {
+                    log::error!(backends::configured, "ldap backend needs LDAP_URL and LDAP_BASE_DN");
+
+                    None
+                },
+                Some(ldap) => Some(Box::new(ldap) as Box<dyn Backend>),
+            },
+            "" => None,
+            name => {
+                log::error!(backends::configured, "unknown authentication backend {}", name);
+
+                None
+            },
+        })
+        .collect()
+}
+
+/// Try the configured backends in order, the user and the name of the
+/// backend that accepted the password on success. Unreachable backends are
+/// only reported when no other backend knows the user
+pub async fn authenticate(
+    db: &DatabaseConnection,
+    identifier: &str,
+    password: &str,
+) -> Result<(users::Model, &'static str), Failure> {
+    attempt(configured(), db, identifier, password).await
+}
+
+/// Walk the backends in order, unknown users fall through to the next one
+async fn attempt(
+    backends: Vec<Box<dyn Backend>>,
+    db: &DatabaseConnection,
+    identifier: &str,
+    password: &str,
+) -> Result<(users::Model, &'static str), Failure> {
+    let mut unavailable = None;
+
+    for backend in backends {
+        match backend.authenticate(db, identifier, password).await {
+            Err(Failure::Unknown) => continue,
+            Err(Failure::Unavailable(e)) => {
+                log::error!(backends::authenticate, "{} {}", backend.name(), e);
+
+                unavailable = Some(e);
+            },
+            Err(failure) => return Err(failure),
+            Ok(user) => return Ok((user, backend.name())),
+        }
+    }
+
+    Err(unavailable.map(Failure::Unavailable).unwrap_or(Failure::Unknown))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::testing;
+
+    /// Backend that answers the same way every time and notes it was asked
+    struct Stub {
+        name: &'static str,
+        answer: Result<(), Failure>,
+        asked: Rc<RefCell<Vec<&'static str>>>,
+    }
+
+    #[async_trait(?Send)]
+    impl Backend for Stub {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn authenticate(
+            &self,
+            _: &DatabaseConnection,
+            identifier: &str,
+            _: &str,
+        ) -> Result<users::Model, Failure> {
+            self.asked.borrow_mut().push(self.name);
+            self.answer.clone().map(|_| testing::user(identifier, "secret"))
+        }
+    }
+
+    fn stubs(answers: Vec<(&'static str, Result<(), Failure>)>) -> (Vec<Box<dyn Backend>>, Rc<RefCell<Vec<&'static str>>>) {
+        let asked = Rc::new(RefCell::new(vec![]));
+        let backends = answers.into_iter()
+            .map(|(name, answer)| Box::new(Stub { name, answer, asked: asked.clone() }) as Box<dyn Backend>)
+            .collect();
+
+        (backends, asked)
+    }
+
+    #[actix_web::test]
+    async fn unknown_users_fall_through_to_the_next_backend() {
+        let (backends, asked) = stubs(vec![("ldap", Err(Failure::Unknown)), ("local", Ok(()))]);
+        let (user, method) = attempt(backends, &DatabaseConnection::Disconnected, "alice", "secret").await.unwrap();
+
+        assert_eq!(user.username, "alice");
+        assert_eq!(method, "local");
+        assert_eq!(*asked.borrow(), vec!["ldap", "local"]);
+    }
+
+    #[actix_web::test]
+    async fn rejection_stops_at_the_backend_that_knows_the_user() {
+        let (backends, asked) = stubs(vec![("local", Err(Failure::Rejected)), ("ldap", Ok(()))]);
+        let result = attempt(backends, &DatabaseConnection::Disconnected, "alice", "wrong").await;
+
+        assert!(matches!(result, Err(Failure::Rejected)));
+        assert_eq!(*asked.borrow(), vec!["local"]);
+    }
+
+    #[actix_web::test]
+    async fn conflict_stops_the_walk() {
+        let (backends, asked) = stubs(vec![("ldap", Err(Failure::Conflict("taken".to_string()))), ("local", Ok(()))]);
+        let result = attempt(backends, &DatabaseConnection::Disconnected, "alice", "secret").await;
+
+        assert!(matches!(result, Err(Failure::Conflict(message)) if message.eq("taken")));
+        assert_eq!(*asked.borrow(), vec!["ldap"]);
+    }
+
+    #[actix_web::test]
+    async fn unavailable_backends_are_skipped() {
+        let (backends, _) = stubs(vec![("ldap", Err(Failure::Unavailable("down".to_string()))), ("local", Ok(()))]);
+        let (_, method) = attempt(backends, &DatabaseConnection::Disconnected, "alice", "secret").await.unwrap();
+
+        assert_eq!(method, "local");
+    }
+
+    #[actix_web::test]
+    async fn unavailable_is_reported_when_nobody_knows_the_user() {
+        let (backends, _) = stubs(vec![("ldap", Err(Failure::Unavailable("down".to_string()))), ("local", Err(Failure::Unknown))]);
+        let result = attempt(backends, &DatabaseConnection::Disconnected, "alice", "secret").await;
+
+        assert!(matches!(result, Err(Failure::Unavailable(e)) if e.eq("down")));
+    }
+
+    #[actix_web::test]
+    async fn no_backends_know_nobody() {
+        let result = attempt(vec![], &DatabaseConnection::Disconnected, "alice", "secret").await;
+
+        assert!(matches!(result, Err(Failure::Unknown)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Breached password filter for `PASSWORD_BREACHED_FILTER`
+//!
+//! ```sh
+//! cargo run --release --bin breach -- build <dump> <filter> [--false-positive 0.001] [--min-count 1]
+//! cargo run --release --bin breach -- check <filter> <password>
+//! ```
+//!
+//! The dump is a file of `SHA1:COUNT` lines or a directory of range files
+//! named by their 5 character prefix holding `SUFFIX:COUNT` lines, both as
+//! downloaded from Have I Been Pwned.
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use openssl::sha::sha1;
+
+#[path = "../services/bloom.rs"]
+mod bloom;
+
+use bloom::Bloom;
+
+const USAGE: &str = "usage: breach build <dump> <filter> [--false-positive 0.001] [--min-count 1]
+       breach check <filter> <password>";
+
+/// Sha1 digest of a 40 character hex string
+fn digest(hex: &str) -> Option<[u8; 20]> {
+    if hex.len() != 40 || !hex.is_ascii() {
+        return None
+    }
+
+    let mut digest = [0u8; 20];
+
+    for (i, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+
+    Some(digest)
+}
+
+/// Files of the dump with the prefix their lines leave out
+fn files(dump: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    if !dump.is_dir() {
+        return Ok(vec![(String::new(), dump.to_path_buf())])
+    }
+
+    let mut files = fs::read_dir(dump)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let prefix = path.file_stem()?.to_str()?.to_uppercase();
+
+            (prefix.len() == 5).then_some((prefix, path))
+        })
+        .collect::<Vec<(String, PathBuf)>>();
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Call back with every hash of the dump seen at least `min_count` times,
+/// lines without a count are always taken
+fn each<F: FnMut([u8; 20])>(dump: &Path, min_count: u64, mut callback: F) -> io::Result<u64> {
+    let mut skipped = 0;
+
+    for (prefix, file) in files(dump)? {
+        for line in BufReader::new(File::open(file)?).lines() {
+            let line = line?;
+            let (hash, count) = line.split_once(':').unwrap_or((line.as_str(), ""));
+            let count = count.trim().parse::<u64>().unwrap_or(u64::MAX);
+
+            match digest(&format!("{}{}", prefix, hash.trim())) {
+                Some(digest) if count >= min_count => callback(digest),
+                _ => skipped += 1,
+            }
+        }
+    }
+
+    Ok(skipped)
+}
+
+fn option<T: std::str::FromStr>(args: &[String], name: &str, default: T) -> Result<T, String> {
+    match args.iter().position(|arg| arg.eq(name)) {
+        None => Ok(default),
+        Some(i) => args.get(i + 1)
+            .and_then(|value| value.parse().ok())
+            .ok_or(format!("{} needs a number", name)),
+    }
+}
+
+fn build(args: &[String]) -> Result<(), String> {
+    let (dump, filter) = match args {
+        [dump, filter, ..] => (Path::new(dump), Path::new(filter)),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let false_positive = option(args, "--false-positive", 0.001f64)?;
+    let min_count = option(args, "--min-count", 1u64)?;
+
+    if !(false_positive > 0.0 && false_positive < 1.0) {
+        return Err("--false-positive must be between 0 and 1".to_string())
+    }
+
+    // the filter is sized from a first pass so it is read twice
+    let mut items = 0u64;
+
+    each(dump, min_count, |_| items += 1).map_err(|e| e.to_string())?;
+
+    let mut bloom = Bloom::new(items, false_positive);
+    let skipped = each(dump, min_count, |digest| bloom.insert(&digest)).map_err(|e| e.to_string())?;
+
+    bloom.write(filter).map_err(|e| e.to_string())?;
+
+    eprintln!(
+        "{} hashes, {} skipped, {} bits, {} hashes per entry, written to {}",
+        items, skipped, bloom.bits(), bloom.hashes(), filter.display(),
+    );
+
+    Ok(())
+}
+
+fn check(args: &[String]) -> Result<(), String> {
+    let (filter, password) = match args {
+        [filter, password, ..] => (filter, password),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let bloom = Bloom::read(filter).map_err(|e| e.to_string())?;
+
+    if bloom.contains(&sha1(password.as_bytes())) {
+        println!("breached");
+    } else {
+        println!("not found");
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let result = match args.first().map(String::as_str) {
+        Some("build") => build(&args[1..]),
+        Some("check") => check(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+
+    match result {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+
+            ExitCode::FAILURE
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn hex(password: &str) -> String {
+        sha1(password.as_bytes()).iter().map(|byte| format!("{:02X}", byte)).collect()
+    }
+
+    fn args(values: &[&Path]) -> Vec<String> {
+        values.iter().map(|value| value.to_str().unwrap().to_string()).collect()
+    }
+
+    #[test]
+    fn digest_reads_hex_hashes_only() {
+        assert_eq!(digest(&hex("password")), Some(sha1(b"password")));
+        assert_eq!(digest(&hex("password").to_lowercase()), Some(sha1(b"password")));
+        assert_eq!(digest(&hex("password")[..39]), None);
+        assert_eq!(digest(&format!("{}Z", &hex("password")[..39])), None);
+    }
+
+    #[test]
+    fn options_fall_back_to_their_default() {
+        let args = ["--min-count".to_string(), "10".to_string(), "--false-positive".to_string()];
+
+        assert_eq!(option(&args, "--min-count", 1u64), Ok(10));
+        assert_eq!(option(&args, "--other", 0.5f64), Ok(0.5));
+        assert_eq!(option(&args, "--false-positive", 0.001f64), Err("--false-positive needs a number".to_string()));
+    }
+
+    #[test]
+    fn dumps_build_filters_that_hold_their_hashes() {
+        let directory = std::env::temp_dir();
+        let dump = directory.join(format!("{}.txt", Uuid::new_v4()));
+        let filter = directory.join(format!("{}.bloom", Uuid::new_v4()));
+
+        fs::write(&dump, format!("{}:3\n{}:1\nnot a hash:9\n", hex("password"), hex("letmein"))).unwrap();
+
+        let built = build(&[args(&[&dump, &filter]), vec!["--min-count".to_string(), "2".to_string()]].concat());
+        let bloom = Bloom::read(&filter);
+
+        fs::remove_file(&dump).unwrap();
+        fs::remove_file(&filter).unwrap();
+
+        let bloom = bloom.unwrap();
+
+        assert_eq!(built, Ok(()));
+        assert!(bloom.contains(&sha1(b"password")));
+    }
+
+    #[test]
+    fn hashes_under_the_min_count_are_skipped() {
+        let dump = std::env::temp_dir().join(format!("{}.txt", Uuid::new_v4()));
+        let mut kept = vec![];
+
+        fs::write(&dump, format!("{}:3\n{}:1\n{}\nnot a hash:9\n", hex("password"), hex("letmein"), hex("qwerty"))).unwrap();
+
+        let skipped = each(&dump, 2, |digest| kept.push(digest));
+
+        fs::remove_file(&dump).unwrap();
+
+        assert_eq!(skipped.unwrap(), 2);
+        assert_eq!(kept, vec![sha1(b"password"), sha1(b"qwerty")]);
+    }
+
+    #[test]
+    fn range_directories_build_filters_that_hold_their_hashes() {
+        let directory = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let filter = std::env::temp_dir().join(format!("{}.bloom", Uuid::new_v4()));
+        let hash = hex("password");
+        let (prefix, suffix) = hash.split_at(5);
+
+        fs::create_dir(&directory).unwrap();
+        fs::write(directory.join(format!("{}.txt", prefix)), format!("{}:3\n", suffix)).unwrap();
+        fs::write(directory.join("README.md"), "ignored\n").unwrap();
+
+        let built = build(&args(&[&directory, &filter]));
+        let bloom = Bloom::read(&filter);
+
+        fs::remove_dir_all(&directory).unwrap();
+        fs::remove_file(&filter).unwrap();
+
+        let bloom = bloom.unwrap();
+
+        assert_eq!(built, Ok(()));
+        assert!(bloom.contains(&sha1(b"password")));
+    }
+}
@@ -0,0 +1,146 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, CreatedWithId, OkWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::access_request::{AccessRequestDecisionRequest, AccessRequestOrderByColumn, AccessRequestStoreRequest};
+use crate::responses::access_request::{AccessRequestOAS, Pagination};
+use crate::services;
+
+/// Access requests made by or awaiting decision of the current user
+#[utoipa::path(
+    tag = "Access Request",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<AccessRequestOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/access-request")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<AccessRequestOrderByColumn>,
+) -> impl Responder {
+    services::access_request::paginate(&db, auth, request).await
+}
+
+/// Request a role or permission
+#[utoipa::path(
+    tag = "Access Request",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-request")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<AccessRequestStoreRequest>,
+) -> impl Responder {
+    services::access_request::store(&db, auth, request.into_inner()).await
+}
+
+/// Get access request with its history
+#[utoipa::path(
+    tag = "Access Request",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        AccessRequestOAS,
+        Unauthorized,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/access-request/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::access_request::show(&db, auth, id.into_inner()).await
+}
+
+/// Approve access request and grant what was requested
+#[utoipa::path(
+    tag = "Access Request",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-request/{id}/approve")]
+pub async fn approve(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<AccessRequestDecisionRequest>,
+) -> impl Responder {
+    services::access_request::approve(&db, auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Reject access request
+#[utoipa::path(
+    tag = "Access Request",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-request/{id}/reject")]
+pub async fn reject(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<AccessRequestDecisionRequest>,
+) -> impl Responder {
+    services::access_request::reject(&db, auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Cancel own pending access request
+#[utoipa::path(
+    tag = "Access Request",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-request/{id}/cancel")]
+pub async fn cancel(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::access_request::cancel(&db, auth, id.into_inner()).await
+}
@@ -0,0 +1,150 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, CreatedWithId, OkWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::access_review::{AccessReviewItemDecisionRequest, AccessReviewOrderByColumn, AccessReviewStoreRequest};
+use crate::responses::access_review::{AccessReviewOAS, Pagination};
+use crate::services;
+
+/// Access review campaign pagination
+#[utoipa::path(
+    tag = "Access Review",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<AccessReviewOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/access-review")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<AccessReviewOrderByColumn>,
+) -> impl Responder {
+    services::access_review::paginate(&db, &auth, request).await
+}
+
+/// Open access review campaign over roles and permissions
+#[utoipa::path(
+    tag = "Access Review",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-review")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<AccessReviewStoreRequest>,
+) -> impl Responder {
+    services::access_review::store(&db, auth, request.into_inner()).await
+}
+
+/// Get access review campaign with its items
+#[utoipa::path(
+    tag = "Access Review",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        AccessReviewOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/access-review/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::access_review::show(&db, &auth, id.into_inner()).await
+}
+
+/// Export access review campaign as csv report
+#[utoipa::path(
+    tag = "Access Review",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", content_type = "text/csv", body = String),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/access-review/{id}/export")]
+pub async fn export(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::access_review::export(&db, &auth, id.into_inner()).await
+}
+
+/// Approve or revoke access review item
+#[utoipa::path(
+    tag = "Access Review",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden, or the item is the reviewer's own access"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-review/{id}/item/{item}")]
+pub async fn decide(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    path: Path<(Id, Id)>,
+    request: Json<AccessReviewItemDecisionRequest>,
+) -> impl Responder {
+    let (id, item) = path.into_inner();
+
+    services::access_review::decide(&db, auth, id, item, request.into_inner()).await
+}
+
+/// Close access review campaign
+#[utoipa::path(
+    tag = "Access Review",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/access-review/{id}/close")]
+pub async fn close(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::access_review::close(&db, &auth, id.into_inner()).await
+}
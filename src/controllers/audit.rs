@@ -0,0 +1,100 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Query};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::audit::{AuditCheckpointOrderByColumn, AuditFilterRequest, AuditOrderByColumn};
+use crate::responses::audit::{AuditVerification, Pagination};
+use crate::responses::audit_checkpoint;
+use crate::services;
+
+/// Audit log pagination filtered by actor, action, target, ip and time
+#[utoipa::path(
+    tag = "Audit",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<AuditOrderByColumn>,
+        AuditFilterRequest,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/audit")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<AuditOrderByColumn>,
+    filter: Query<AuditFilterRequest>,
+) -> impl Responder {
+    services::audit::paginate(&db, &auth, request, filter.into_inner()).await
+}
+
+/// Walk the hash chain and report the first entry that doesn't hold
+#[utoipa::path(
+    tag = "Audit",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        AuditVerification,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/audit/verify")]
+pub async fn verify(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+) -> impl Responder {
+    services::audit::verify(&db, &auth).await
+}
+
+/// Signed checkpoints of the hash chain
+#[utoipa::path(
+    tag = "Audit",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<AuditCheckpointOrderByColumn>,
+    ),
+    responses(
+        audit_checkpoint::Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/audit/checkpoint")]
+pub async fn checkpoints(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<AuditCheckpointOrderByColumn>,
+) -> impl Responder {
+    services::audit::checkpoints(&db, &auth, request).await
+}
+
+
+/// Public key the checkpoints are signed with
+#[utoipa::path(
+    tag = "Audit",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+    ),
+)]
+#[get("/audit/jwks")]
+pub async fn jwks(
+    auth: Auth,
+) -> impl Responder {
+    services::audit::jwks(&auth)
+}
@@ -1,8 +1,10 @@
 use actix_web::{web::{Data, Json, Path}, Responder};
-use nightmare_common::response::http::{InternalServerError, Ok, Unauthorized};
+use nightmare_common::response::http::{InternalServerError, Ok, Unauthorized, UnprocessableEntity};
 use nightmare_common::middleware::auth::Auth;
 use sea_orm::DatabaseConnection;
 
+use crate::middleware::audit::Audit;
+use crate::middleware::tenant::Tenant;
 use crate::{services, requests::auth::Login, responses};
 
 /// Login by email or username
@@ -10,15 +12,20 @@ use crate::{services, requests::auth::Login, responses};
     tag = "Authentication",
     responses(
         responses::auth::Login,
+        UnprocessableEntity,
+        (status = 403, description = "Password expired, change it with /password/expired"),
+        (status = 409, description = "Conflict"),
         InternalServerError,
+        (status = 503, description = "Service Unavailable"),
     ),
 )]
 #[post("/login")]
 pub async fn login(
     db: Data<DatabaseConnection>,
+    audit: Audit,
     request: Json<Login>,
 ) -> impl Responder {
-    services::auth::login(&db, request.into_inner()).await
+    services::auth::login(&db, &audit, request.into_inner()).await
 }
 
 /// Get authenticated user, permissions and roles
@@ -51,9 +58,11 @@ pub async fn authenticate(
 #[delete("/logout")]
 pub async fn logout(
     db: Data<DatabaseConnection>,
+    audit: Audit,
     auth: Auth,
+    tenant: Tenant,
 ) -> impl Responder {
-    services::auth::logout(&db, auth).await
+    services::auth::logout(&db, &audit, auth, tenant).await
 }
 
 #[utoipa::path(
@@ -0,0 +1,37 @@
+use actix_web::{HttpRequest, Responder};
+use actix_web::web::{Data, Json};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::response::http::{InternalServerError, NotFound, Unauthorized, UnprocessableEntity};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::authorization::AuthorizationRequest;
+use crate::responses::authorization::Authorized;
+use crate::services;
+
+/// Check one or more permissions for a user or token, set explain to see evaluated policies
+#[utoipa::path(
+    tag = "Authorization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Authorized,
+        Unauthorized,
+        (status = 403, description = "Subject is someone else and the caller lacks AUTHORIZE_SUBJECT"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/authorize")]
+pub async fn authorize(
+    auth: Auth,
+    http: HttpRequest,
+    db: Data<DatabaseConnection>,
+    request: Json<AuthorizationRequest>,
+) -> impl Responder {
+    // forwarding headers are the caller's word, policies only see the peer
+    let ip = http.peer_addr()
+        .map(|address| address.ip().to_string());
+
+    services::authorization::authorize(&db, auth, ip, request.into_inner()).await
+}
@@ -0,0 +1,143 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, CreatedWithId, OkWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::elevation::{ElevationRequest, RoleEligibilityStoreRequest};
+use crate::responses::elevation::{RoleEligibilityOAS, RoleElevationOAS};
+use crate::services;
+
+/// Users and groups eligible to activate the role
+#[utoipa::path(
+    tag = "Elevation",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", body = [RoleEligibilityOAS]),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/role/{id}/eligibility")]
+pub async fn eligibilities(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::elevation::eligibilities(&db, auth, id.into_inner()).await
+}
+
+/// Make user or group eligible to activate the role
+#[utoipa::path(
+    tag = "Elevation",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/role/{id}/eligibility")]
+pub async fn store_eligibility(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<RoleEligibilityStoreRequest>,
+) -> impl Responder {
+    services::elevation::store_eligibility(&db, auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete role eligibility
+#[utoipa::path(
+    tag = "Elevation",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/role/{id}/eligibility/{eligibility}")]
+pub async fn delete_eligibility(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    path: Path<(Id, Id)>,
+) -> impl Responder {
+    let (id, eligibility) = path.into_inner();
+
+    services::elevation::delete_eligibility(&db, auth, id, eligibility).await
+}
+
+/// Elevations of the current user
+#[utoipa::path(
+    tag = "Elevation",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", body = [RoleElevationOAS]),
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/elevation")]
+pub async fn history(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+) -> impl Responder {
+    services::elevation::history(&db, auth).await
+}
+
+/// Activate eligible role for a bounded time
+#[utoipa::path(
+    tag = "Elevation",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        UnprocessableEntity,
+        InternalServerError,
+        (status = 503, description = "Service Unavailable"),
+    ),
+)]
+#[post("/elevation")]
+pub async fn activate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<ElevationRequest>,
+) -> impl Responder {
+    services::elevation::activate(&db, auth, request.into_inner()).await
+}
+
+/// End own elevation before it expires
+#[utoipa::path(
+    tag = "Elevation",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[delete("/elevation/{id}")]
+pub async fn end(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::elevation::end(&db, auth, id.into_inner()).await
+}
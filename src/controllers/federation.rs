@@ -0,0 +1,51 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Path, Query};
+use nightmare_common::response::http::{InternalServerError, NotFound};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::audit::Audit;
+use crate::requests::identity_provider::FederatedCallbackRequest;
+use crate::{responses, services};
+
+/// Start signing in with an upstream identity provider
+#[utoipa::path(
+    tag = "Authentication",
+    responses(
+        (status = 302, description = "Redirect to the identity provider"),
+        NotFound,
+        (status = 502, description = "Identity provider is unreachable"),
+        InternalServerError,
+    ),
+)]
+#[get("/login/{provider}")]
+pub async fn start(
+    db: Data<DatabaseConnection>,
+    provider: Path<String>,
+) -> impl Responder {
+    services::federation::start(&db, services::oidc::issuer(), provider.into_inner()).await
+}
+
+/// Callback of the upstream identity provider, signs the user in
+#[utoipa::path(
+    tag = "Authentication",
+    params(
+        FederatedCallbackRequest,
+    ),
+    responses(
+        responses::auth::Login,
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        (status = 409, description = "Conflict"),
+        InternalServerError,
+    ),
+)]
+#[get("/login/{provider}/callback")]
+pub async fn callback(
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    provider: Path<String>,
+    request: Query<FederatedCallbackRequest>,
+) -> impl Responder {
+    services::federation::callback(&db, &audit, services::oidc::issuer(), provider.into_inner(), request.into_inner()).await
+}
@@ -0,0 +1,200 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, CreatedWithId, OkWithId, Ok};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::audit::Audit;
+use crate::requests::group::{GroupOrderByColumn, GroupStoreRequest, GroupUpdateRequest, GroupUserBulkRequest};
+use crate::requests::permission::PermissionBulkRequest;
+use crate::requests::role::RoleBulkRequest;
+use crate::responses::group::{GroupOAS, Pagination};
+use crate::services;
+
+/// Group pagination
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<GroupOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/group")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<GroupOrderByColumn>,
+) -> impl Responder {
+    services::group::paginate(&db, &auth, request).await
+}
+
+/// Store new group
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/group")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<GroupStoreRequest>,
+) -> impl Responder {
+    services::group::store(&db, &auth, request.into_inner()).await
+}
+
+/// Get group by id
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        GroupOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/group/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::group::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update group by id
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/group/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<GroupUpdateRequest>,
+) -> impl Responder {
+    services::group::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete group by id
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/group/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::group::delete(&db, &auth, id.into_inner()).await
+}
+
+/// Sync group members
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[put("/group/{id}/users")]
+pub async fn sync_users(
+    auth: Auth,
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<GroupUserBulkRequest>,
+) -> impl Responder {
+    services::group::sync_users(&db, &auth, &audit, id.into_inner(), request.into_inner()).await
+}
+
+/// Sync group roles
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[put("/group/{id}/roles")]
+pub async fn sync_roles(
+    auth: Auth,
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<RoleBulkRequest>,
+) -> impl Responder {
+    services::group::sync_roles(&db, &auth, &audit, id.into_inner(), request.into_inner()).await
+}
+
+/// Sync group permissions
+#[utoipa::path(
+    tag = "Group",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[put("/group/{id}/permissions")]
+pub async fn sync_permissions(
+    auth: Auth,
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<PermissionBulkRequest>,
+) -> impl Responder {
+    services::group::sync_permissions(&db, &auth, &audit, id.into_inner(), request.into_inner()).await
+}
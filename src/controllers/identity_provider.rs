@@ -0,0 +1,265 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, OkWithId, CreatedWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::identity_provider::{ExternalIdentityLinkRequest, IdentityProviderOrderByColumn, IdentityProviderStoreRequest, IdentityProviderUpdateRequest, RoleMappingStoreRequest};
+use crate::responses::identity_provider::{ExternalIdentityOAS, IdentityProviderOAS, Pagination, RoleMappingOAS};
+use crate::services;
+
+/// Identity provider pagination
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<IdentityProviderOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/identity-provider")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<IdentityProviderOrderByColumn>,
+) -> impl Responder {
+    services::identity_provider::paginate(&db, &auth, request).await
+}
+
+/// Store new upstream OpenID Connect identity provider
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/identity-provider")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<IdentityProviderStoreRequest>,
+) -> impl Responder {
+    services::identity_provider::store(&db, &auth, request.into_inner()).await
+}
+
+/// Get identity provider by id
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        IdentityProviderOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/identity-provider/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::identity_provider::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update identity provider by id
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/identity-provider/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<IdentityProviderUpdateRequest>,
+) -> impl Responder {
+    services::identity_provider::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete identity provider by id, linked identities are removed with it
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/identity-provider/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::identity_provider::delete(&db, &auth, id.into_inner()).await
+}
+
+/// Get claim to role mapping rules of the identity provider
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", body = [RoleMappingOAS]),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/identity-provider/{id}/mapping")]
+pub async fn mappings(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::identity_provider::mappings(&db, &auth, id.into_inner()).await
+}
+
+/// Store claim to role mapping rule
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/identity-provider/{id}/mapping")]
+pub async fn store_mapping(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<RoleMappingStoreRequest>,
+) -> impl Responder {
+    services::identity_provider::store_mapping(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete claim to role mapping rule
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/identity-provider/{id}/mapping/{mapping}")]
+pub async fn delete_mapping(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    path: Path<(Id, Id)>,
+) -> impl Responder {
+    let (id, mapping) = path.into_inner();
+
+    services::identity_provider::delete_mapping(&db, &auth, id, mapping).await
+}
+
+/// Get external identities linked to the user
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", body = [ExternalIdentityOAS]),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/user/{id}/identity")]
+pub async fn identities(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::identity_provider::identities(&db, &auth, id.into_inner()).await
+}
+
+/// Link an external identity to the user
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/user/{id}/identity")]
+pub async fn link(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<ExternalIdentityLinkRequest>,
+) -> impl Responder {
+    services::identity_provider::link(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Unlink an external identity from the user
+#[utoipa::path(
+    tag = "Identity Provider",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/user/{id}/identity/{identity}")]
+pub async fn unlink(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    path: Path<(Id, Id)>,
+) -> impl Responder {
+    let (id, identity) = path.into_inner();
+
+    services::identity_provider::unlink(&db, &auth, id, identity).await
+}
@@ -0,0 +1,74 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, OkWithId, CreatedWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::ldap::LdapGroupMappingStoreRequest;
+use crate::responses::ldap::LdapGroupMappingOAS;
+use crate::services;
+
+/// Get directory group to role mapping rules
+#[utoipa::path(
+    tag = "LDAP",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", body = [LdapGroupMappingOAS]),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/ldap/mapping")]
+pub async fn mappings(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+) -> impl Responder {
+    services::ldap::mappings(&db, &auth).await
+}
+
+/// Store directory group to role mapping rule
+#[utoipa::path(
+    tag = "LDAP",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/ldap/mapping")]
+pub async fn store_mapping(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<LdapGroupMappingStoreRequest>,
+) -> impl Responder {
+    services::ldap::store_mapping(&db, &auth, request.into_inner()).await
+}
+
+/// Delete directory group to role mapping rule
+#[utoipa::path(
+    tag = "LDAP",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/ldap/mapping/{id}")]
+pub async fn delete_mapping(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::ldap::delete_mapping(&db, &auth, id.into_inner()).await
+}
@@ -0,0 +1,66 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Form, Query};
+use nightmare_common::response::http::InternalServerError;
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::audit::Audit;
+use crate::requests::oauth::{AuthorizeRequest, ConsentRequest, TokenRequest};
+use crate::responses::oauth::TokenResponse;
+use crate::services;
+
+/// OAuth2 authorization endpoint, renders the consent page
+#[utoipa::path(
+    tag = "OAuth",
+    params(
+        AuthorizeRequest,
+    ),
+    responses(
+        (status = 200, description = "Consent page", content_type = "text/html"),
+        (status = 302, description = "Redirect back to the client with an error"),
+        (status = 400, description = "Bad Request"),
+    ),
+)]
+#[get("/oauth/authorize")]
+pub async fn authorize(
+    db: Data<DatabaseConnection>,
+    request: Query<AuthorizeRequest>,
+) -> impl Responder {
+    services::oauth::authorize(&db, request.into_inner()).await
+}
+
+/// Submit the consent page, redirects back to the client with a code
+#[utoipa::path(
+    tag = "OAuth",
+    request_body(content = ConsentRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 302, description = "Redirect back to the client"),
+        (status = 400, description = "Bad Request"),
+    ),
+)]
+#[post("/oauth/authorize")]
+pub async fn consent(
+    db: Data<DatabaseConnection>,
+    audit: Audit,
+    request: Form<ConsentRequest>,
+) -> impl Responder {
+    services::oauth::consent(&db, &audit, request.into_inner()).await
+}
+
+/// OAuth2 token endpoint
+#[utoipa::path(
+    tag = "OAuth",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        TokenResponse,
+        (status = 400, description = "Bad Request"),
+        (status = 401, description = "Unauthorized"),
+        InternalServerError,
+    ),
+)]
+#[post("/oauth/token")]
+pub async fn token(
+    db: Data<DatabaseConnection>,
+    request: Form<TokenRequest>,
+) -> impl Responder {
+    services::oauth::token(&db, services::oidc::issuer(), request.into_inner()).await
+}
@@ -0,0 +1,148 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, OkWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::oauth_client::{OauthClientOrderByColumn, OauthClientStoreRequest, OauthClientUpdateRequest};
+use crate::responses::oauth_client::{OauthClientCredentials, OauthClientOAS, Pagination};
+use crate::services;
+
+/// OAuth client pagination
+#[utoipa::path(
+    tag = "OAuth Client",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<OauthClientOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/oauth-client")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<OauthClientOrderByColumn>,
+) -> impl Responder {
+    services::oauth_client::paginate(&db, &auth, request).await
+}
+
+/// Register new OAuth client, confidential clients get their secret once
+#[utoipa::path(
+    tag = "OAuth Client",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OauthClientCredentials,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/oauth-client")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<OauthClientStoreRequest>,
+) -> impl Responder {
+    services::oauth_client::store(&db, &auth, request.into_inner()).await
+}
+
+/// Get OAuth client by id
+#[utoipa::path(
+    tag = "OAuth Client",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OauthClientOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/oauth-client/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::oauth_client::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update OAuth client by id
+#[utoipa::path(
+    tag = "OAuth Client",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/oauth-client/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<OauthClientUpdateRequest>,
+) -> impl Responder {
+    services::oauth_client::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete OAuth client by id, tokens issued to it are revoked
+#[utoipa::path(
+    tag = "OAuth Client",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/oauth-client/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::oauth_client::delete(&db, &auth, id.into_inner()).await
+}
+
+/// Rotate OAuth client secret
+#[utoipa::path(
+    tag = "OAuth Client",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OauthClientCredentials,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/oauth-client/{id}/secret")]
+pub async fn rotate_secret(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::oauth_client::rotate_secret(&db, &auth, id.into_inner()).await
+}
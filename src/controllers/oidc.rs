@@ -0,0 +1,73 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Query};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::response::http::{InternalServerError, Unauthorized};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::tenant::Tenant;
+use crate::requests::oidc::LogoutRequest;
+use crate::services;
+
+/// OpenID Connect discovery document
+#[utoipa::path(
+    tag = "OpenID Connect",
+    responses(
+        (status = 200, description = "Ok"),
+    ),
+)]
+#[get("/.well-known/openid-configuration")]
+pub async fn configuration() -> impl Responder {
+    services::oidc::configuration(services::oidc::issuer())
+}
+
+/// Public keys used to sign id tokens
+#[utoipa::path(
+    tag = "OpenID Connect",
+    responses(
+        (status = 200, description = "Ok"),
+    ),
+)]
+#[get("/.well-known/jwks.json")]
+pub async fn jwks() -> impl Responder {
+    services::oidc::jwks()
+}
+
+/// Claims of the user the access token was issued for
+#[utoipa::path(
+    tag = "OpenID Connect",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/userinfo")]
+pub async fn userinfo(
+    auth: Auth,
+    tenant: Tenant,
+    db: Data<DatabaseConnection>,
+) -> impl Responder {
+    services::oidc::userinfo(&db, auth, tenant).await
+}
+
+/// RP-initiated logout
+#[utoipa::path(
+    tag = "OpenID Connect",
+    params(
+        LogoutRequest,
+    ),
+    responses(
+        (status = 200, description = "Signed out page", content_type = "text/html"),
+        (status = 302, description = "Redirect to the post logout redirect uri"),
+        (status = 400, description = "Bad Request"),
+    ),
+)]
+#[get("/oauth/logout")]
+pub async fn logout(
+    db: Data<DatabaseConnection>,
+    request: Query<LogoutRequest>,
+) -> impl Responder {
+    services::oidc::logout(&db, services::oidc::issuer(), request.into_inner()).await
+}
@@ -0,0 +1,172 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, CreatedWithId, OkWithId, Ok};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::tenant::Tenant;
+use crate::requests::organization::{OrganizationOrderByColumn, OrganizationStoreRequest, OrganizationUpdateRequest, OrganizationUserBulkRequest};
+use crate::responses::organization::{OrganizationOAS, Pagination};
+use crate::services;
+
+/// Organization pagination
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<OrganizationOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/organization")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<OrganizationOrderByColumn>,
+) -> impl Responder {
+    services::organization::paginate(&db, &auth, request).await
+}
+
+/// Store new organization
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/organization")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<OrganizationStoreRequest>,
+) -> impl Responder {
+    services::organization::store(&db, &auth, request.into_inner()).await
+}
+
+/// Get organization by id
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OrganizationOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/organization/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::organization::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update organization by id
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/organization/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<OrganizationUpdateRequest>,
+) -> impl Responder {
+    services::organization::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete organization by id
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/organization/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::organization::delete(&db, &auth, id.into_inner()).await
+}
+
+/// Sync organization members
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[put("/organization/{id}/users")]
+pub async fn sync_users(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<OrganizationUserBulkRequest>,
+) -> impl Responder {
+    services::organization::sync_users(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Switch the organization of the current token
+#[utoipa::path(
+    tag = "Organization",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/organization/{id}/switch")]
+pub async fn switch(
+    _: Auth,
+    tenant: Tenant,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::organization::switch(&db, tenant, id.into_inner()).await
+}
@@ -0,0 +1,40 @@
+use actix_web::{web::{Data, Json}, Responder};
+use nightmare_common::response::http::{InternalServerError, Ok, UnprocessableEntity};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::audit::Audit;
+use crate::requests::password::ExpiredPasswordRequest;
+use crate::responses::password::PasswordPolicyOAS;
+use crate::services;
+
+/// Rules new passwords have to follow, for forms to check as the user types
+#[utoipa::path(
+    tag = "Authentication",
+    responses(
+        PasswordPolicyOAS,
+    ),
+)]
+#[get("/password/policy")]
+pub async fn policy() -> impl Responder {
+    services::password::show()
+}
+
+/// Replace an expired password, login refuses the user until it's done
+#[utoipa::path(
+    tag = "Authentication",
+    responses(
+        Ok,
+        UnprocessableEntity,
+        (status = 409, description = "Password hasn't expired"),
+        InternalServerError,
+        (status = 503, description = "Service Unavailable"),
+    ),
+)]
+#[post("/password/expired")]
+pub async fn expired(
+    db: Data<DatabaseConnection>,
+    audit: Audit,
+    request: Json<ExpiredPasswordRequest>,
+) -> impl Responder {
+    services::password::change_expired(&db, &audit, request.into_inner()).await
+}
@@ -8,6 +8,8 @@ use sea_orm::DatabaseConnection;
 
 use crate::requests::permission::{PermissionStoreRequest, PermissionUpdateRequest, PermissionOrderByColumn};
 use crate::responses::permission::{PermissionOAS, Pagination};
+use crate::middleware::audit::Audit;
+use crate::middleware::tenant::Tenant;
 use crate::services;
 
 /// Permission pagination
@@ -26,11 +28,12 @@ use crate::services;
 )]
 #[get("/permission")]
 pub async fn paginate(
-    _: Auth,
+    auth: Auth,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     request: PaginationRequest<PermissionOrderByColumn>,
 ) -> impl Responder {
-    services::permission::paginate(&db, request).await
+    services::permission::paginate(&db, tenant.scope(&auth), request).await
 }
 
 /// Store new permission
@@ -41,16 +44,19 @@ pub async fn paginate(
     responses(
         CreatedWithId,
         Unauthorized,
+        (status = 403, description = "Forbidden"),
         InternalServerError,
     ),
 )]
 #[post("/permission")]
 pub async fn store(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     request: Json<PermissionStoreRequest>,
 ) -> impl Responder {
-    services::permission::store(&db, request.into_inner()).await
+    services::permission::store(&db, &audit, tenant.scope(&auth), request.into_inner()).await
 }
 
 /// Get permission by id
@@ -67,11 +73,12 @@ pub async fn store(
 )]
 #[get("/permission/{id}")]
 pub async fn show(
-    _: Auth,
+    auth: Auth,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
 ) -> impl Responder {
-    services::permission::show(&db, id.into_inner()).await
+    services::permission::show(&db, tenant.scope(&auth), id.into_inner()).await
 }
 
 /// Update permission by id
@@ -88,12 +95,14 @@ pub async fn show(
 )]
 #[put("/permission/{id}")]
 pub async fn update(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
     request: Json<PermissionUpdateRequest>,
 ) -> impl Responder {
-    services::permission::update(&db, id.into_inner(), request.into_inner()).await
+    services::permission::update(&db, &audit, tenant.scope(&auth), id.into_inner(), request.into_inner()).await
 }
 
 /// Delete permission by id
@@ -110,9 +119,11 @@ pub async fn update(
 )]
 #[delete("/permission/{id}")]
 pub async fn delete(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
 ) -> impl Responder {
-    services::permission::delete(&db, id.into_inner()).await
+    services::permission::delete(&db, &audit, tenant.scope(&auth), id.into_inner()).await
 }
@@ -0,0 +1,74 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, OkWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::tenant::Tenant;
+use crate::requests::personal_access_token::PersonalAccessTokenStoreRequest;
+use crate::responses::personal_access_token::{PersonalAccessTokenCreated, PersonalAccessTokenOAS};
+use crate::services;
+
+/// Personal access tokens of the current user
+#[utoipa::path(
+    tag = "Personal Access Token",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        (status = 200, description = "Ok", body = [PersonalAccessTokenOAS]),
+        Unauthorized,
+        InternalServerError,
+    ),
+)]
+#[get("/personal-access-token")]
+pub async fn all(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+) -> impl Responder {
+    services::personal_access_token::all(&db, auth).await
+}
+
+/// Create personal access token, the secret is only returned once
+#[utoipa::path(
+    tag = "Personal Access Token",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        PersonalAccessTokenCreated,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/personal-access-token")]
+pub async fn store(
+    auth: Auth,
+    tenant: Tenant,
+    db: Data<DatabaseConnection>,
+    request: Json<PersonalAccessTokenStoreRequest>,
+) -> impl Responder {
+    services::personal_access_token::store(&db, auth, tenant, request.into_inner()).await
+}
+
+/// Revoke personal access token
+#[utoipa::path(
+    tag = "Personal Access Token",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/personal-access-token/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::personal_access_token::delete(&db, auth, id.into_inner()).await
+}
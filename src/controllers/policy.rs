@@ -0,0 +1,125 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, CreatedWithId, OkWithId, Ok};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::policy::{PolicyOrderByColumn, PolicyStoreRequest};
+use crate::responses::policy::{PolicyOAS, Pagination};
+use crate::services;
+
+/// Policy pagination
+#[utoipa::path(
+    tag = "Policy",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<PolicyOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/policy")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<PolicyOrderByColumn>,
+) -> impl Responder {
+    services::policy::paginate(&db, &auth, request).await
+}
+
+/// Store new policy
+#[utoipa::path(
+    tag = "Policy",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        CreatedWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/policy")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<PolicyStoreRequest>,
+) -> impl Responder {
+    services::policy::store(&db, &auth, request.into_inner()).await
+}
+
+/// Get policy by id
+#[utoipa::path(
+    tag = "Policy",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        PolicyOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/policy/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::policy::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update policy by id
+#[utoipa::path(
+    tag = "Policy",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/policy/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<PolicyStoreRequest>,
+) -> impl Responder {
+    services::policy::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete policy by id
+#[utoipa::path(
+    tag = "Policy",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/policy/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::policy::delete(&db, &auth, id.into_inner()).await
+}
@@ -6,9 +6,11 @@ use nightmare_common::request::pagination::{PaginationRequest, PaginationRequest
 use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, CreatedWithId, OkWithId, Ok};
 use sea_orm::DatabaseConnection;
 
-use crate::requests::role::{RoleStoreRequest, RoleUpdateRequest};
+use crate::requests::role::{RoleOwnerBulkRequest, RoleStoreRequest, RoleUpdateRequest};
 use crate::responses::role::RoleOAS;
 use crate::{requests::role::RoleOrderByColumn, responses::role::Pagination};
+use crate::middleware::audit::Audit;
+use crate::middleware::tenant::Tenant;
 use crate::services;
 
 /// Role pagination
@@ -27,11 +29,12 @@ use crate::services;
 )]
 #[get("/role")]
 pub async fn paginate(
-    _: Auth,
+    auth: Auth,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     request: PaginationRequest<RoleOrderByColumn>,
 ) -> impl Responder {
-    services::role::paginate(&db, request).await
+    services::role::paginate(&db, tenant.scope(&auth), request).await
 }
 
 /// Store new role
@@ -42,16 +45,19 @@ pub async fn paginate(
     responses(
         CreatedWithId,
         Unauthorized,
+        (status = 403, description = "Forbidden"),
         InternalServerError,
     ),
 )]
 #[post("/role")]
 pub async fn store(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     request: Json<RoleStoreRequest>,
 ) -> impl Responder {
-    services::role::store(&db, request.into_inner()).await
+    services::role::store(&db, &audit, tenant.scope(&auth), request.into_inner()).await
 }
 
 /// Get role by id
@@ -68,11 +74,12 @@ pub async fn store(
 )]
 #[get("/role/{id}")]
 pub async fn show(
-    _: Auth,
+    auth: Auth,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
 ) -> impl Responder {
-    services::role::show(&db, id.into_inner()).await
+    services::role::show(&db, tenant.scope(&auth), id.into_inner()).await
 }
 
 /// Update role by id
@@ -89,12 +96,14 @@ pub async fn show(
 )]
 #[put("/role/{id}")]
 pub async fn update(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
     request: Json<RoleUpdateRequest>,
 ) -> impl Responder {
-    services::role::update(&db, id.into_inner(), request.into_inner()).await
+    services::role::update(&db, &audit, tenant.scope(&auth), id.into_inner(), request.into_inner()).await
 }
 
 /// Delete role by id
@@ -111,9 +120,35 @@ pub async fn update(
 )]
 #[delete("/role/{id}")]
 pub async fn delete(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
 ) -> impl Responder {
-    services::role::delete(&db,  id.into_inner()).await
+    services::role::delete(&db, &audit, tenant.scope(&auth), id.into_inner()).await
 }
+
+/// Sync users allowed to approve access requests for the role
+#[utoipa::path(
+    tag = "Role",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[put("/role/{id}/owners")]
+pub async fn sync_owners(
+    auth: Auth,
+    tenant: Tenant,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<RoleOwnerBulkRequest>,
+) -> impl Responder {
+    services::role::sync_owners(&db, &auth, tenant.scope(&auth), id.into_inner(), request.into_inner()).await
+}
\ No newline at end of file
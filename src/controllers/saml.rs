@@ -0,0 +1,49 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Form, Path};
+use nightmare_common::response::http::{InternalServerError, NotFound};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::audit::Audit;
+use crate::requests::identity_provider::SamlResponseRequest;
+use crate::{responses, services};
+
+/// Service provider metadata to register with a saml identity provider
+#[utoipa::path(
+    tag = "Authentication",
+    context_path = "/saml",
+    responses(
+        (status = 200, description = "Ok", content_type = "application/samlmetadata+xml"),
+        NotFound,
+    ),
+)]
+#[get("/{provider}/metadata")]
+pub async fn metadata(
+    db: Data<DatabaseConnection>,
+    provider: Path<String>,
+) -> impl Responder {
+    services::saml::metadata(&db, services::oidc::issuer(), provider.into_inner()).await
+}
+
+/// Assertion consumer service, signs the user in with the saml response
+#[utoipa::path(
+    tag = "Authentication",
+    context_path = "/saml",
+    request_body(content = SamlResponseRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        responses::auth::Login,
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        (status = 409, description = "Conflict"),
+        InternalServerError,
+    ),
+)]
+#[post("/{provider}/acs")]
+pub async fn acs(
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    provider: Path<String>,
+    request: Form<SamlResponseRequest>,
+) -> impl Responder {
+    services::saml::acs(&db, &audit, services::oidc::issuer(), provider.into_inner(), request.into_inner()).await
+}
@@ -0,0 +1,321 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path, Query};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::response::http::Unauthorized;
+use sea_orm::DatabaseConnection;
+
+use crate::requests::scim::{ScimGroupRequest, ScimListRequest, ScimPatchRequest, ScimUserRequest};
+use crate::responses::scim::{ScimGroupList, ScimGroupOAS, ScimUserList, ScimUserOAS};
+use crate::services;
+
+/// Get provisioned users
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    params(ScimListRequest),
+    responses(
+        ScimUserList,
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+    ),
+)]
+#[get("/Users")]
+pub async fn users(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Query<ScimListRequest>,
+) -> impl Responder {
+    services::scim::users(&db, &auth, services::scim::base(), request.into_inner()).await
+}
+
+/// Get provisioned user
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    responses(
+        ScimUserOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+    ),
+)]
+#[get("/Users/{id}")]
+pub async fn user(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::scim::user(&db, &auth, services::scim::base(), id.into_inner()).await
+}
+
+/// Provision user
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    request_body(content = ScimUserRequest, content_type = "application/scim+json"),
+    responses(
+        (status = 201, description = "Created", body = ScimUserOAS),
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 409, description = "Conflict"),
+    ),
+)]
+#[post("/Users")]
+pub async fn store_user(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<ScimUserRequest>,
+) -> impl Responder {
+    services::scim::store_user(&db, &auth, services::scim::base(), request.into_inner()).await
+}
+
+/// Replace provisioned user, `active` false deactivates the user
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    request_body(content = ScimUserRequest, content_type = "application/scim+json"),
+    responses(
+        ScimUserOAS,
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+        (status = 409, description = "Conflict"),
+    ),
+)]
+#[put("/Users/{id}")]
+pub async fn replace_user(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<ScimUserRequest>,
+) -> impl Responder {
+    services::scim::replace_user(&db, &auth, services::scim::base(), id.into_inner(), request.into_inner()).await
+}
+
+/// Patch provisioned user
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    request_body(content = ScimPatchRequest, content_type = "application/scim+json"),
+    responses(
+        ScimUserOAS,
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+        (status = 409, description = "Conflict"),
+    ),
+)]
+#[patch("/Users/{id}")]
+pub async fn patch_user(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<ScimPatchRequest>,
+) -> impl Responder {
+    services::scim::patch_user(&db, &auth, services::scim::base(), id.into_inner(), request.into_inner()).await
+}
+
+/// Deprovision user, the user is soft deleted
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    responses(
+        (status = 204, description = "No Content"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+    ),
+)]
+#[delete("/Users/{id}")]
+pub async fn delete_user(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::scim::delete_user(&db, &auth, id.into_inner()).await
+}
+
+/// Get provisioned groups, groups are roles
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    params(ScimListRequest),
+    responses(
+        ScimGroupList,
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+    ),
+)]
+#[get("/Groups")]
+pub async fn groups(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Query<ScimListRequest>,
+) -> impl Responder {
+    services::scim::groups(&db, &auth, services::scim::base(), request.into_inner()).await
+}
+
+/// Get provisioned group
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    responses(
+        ScimGroupOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+    ),
+)]
+#[get("/Groups/{id}")]
+pub async fn group(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::scim::group(&db, &auth, services::scim::base(), id.into_inner()).await
+}
+
+/// Provision group as a role with its code derived from the display name
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    request_body(content = ScimGroupRequest, content_type = "application/scim+json"),
+    responses(
+        (status = 201, description = "Created", body = ScimGroupOAS),
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 409, description = "Conflict"),
+    ),
+)]
+#[post("/Groups")]
+pub async fn store_group(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<ScimGroupRequest>,
+) -> impl Responder {
+    services::scim::store_group(&db, &auth, services::scim::base(), request.into_inner()).await
+}
+
+/// Replace provisioned group and its members
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    request_body(content = ScimGroupRequest, content_type = "application/scim+json"),
+    responses(
+        ScimGroupOAS,
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+    ),
+)]
+#[put("/Groups/{id}")]
+pub async fn replace_group(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<ScimGroupRequest>,
+) -> impl Responder {
+    services::scim::replace_group(&db, &auth, services::scim::base(), id.into_inner(), request.into_inner()).await
+}
+
+/// Patch provisioned group name or members
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    request_body(content = ScimPatchRequest, content_type = "application/scim+json"),
+    responses(
+        ScimGroupOAS,
+        (status = 400, description = "Bad Request"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+    ),
+)]
+#[patch("/Groups/{id}")]
+pub async fn patch_group(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<ScimPatchRequest>,
+) -> impl Responder {
+    services::scim::patch_group(&db, &auth, services::scim::base(), id.into_inner(), request.into_inner()).await
+}
+
+/// Deprovision group, the role is deleted
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    security(("token" = [])),
+    responses(
+        (status = 204, description = "No Content"),
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        (status = 404, description = "Not Found"),
+    ),
+)]
+#[delete("/Groups/{id}")]
+pub async fn delete_group(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::scim::delete_group(&db, &auth, id.into_inner()).await
+}
+
+/// Capabilities of the provisioning api
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    responses(
+        (status = 200, description = "Ok", content_type = "application/scim+json"),
+    ),
+)]
+#[get("/ServiceProviderConfig")]
+pub async fn service_provider_config() -> impl Responder {
+    services::scim::service_provider_config(services::scim::base())
+}
+
+/// Attribute definitions of the user and group resources
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    responses(
+        (status = 200, description = "Ok", content_type = "application/scim+json"),
+    ),
+)]
+#[get("/Schemas")]
+pub async fn schemas() -> impl Responder {
+    services::scim::schemas(services::scim::base())
+}
+
+/// Resource types served by the provisioning api
+#[utoipa::path(
+    tag = "SCIM",
+    context_path = "/scim/v2",
+    responses(
+        (status = 200, description = "Ok", content_type = "application/scim+json"),
+    ),
+)]
+#[get("/ResourceTypes")]
+pub async fn resource_types() -> impl Responder {
+    services::scim::resource_types(services::scim::base())
+}
@@ -0,0 +1,223 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, OkWithId, Ok};
+use sea_orm::DatabaseConnection;
+
+use crate::middleware::audit::Audit;
+use crate::requests::permission::PermissionBulkRequest;
+use crate::requests::role::RoleBulkRequest;
+use crate::requests::service_account::{ServiceAccountOrderByColumn, ServiceAccountStoreRequest, ServiceAccountUpdateRequest};
+use crate::responses::service_account::{Pagination, ServiceAccountCredentials, ServiceAccountOAS};
+use crate::services;
+
+/// Service account pagination
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<ServiceAccountOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/service-account")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<ServiceAccountOrderByColumn>,
+) -> impl Responder {
+    services::service_account::paginate(&db, &auth, request).await
+}
+
+/// Store new service account, returns its client credentials once
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        ServiceAccountCredentials,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/service-account")]
+pub async fn store(
+    auth: Auth,
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    request: Json<ServiceAccountStoreRequest>,
+) -> impl Responder {
+    services::service_account::store(&db, &auth, &audit, request.into_inner()).await
+}
+
+/// Get service account by id
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        ServiceAccountOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/service-account/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::service_account::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update service account by id
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/service-account/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<ServiceAccountUpdateRequest>,
+) -> impl Responder {
+    services::service_account::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete service account by id
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/service-account/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::service_account::delete(&db, &auth, id.into_inner()).await
+}
+
+/// Rotate service account client secret
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        ServiceAccountCredentials,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[post("/service-account/{id}/secret")]
+pub async fn rotate_secret(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::service_account::rotate_secret(&db, &auth, id.into_inner()).await
+}
+
+/// Issue a new api key for the service account, replacing the old one
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        ServiceAccountCredentials,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[post("/service-account/{id}/api-key")]
+pub async fn rotate_api_key(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::service_account::rotate_api_key(&db, &auth, id.into_inner()).await
+}
+
+/// Sync service account roles
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/service-account/{id}/roles")]
+pub async fn sync_roles(
+    auth: Auth,
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<RoleBulkRequest>,
+) -> impl Responder {
+    services::service_account::sync_roles(&db, &auth, &audit, id.into_inner(), request.into_inner()).await
+}
+
+/// Sync service account permissions
+#[utoipa::path(
+    tag = "Service Account",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Ok,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/service-account/{id}/permissions")]
+pub async fn sync_permissions(
+    auth: Auth,
+    audit: Audit,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<PermissionBulkRequest>,
+) -> impl Responder {
+    services::service_account::sync_permissions(&db, &auth, &audit, id.into_inner(), request.into_inner()).await
+}
@@ -9,7 +9,10 @@ use sea_orm::DatabaseConnection;
 use crate::requests::permission::PermissionBulkRequest;
 use crate::requests::role::RoleBulkRequest;
 use crate::requests::user::{UserOrderByColumn, UserStoreRequest, UserUpdateGeneralInformationRequest, UserUpdatePasswordRequest};
+use crate::responses::auth::Impersonated;
 use crate::responses::user::{Pagination, UserOAS, Created};
+use crate::middleware::audit::Audit;
+use crate::middleware::tenant::Tenant;
 use crate::services;
 
 /// user pagination
@@ -28,11 +31,12 @@ use crate::services;
 )]
 #[get("/user")]
 pub async fn paginate(
-    _: Auth,
+    auth: Auth,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     request: PaginationRequest<UserOrderByColumn>,
 ) -> impl Responder {
-    services::user::paginate(&db, request).await
+    services::user::paginate(&db, tenant.scope(&auth), request).await
 }
 
 /// store new user
@@ -42,17 +46,20 @@ pub async fn paginate(
     security(("token" = [])),
     responses(
         Created,
+        (status = 403, description = "Forbidden"),
         UnprocessableEntity,
         InternalServerError,
     ),
 )]
 #[post("/user")]
 pub async fn store(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     request: Json<UserStoreRequest>,
 ) -> impl Responder {
-    services::user::store(&db, request.into_inner()).await
+    services::user::store(&db, &audit, tenant.scope(&auth), request.into_inner()).await
 }
 
 /// show user by id
@@ -69,11 +76,12 @@ pub async fn store(
 )]
 #[get("/user/{id}")]
 pub async fn show(
-    _: Auth,
+    auth: Auth,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
 ) -> impl Responder {
-    services::user::show(&db, id.to_owned(),).await
+    services::user::show(&db, tenant.scope(&auth), id.to_owned()).await
 }
 
 /// update user by id
@@ -91,12 +99,14 @@ pub async fn show(
 )]
 #[put("/user/{id}")]
 pub async fn update_general_information(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
     request: Json<UserUpdateGeneralInformationRequest>,
 ) -> impl Responder {
-    services::user::update_general_information(&db, id.to_owned(), request.into_inner()).await
+    services::user::update_general_information(&db, &audit, tenant.scope(&auth), id.to_owned(), request.into_inner()).await
 }
 
 /// update user password by id
@@ -115,12 +125,14 @@ pub async fn update_general_information(
 )]
 #[patch("/user/{id}")]
 pub async fn update_password(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
     request: Json<UserUpdatePasswordRequest>,
 ) -> impl Responder {
-    services::user::update_password(&db, id.to_owned(), request.into_inner()).await
+    services::user::update_password(&db, &audit, tenant.scope(&auth), id.to_owned(), request.into_inner()).await
 }
 
 /// delete user by id
@@ -137,11 +149,13 @@ pub async fn update_password(
 )]
 #[delete("/user/{id}")]
 pub async fn delete(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
 ) -> impl Responder {
-    services::user::delete(&db, id.to_owned()).await
+    services::user::delete(&db, &audit, tenant.scope(&auth), id.to_owned()).await
 }
 
 /// sync user permissions
@@ -159,12 +173,14 @@ pub async fn delete(
 )]
 #[put("/user/{id}/permissions")]
 pub async fn sync_permissions(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
     request: Json<PermissionBulkRequest>,
 ) -> impl Responder {
-    services::user::sync_permissions(&db, id.into_inner(), request.into_inner()).await
+    services::user::sync_permissions(&db, &audit, tenant.scope(&auth), id.into_inner(), request.into_inner()).await
 }
 
 /// sync user roles
@@ -182,10 +198,37 @@ pub async fn sync_permissions(
 )]
 #[put("/user/{id}/roles")]
 pub async fn sync_roles(
-    _: Auth,
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
     db: Data<DatabaseConnection>,
     id: Path<Id>,
     request: Json<RoleBulkRequest>,
 ) -> impl Responder {
-    services::user::sync_roles(&db, id.into_inner(), request.into_inner()).await
+    services::user::sync_roles(&db, &audit, tenant.scope(&auth), id.into_inner(), request.into_inner()).await
 }
+
+/// Issue a short lived token acting as the user
+#[utoipa::path(
+    tag = "Master User",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        Impersonated,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/user/{id}/impersonate")]
+pub async fn impersonate(
+    auth: Auth,
+    audit: Audit,
+    tenant: Tenant,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::user::impersonate(&db, &audit, auth, tenant, id.into_inner()).await
+}
\ No newline at end of file
@@ -0,0 +1,179 @@
+use actix_web::Responder;
+use actix_web::web::{Data, Json, Path};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::{PaginationRequest, PaginationRequestParam};
+use nightmare_common::response::http::{Unauthorized, InternalServerError, NotFound, UnprocessableEntity, OkWithId};
+use sea_orm::DatabaseConnection;
+
+use crate::requests::webhook::{WebhookDeliveryOrderByColumn, WebhookOrderByColumn, WebhookStoreRequest, WebhookUpdateRequest};
+use crate::responses::webhook::{Pagination, WebhookOAS, WebhookSecret};
+use crate::responses::webhook_delivery::{self, WebhookDeliveryOAS};
+use crate::services;
+
+/// Webhook pagination
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<WebhookOrderByColumn>,
+    ),
+    responses(
+        Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        InternalServerError,
+    ),
+)]
+#[get("/webhook")]
+pub async fn paginate(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: PaginationRequest<WebhookOrderByColumn>,
+) -> impl Responder {
+    services::webhook::paginate(&db, &auth, request).await
+}
+
+/// Subscribe a url to domain events, the signing secret is only returned
+/// here
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        WebhookSecret,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/webhook")]
+pub async fn store(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    request: Json<WebhookStoreRequest>,
+) -> impl Responder {
+    services::webhook::store(&db, &auth, request.into_inner()).await
+}
+
+/// Get webhook by id
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        WebhookOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/webhook/{id}")]
+pub async fn show(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::webhook::show(&db, &auth, id.into_inner()).await
+}
+
+/// Update webhook by id
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[put("/webhook/{id}")]
+pub async fn update(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: Json<WebhookUpdateRequest>,
+) -> impl Responder {
+    services::webhook::update(&db, &auth, id.into_inner(), request.into_inner()).await
+}
+
+/// Delete webhook by id, its delivery log is removed with it
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        OkWithId,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[delete("/webhook/{id}")]
+pub async fn delete(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+) -> impl Responder {
+    services::webhook::delete(&db, &auth, id.into_inner()).await
+}
+
+/// Delivery log of the webhook
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    params(
+        PaginationRequestParam<WebhookDeliveryOrderByColumn>,
+    ),
+    responses(
+        webhook_delivery::Pagination,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        InternalServerError,
+    ),
+)]
+#[get("/webhook/{id}/delivery")]
+pub async fn deliveries(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    id: Path<Id>,
+    request: PaginationRequest<WebhookDeliveryOrderByColumn>,
+) -> impl Responder {
+    services::webhook::deliveries(&db, &auth, id.into_inner(), request).await
+}
+
+/// Send a delivery again right away, dead deliveries get a fresh retry
+/// schedule
+#[utoipa::path(
+    tag = "Webhook",
+    context_path = "/api/v1",
+    security(("token" = [])),
+    responses(
+        WebhookDeliveryOAS,
+        Unauthorized,
+        (status = 403, description = "Forbidden"),
+        NotFound,
+        UnprocessableEntity,
+        InternalServerError,
+    ),
+)]
+#[post("/webhook/{id}/delivery/{delivery}/redeliver")]
+pub async fn redeliver(
+    auth: Auth,
+    db: Data<DatabaseConnection>,
+    path: Path<(Id, Id)>,
+) -> impl Responder {
+    let (id, delivery) = path.into_inner();
+
+    services::webhook::redeliver(&db, &auth, id, delivery).await
+}
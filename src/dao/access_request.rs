@@ -0,0 +1,229 @@
+use nightmare_common::time;
+use nightmare_common::models::{permissions, roles, users, Id};
+use sea_orm::{QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::dao;
+use crate::dao::authorization::{Resource, Window};
+use crate::models::{access_request_histories, access_requests, permission_user, role_user};
+
+pub const PENDING: &str = "pending";
+pub const APPROVED: &str = "approved";
+pub const REJECTED: &str = "rejected";
+pub const CANCELLED: &str = "cancelled";
+
+pub const REQUESTED: &str = "requested";
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<access_requests::Model> {
+    let id: Id = id.into();
+
+    access_requests::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+/// Whether the user already waits on the same role or permission
+pub async fn pending(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    role_id: Option<Id>,
+    permission_id: Option<Id>,
+) -> bool {
+    let mut query = access_requests::Entity::find()
+        .filter(access_requests::Column::UserId.eq(user.id.clone()))
+        .filter(access_requests::Column::Status.eq(PENDING));
+
+    query = match role_id {
+        None => query.filter(access_requests::Column::RoleId.is_null()),
+        Some(role_id) => query.filter(access_requests::Column::RoleId.eq(role_id)),
+    };
+
+    query = match permission_id {
+        None => query.filter(access_requests::Column::PermissionId.is_null()),
+        Some(permission_id) => query.filter(access_requests::Column::PermissionId.eq(permission_id)),
+    };
+
+    query.count(db)
+        .await
+        .unwrap_or(0)
+        > 0
+}
+
+pub async fn histories(
+    db: &DatabaseConnection,
+    request: &access_requests::Model,
+) -> Result<Vec<access_request_histories::Model>, DbErr> {
+    access_request_histories::Entity::find()
+        .filter(access_request_histories::Column::AccessRequestId.eq(request.id.clone()))
+        .order_by_asc(access_request_histories::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn store<D: ConnectionTrait, J: ToString>(
+    db: &D,
+    user: &users::Model,
+    role_id: Option<Id>,
+    permission_id: Option<Id>,
+    justification: J,
+    window: Window,
+) -> Result<access_requests::Model, DbErr> {
+    let request = access_requests::ActiveModel::from(access_requests::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        role_id,
+        permission_id,
+        justification: justification.to_string(),
+        status: PENDING.to_string(),
+        starts_at: window.starts_at,
+        expires_at: window.expires_at,
+        created_at: time::now(),
+        updated_at: time::now(),
+    });
+
+    let request = request.insert(db).await?;
+
+    record(db, &request, &user.id, REQUESTED, None).await?;
+
+    Ok(request)
+}
+
+/// Move a pending request into its final status and keep the step in
+/// history, none when another decision got there first
+pub async fn decide<D: ConnectionTrait>(
+    db: &D,
+    request: &access_requests::Model,
+    actor_id: &Id,
+    status: &str,
+    comment: Option<String>,
+) -> Result<Option<access_requests::Model>, DbErr> {
+    let updated = access_requests::Entity::update_many()
+        .col_expr(access_requests::Column::Status, Expr::value(status))
+        .col_expr(access_requests::Column::UpdatedAt, Expr::value(time::now()))
+        .filter(access_requests::Column::Id.eq(request.id.clone()))
+        .filter(access_requests::Column::Status.eq(PENDING))
+        .exec(db)
+        .await?;
+
+    if updated.rows_affected.eq(&0) {
+        return Ok(None)
+    }
+
+    let request = access_requests::Entity::find_by_id(request.id.clone())
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound("access request".to_string()))?;
+
+    record(db, &request, actor_id, status, comment).await?;
+
+    Ok(Some(request))
+}
+
+pub async fn record<D: ConnectionTrait>(
+    db: &D,
+    request: &access_requests::Model,
+    actor_id: &Id,
+    action: &str,
+    comment: Option<String>,
+) -> Result<access_request_histories::Model, DbErr> {
+    let history = access_request_histories::ActiveModel::from(access_request_histories::Model {
+        id: Uuid::new_v4().into(),
+        access_request_id: request.id.clone(),
+        actor_id: actor_id.clone(),
+        action: action.to_string(),
+        comment,
+        created_at: time::now(),
+    });
+
+    history.insert(db).await
+}
+
+/// Grant what was requested on top of the user's existing assignments
+pub async fn apply<D: ConnectionTrait>(
+    db: &D,
+    request: &access_requests::Model,
+) -> Result<(), DbErr> {
+    let user = users::Entity::find_by_id(request.user_id.clone())
+        .one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound(format!("user {}", request.user_id)))?;
+
+    let window = Window {
+        starts_at: request.starts_at,
+        expires_at: request.expires_at,
+    };
+
+    if let Some(role_id) = &request.role_id {
+        let assignments = role_user::Entity::find()
+            .filter(role_user::Column::UserId.eq(user.id.clone()))
+            .all(db)
+            .await?;
+
+        let roles = roles::Entity::find()
+            .filter(roles::Column::Id.is_in(
+                assignments.iter()
+                    .map(|assignment| assignment.role_id.clone())
+                    .chain([role_id.clone()])
+                    .collect::<Vec<Id>>()
+            ))
+            .all(db)
+            .await?;
+
+        let mut assigned = assignments.iter()
+            .filter_map(|assignment| {
+                roles.iter()
+                    .find(|role| role.id.eq(&assignment.role_id))
+                    .map(|role| (role.clone(), Resource::from_assignment(assignment), Window::from_assignment(assignment)))
+            })
+            .collect::<Vec<_>>();
+
+        let requested = roles.iter()
+            .find(|role| role.id.eq(role_id))
+            .cloned()
+            .ok_or(DbErr::RecordNotFound(format!("role {}", role_id)))?;
+
+        assigned.push((requested, None, window.clone()));
+
+        dao::user::sync_roles(db, &user, assigned).await?;
+    }
+
+    if let Some(permission_id) = &request.permission_id {
+        let grants = permission_user::Entity::find()
+            .filter(permission_user::Column::UserId.eq(user.id.clone()))
+            .all(db)
+            .await?;
+
+        let permissions = permissions::Entity::find()
+            .filter(permissions::Column::Id.is_in(
+                grants.iter()
+                    .map(|grant| grant.permission_id.clone())
+                    .chain([permission_id.clone()])
+                    .collect::<Vec<Id>>()
+            ))
+            .all(db)
+            .await?;
+
+        let mut granted = grants.iter()
+            .filter_map(|grant| {
+                permissions.iter()
+                    .find(|permission| permission.id.eq(&grant.permission_id))
+                    .map(|permission| (permission.clone(), Window::from_grant(grant)))
+            })
+            .collect::<Vec<_>>();
+
+        let requested = permissions.iter()
+            .find(|permission| permission.id.eq(permission_id))
+            .cloned()
+            .ok_or(DbErr::RecordNotFound(format!("permission {}", permission_id)))?;
+
+        granted.push((requested, window));
+
+        dao::user::sync_permissions(db, &user, granted).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use nightmare_common::time;
+use nightmare_common::models::{permissions, roles, users, Id};
+use sea_orm::{QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::dao;
+use crate::models::{access_review_campaigns, access_review_items, permission_user, role_user};
+
+pub const OPEN: &str = "open";
+pub const CLOSED: &str = "closed";
+
+pub const PENDING: &str = "pending";
+pub const APPROVED: &str = "approved";
+pub const REVOKED: &str = "revoked";
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<access_review_campaigns::Model> {
+    let id: Id = id.into();
+
+    access_review_campaigns::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn items(
+    db: &DatabaseConnection,
+    campaign: &access_review_campaigns::Model,
+) -> Result<Vec<access_review_items::Model>, DbErr> {
+    access_review_items::Entity::find()
+        .filter(access_review_items::Column::CampaignId.eq(campaign.id.clone()))
+        .order_by_asc(access_review_items::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn find_item<I: Into<Id>>(
+    db: &DatabaseConnection,
+    campaign: &access_review_campaigns::Model,
+    id: I,
+) -> Option<access_review_items::Model> {
+    let id: Id = id.into();
+
+    access_review_items::Entity::find_by_id(id)
+        .filter(access_review_items::Column::CampaignId.eq(campaign.id.clone()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+/// Open a campaign with one item per current assignment of the given
+/// roles and permissions
+pub async fn store<D: ConnectionTrait, N: ToString>(
+    db: &D,
+    name: N,
+    creator: &users::Model,
+    roles: Vec<roles::Model>,
+    permissions: Vec<permissions::Model>,
+) -> Result<access_review_campaigns::Model, DbErr> {
+    let campaign = access_review_campaigns::ActiveModel::from(access_review_campaigns::Model {
+        id: Uuid::new_v4().into(),
+        name: name.to_string(),
+        status: OPEN.to_string(),
+        created_by: creator.id.clone(),
+        closed_at: None,
+        created_at: time::now(),
+        updated_at: time::now(),
+    });
+
+    let campaign = campaign.insert(db).await?;
+
+    let assignments = role_user::Entity::find()
+        .filter(role_user::Column::RoleId.is_in(
+            roles.iter().map(|role| role.id.clone()).collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await?;
+
+    let grants = permission_user::Entity::find()
+        .filter(permission_user::Column::PermissionId.is_in(
+            permissions.iter().map(|permission| permission.id.clone()).collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await?;
+
+    let items = assignments.iter()
+        .map(|assignment| {
+            let mut model = access_review_items::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.campaign_id = Set(campaign.id.clone());
+            model.user_id = Set(assignment.user_id.clone());
+            model.role_id = Set(Some(assignment.role_id.clone()));
+            model.permission_id = Set(None);
+            model.assignment_id = Set(assignment.id.clone());
+            model.resource_type = Set(assignment.resource_type.clone());
+            model.resource_id = Set(assignment.resource_id.clone());
+            model.decision = Set(PENDING.to_string());
+            model.reviewer_id = Set(None);
+            model.comment = Set(None);
+            model.decided_at = Set(None);
+            model.created_at = Set(time::now());
+            model
+        })
+        .chain(grants.iter().map(|grant| {
+            let mut model = access_review_items::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.campaign_id = Set(campaign.id.clone());
+            model.user_id = Set(grant.user_id.clone());
+            model.role_id = Set(None);
+            model.permission_id = Set(Some(grant.permission_id.clone()));
+            model.assignment_id = Set(grant.id.clone());
+            model.resource_type = Set(None);
+            model.resource_id = Set(None);
+            model.decision = Set(PENDING.to_string());
+            model.reviewer_id = Set(None);
+            model.comment = Set(None);
+            model.decided_at = Set(None);
+            model.created_at = Set(time::now());
+            model
+        }))
+        .collect::<Vec<access_review_items::ActiveModel>>();
+
+    if !items.is_empty() {
+        access_review_items::Entity::insert_many(items).exec(db).await?;
+    }
+
+    Ok(campaign)
+}
+
+/// Record the reviewer's decision, revoked items lose the assignment
+/// they were created from
+pub async fn decide<D: ConnectionTrait>(
+    db: &D,
+    item: &access_review_items::Model,
+    reviewer: &users::Model,
+    decision: &str,
+    comment: Option<String>,
+) -> Result<access_review_items::Model, DbErr> {
+    if decision.eq(REVOKED) {
+        if item.role_id.is_some() {
+            dao::authorization::revoke_role(db, item.assignment_id.clone()).await?;
+        } else {
+            dao::authorization::revoke_permission(db, item.assignment_id.clone()).await?;
+        }
+    }
+
+    let mut model = access_review_items::ActiveModel::new();
+
+    model.id = Set(item.id.clone());
+    model.decision = Set(decision.to_string());
+    model.reviewer_id = Set(Some(reviewer.id.clone()));
+    model.comment = Set(comment);
+    model.decided_at = Set(Some(time::now()));
+    model.update(db).await
+}
+
+pub async fn close(
+    db: &DatabaseConnection,
+    campaign: &access_review_campaigns::Model,
+) -> Result<access_review_campaigns::Model, DbErr> {
+    let mut model = access_review_campaigns::ActiveModel::new();
+
+    model.id = Set(campaign.id.clone());
+    model.status = Set(CLOSED.to_string());
+    model.closed_at = Set(Some(time::now()));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
@@ -0,0 +1,91 @@
+use nightmare_common::time;
+use sea_orm::{QueryOrder, QuerySelect, Set};
+use sea_orm::prelude::*;
+
+use crate::models::{audit_chain, audit_checkpoints, audit_logs};
+
+/// Entries are only ever inserted, the table rejects updates and deletes
+pub async fn store<D: ConnectionTrait>(
+    db: &D,
+    entry: audit_logs::Model,
+) -> Result<audit_logs::Model, DbErr> {
+    audit_logs::ActiveModel::from(entry)
+        .insert(db)
+        .await
+}
+
+/// Last link of the chain, locked until the transaction ends when `lock`
+/// is set so appends are serialized
+pub async fn head<D: ConnectionTrait>(
+    db: &D,
+    lock: bool,
+) -> Result<audit_chain::Model, DbErr> {
+    let mut query = audit_chain::Entity::find();
+
+    if lock {
+        query = query.lock_exclusive();
+    }
+
+    query.one(db)
+        .await?
+        .ok_or(DbErr::RecordNotFound("audit chain head".to_string()))
+}
+
+pub async fn advance<D: ConnectionTrait>(
+    db: &D,
+    head: &audit_chain::Model,
+    entry: &audit_logs::Model,
+) -> Result<audit_chain::Model, DbErr> {
+    let mut model = audit_chain::ActiveModel::new();
+
+    model.id = Set(head.id.clone());
+    model.sequence = Set(entry.sequence.unwrap_or(head.sequence));
+    model.hash = Set(entry.hash.clone().unwrap_or(head.hash.clone()));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+/// Chained entries after the sequence, in chain order
+pub async fn chained(
+    db: &DatabaseConnection,
+    after: i64,
+    limit: u64,
+) -> Result<Vec<audit_logs::Model>, DbErr> {
+    audit_logs::Entity::find()
+        .filter(audit_logs::Column::Sequence.gt(after))
+        .order_by_asc(audit_logs::Column::Sequence)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+/// Entries written before the chain existed
+pub async fn unchained(db: &DatabaseConnection) -> Result<u64, DbErr> {
+    audit_logs::Entity::find()
+        .filter(audit_logs::Column::Sequence.is_null())
+        .count(db)
+        .await
+}
+
+pub async fn checkpoints(db: &DatabaseConnection) -> Result<Vec<audit_checkpoints::Model>, DbErr> {
+    audit_checkpoints::Entity::find()
+        .order_by_asc(audit_checkpoints::Column::Sequence)
+        .all(db)
+        .await
+}
+
+pub async fn last_checkpoint(db: &DatabaseConnection) -> Result<Option<audit_checkpoints::Model>, DbErr> {
+    audit_checkpoints::Entity::find()
+        .order_by_desc(audit_checkpoints::Column::Sequence)
+        .one(db)
+        .await
+}
+
+pub async fn store_checkpoint(
+    db: &DatabaseConnection,
+    checkpoint: audit_checkpoints::Model,
+) -> Result<audit_checkpoints::Model, DbErr> {
+    audit_checkpoints::ActiveModel::from(checkpoint)
+        .insert(db)
+        .await
+}
@@ -1,16 +1,24 @@
 use nightmare_common::log;
-use nightmare_common::models::{users, tokens, Timestamp, Id};
+use nightmare_common::models::{users, Timestamp, Id};
+use sea_orm::Set;
 use sea_orm::prelude::*;
 
-pub async fn generate(
-    db: &DatabaseConnection,
+use crate::models::{oauth_clients, tokens};
+
+pub async fn generate<D: ConnectionTrait>(
+    db: &D,
     user: &users::Model,
     expired_at: Option<Timestamp>,
+    organization_id: Option<Id>,
 ) -> Result<tokens::Model, DbErr> {
     let token = tokens::ActiveModel::from(tokens::Model {
         id: Uuid::new_v4().into(),
         user_id: user.id.clone(),
         expired_at,
+        organization_id,
+        impersonator_id: None,
+        oauth_client_id: None,
+        scopes: None,
     });
     
     match token.insert(db).await {
@@ -23,16 +31,108 @@ pub async fn generate(
     }
 }
 
-pub async fn delete<I: Into<Id>>(
-    db: &DatabaseConnection,
+pub async fn delete<D: ConnectionTrait, I: Into<Id>>(
+    db: &D,
     user_id: I,
 ) -> Result<(), DbErr> {
     let id: Id = user_id.into();
 
     tokens::Entity::delete_many()
         .filter(tokens::Column::UserId.eq(id))
+        .filter(tokens::Column::ImpersonatorId.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Token acting as `user` on behalf of `impersonator`
+pub async fn impersonate<D: ConnectionTrait>(
+    db: &D,
+    user: &users::Model,
+    impersonator: &users::Model,
+    expired_at: Timestamp,
+    organization_id: Option<Id>,
+) -> Result<tokens::Model, DbErr> {
+    let token = tokens::ActiveModel::from(tokens::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        expired_at: Some(expired_at),
+        organization_id,
+        impersonator_id: Some(impersonator.id.clone()),
+        oauth_client_id: None,
+        scopes: None,
+    });
+
+    token.insert(db).await
+}
+
+/// Access token issued to an oauth client, limited to the granted scopes
+pub async fn delegate<D: ConnectionTrait>(
+    db: &D,
+    user: &users::Model,
+    client: &oauth_clients::Model,
+    scopes: &[String],
+    expired_at: Timestamp,
+) -> Result<tokens::Model, DbErr> {
+    let token = tokens::ActiveModel::from(tokens::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        expired_at: Some(expired_at),
+        organization_id: None,
+        impersonator_id: None,
+        oauth_client_id: Some(client.id.clone()),
+        scopes: Some(serde_json::to_string(scopes).unwrap()),
+    });
+
+    token.insert(db).await
+}
+
+/// Revoke the access tokens an oauth client holds for the user
+pub async fn revoke_delegated(
+    db: &DatabaseConnection,
+    client_id: &Id,
+    user_id: &Id,
+) -> Result<(), DbErr> {
+    tokens::Entity::delete_many()
+        .filter(tokens::Column::OauthClientId.eq(client_id.clone()))
+        .filter(tokens::Column::UserId.eq(user_id.clone()))
         .exec(db)
         .await?;
 
     Ok(())
 }
+
+pub async fn revoke<D: ConnectionTrait>(
+    db: &D,
+    token: &tokens::Model,
+) -> Result<(), DbErr> {
+    tokens::Entity::delete_by_id(token.id.clone())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<tokens::Model> {
+    let id: Id = id.into();
+
+    tokens::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn switch(
+    db: &DatabaseConnection,
+    token: &tokens::Model,
+    organization_id: Option<Id>,
+) -> Result<tokens::Model, DbErr> {
+    let mut model = tokens::ActiveModel::from(token.clone());
+
+    model.organization_id = Set(organization_id);
+    model.update(db).await
+}
@@ -0,0 +1,327 @@
+use nightmare_common::time;
+use nightmare_common::models::{permission_role, permissions, roles, users, Id, Timestamp};
+use sea_orm::Condition;
+use sea_orm::prelude::*;
+
+use crate::dao;
+use crate::models::{group_permission, group_role, group_user, groups, permission_user, role_elevations, role_user};
+
+/// Resource type of role assignments scoped to an organization, they count
+/// as unscoped while the token works in that organization
+pub const ORGANIZATION: &str = "organization";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resource {
+    pub kind: String,
+    pub id: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Window {
+    pub starts_at: Option<Timestamp>,
+    pub expires_at: Option<Timestamp>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Source {
+    Direct,
+    Role(roles::Model),
+    Group(groups::Model),
+}
+
+#[derive(Clone, Debug)]
+pub struct Grant {
+    pub permission: permissions::Model,
+    pub source: Source,
+    pub resource: Option<Resource>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ScopedRole {
+    pub role: roles::Model,
+    pub resource: Resource,
+}
+
+#[derive(Clone, Debug)]
+pub struct Grants {
+    pub roles: Vec<roles::Model>,
+    pub scoped: Vec<ScopedRole>,
+    pub grants: Vec<Grant>,
+}
+
+impl Resource {
+    pub fn from_assignment(assignment: &role_user::Model) -> Option<Self> {
+        match (&assignment.resource_type, &assignment.resource_id) {
+            (Some(kind), Some(id)) => Some(Self {
+                kind: kind.clone(),
+                id: id.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Window {
+    pub fn from_assignment(assignment: &role_user::Model) -> Self {
+        Self {
+            starts_at: assignment.starts_at,
+            expires_at: assignment.expires_at,
+        }
+    }
+
+    pub fn from_grant(grant: &permission_user::Model) -> Self {
+        Self {
+            starts_at: grant.starts_at,
+            expires_at: grant.expires_at,
+        }
+    }
+}
+
+impl Grants {
+    /// Permissions granted regardless of resource
+    pub fn permissions(&self) -> Vec<permissions::Model> {
+        let mut permissions: Vec<permissions::Model> = vec![];
+
+        for grant in self.grants.iter().filter(|grant| grant.resource.is_none()) {
+            if !permissions.iter().any(|permission| permission.id.eq(&grant.permission.id)) {
+                permissions.push(grant.permission.clone());
+            }
+        }
+
+        permissions
+    }
+
+    /// Keep only the global grants within the scopes, roles are dropped so
+    /// nothing outside the scopes can be derived from them
+    pub fn restrict(&mut self, scopes: &[String]) {
+        self.roles.clear();
+        self.scoped.clear();
+        self.grants.retain(|grant| grant.resource.is_none() && scopes.contains(&grant.permission.code));
+    }
+
+    /// Grants of the permission code that apply to the given resource,
+    /// global grants always apply
+    pub fn find<C: AsRef<str>>(&self, code: C, resource: Option<&Resource>) -> Vec<&Grant> {
+        self.grants.iter()
+            .filter(|grant| grant.permission.code.eq(code.as_ref()))
+            .filter(|grant| grant.resource.is_none() || grant.resource.as_ref().eq(&resource))
+            .collect()
+    }
+}
+
+/// Grants without window or with the current time inside it
+pub fn active<C: ColumnTrait>(starts_at: C, expires_at: C) -> Condition {
+    let now = time::now();
+
+    Condition::all()
+        .add(Condition::any().add(starts_at.is_null()).add(starts_at.lte(now)))
+        .add(Condition::any().add(expires_at.is_null()).add(expires_at.gt(now)))
+}
+
+pub async fn grants(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    organization_id: Option<&Id>,
+) -> Result<Grants, DbErr> {
+    let assignments = role_user::Entity::find()
+        .filter(role_user::Column::UserId.eq(user.id.clone()))
+        .filter(active(role_user::Column::StartsAt, role_user::Column::ExpiresAt))
+        .all(db)
+        .await?;
+
+    let group_ids = group_user::Entity::find()
+        .filter(group_user::Column::UserId.eq(user.id.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|membership| membership.group_id)
+        .collect::<Vec<Id>>();
+
+    let groups = groups::Entity::find()
+        .filter(groups::Column::Id.is_in(group_ids.clone()))
+        .all(db)
+        .await?;
+
+    let group_roles = group_role::Entity::find()
+        .filter(group_role::Column::GroupId.is_in(group_ids.clone()))
+        .all(db)
+        .await?;
+
+    let elevations = role_elevations::Entity::find()
+        .filter(role_elevations::Column::UserId.eq(user.id.clone()))
+        .filter(dao::elevation::active())
+        .all(db)
+        .await?;
+
+    let role_ids = assignments.iter()
+        .map(|assignment| assignment.role_id.clone())
+        .chain(group_roles.iter().map(|grant| grant.role_id.clone()))
+        .chain(elevations.iter().map(|elevation| elevation.role_id.clone()))
+        .collect::<Vec<Id>>();
+
+    let assigned = roles::Entity::find()
+        .filter(roles::Column::Id.is_in(role_ids.clone()))
+        .all(db)
+        .await?;
+
+    let mut roles: Vec<roles::Model> = vec![];
+    let mut scoped = vec![];
+
+    for assignment in &assignments {
+        if let Some(role) = assigned.iter().find(|role| role.id.eq(&assignment.role_id)) {
+            match Resource::from_assignment(assignment) {
+                None => roles.push(role.clone()),
+                Some(resource) if resource.kind.eq(ORGANIZATION)
+                    && organization_id.map(|id| id.to_string()).eq(&Some(resource.id.clone())) => {
+                    roles.push(role.clone())
+                },
+                Some(resource) => scoped.push(ScopedRole {
+                    role: role.clone(),
+                    resource,
+                }),
+            }
+        }
+    }
+
+    for grant in &group_roles {
+        if let Some(role) = assigned.iter().find(|role| role.id.eq(&grant.role_id)) {
+            roles.push(role.clone());
+        }
+    }
+
+    for elevation in &elevations {
+        if let Some(role) = assigned.iter().find(|role| role.id.eq(&elevation.role_id)) {
+            roles.push(role.clone());
+        }
+    }
+
+    let mut unique: Vec<roles::Model> = vec![];
+
+    for role in roles {
+        if !unique.iter().any(|exist| exist.id.eq(&role.id)) {
+            unique.push(role);
+        }
+    }
+
+    let roles = unique;
+
+    let direct = permission_user::Entity::find()
+        .filter(permission_user::Column::UserId.eq(user.id.clone()))
+        .filter(active(permission_user::Column::StartsAt, permission_user::Column::ExpiresAt))
+        .all(db)
+        .await?;
+
+    let group_permissions = group_permission::Entity::find()
+        .filter(group_permission::Column::GroupId.is_in(group_ids))
+        .all(db)
+        .await?;
+
+    let inherited = permission_role::Entity::find()
+        .filter(permission_role::Column::RoleId.is_in(role_ids))
+        .all(db)
+        .await?;
+
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Id.is_in(
+            direct.iter()
+                .map(|grant| grant.permission_id.clone())
+                .chain(group_permissions.iter().map(|grant| grant.permission_id.clone()))
+                .chain(inherited.iter().map(|grant| grant.permission_id.clone()))
+                .collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await?;
+
+    let mut grants = vec![];
+
+    for grant in &direct {
+        if let Some(permission) = permissions.iter().find(|permission| permission.id.eq(&grant.permission_id)) {
+            grants.push(Grant {
+                permission: permission.clone(),
+                source: Source::Direct,
+                resource: None,
+            });
+        }
+    }
+
+    for grant in &group_permissions {
+        let permission = permissions.iter().find(|permission| permission.id.eq(&grant.permission_id));
+        let group = groups.iter().find(|group| group.id.eq(&grant.group_id));
+
+        if let (Some(permission), Some(group)) = (permission, group) {
+            grants.push(Grant {
+                permission: permission.clone(),
+                source: Source::Group(group.clone()),
+                resource: None,
+            });
+        }
+    }
+
+    for grant in &inherited {
+        let permission = permissions.iter().find(|permission| permission.id.eq(&grant.permission_id));
+
+        if let Some(permission) = permission {
+            for role in roles.iter().filter(|role| role.id.eq(&grant.role_id)) {
+                grants.push(Grant {
+                    permission: permission.clone(),
+                    source: Source::Role(role.clone()),
+                    resource: None,
+                });
+            }
+
+            for scope in scoped.iter().filter(|scope| scope.role.id.eq(&grant.role_id)) {
+                grants.push(Grant {
+                    permission: permission.clone(),
+                    source: Source::Role(scope.role.clone()),
+                    resource: Some(scope.resource.clone()),
+                });
+            }
+        }
+    }
+
+    Ok(Grants { roles, scoped, grants })
+}
+
+pub async fn lapsed_roles(
+    db: &DatabaseConnection,
+) -> Result<Vec<role_user::Model>, DbErr> {
+    role_user::Entity::find()
+        .filter(role_user::Column::ExpiresAt.lte(time::now()))
+        .all(db)
+        .await
+}
+
+pub async fn lapsed_permissions(
+    db: &DatabaseConnection,
+) -> Result<Vec<permission_user::Model>, DbErr> {
+    permission_user::Entity::find()
+        .filter(permission_user::Column::ExpiresAt.lte(time::now()))
+        .all(db)
+        .await
+}
+
+pub async fn revoke_role<D: ConnectionTrait, I: Into<Id>>(
+    db: &D,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    role_user::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn revoke_permission<D: ConnectionTrait, I: Into<Id>>(
+    db: &D,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    permission_user::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
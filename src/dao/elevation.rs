@@ -0,0 +1,176 @@
+use nightmare_common::time;
+use nightmare_common::models::{roles, users, Id};
+use sea_orm::{Condition, QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::models::{group_user, role_eligibilities, role_elevations};
+
+pub async fn eligibilities(
+    db: &DatabaseConnection,
+    role: &roles::Model,
+) -> Result<Vec<role_eligibilities::Model>, DbErr> {
+    role_eligibilities::Entity::find()
+        .filter(role_eligibilities::Column::RoleId.eq(role.id.clone()))
+        .all(db)
+        .await
+}
+
+pub async fn find_eligibility<I: Into<Id>>(
+    db: &DatabaseConnection,
+    role: &roles::Model,
+    id: I,
+) -> Option<role_eligibilities::Model> {
+    let id: Id = id.into();
+
+    role_eligibilities::Entity::find_by_id(id)
+        .filter(role_eligibilities::Column::RoleId.eq(role.id.clone()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn store_eligibility(
+    db: &DatabaseConnection,
+    role: &roles::Model,
+    user_id: Option<Id>,
+    group_id: Option<Id>,
+    max_minutes: i32,
+    require_reauthentication: bool,
+) -> Result<role_eligibilities::Model, DbErr> {
+    let eligibility = role_eligibilities::ActiveModel::from(role_eligibilities::Model {
+        id: Uuid::new_v4().into(),
+        role_id: role.id.clone(),
+        user_id,
+        group_id,
+        max_minutes,
+        require_reauthentication,
+        created_at: time::now(),
+        updated_at: time::now(),
+    });
+
+    eligibility.insert(db).await
+}
+
+pub async fn delete_eligibility<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    role_eligibilities::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Rule that makes the user eligible for the role, directly or through
+/// one of their groups, the longest allowed duration wins
+pub async fn eligible(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    role: &roles::Model,
+) -> Result<Option<role_eligibilities::Model>, DbErr> {
+    let group_ids = group_user::Entity::find()
+        .filter(group_user::Column::UserId.eq(user.id.clone()))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|membership| membership.group_id)
+        .collect::<Vec<Id>>();
+
+    let eligibility = role_eligibilities::Entity::find()
+        .filter(role_eligibilities::Column::RoleId.eq(role.id.clone()))
+        .filter(
+            Condition::any()
+                .add(role_eligibilities::Column::UserId.eq(user.id.clone()))
+                .add(role_eligibilities::Column::GroupId.is_in(group_ids))
+        )
+        .order_by_desc(role_eligibilities::Column::MaxMinutes)
+        .one(db)
+        .await?;
+
+    Ok(eligibility)
+}
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<role_elevations::Model> {
+    let id: Id = id.into();
+
+    role_elevations::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+/// Elevations currently in effect
+pub fn active() -> Condition {
+    let now = time::now();
+
+    Condition::all()
+        .add(role_elevations::Column::EndedAt.is_null())
+        .add(role_elevations::Column::StartsAt.lte(now))
+        .add(role_elevations::Column::ExpiresAt.gt(now))
+}
+
+pub async fn history(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Vec<role_elevations::Model>, DbErr> {
+    role_elevations::Entity::find()
+        .filter(role_elevations::Column::UserId.eq(user.id.clone()))
+        .order_by_desc(role_elevations::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn activate<D: ConnectionTrait, R: ToString>(
+    db: &D,
+    user: &users::Model,
+    role: &roles::Model,
+    eligibility: &role_eligibilities::Model,
+    reason: R,
+    minutes: i32,
+) -> Result<role_elevations::Model, DbErr> {
+    let now = time::now();
+    let elevation = role_elevations::ActiveModel::from(role_elevations::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        role_id: role.id.clone(),
+        eligibility_id: Some(eligibility.id.clone()),
+        reason: reason.to_string(),
+        starts_at: now,
+        expires_at: now + chrono::Duration::minutes(minutes as i64),
+        ended_at: None,
+        ended_by: None,
+        created_at: now,
+    });
+
+    elevation.insert(db).await
+}
+
+/// End the elevation, `actor` is empty when it simply ran out
+pub async fn end<D: ConnectionTrait>(
+    db: &D,
+    elevation: &role_elevations::Model,
+    actor: Option<Id>,
+) -> Result<role_elevations::Model, DbErr> {
+    let mut model = role_elevations::ActiveModel::new();
+
+    model.id = Set(elevation.id.clone());
+    model.ended_at = Set(Some(time::now().min(elevation.expires_at)));
+    model.ended_by = Set(actor);
+    model.update(db).await
+}
+
+pub async fn lapsed(
+    db: &DatabaseConnection,
+) -> Result<Vec<role_elevations::Model>, DbErr> {
+    role_elevations::Entity::find()
+        .filter(role_elevations::Column::EndedAt.is_null())
+        .filter(role_elevations::Column::ExpiresAt.lte(time::now()))
+        .all(db)
+        .await
+}
@@ -0,0 +1,89 @@
+use nightmare_common::time;
+use nightmare_common::models::{users, Id};
+use sea_orm::{QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::models::{external_identities, identity_providers};
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<external_identities::Model> {
+    let id: Id = id.into();
+
+    external_identities::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn find_by_subject<I: ToString, S: ToString>(
+    db: &DatabaseConnection,
+    issuer: I,
+    subject: S,
+) -> Option<external_identities::Model> {
+    external_identities::Entity::find()
+        .filter(external_identities::Column::Issuer.eq(issuer.to_string()))
+        .filter(external_identities::Column::Subject.eq(subject.to_string()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn all(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Vec<external_identities::Model>, DbErr> {
+    external_identities::Entity::find()
+        .filter(external_identities::Column::UserId.eq(user.id.clone()))
+        .order_by_asc(external_identities::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn link<D: ConnectionTrait, S: ToString>(
+    db: &D,
+    user: &users::Model,
+    provider: &identity_providers::Model,
+    subject: S,
+    email: Option<String>,
+) -> Result<external_identities::Model, DbErr> {
+    let identity = external_identities::ActiveModel::from(external_identities::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        provider_id: provider.id.clone(),
+        issuer: provider.issuer.clone(),
+        subject: subject.to_string(),
+        email,
+        last_login_at: None,
+        created_at: time::now(),
+    });
+
+    identity.insert(db).await
+}
+
+pub async fn touch(
+    db: &DatabaseConnection,
+    identity: &external_identities::Model,
+    email: Option<String>,
+) -> Result<external_identities::Model, DbErr> {
+    let mut model = external_identities::ActiveModel::new();
+
+    model.id = Set(identity.id.clone());
+    model.email = Set(email.or(identity.email.clone()));
+    model.last_login_at = Set(Some(time::now()));
+    model.update(db).await
+}
+
+pub async fn unlink<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    external_identities::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use nightmare_common::time;
+use nightmare_common::models::{permissions, roles, users, Id};
+use sea_orm::Set;
+use sea_orm::prelude::*;
+
+use crate::models::{group_permission, group_role, group_user, groups};
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<groups::Model> {
+    let id: Id = id.into();
+
+    groups::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn exist<C: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+) -> bool {
+    groups::Entity::find()
+        .filter(groups::Column::Code.eq(code.to_string()))
+        .count(db)
+        .await
+        .unwrap()
+        > 0
+}
+
+pub async fn has_member(
+    db: &DatabaseConnection,
+    group: &groups::Model,
+    user: &users::Model,
+) -> bool {
+    group_user::Entity::find()
+        .filter(group_user::Column::GroupId.eq(group.id.clone()))
+        .filter(group_user::Column::UserId.eq(user.id.clone()))
+        .count(db)
+        .await
+        .unwrap_or(0)
+        > 0
+}
+
+pub async fn store<C: ToString, N: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+    name: N,
+) -> Result<groups::Model, DbErr> {
+    let group = groups::ActiveModel::from(groups::Model {
+        id: Uuid::new_v4().into(),
+        code: code.to_string(),
+        name: name.to_string(),
+        created_at: time::now(),
+        updated_at: time::now(),
+    });
+
+    group.insert(db).await
+}
+
+pub async fn update<N: ToString, I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    name: N,
+) -> Result<groups::Model, DbErr> {
+    let mut group = groups::ActiveModel::new();
+
+    group.id = Set(id.into());
+    group.name = Set(name.to_string());
+    group.updated_at = Set(time::now());
+    group.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    groups::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Replace the group's users, returns the ids it held before
+pub async fn sync_users<D: ConnectionTrait>(
+    db: &D,
+    group: &groups::Model,
+    users: Vec<users::Model>,
+) -> Result<Vec<Id>, DbErr> {
+    let exists = group_user::Entity::find()
+        .filter(group_user::Column::GroupId.eq(group.id.clone()))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !users.iter().any(|user| user.id.eq(&exist.user_id)))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    let attached = users.iter()
+        .filter(|user| !exists.iter().any(|exist| exist.user_id.eq(&user.id)))
+        .map(|user| {
+            let mut model = group_user::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.group_id = Set(group.id.clone());
+            model.user_id = Set(user.id.clone());
+            model
+        })
+        .collect::<Vec<group_user::ActiveModel>>();
+
+    if !detached.is_empty() {
+        group_user::Entity::delete_many()
+            .filter(group_user::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    if !attached.is_empty() {
+        group_user::Entity::insert_many(attached).exec(db).await?;
+    }
+
+    Ok(exists.into_iter().map(|exist| exist.user_id).collect())
+}
+
+/// Replace the group's roles, returns the ids it held before
+pub async fn sync_roles<D: ConnectionTrait>(
+    db: &D,
+    group: &groups::Model,
+    roles: Vec<roles::Model>,
+) -> Result<Vec<Id>, DbErr> {
+    let exists = group_role::Entity::find()
+        .filter(group_role::Column::GroupId.eq(group.id.clone()))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !roles.iter().any(|role| role.id.eq(&exist.role_id)))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    let attached = roles.iter()
+        .filter(|role| !exists.iter().any(|exist| exist.role_id.eq(&role.id)))
+        .map(|role| {
+            let mut model = group_role::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.group_id = Set(group.id.clone());
+            model.role_id = Set(role.id.clone());
+            model
+        })
+        .collect::<Vec<group_role::ActiveModel>>();
+
+    if !detached.is_empty() {
+        group_role::Entity::delete_many()
+            .filter(group_role::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    if !attached.is_empty() {
+        group_role::Entity::insert_many(attached).exec(db).await?;
+    }
+
+    Ok(exists.into_iter().map(|exist| exist.role_id).collect())
+}
+
+/// Replace the group's permissions, returns the ids it held before
+pub async fn sync_permissions<D: ConnectionTrait>(
+    db: &D,
+    group: &groups::Model,
+    permissions: Vec<permissions::Model>,
+) -> Result<Vec<Id>, DbErr> {
+    let exists = group_permission::Entity::find()
+        .filter(group_permission::Column::GroupId.eq(group.id.clone()))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !permissions.iter().any(|permission| permission.id.eq(&exist.permission_id)))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    let attached = permissions.iter()
+        .filter(|permission| !exists.iter().any(|exist| exist.permission_id.eq(&permission.id)))
+        .map(|permission| {
+            let mut model = group_permission::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.group_id = Set(group.id.clone());
+            model.permission_id = Set(permission.id.clone());
+            model
+        })
+        .collect::<Vec<group_permission::ActiveModel>>();
+
+    if !detached.is_empty() {
+        group_permission::Entity::delete_many()
+            .filter(group_permission::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    if !attached.is_empty() {
+        group_permission::Entity::insert_many(attached).exec(db).await?;
+    }
+
+    Ok(exists.into_iter().map(|exist| exist.permission_id).collect())
+}
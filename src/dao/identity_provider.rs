@@ -0,0 +1,326 @@
+use nightmare_common::time;
+use nightmare_common::models::{roles, users, Id, Timestamp};
+use sea_orm::{QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::models::{federated_logins, identity_provider_role_mappings, identity_providers, role_user};
+
+pub const OIDC: &str = "oidc";
+pub const SAML: &str = "saml";
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<identity_providers::Model> {
+    let id: Id = id.into();
+
+    identity_providers::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn find_by_code<C: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+) -> Option<identity_providers::Model> {
+    identity_providers::Entity::find()
+        .filter(identity_providers::Column::Code.eq(code.to_string()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn exist<C: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+) -> bool {
+    identity_providers::Entity::find()
+        .filter(identity_providers::Column::Code.eq(code.to_string()))
+        .count(db)
+        .await
+        .unwrap()
+        > 0
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    provider: identity_providers::Model,
+) -> Result<identity_providers::Model, DbErr> {
+    identity_providers::ActiveModel::from(provider)
+        .insert(db)
+        .await
+}
+
+pub async fn update(
+    db: &DatabaseConnection,
+    provider: identity_providers::Model,
+) -> Result<identity_providers::Model, DbErr> {
+    let mut model = identity_providers::ActiveModel::new();
+
+    model.id = Set(provider.id);
+    model.name = Set(provider.name);
+    model.issuer = Set(provider.issuer);
+    model.client_id = Set(provider.client_id);
+    model.client_secret = Set(provider.client_secret);
+    model.scopes = Set(provider.scopes);
+    model.jit_provisioning = Set(provider.jit_provisioning);
+    model.enabled = Set(provider.enabled);
+    model.sso_url = Set(provider.sso_url);
+    model.certificate = Set(provider.certificate);
+    model.email_attribute = Set(provider.email_attribute);
+    model.name_attribute = Set(provider.name_attribute);
+    model.username_attribute = Set(provider.username_attribute);
+    model.algorithm = Set(provider.algorithm);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    identity_providers::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn mappings(
+    db: &DatabaseConnection,
+    provider: &identity_providers::Model,
+) -> Result<Vec<identity_provider_role_mappings::Model>, DbErr> {
+    identity_provider_role_mappings::Entity::find()
+        .filter(identity_provider_role_mappings::Column::ProviderId.eq(provider.id.clone()))
+        .order_by_asc(identity_provider_role_mappings::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn find_mapping<I: Into<Id>>(
+    db: &DatabaseConnection,
+    provider: &identity_providers::Model,
+    id: I,
+) -> Option<identity_provider_role_mappings::Model> {
+    let id: Id = id.into();
+
+    identity_provider_role_mappings::Entity::find_by_id(id)
+        .filter(identity_provider_role_mappings::Column::ProviderId.eq(provider.id.clone()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn store_mapping<C: ToString, V: ToString>(
+    db: &DatabaseConnection,
+    provider: &identity_providers::Model,
+    claim: C,
+    value: V,
+    role: &roles::Model,
+) -> Result<identity_provider_role_mappings::Model, DbErr> {
+    let mapping = identity_provider_role_mappings::ActiveModel::from(identity_provider_role_mappings::Model {
+        id: Uuid::new_v4().into(),
+        provider_id: provider.id.clone(),
+        claim: claim.to_string(),
+        value: value.to_string(),
+        role_id: role.id.clone(),
+        created_at: time::now(),
+    });
+
+    mapping.insert(db).await
+}
+
+pub async fn delete_mapping<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    identity_provider_role_mappings::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Remember the nonce and pkce verifier of a login in flight, the row id
+/// is the state sent to the provider
+pub async fn start<N: ToString, V: ToString>(
+    db: &DatabaseConnection,
+    provider: &identity_providers::Model,
+    nonce: N,
+    code_verifier: V,
+    expires_at: Timestamp,
+) -> Result<federated_logins::Model, DbErr> {
+    let login = federated_logins::ActiveModel::from(federated_logins::Model {
+        id: Uuid::new_v4().into(),
+        provider_id: provider.id.clone(),
+        nonce: nonce.to_string(),
+        code_verifier: code_verifier.to_string(),
+        expires_at,
+        created_at: time::now(),
+    });
+
+    login.insert(db).await
+}
+
+/// Take the login in flight, each state can only be used once
+pub async fn finish<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<federated_logins::Model> {
+    let id: Id = id.into();
+    let login = federated_logins::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)?;
+
+    // only the request that deletes the row gets to use it
+    let deleted = federated_logins::Entity::delete_many()
+        .filter(federated_logins::Column::Id.eq(login.id.clone()))
+        .exec(db)
+        .await
+        .ok()?;
+
+    match deleted.rows_affected {
+        1 => Some(login),
+        _ => None,
+    }
+}
+
+/// Give the user the roles its claims map to and take away mapped roles
+/// the claims no longer match, roles assigned some other way are left alone
+pub async fn sync_roles(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    managed: Vec<Id>,
+    granted: Vec<Id>,
+) -> Result<(), DbErr> {
+    let exists = role_user::Entity::find()
+        .filter(role_user::Column::UserId.eq(user.id.clone()))
+        .filter(role_user::Column::ResourceType.is_null())
+        .filter(role_user::Column::RoleId.is_in(managed))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !granted.contains(&exist.role_id))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    if !detached.is_empty() {
+        role_user::Entity::delete_many()
+            .filter(role_user::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    let mut attached = granted.into_iter()
+        .filter(|role| !exists.iter().any(|exist| exist.role_id.eq(role)))
+        .collect::<Vec<Id>>();
+
+    attached.sort_by_key(|role| role.to_string());
+    attached.dedup();
+
+    if !attached.is_empty() {
+        role_user::Entity::insert_many(
+            attached.into_iter().map(|role| {
+                let mut model = role_user::ActiveModel::new();
+
+                model.id = Set(Uuid::new_v4().into());
+                model.user_id = Set(user.id.clone());
+                model.role_id = Set(role);
+                model.resource_type = Set(None);
+                model.resource_id = Set(None);
+                model.starts_at = Set(None);
+                model.expires_at = Set(None);
+                model
+            }).collect::<Vec<role_user::ActiveModel>>()
+        ).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    fn assigned(user: &users::Model, role: &Id) -> role_user::ActiveModel {
+        role_user::ActiveModel::from(role_user::Model {
+            id: Uuid::new_v4().into(),
+            role_id: role.clone(),
+            user_id: user.id.clone(),
+            resource_type: None,
+            resource_id: None,
+            starts_at: None,
+            expires_at: None,
+        })
+    }
+
+    #[actix_web::test]
+    async fn sync_roles_revokes_mapped_roles_the_claims_lost() {
+        let db = testing::database(vec![testing::table(role_user::Entity)]).await;
+        let user = testing::user("alice", "secret");
+        let [kept, lost, manual, gained]: [Id; 4] = std::array::from_fn(|_| Uuid::new_v4().into());
+
+        for role in [&kept, &lost, &manual] {
+            assigned(&user, role).insert(&db).await.unwrap();
+        }
+
+        sync_roles(&db, &user, vec![kept.clone(), lost.clone(), gained.clone()], vec![kept.clone(), gained.clone()]).await.unwrap();
+
+        let mut roles = role_user::Entity::find()
+            .filter(role_user::Column::UserId.eq(user.id.clone()))
+            .all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|role| role.role_id.to_string())
+            .collect::<Vec<String>>();
+        let mut expected = vec![kept.to_string(), manual.to_string(), gained.to_string()];
+
+        roles.sort();
+        expected.sort();
+
+        assert_eq!(roles, expected);
+    }
+
+    #[actix_web::test]
+    async fn sync_roles_leaves_roles_alone_without_mappings() {
+        let db = testing::database(vec![testing::table(role_user::Entity)]).await;
+        let user = testing::user("bob", "secret");
+        let role: Id = Uuid::new_v4().into();
+
+        assigned(&user, &role).insert(&db).await.unwrap();
+        sync_roles(&db, &user, vec![], vec![]).await.unwrap();
+
+        let count = role_user::Entity::find()
+            .filter(role_user::Column::UserId.eq(user.id.clone()))
+            .count(&db)
+            .await
+            .unwrap();
+
+        assert_eq!(count, 1);
+    }
+
+    #[actix_web::test]
+    async fn finish_hands_out_a_login_once() {
+        let db = testing::database(vec![testing::table(federated_logins::Entity)]).await;
+        let login = federated_logins::ActiveModel::from(federated_logins::Model {
+            id: Uuid::new_v4().into(),
+            provider_id: Uuid::new_v4().into(),
+            nonce: "nonce".to_string(),
+            code_verifier: "verifier".to_string(),
+            expires_at: time::now(),
+            created_at: time::now(),
+        }).insert(&db).await.unwrap();
+
+        assert!(finish(&db, login.id.clone()).await.is_some());
+        assert!(finish(&db, login.id).await.is_none());
+    }
+}
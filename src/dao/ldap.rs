@@ -0,0 +1,157 @@
+use nightmare_common::time;
+use nightmare_common::models::{roles, users, Id};
+use sea_orm::{QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::models::{ldap_group_mappings, ldap_users, role_user};
+
+pub async fn find_by_dn<D: ToString>(
+    db: &DatabaseConnection,
+    dn: D,
+) -> Option<ldap_users::Model> {
+    ldap_users::Entity::find()
+        .filter(ldap_users::Column::Dn.eq(dn.to_string()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn find_by_user(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Option<ldap_users::Model>, DbErr> {
+    ldap_users::Entity::find()
+        .filter(ldap_users::Column::UserId.eq(user.id.clone()))
+        .one(db)
+        .await
+}
+
+pub async fn link<C: ConnectionTrait, D: ToString>(
+    db: &C,
+    user: &users::Model,
+    dn: D,
+) -> Result<ldap_users::Model, DbErr> {
+    let shadow = ldap_users::ActiveModel::from(ldap_users::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        dn: dn.to_string(),
+        synced_at: time::now(),
+        created_at: time::now(),
+    });
+
+    shadow.insert(db).await
+}
+
+pub async fn touch(
+    db: &DatabaseConnection,
+    shadow: &ldap_users::Model,
+) -> Result<ldap_users::Model, DbErr> {
+    let mut model = ldap_users::ActiveModel::new();
+
+    model.id = Set(shadow.id.clone());
+    model.synced_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn mappings(
+    db: &DatabaseConnection,
+) -> Result<Vec<ldap_group_mappings::Model>, DbErr> {
+    ldap_group_mappings::Entity::find()
+        .order_by_asc(ldap_group_mappings::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn find_mapping<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<ldap_group_mappings::Model> {
+    let id: Id = id.into();
+
+    ldap_group_mappings::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn store_mapping<G: ToString>(
+    db: &DatabaseConnection,
+    group_dn: G,
+    role: &roles::Model,
+) -> Result<ldap_group_mappings::Model, DbErr> {
+    let mapping = ldap_group_mappings::ActiveModel::from(ldap_group_mappings::Model {
+        id: Uuid::new_v4().into(),
+        group_dn: group_dn.to_string(),
+        role_id: role.id.clone(),
+        created_at: time::now(),
+    });
+
+    mapping.insert(db).await
+}
+
+pub async fn delete_mapping<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    ldap_group_mappings::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Make the user's global assignments of mapped roles match the granted
+/// ones, roles no group maps to are left alone
+pub async fn sync_roles(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    managed: Vec<Id>,
+    granted: Vec<Id>,
+) -> Result<(), DbErr> {
+    let exists = role_user::Entity::find()
+        .filter(role_user::Column::UserId.eq(user.id.clone()))
+        .filter(role_user::Column::ResourceType.is_null())
+        .filter(role_user::Column::RoleId.is_in(managed))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !granted.contains(&exist.role_id))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    if !detached.is_empty() {
+        role_user::Entity::delete_many()
+            .filter(role_user::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    let mut attached = granted.into_iter()
+        .filter(|role| !exists.iter().any(|exist| exist.role_id.eq(role)))
+        .collect::<Vec<Id>>();
+
+    attached.sort_by_key(|role| role.to_string());
+    attached.dedup();
+
+    if !attached.is_empty() {
+        role_user::Entity::insert_many(
+            attached.into_iter().map(|role| {
+                let mut model = role_user::ActiveModel::new();
+
+                model.id = Set(Uuid::new_v4().into());
+                model.user_id = Set(user.id.clone());
+                model.role_id = Set(role);
+                model.resource_type = Set(None);
+                model.resource_id = Set(None);
+                model.starts_at = Set(None);
+                model.expires_at = Set(None);
+                model
+            }).collect::<Vec<role_user::ActiveModel>>()
+        ).exec(db).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use nightmare_common::time;
+use nightmare_common::models::{users, Id, Timestamp};
+use sea_orm::prelude::*;
+
+use crate::models::{oauth_authorization_codes, oauth_clients, oauth_refresh_tokens};
+
+pub async fn find_code<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<oauth_authorization_codes::Model> {
+    let id: Id = id.into();
+
+    oauth_authorization_codes::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn store_code<D: ConnectionTrait>(
+    db: &D,
+    code: oauth_authorization_codes::Model,
+) -> Result<oauth_authorization_codes::Model, DbErr> {
+    oauth_authorization_codes::ActiveModel::from(code)
+        .insert(db)
+        .await
+}
+
+/// Mark the code as used, codes are single use. False when another request
+/// already redeemed it
+pub async fn consume_code(
+    db: &DatabaseConnection,
+    code: &oauth_authorization_codes::Model,
+) -> Result<bool, DbErr> {
+    let consumed = oauth_authorization_codes::Entity::update_many()
+        .col_expr(oauth_authorization_codes::Column::UsedAt, Expr::value(time::now()))
+        .filter(oauth_authorization_codes::Column::Id.eq(code.id.clone()))
+        .filter(oauth_authorization_codes::Column::UsedAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(consumed.rows_affected > 0)
+}
+
+pub async fn find_refresh_token<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<oauth_refresh_tokens::Model> {
+    let id: Id = id.into();
+
+    oauth_refresh_tokens::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn store_refresh_token<D: ConnectionTrait>(
+    db: &D,
+    client: &oauth_clients::Model,
+    user: &users::Model,
+    scopes: &[String],
+    expires_at: Timestamp,
+) -> Result<oauth_refresh_tokens::Model, DbErr> {
+    let token = oauth_refresh_tokens::ActiveModel::from(oauth_refresh_tokens::Model {
+        id: Uuid::new_v4().into(),
+        oauth_client_id: client.id.clone(),
+        user_id: user.id.clone(),
+        scopes: serde_json::to_string(scopes).unwrap(),
+        expires_at,
+        revoked_at: None,
+        created_at: time::now(),
+    });
+
+    token.insert(db).await
+}
+
+/// Revoke the refresh token, false when another request already revoked it
+pub async fn revoke_refresh_token<D: ConnectionTrait>(
+    db: &D,
+    token: &oauth_refresh_tokens::Model,
+) -> Result<bool, DbErr> {
+    let revoked = oauth_refresh_tokens::Entity::update_many()
+        .col_expr(oauth_refresh_tokens::Column::RevokedAt, Expr::value(time::now()))
+        .filter(oauth_refresh_tokens::Column::Id.eq(token.id.clone()))
+        .filter(oauth_refresh_tokens::Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(revoked.rows_affected > 0)
+}
+
+/// Revoke every refresh token the client holds for the user, used when a
+/// revoked refresh token is presented again
+pub async fn revoke_refresh_tokens(
+    db: &DatabaseConnection,
+    client_id: &Id,
+    user_id: &Id,
+) -> Result<(), DbErr> {
+    oauth_refresh_tokens::Entity::update_many()
+        .col_expr(oauth_refresh_tokens::Column::RevokedAt, Expr::value(time::now()))
+        .filter(oauth_refresh_tokens::Column::OauthClientId.eq(client_id.clone()))
+        .filter(oauth_refresh_tokens::Column::UserId.eq(user_id.clone()))
+        .filter(oauth_refresh_tokens::Column::RevokedAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing;
+
+    #[actix_web::test]
+    async fn refresh_tokens_are_revoked_once() {
+        let db = testing::database(vec![testing::table(oauth_refresh_tokens::Entity)]).await;
+        let user = testing::user("alice", "secret");
+        let client = oauth_clients::Model {
+            id: Uuid::new_v4().into(),
+            name: "dashboard".to_string(),
+            client_id: "dashboard".to_string(),
+            client_secret: None,
+            confidential: false,
+            redirect_uris: "[]".to_string(),
+            scopes: "[]".to_string(),
+            created_at: time::now(),
+            updated_at: time::now(),
+            post_logout_redirect_uris: None,
+        };
+        let token = store_refresh_token(&db, &client, &user, &["openid".to_string()], time::now()).await.unwrap();
+
+        assert!(revoke_refresh_token(&db, &token).await.unwrap());
+        assert!(!revoke_refresh_token(&db, &token).await.unwrap());
+        assert!(find_refresh_token(&db, token.id).await.unwrap().revoked_at.is_some());
+    }
+}
@@ -0,0 +1,83 @@
+use nightmare_common::time;
+use nightmare_common::models::Id;
+use sea_orm::Set;
+use sea_orm::prelude::*;
+
+use crate::models::oauth_clients;
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<oauth_clients::Model> {
+    let id: Id = id.into();
+
+    oauth_clients::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn find_by_client_id<C: ToString>(
+    db: &DatabaseConnection,
+    client_id: C,
+) -> Option<oauth_clients::Model> {
+    oauth_clients::Entity::find()
+        .filter(oauth_clients::Column::ClientId.eq(client_id.to_string()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    client: oauth_clients::Model,
+) -> Result<oauth_clients::Model, DbErr> {
+    oauth_clients::ActiveModel::from(client)
+        .insert(db)
+        .await
+}
+
+pub async fn update<I: Into<Id>, N: ToString>(
+    db: &DatabaseConnection,
+    id: I,
+    name: N,
+    redirect_uris: Vec<String>,
+    scopes: Vec<String>,
+    post_logout_redirect_uris: Vec<String>,
+) -> Result<oauth_clients::Model, DbErr> {
+    let mut client = oauth_clients::ActiveModel::new();
+
+    client.id = Set(id.into());
+    client.name = Set(name.to_string());
+    client.redirect_uris = Set(serde_json::to_string(&redirect_uris).unwrap());
+    client.scopes = Set(serde_json::to_string(&scopes).unwrap());
+    client.post_logout_redirect_uris = Set(Some(serde_json::to_string(&post_logout_redirect_uris).unwrap()));
+    client.updated_at = Set(time::now());
+    client.update(db).await
+}
+
+pub async fn rotate_secret(
+    db: &DatabaseConnection,
+    client: &oauth_clients::Model,
+    client_secret: String,
+) -> Result<oauth_clients::Model, DbErr> {
+    let mut model = oauth_clients::ActiveModel::new();
+
+    model.id = Set(client.id.clone());
+    model.client_secret = Set(Some(client_secret));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    oauth_clients::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
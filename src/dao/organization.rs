@@ -0,0 +1,303 @@
+use nightmare_common::time;
+use nightmare_common::models::{permissions, roles, users, Id};
+use sea_orm::{Condition, Set};
+use sea_orm::prelude::*;
+use sea_query::Query;
+
+use crate::models::{organization_permission, organization_role, organization_user, organizations};
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<organizations::Model> {
+    let id: Id = id.into();
+
+    organizations::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn exist<C: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+) -> bool {
+    organizations::Entity::find()
+        .filter(organizations::Column::Code.eq(code.to_string()))
+        .count(db)
+        .await
+        .unwrap()
+        > 0
+}
+
+pub async fn store<C: ToString, N: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+    name: N,
+) -> Result<organizations::Model, DbErr> {
+    let organization = organizations::ActiveModel::from(organizations::Model {
+        id: Uuid::new_v4().into(),
+        code: code.to_string(),
+        name: name.to_string(),
+        created_at: time::now(),
+        updated_at: time::now(),
+    });
+
+    organization.insert(db).await
+}
+
+pub async fn update<N: ToString, I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    name: N,
+) -> Result<organizations::Model, DbErr> {
+    let mut organization = organizations::ActiveModel::new();
+
+    organization.id = Set(id.into());
+    organization.name = Set(name.to_string());
+    organization.updated_at = Set(time::now());
+    organization.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    organizations::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn is_member<D: ConnectionTrait>(
+    db: &D,
+    organization_id: &Id,
+    user_id: &Id,
+) -> bool {
+    organization_user::Entity::find()
+        .filter(organization_user::Column::OrganizationId.eq(organization_id.clone()))
+        .filter(organization_user::Column::UserId.eq(user_id.clone()))
+        .count(db)
+        .await
+        .unwrap_or(0)
+        > 0
+}
+
+pub async fn memberships(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Vec<organizations::Model>, DbErr> {
+    organizations::Entity::find()
+        .filter(organizations::Column::Id.in_subquery(
+            Query::select()
+                .column(organization_user::Column::OrganizationId)
+                .from(organization_user::Entity)
+                .and_where(organization_user::Column::UserId.eq(user.id.clone()))
+                .to_owned()
+        ))
+        .all(db)
+        .await
+}
+
+pub async fn join<D: ConnectionTrait>(
+    db: &D,
+    organization_id: &Id,
+    user_id: &Id,
+) -> Result<(), DbErr> {
+    if is_member(db, organization_id, user_id).await {
+        return Ok(())
+    }
+
+    organization_user::ActiveModel::from(organization_user::Model {
+        id: Uuid::new_v4().into(),
+        organization_id: organization_id.clone(),
+        user_id: user_id.clone(),
+    }).insert(db).await?;
+
+    Ok(())
+}
+
+pub async fn sync_users(
+    db: &DatabaseConnection,
+    organization: &organizations::Model,
+    users: Vec<users::Model>,
+) -> Result<(), DbErr> {
+    let exists = organization_user::Entity::find()
+        .filter(organization_user::Column::OrganizationId.eq(organization.id.clone()))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !users.iter().any(|user| user.id.eq(&exist.user_id)))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    let attached = users.iter()
+        .filter(|user| !exists.iter().any(|exist| exist.user_id.eq(&user.id)))
+        .map(|user| {
+            let mut model = organization_user::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.organization_id = Set(organization.id.clone());
+            model.user_id = Set(user.id.clone());
+            model
+        })
+        .collect::<Vec<organization_user::ActiveModel>>();
+
+    if !detached.is_empty() {
+        organization_user::Entity::delete_many()
+            .filter(organization_user::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    if !attached.is_empty() {
+        organization_user::Entity::insert_many(attached).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn own_role<D: ConnectionTrait>(
+    db: &D,
+    organization_id: &Id,
+    role: &roles::Model,
+) -> Result<(), DbErr> {
+    organization_role::ActiveModel::from(organization_role::Model {
+        id: Uuid::new_v4().into(),
+        organization_id: organization_id.clone(),
+        role_id: role.id.clone(),
+    }).insert(db).await?;
+
+    Ok(())
+}
+
+pub async fn own_permission<D: ConnectionTrait>(
+    db: &D,
+    organization_id: &Id,
+    permission: &permissions::Model,
+) -> Result<(), DbErr> {
+    organization_permission::ActiveModel::from(organization_permission::Model {
+        id: Uuid::new_v4().into(),
+        organization_id: organization_id.clone(),
+        permission_id: permission.id.clone(),
+    }).insert(db).await?;
+
+    Ok(())
+}
+
+/// Which tenants a request reaches
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scope {
+    /// Every tenant, only for callers holding the platform permission
+    Platform,
+    Organization(Id),
+    /// Neither an organization nor the platform permission, nothing is visible
+    Nowhere,
+}
+
+impl Scope {
+    pub fn organization_id(&self) -> Option<Id> {
+        match self {
+            Self::Organization(organization_id) => Some(organization_id.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Shared roles plus the roles owned by the organization
+pub fn visible_roles(scope: Scope) -> Condition {
+    match scope {
+        Scope::Platform => Condition::all(),
+        Scope::Nowhere => Condition::any(),
+        Scope::Organization(organization_id) => Condition::any()
+            .add(roles::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(organization_role::Column::RoleId)
+                    .from(organization_role::Entity)
+                    .to_owned()
+            ))
+            .add(roles::Column::Id.in_subquery(
+                Query::select()
+                    .column(organization_role::Column::RoleId)
+                    .from(organization_role::Entity)
+                    .and_where(organization_role::Column::OrganizationId.eq(organization_id))
+                    .to_owned()
+            )),
+    }
+}
+
+/// Roles the organization owns, shared roles are only changed on the
+/// platform scope
+pub fn owned_roles(scope: Scope) -> Condition {
+    match scope {
+        Scope::Platform => Condition::all(),
+        Scope::Nowhere => Condition::any(),
+        Scope::Organization(organization_id) => Condition::all()
+            .add(roles::Column::Id.in_subquery(
+                Query::select()
+                    .column(organization_role::Column::RoleId)
+                    .from(organization_role::Entity)
+                    .and_where(organization_role::Column::OrganizationId.eq(organization_id))
+                    .to_owned()
+            )),
+    }
+}
+
+/// Shared permissions plus the permissions owned by the organization
+pub fn visible_permissions(scope: Scope) -> Condition {
+    match scope {
+        Scope::Platform => Condition::all(),
+        Scope::Nowhere => Condition::any(),
+        Scope::Organization(organization_id) => Condition::any()
+            .add(permissions::Column::Id.not_in_subquery(
+                Query::select()
+                    .column(organization_permission::Column::PermissionId)
+                    .from(organization_permission::Entity)
+                    .to_owned()
+            ))
+            .add(permissions::Column::Id.in_subquery(
+                Query::select()
+                    .column(organization_permission::Column::PermissionId)
+                    .from(organization_permission::Entity)
+                    .and_where(organization_permission::Column::OrganizationId.eq(organization_id))
+                    .to_owned()
+            )),
+    }
+}
+
+/// Permissions the organization owns, shared permissions are only changed
+/// on the platform scope
+pub fn owned_permissions(scope: Scope) -> Condition {
+    match scope {
+        Scope::Platform => Condition::all(),
+        Scope::Nowhere => Condition::any(),
+        Scope::Organization(organization_id) => Condition::all()
+            .add(permissions::Column::Id.in_subquery(
+                Query::select()
+                    .column(organization_permission::Column::PermissionId)
+                    .from(organization_permission::Entity)
+                    .and_where(organization_permission::Column::OrganizationId.eq(organization_id))
+                    .to_owned()
+            )),
+    }
+}
+
+/// Members of the organization
+pub fn members(scope: Scope) -> Condition {
+    match scope {
+        Scope::Platform => Condition::all(),
+        Scope::Nowhere => Condition::any(),
+        Scope::Organization(organization_id) => Condition::all()
+            .add(users::Column::Id.in_subquery(
+                Query::select()
+                    .column(organization_user::Column::UserId)
+                    .from(organization_user::Entity)
+                    .and_where(organization_user::Column::OrganizationId.eq(organization_id))
+                    .to_owned()
+            )),
+    }
+}
@@ -0,0 +1,81 @@
+use nightmare_common::time;
+use nightmare_common::models::Timestamp;
+use sea_orm::{QueryOrder, QuerySelect, Set};
+use sea_orm::prelude::*;
+use serde_json::Value;
+
+use crate::models::outbox_events;
+
+/// Record an event, meant to run on the transaction of the change it
+/// describes so it's only published when the change is committed
+pub async fn store<D: ConnectionTrait, N: ToString>(
+    db: &D,
+    name: N,
+    version: i32,
+    payload: &Value,
+) -> Result<outbox_events::Model, DbErr> {
+    let event = outbox_events::ActiveModel::from(outbox_events::Model {
+        id: Uuid::new_v4().into(),
+        name: name.to_string(),
+        version,
+        payload: payload.to_string(),
+        attempts: 0,
+        last_error: None,
+        published_at: None,
+        created_at: time::now(),
+    });
+
+    event.insert(db).await
+}
+
+/// Unpublished events, oldest first
+pub async fn pending(
+    db: &DatabaseConnection,
+    limit: u64,
+) -> Result<Vec<outbox_events::Model>, DbErr> {
+    outbox_events::Entity::find()
+        .filter(outbox_events::Column::PublishedAt.is_null())
+        .order_by_asc(outbox_events::Column::CreatedAt)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+pub async fn published(
+    db: &DatabaseConnection,
+    event: &outbox_events::Model,
+) -> Result<outbox_events::Model, DbErr> {
+    let mut model = outbox_events::ActiveModel::new();
+
+    model.id = Set(event.id.clone());
+    model.attempts = Set(event.attempts + 1);
+    model.last_error = Set(None);
+    model.published_at = Set(Some(time::now()));
+    model.update(db).await
+}
+
+pub async fn failed<E: ToString>(
+    db: &DatabaseConnection,
+    event: &outbox_events::Model,
+    error: E,
+) -> Result<outbox_events::Model, DbErr> {
+    let mut model = outbox_events::ActiveModel::new();
+
+    model.id = Set(event.id.clone());
+    model.attempts = Set(event.attempts + 1);
+    model.last_error = Set(Some(error.to_string()));
+    model.update(db).await
+}
+
+/// Remove events published before the cutoff
+pub async fn prune(
+    db: &DatabaseConnection,
+    before: Timestamp,
+) -> Result<u64, DbErr> {
+    let result = outbox_events::Entity::delete_many()
+        .filter(outbox_events::Column::PublishedAt.lt(before))
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected)
+}
@@ -0,0 +1,88 @@
+use nightmare_common::time;
+use nightmare_common::models::{users, Timestamp};
+use sea_orm::{QueryOrder, QuerySelect};
+use sea_orm::prelude::*;
+use sea_query::{Alias, Expr};
+
+use crate::models::password_histories;
+
+/// Not part of the shared users model, read and written by name
+const CHANGED_AT: &str = "password_changed_at";
+
+/// When the current password was set, none for users that predate the
+/// column and were never migrated
+pub async fn changed_at(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Option<Timestamp>, DbErr> {
+    let changed_at = users::Entity::find_by_id(user.id.clone())
+        .select_only()
+        .column_as(Expr::col(Alias::new(CHANGED_AT)), CHANGED_AT)
+        .into_tuple::<Option<Timestamp>>()
+        .one(db)
+        .await?;
+
+    Ok(changed_at.flatten())
+}
+
+/// Newest hashes the user had first, the current one included
+pub async fn histories(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    limit: u64,
+) -> Result<Vec<password_histories::Model>, DbErr> {
+    password_histories::Entity::find()
+        .filter(password_histories::Column::UserId.eq(user.id.clone()))
+        .order_by_desc(password_histories::Column::CreatedAt)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+/// Records the hash the user now has and restarts the expiry period, only
+/// the newest `keep` hashes are kept
+pub async fn changed<D: ConnectionTrait>(
+    db: &D,
+    user: &users::Model,
+    keep: usize,
+) -> Result<(), DbErr> {
+    let now = time::now();
+
+    users::Entity::update_many()
+        .col_expr(Alias::new(CHANGED_AT), Expr::value(now))
+        .filter(users::Column::Id.eq(user.id.clone()))
+        .exec(db)
+        .await?;
+
+    if keep.eq(&0) {
+        return Ok(())
+    }
+
+    password_histories::ActiveModel::from(password_histories::Model {
+        id: Uuid::new_v4().into(),
+        user_id: user.id.clone(),
+        password: user.password.clone(),
+        created_at: now,
+    })
+        .insert(db)
+        .await?;
+
+    let stale = password_histories::Entity::find()
+        .filter(password_histories::Column::UserId.eq(user.id.clone()))
+        .order_by_desc(password_histories::Column::CreatedAt)
+        .all(db)
+        .await?
+        .into_iter()
+        .skip(keep)
+        .map(|history| history.id)
+        .collect::<Vec<_>>();
+
+    if !stale.is_empty() {
+        password_histories::Entity::delete_many()
+            .filter(password_histories::Column::Id.is_in(stale))
+            .exec(db)
+            .await?;
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use nightmare_common::models::Id;
 use nightmare_common::models::permissions;
-use sea_orm::Set;
+use sea_orm::{Condition, Set};
 use sea_orm::prelude::*;
 
 pub async fn find<I: Into<Id>>(
@@ -14,6 +14,20 @@ pub async fn find<I: Into<Id>>(
         .unwrap_or(None)
 }
 
+/// Like `find`, limited by a tenant condition of `dao::organization`
+pub async fn find_within<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    condition: Condition,
+) -> Option<permissions::Model> {
+    let id: Id = id.into();
+    permissions::Entity::find_by_id(id)
+        .filter(condition)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
 pub async fn exist<C: ToString>(
     db: &DatabaseConnection,
     code: C,
@@ -26,8 +40,8 @@ pub async fn exist<C: ToString>(
         > 0
 }
 
-pub async fn store<C: ToString, N: ToString>(
-    db: &DatabaseConnection,
+pub async fn store<D: ConnectionTrait, C: ToString, N: ToString>(
+    db: &D,
     code: C,
     name: N,
 ) -> Result<permissions::Model, DbErr> {
@@ -40,8 +54,8 @@ pub async fn store<C: ToString, N: ToString>(
     permission.insert(db).await
 }
 
-pub async fn update<N: ToString, I: Into<Id>>(
-    db: &DatabaseConnection,
+pub async fn update<D: ConnectionTrait, N: ToString, I: Into<Id>>(
+    db: &D,
     id: I,
     name: N,
 ) -> Result<permissions::Model, DbErr> {
@@ -52,8 +66,8 @@ pub async fn update<N: ToString, I: Into<Id>>(
     permission.update(db).await
 }
 
-pub async fn delete<I: Into<Id>>(
-    db: &DatabaseConnection,
+pub async fn delete<D: ConnectionTrait, I: Into<Id>>(
+    db: &D,
     id: I,
 ) -> Result<(), DbErr> {
     let id: Id = id.into();
@@ -0,0 +1,80 @@
+use nightmare_common::time;
+use nightmare_common::models::{users, Id, Timestamp};
+use sea_orm::{QueryOrder, Set};
+use sea_orm::prelude::*;
+
+use crate::models::personal_access_tokens;
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<personal_access_tokens::Model> {
+    let id: Id = id.into();
+
+    personal_access_tokens::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn all(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Vec<personal_access_tokens::Model>, DbErr> {
+    personal_access_tokens::Entity::find()
+        .filter(personal_access_tokens::Column::UserId.eq(user.id.clone()))
+        .order_by_desc(personal_access_tokens::Column::CreatedAt)
+        .all(db)
+        .await
+}
+
+pub async fn store<D: ConnectionTrait, N: ToString>(
+    db: &D,
+    id: Id,
+    user: &users::Model,
+    organization_id: Option<Id>,
+    name: N,
+    secret: String,
+    scopes: Vec<String>,
+    expires_at: Option<Timestamp>,
+) -> Result<personal_access_tokens::Model, DbErr> {
+    let token = personal_access_tokens::ActiveModel::from(personal_access_tokens::Model {
+        id,
+        user_id: user.id.clone(),
+        organization_id,
+        name: name.to_string(),
+        secret,
+        scopes: serde_json::to_string(&scopes).unwrap_or("[]".to_string()),
+        expires_at,
+        last_used_at: None,
+        created_at: time::now(),
+    });
+
+    token.insert(db).await
+}
+
+pub async fn touch(
+    db: &DatabaseConnection,
+    token: &personal_access_tokens::Model,
+) -> Result<(), DbErr> {
+    let mut model = personal_access_tokens::ActiveModel::new();
+
+    model.id = Set(token.id.clone());
+    model.last_used_at = Set(Some(time::now()));
+    model.update(db).await?;
+
+    Ok(())
+}
+
+pub async fn delete<D: ConnectionTrait, I: Into<Id>>(
+    db: &D,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    personal_access_tokens::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
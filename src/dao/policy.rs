@@ -0,0 +1,90 @@
+use nightmare_common::time;
+use nightmare_common::models::{permissions, Id};
+use sea_orm::Set;
+use sea_orm::prelude::*;
+
+use crate::models::policies;
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<policies::Model> {
+    let id: Id = id.into();
+
+    policies::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn for_permissions<C: ToString>(
+    db: &DatabaseConnection,
+    codes: Vec<C>,
+) -> Result<Vec<(policies::Model, permissions::Model)>, DbErr> {
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Code.is_in(
+            codes.iter()
+                .map(|code| code.to_string())
+                .collect::<Vec<String>>()
+        ))
+        .all(db)
+        .await?;
+
+    let policies = policies::Entity::find()
+        .filter(policies::Column::PermissionId.is_in(
+            permissions.iter()
+                .map(|permission| permission.id.clone())
+                .collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await?;
+
+    Ok(policies.into_iter()
+        .filter_map(|policy| {
+            let permission = permissions.iter()
+                .find(|permission| permission.id.eq(&policy.permission_id))?
+                .clone();
+
+            Some((policy, permission))
+        })
+        .collect())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    policy: policies::Model,
+) -> Result<policies::Model, DbErr> {
+    policies::ActiveModel::from(policy)
+        .insert(db)
+        .await
+}
+
+pub async fn update(
+    db: &DatabaseConnection,
+    policy: &policies::Model,
+) -> Result<policies::Model, DbErr> {
+    let mut model = policies::ActiveModel::from(policy.clone());
+
+    model.name = Set(policy.name.clone());
+    model.effect = Set(policy.effect.clone());
+    model.permission_id = Set(policy.permission_id.clone());
+    model.role_id = Set(policy.role_id.clone());
+    model.resource_type = Set(policy.resource_type.clone());
+    model.conditions = Set(policy.conditions.clone());
+    model.dry_run = Set(policy.dry_run);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    policies::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use nightmare_common::models::Id;
-use nightmare_common::models::roles;
-use sea_orm::Set;
+use nightmare_common::models::{roles, users};
+use sea_orm::{Condition, Set};
 use sea_orm::prelude::*;
 
+use crate::models::{role_owner, role_user};
+
 pub async fn find<I: Into<Id>>(
     db: &DatabaseConnection,
     id: I,
@@ -15,6 +17,21 @@ pub async fn find<I: Into<Id>>(
         .unwrap_or(None)
 }
 
+/// Like `find`, limited by a tenant condition of `dao::organization`
+pub async fn find_within<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    condition: Condition,
+) -> Option<roles::Model> {
+    let id: Id = id.into();
+
+    roles::Entity::find_by_id(id)
+        .filter(condition)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
 pub async fn exist<C: ToString>(
     db: &DatabaseConnection,
     code: C,
@@ -27,8 +44,8 @@ pub async fn exist<C: ToString>(
         > 0
 }
 
-pub async fn store<C: ToString, N: ToString>(
-    db: &DatabaseConnection,
+pub async fn store<D: ConnectionTrait, C: ToString, N: ToString>(
+    db: &D,
     code: C,
     name: N,
 ) -> Result<roles::Model, DbErr> {
@@ -41,8 +58,8 @@ pub async fn store<C: ToString, N: ToString>(
     role.insert(db).await
 }
 
-pub async fn update<N: ToString, I: Into<Id>>(
-    db: &DatabaseConnection,
+pub async fn update<D: ConnectionTrait, N: ToString, I: Into<Id>>(
+    db: &D,
     id: I,
     name: N,
 ) -> Result<roles::Model, DbErr> {
@@ -53,8 +70,8 @@ pub async fn update<N: ToString, I: Into<Id>>(
     role.update(db).await
 }
 
-pub async fn delete<I: Into<Id>>(
-    db: &DatabaseConnection,
+pub async fn delete<D: ConnectionTrait, I: Into<Id>>(
+    db: &D,
     id: I,
 ) -> Result<(), DbErr> {
     let id: Id = id.into();
@@ -65,3 +82,167 @@ pub async fn delete<I: Into<Id>>(
 
     Ok(())
 }
+
+
+pub async fn is_owner(
+    db: &DatabaseConnection,
+    role_id: &Id,
+    user_id: &Id,
+) -> bool {
+    role_owner::Entity::find()
+        .filter(role_owner::Column::RoleId.eq(role_id.clone()))
+        .filter(role_owner::Column::UserId.eq(user_id.clone()))
+        .count(db)
+        .await
+        .unwrap_or(0)
+        > 0
+}
+
+pub async fn owned(
+    db: &DatabaseConnection,
+    user_id: &Id,
+) -> Result<Vec<Id>, DbErr> {
+    Ok(
+        role_owner::Entity::find()
+            .filter(role_owner::Column::UserId.eq(user_id.clone()))
+            .all(db)
+            .await?
+            .into_iter()
+            .map(|owner| owner.role_id)
+            .collect()
+    )
+}
+
+pub async fn sync_owners(
+    db: &DatabaseConnection,
+    role: &roles::Model,
+    users: Vec<users::Model>,
+) -> Result<(), DbErr> {
+    let exists = role_owner::Entity::find()
+        .filter(role_owner::Column::RoleId.eq(role.id.clone()))
+        .all(db)
+        .await?;
+
+    let detached = exists.iter()
+        .filter(|exist| !users.iter().any(|user| user.id.eq(&exist.user_id)))
+        .map(|exist| exist.id.clone())
+        .collect::<Vec<Id>>();
+
+    let attached = users.iter()
+        .filter(|user| !exists.iter().any(|exist| exist.user_id.eq(&user.id)))
+        .map(|user| {
+            let mut model = role_owner::ActiveModel::new();
+
+            model.id = Set(Uuid::new_v4().into());
+            model.role_id = Set(role.id.clone());
+            model.user_id = Set(user.id.clone());
+            model
+        })
+        .collect::<Vec<role_owner::ActiveModel>>();
+
+    if !detached.is_empty() {
+        role_owner::Entity::delete_many()
+            .filter(role_owner::Column::Id.is_in(detached))
+            .exec(db)
+            .await?;
+    }
+
+    if !attached.is_empty() {
+        role_owner::Entity::insert_many(attached).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+/// Users holding the role globally, scoped and time boxed assignments
+/// aren't memberships
+pub async fn members<D: ConnectionTrait>(
+    db: &D,
+    role: &roles::Model,
+) -> Result<Vec<users::Model>, DbErr> {
+    let assignments = role_user::Entity::find()
+        .filter(role_user::Column::RoleId.eq(role.id.clone()))
+        .filter(role_user::Column::ResourceType.is_null())
+        .filter(role_user::Column::StartsAt.is_null())
+        .filter(role_user::Column::ExpiresAt.is_null())
+        .all(db)
+        .await?;
+
+    users::Entity::find()
+        .filter(users::Column::Id.is_in(
+            assignments.into_iter().map(|assignment| assignment.user_id).collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await
+}
+
+/// Roles the user holds globally
+pub async fn assigned(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Result<Vec<roles::Model>, DbErr> {
+    let assignments = role_user::Entity::find()
+        .filter(role_user::Column::UserId.eq(user.id.clone()))
+        .filter(role_user::Column::ResourceType.is_null())
+        .filter(role_user::Column::StartsAt.is_null())
+        .filter(role_user::Column::ExpiresAt.is_null())
+        .all(db)
+        .await?;
+
+    roles::Entity::find()
+        .filter(roles::Column::Id.is_in(
+            assignments.into_iter().map(|assignment| assignment.role_id).collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await
+}
+
+pub async fn attach<D: ConnectionTrait>(
+    db: &D,
+    role: &roles::Model,
+    users: Vec<Id>,
+) -> Result<(), DbErr> {
+    let members = members(db, role).await?;
+    let mut attached = users.into_iter()
+        .filter(|user| !members.iter().any(|member| member.id.eq(user)))
+        .collect::<Vec<Id>>();
+
+    attached.sort_by_key(|user| user.to_string());
+    attached.dedup();
+
+    if !attached.is_empty() {
+        role_user::Entity::insert_many(
+            attached.into_iter().map(|user| {
+                let mut model = role_user::ActiveModel::new();
+
+                model.id = Set(Uuid::new_v4().into());
+                model.user_id = Set(user);
+                model.role_id = Set(role.id.clone());
+                model.resource_type = Set(None);
+                model.resource_id = Set(None);
+                model.starts_at = Set(None);
+                model.expires_at = Set(None);
+                model
+            }).collect::<Vec<role_user::ActiveModel>>()
+        ).exec(db).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn detach<D: ConnectionTrait>(
+    db: &D,
+    role: &roles::Model,
+    users: Vec<Id>,
+) -> Result<(), DbErr> {
+    role_user::Entity::delete_many()
+        .filter(role_user::Column::RoleId.eq(role.id.clone()))
+        .filter(role_user::Column::UserId.is_in(users))
+        .filter(role_user::Column::ResourceType.is_null())
+        .filter(role_user::Column::StartsAt.is_null())
+        .filter(role_user::Column::ExpiresAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
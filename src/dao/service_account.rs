@@ -0,0 +1,113 @@
+use nightmare_common::time;
+use nightmare_common::models::{users, Id};
+use sea_orm::Set;
+use sea_orm::prelude::*;
+
+use crate::models::service_accounts;
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<service_accounts::Model> {
+    let id: Id = id.into();
+
+    service_accounts::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn find_by_client_id<C: ToString>(
+    db: &DatabaseConnection,
+    client_id: C,
+) -> Option<service_accounts::Model> {
+    service_accounts::Entity::find()
+        .filter(service_accounts::Column::ClientId.eq(client_id.to_string()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn find_by_user(
+    db: &DatabaseConnection,
+    user: &users::Model,
+) -> Option<service_accounts::Model> {
+    service_accounts::Entity::find()
+        .filter(service_accounts::Column::UserId.eq(user.id.clone()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+pub async fn exist<C: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+) -> bool {
+    service_accounts::Entity::find()
+        .filter(service_accounts::Column::Code.eq(code.to_string()))
+        .count(db)
+        .await
+        .unwrap()
+        > 0
+}
+
+pub async fn store<D: ConnectionTrait>(
+    db: &D,
+    account: service_accounts::Model,
+) -> Result<service_accounts::Model, DbErr> {
+    service_accounts::ActiveModel::from(account)
+        .insert(db)
+        .await
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    description: Option<String>,
+) -> Result<service_accounts::Model, DbErr> {
+    let mut account = service_accounts::ActiveModel::new();
+
+    account.id = Set(id.into());
+    account.description = Set(description);
+    account.updated_at = Set(time::now());
+    account.update(db).await
+}
+
+pub async fn rotate_secret(
+    db: &DatabaseConnection,
+    account: &service_accounts::Model,
+    client_secret: String,
+) -> Result<service_accounts::Model, DbErr> {
+    let mut model = service_accounts::ActiveModel::new();
+
+    model.id = Set(account.id.clone());
+    model.client_secret = Set(client_secret);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn rotate_api_key(
+    db: &DatabaseConnection,
+    account: &service_accounts::Model,
+    api_key: String,
+) -> Result<service_accounts::Model, DbErr> {
+    let mut model = service_accounts::ActiveModel::new();
+
+    model.id = Set(account.id.clone());
+    model.api_key = Set(Some(api_key));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    service_accounts::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
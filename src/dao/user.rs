@@ -1,8 +1,11 @@
 use nightmare_common::time;
-use nightmare_common::models::{users, permissions, permission_user, role_user, roles};
+use nightmare_common::models::{users, permissions, roles};
 use sea_orm::{Set, Condition};
 use sea_orm::prelude::*;
 
+use crate::dao::authorization::{Resource, Window};
+use crate::models::{permission_user, role_user};
+
 type Id = nightmare_common::models::Id;
 
 pub async fn email_exist_except<T: AsRef<str>, I: Into<Id> + Clone>(
@@ -73,6 +76,21 @@ pub async fn find<I: Into<Id>>(
         .unwrap_or(None)
 }
 
+/// Like `find`, limited by a tenant condition of `dao::organization`
+pub async fn find_within<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+    condition: Condition,
+) -> Option<users::Model> {
+    let id = id.into();
+
+    users::Entity::find_by_id(id)
+        .filter(condition)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
 pub async fn find_by_email_or_username<T: ToString>(
     db: &DatabaseConnection,
     email_or_username: T,
@@ -88,8 +106,8 @@ pub async fn find_by_email_or_username<T: ToString>(
         .unwrap_or(None)
 }
 
-pub async fn store(
-    db: &DatabaseConnection,
+pub async fn store<D: ConnectionTrait>(
+    db: &D,
     user: users::Model,
 ) -> Result<users::Model, DbErr> {
     users::ActiveModel::from(user)
@@ -97,8 +115,8 @@ pub async fn store(
         .await
 }
 
-pub async fn update(
-    db: &DatabaseConnection,
+pub async fn update<D: ConnectionTrait>(
+    db: &D,
     user: &users::Model,
 ) -> Result<users::Model, DbErr> {
     let mut model = users::ActiveModel::from(user.clone());
@@ -114,8 +132,8 @@ pub async fn update(
     model.update(db).await
 }
 
-pub async fn delete(
-    db: &DatabaseConnection,
+pub async fn delete<D: ConnectionTrait>(
+    db: &D,
     user: &users::Model,
 ) -> Result<users::Model, DbErr> {
     let mut user = users::ActiveModel::from(user.clone());
@@ -124,10 +142,32 @@ pub async fn delete(
     user.update(db).await
 }
 
-pub async fn sync_permissions(
-    db: &DatabaseConnection,
+/// Direct permission grants of the user
+pub async fn permission_grants<D: ConnectionTrait>(
+    db: &D,
     user: &users::Model,
-    permissions: Vec<permissions::Model>,
+) -> Result<Vec<permission_user::Model>, DbErr> {
+    permission_user::Entity::find()
+        .filter(permission_user::Column::UserId.eq(user.id.clone()))
+        .all(db)
+        .await
+}
+
+/// Role assignments of the user
+pub async fn role_assignments<D: ConnectionTrait>(
+    db: &D,
+    user: &users::Model,
+) -> Result<Vec<role_user::Model>, DbErr> {
+    role_user::Entity::find()
+        .filter(role_user::Column::UserId.eq(user.id.clone()))
+        .all(db)
+        .await
+}
+
+pub async fn sync_permissions<D: ConnectionTrait>(
+    db: &D,
+    user: &users::Model,
+    permissions: Vec<(permissions::Model, Window)>,
 ) -> Result<(), DbErr> {
     let exists = permission_user::Entity::find()
         .filter(permission_user::Column::UserId.eq(user.id.clone()))
@@ -141,8 +181,8 @@ pub async fn sync_permissions(
     for exist in &exists {
         let mut detach = true;
 
-        for permission in &permissions {
-            if exist.permission_id.eq(&permission.id) {
+        for (permission, window) in &permissions {
+            if exist.permission_id.eq(&permission.id) && Window::from_grant(exist).eq(window) {
                 detach = false;
 
                 break;
@@ -154,11 +194,11 @@ pub async fn sync_permissions(
         }
     }
 
-    for permission in &permissions {
+    for (permission, window) in &permissions {
         let mut attach = true;
 
         for exist in &exists {
-            if permission.id.eq(&exist.permission_id) {
+            if permission.id.eq(&exist.permission_id) && Window::from_grant(exist).eq(window) {
                 attach = false;
 
                 break;
@@ -166,7 +206,7 @@ pub async fn sync_permissions(
         }
 
         if attach {
-            attached.push(permission.clone());
+            attached.push((permission.clone(), window.clone()));
         }
     }
 
@@ -183,12 +223,14 @@ pub async fn sync_permissions(
 
     if !attached.is_empty() {
         permission_user::Entity::insert_many(
-            attached.iter().map(|attach| {
+            attached.iter().map(|(permission, window)| {
                 let mut model = permission_user::ActiveModel::new();
     
                 model.id = Set(Uuid::new_v4().into());
                 model.user_id = Set(user.id.clone());
-                model.permission_id = Set(attach.id.clone());
+                model.permission_id = Set(permission.id.clone());
+                model.starts_at = Set(window.starts_at);
+                model.expires_at = Set(window.expires_at);
                 model
             }).collect::<Vec<permission_user::ActiveModel>>()
         ).exec(db).await?;
@@ -198,10 +240,10 @@ pub async fn sync_permissions(
 }
 
 
-pub async fn sync_roles(
-    db: &DatabaseConnection,
+pub async fn sync_roles<D: ConnectionTrait>(
+    db: &D,
     user: &users::Model,
-    roles: Vec<roles::Model>,
+    roles: Vec<(roles::Model, Option<Resource>, Window)>,
 ) -> Result<(), DbErr> {
     let exists = role_user::Entity::find()
         .filter(role_user::Column::UserId.eq(user.id.clone()))
@@ -215,8 +257,10 @@ pub async fn sync_roles(
     for exist in &exists {
         let mut detach = true;
 
-        for role in &roles {
-            if exist.role_id.eq(&role.id) {
+        for (role, resource, window) in &roles {
+            if exist.role_id.eq(&role.id)
+                && Resource::from_assignment(exist).eq(resource)
+                && Window::from_assignment(exist).eq(window) {
                 detach = false;
 
                 break;
@@ -228,11 +272,13 @@ pub async fn sync_roles(
         }
     }
 
-    for role in &roles {
+    for (role, resource, window) in &roles {
         let mut attach = true;
 
         for exist in &exists {
-            if role.id.eq(&exist.role_id) {
+            if role.id.eq(&exist.role_id)
+                && Resource::from_assignment(exist).eq(resource)
+                && Window::from_assignment(exist).eq(window) {
                 attach = false;
 
                 break;
@@ -240,7 +286,7 @@ pub async fn sync_roles(
         }
 
         if attach {
-            attached.push(role.clone());
+            attached.push((role.clone(), resource.clone(), window.clone()));
         }
     }
 
@@ -257,12 +303,16 @@ pub async fn sync_roles(
 
     if !attached.is_empty() {
         role_user::Entity::insert_many(
-            attached.iter().map(|attach| {
+            attached.iter().map(|(role, resource, window)| {
                 let mut model = role_user::ActiveModel::new();
-        
-                let id = Uuid::new_v4();
-                model.user_id = Set(id.into());
-                model.role_id = Set(attach.id.clone());
+
+                model.id = Set(Uuid::new_v4().into());
+                model.user_id = Set(user.id.clone());
+                model.role_id = Set(role.id.clone());
+                model.resource_type = Set(resource.as_ref().map(|resource| resource.kind.clone()));
+                model.resource_id = Set(resource.as_ref().map(|resource| resource.id.clone()));
+                model.starts_at = Set(window.starts_at);
+                model.expires_at = Set(window.expires_at);
                 model
             }).collect::<Vec<role_user::ActiveModel>>()
         ).exec(db).await?;
@@ -0,0 +1,208 @@
+use nightmare_common::time;
+use nightmare_common::models::{Id, Timestamp};
+use sea_orm::{QueryOrder, QuerySelect, Set};
+use sea_orm::prelude::*;
+
+use crate::models::{webhook_deliveries, webhooks};
+
+/// Waiting for its next attempt
+pub const PENDING: &str = "pending";
+/// Accepted by the receiver
+pub const DELIVERED: &str = "delivered";
+/// Gave up after the last retry, only a manual redeliver sends it again
+pub const DEAD: &str = "dead";
+
+/// Event types of the subscription, `*` matches every event and `user.*`
+/// every user event
+pub fn events(webhook: &webhooks::Model) -> Vec<String> {
+    webhook.events.split(',')
+        .map(str::trim)
+        .filter(|event| !event.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn subscribes(webhook: &webhooks::Model, event: &str) -> bool {
+    events(webhook).iter().any(|subscribed| match subscribed.strip_suffix('*') {
+        None => subscribed.eq(event),
+        Some(prefix) => event.starts_with(prefix),
+    })
+}
+
+pub async fn find<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Option<webhooks::Model> {
+    let id: Id = id.into();
+
+    webhooks::Entity::find_by_id(id)
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+/// Enabled webhooks subscribed to the event
+pub async fn subscribed<D: ConnectionTrait>(
+    db: &D,
+    event: &str,
+) -> Result<Vec<webhooks::Model>, DbErr> {
+    let webhooks = webhooks::Entity::find()
+        .filter(webhooks::Column::Enabled.eq(true))
+        .all(db)
+        .await?;
+
+    Ok(webhooks.into_iter().filter(|webhook| subscribes(webhook, event)).collect())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    webhook: webhooks::Model,
+) -> Result<webhooks::Model, DbErr> {
+    webhooks::ActiveModel::from(webhook)
+        .insert(db)
+        .await
+}
+
+pub async fn update(
+    db: &DatabaseConnection,
+    webhook: webhooks::Model,
+) -> Result<webhooks::Model, DbErr> {
+    let mut model = webhooks::ActiveModel::new();
+
+    model.id = Set(webhook.id);
+    model.url = Set(webhook.url);
+    model.description = Set(webhook.description);
+    model.events = Set(webhook.events);
+    model.secret = Set(webhook.secret);
+    model.enabled = Set(webhook.enabled);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    id: I,
+) -> Result<(), DbErr> {
+    let id: Id = id.into();
+
+    webhooks::Entity::delete_by_id(id)
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn find_delivery<I: Into<Id>>(
+    db: &DatabaseConnection,
+    webhook: &webhooks::Model,
+    id: I,
+) -> Option<webhook_deliveries::Model> {
+    let id: Id = id.into();
+
+    webhook_deliveries::Entity::find_by_id(id)
+        .filter(webhook_deliveries::Column::WebhookId.eq(webhook.id.clone()))
+        .one(db)
+        .await
+        .unwrap_or(None)
+}
+
+/// Queue the event for the webhook, the payload is the exact body that is
+/// signed and sent on every attempt
+pub async fn store_delivery<D: ConnectionTrait, E: ToString, P: ToString>(
+    db: &D,
+    webhook: &webhooks::Model,
+    event_id: Id,
+    event: E,
+    payload: P,
+) -> Result<webhook_deliveries::Model, DbErr> {
+    let delivery = webhook_deliveries::ActiveModel::from(webhook_deliveries::Model {
+        id: Uuid::new_v4().into(),
+        webhook_id: webhook.id.clone(),
+        event_id,
+        event: event.to_string(),
+        payload: payload.to_string(),
+        status: PENDING.to_string(),
+        attempts: 0,
+        response_status: None,
+        response_body: None,
+        last_error: None,
+        next_attempt_at: Some(time::now()),
+        delivered_at: None,
+        created_at: time::now(),
+        updated_at: time::now(),
+    });
+
+    delivery.insert(db).await
+}
+
+/// Pending deliveries whose next attempt is due, oldest first
+pub async fn due(
+    db: &DatabaseConnection,
+    limit: u64,
+) -> Result<Vec<webhook_deliveries::Model>, DbErr> {
+    webhook_deliveries::Entity::find()
+        .filter(webhook_deliveries::Column::Status.eq(PENDING))
+        .filter(webhook_deliveries::Column::NextAttemptAt.lte(time::now()))
+        .order_by_asc(webhook_deliveries::Column::CreatedAt)
+        .limit(limit)
+        .all(db)
+        .await
+}
+
+pub async fn delivered(
+    db: &DatabaseConnection,
+    delivery: &webhook_deliveries::Model,
+    response_status: i32,
+    response_body: Option<String>,
+) -> Result<webhook_deliveries::Model, DbErr> {
+    let mut model = webhook_deliveries::ActiveModel::new();
+
+    model.id = Set(delivery.id.clone());
+    model.status = Set(DELIVERED.to_string());
+    model.attempts = Set(delivery.attempts + 1);
+    model.response_status = Set(Some(response_status));
+    model.response_body = Set(response_body);
+    model.last_error = Set(None);
+    model.next_attempt_at = Set(None);
+    model.delivered_at = Set(Some(time::now()));
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+/// Record a failed attempt, without a next attempt the delivery is dead
+pub async fn failed<E: ToString>(
+    db: &DatabaseConnection,
+    delivery: &webhook_deliveries::Model,
+    response_status: Option<i32>,
+    response_body: Option<String>,
+    error: E,
+    next_attempt_at: Option<Timestamp>,
+) -> Result<webhook_deliveries::Model, DbErr> {
+    let mut model = webhook_deliveries::ActiveModel::new();
+
+    model.id = Set(delivery.id.clone());
+    model.status = Set(if next_attempt_at.is_some() { PENDING } else { DEAD }.to_string());
+    model.attempts = Set(delivery.attempts + 1);
+    model.response_status = Set(response_status);
+    model.response_body = Set(response_body);
+    model.last_error = Set(Some(error.to_string()));
+    model.next_attempt_at = Set(next_attempt_at);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
+
+/// Send the delivery again right away with a fresh retry schedule
+pub async fn redeliver(
+    db: &DatabaseConnection,
+    delivery: &webhook_deliveries::Model,
+) -> Result<webhook_deliveries::Model, DbErr> {
+    let mut model = webhook_deliveries::ActiveModel::new();
+
+    model.id = Set(delivery.id.clone());
+    model.status = Set(PENDING.to_string());
+    model.attempts = Set(0);
+    model.next_attempt_at = Set(Some(time::now()));
+    model.delivered_at = Set(None);
+    model.updated_at = Set(time::now());
+    model.update(db).await
+}
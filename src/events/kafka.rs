@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use rskafka::client::ClientBuilder;
+use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+use rskafka::record::Record;
+
+use crate::events::Event;
+use crate::events::publisher::Publisher;
+
+/// Events go to a single partition so consumers see them in the order
+/// they were committed
+const PARTITION: i32 = 0;
+
+pub struct Kafka {
+    brokers: Vec<String>,
+    topic: String,
+    client: Mutex<Option<Arc<PartitionClient>>>,
+}
+
+impl Kafka {
+    /// `KAFKA_BROKERS` is a comma separated list of `host:port`, events are
+    /// produced to `KAFKA_TOPIC`
+    pub fn from_env() -> Option<Self> {
+        let brokers = std::env::var("KAFKA_BROKERS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|broker| !broker.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<String>>();
+
+        if brokers.is_empty() {
+            return None
+        }
+
+        Some(Self {
+            brokers,
+            topic: std::env::var("KAFKA_TOPIC")
+                .ok()
+                .filter(|topic| !topic.trim().is_empty())
+                .unwrap_or("nightmare-auth.events".to_string()),
+            client: Mutex::new(None),
+        })
+    }
+
+    /// Connection is made on first use and kept until publishing fails
+    async fn partition(&self) -> Result<Arc<PartitionClient>, String> {
+        let cached = self.client.lock().map_err(|e| e.to_string())?.clone();
+
+        if let Some(partition) = cached {
+            return Ok(partition)
+        }
+
+        let client = ClientBuilder::new(self.brokers.clone())
+            .build()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let partition = client.partition_client(self.topic.clone(), PARTITION, UnknownTopicHandling::Retry)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let partition = Arc::new(partition);
+
+        *self.client.lock().map_err(|e| e.to_string())? = Some(partition.clone());
+
+        Ok(partition)
+    }
+
+    fn reset(&self) {
+        if let Ok(mut client) = self.client.lock() {
+            *client = None;
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Publisher for Kafka {
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+
+    async fn publish(&self, event: &Event) -> Result<(), String> {
+        let record = Record {
+            key: Some(event.id.to_string().into_bytes()),
+            value: Some(serde_json::to_vec(event).map_err(|e| e.to_string())?),
+            headers: BTreeMap::from([
+                ("type".to_string(), event.name.clone().into_bytes()),
+                ("version".to_string(), event.version.to_string().into_bytes()),
+            ]),
+            timestamp: chrono::Utc::now(),
+        };
+
+        let partition = self.partition().await?;
+
+        if let Err(e) = partition.produce(vec![record], Compression::NoCompression).await {
+            self.reset();
+
+            return Err(e.to_string())
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use nightmare_common::log;
+use nightmare_common::models::{Id, Timestamp};
+use sea_orm::{ConnectionTrait, DbErr};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::dao;
+use crate::models::outbox_events;
+
+pub mod kafka;
+pub mod publisher;
+
+/// Version of the event envelope, bumped when an event changes shape in a
+/// way consumers have to handle
+pub const VERSION: i32 = 1;
+
+/// Service the events originate from
+pub const SOURCE: &str = "nightmare-auth";
+
+pub const USER_CREATED: &str = "user.created";
+pub const USER_UPDATED: &str = "user.updated";
+pub const USER_PASSWORD_CHANGED: &str = "user.password_changed";
+pub const USER_DELETED: &str = "user.deleted";
+pub const ROLE_CREATED: &str = "role.created";
+pub const ROLE_UPDATED: &str = "role.updated";
+pub const ROLE_DELETED: &str = "role.deleted";
+pub const ROLE_SYNCED: &str = "role.synced";
+pub const PERMISSION_CREATED: &str = "permission.created";
+pub const PERMISSION_UPDATED: &str = "permission.updated";
+pub const PERMISSION_DELETED: &str = "permission.deleted";
+pub const PERMISSION_SYNCED: &str = "permission.synced";
+pub const GROUP_USERS_SYNCED: &str = "group.users_synced";
+pub const GROUP_ROLES_SYNCED: &str = "group.roles_synced";
+pub const GROUP_PERMISSIONS_SYNCED: &str = "group.permissions_synced";
+pub const SESSION_CREATED: &str = "session.created";
+pub const SESSION_REVOKED: &str = "session.revoked";
+pub const TOKEN_CREATED: &str = "token.created";
+pub const TOKEN_REVOKED: &str = "token.revoked";
+pub const GRANT_EXPIRED: &str = "grant.expired";
+pub const ACCESS_REQUEST_CREATED: &str = "access_request.created";
+pub const ACCESS_REQUEST_APPROVED: &str = "access_request.approved";
+pub const ACCESS_REQUEST_REJECTED: &str = "access_request.rejected";
+pub const ACCESS_REVIEW_REVOKED: &str = "access_review.revoked";
+pub const ELEVATION_ACTIVATED: &str = "elevation.activated";
+pub const ELEVATION_ENDED: &str = "elevation.ended";
+pub const IMPERSONATION_STARTED: &str = "impersonation.started";
+pub const IMPERSONATION_ENDED: &str = "impersonation.ended";
+
+/// Every event type, webhooks may only subscribe to these
+pub const TYPES: [&str; 28] = [
+    USER_CREATED,
+    USER_UPDATED,
+    USER_PASSWORD_CHANGED,
+    USER_DELETED,
+    ROLE_CREATED,
+    ROLE_UPDATED,
+    ROLE_DELETED,
+    ROLE_SYNCED,
+    PERMISSION_CREATED,
+    PERMISSION_UPDATED,
+    PERMISSION_DELETED,
+    PERMISSION_SYNCED,
+    GROUP_USERS_SYNCED,
+    GROUP_ROLES_SYNCED,
+    GROUP_PERMISSIONS_SYNCED,
+    SESSION_CREATED,
+    SESSION_REVOKED,
+    TOKEN_CREATED,
+    TOKEN_REVOKED,
+    GRANT_EXPIRED,
+    ACCESS_REQUEST_CREATED,
+    ACCESS_REQUEST_APPROVED,
+    ACCESS_REQUEST_REJECTED,
+    ACCESS_REVIEW_REVOKED,
+    ELEVATION_ACTIVATED,
+    ELEVATION_ENDED,
+    IMPERSONATION_STARTED,
+    IMPERSONATION_ENDED,
+];
+
+/// Envelope every published event is wrapped in
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Event {
+    pub id: Id,
+    #[serde(rename = "type")]
+    pub name: String,
+    pub version: i32,
+    pub source: String,
+    pub occurred_at: Timestamp,
+    pub data: Value,
+}
+
+impl From<&outbox_events::Model> for Event {
+    fn from(event: &outbox_events::Model) -> Self {
+        Self {
+            id: event.id.clone(),
+            name: event.name.clone(),
+            version: event.version,
+            source: SOURCE.to_string(),
+            occurred_at: event.created_at,
+            data: serde_json::from_str(&event.payload).unwrap_or(Value::Null),
+        }
+    }
+}
+
+/// Emit a domain event by writing it to the outbox and queueing it for the
+/// subscribed webhooks, pass the transaction of the change so the event and
+/// the change commit together
+pub async fn emit<D: ConnectionTrait, N: AsRef<str>>(db: &D, name: N, payload: Value) -> Result<(), DbErr> {
+    let event = dao::outbox::store(db, name.as_ref(), VERSION, &payload).await?;
+    let webhooks = dao::webhook::subscribed(db, &event.name).await?;
+
+    if !webhooks.is_empty() {
+        let body = serde_json::to_string(&Event::from(&event))
+            .map_err(|e| DbErr::Custom(e.to_string()))?;
+
+        for webhook in &webhooks {
+            dao::webhook::store_delivery(db, webhook, event.id.clone(), &event.name, &body).await?;
+        }
+    }
+
+    log::debug!(events::emit, "{} {}, {} webhooks", event.name, event.id, webhooks.len());
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use nightmare_common::log;
+
+use crate::events::Event;
+use crate::events::kafka::Kafka;
+
+/// Destination the outbox relay hands committed events to
+#[async_trait(?Send)]
+pub trait Publisher {
+    fn name(&self) -> &'static str;
+
+    async fn publish(&self, event: &Event) -> Result<(), String>;
+}
+
+/// Writes events to the log, used when no broker is configured
+#[derive(Clone, Debug, Default)]
+pub struct Log;
+
+#[async_trait(?Send)]
+impl Publisher for Log {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn publish(&self, event: &Event) -> Result<(), String> {
+        let event = serde_json::to_string(event).map_err(|e| e.to_string())?;
+
+        log::info!(events::publisher::log, "{}", event);
+
+        Ok(())
+    }
+}
+
+/// Keeps published events in process, for tests and runs without a broker
+#[derive(Debug, Default)]
+pub struct Memory {
+    events: Mutex<Vec<Event>>,
+}
+
+#[async_trait(?Send)]
+impl Publisher for Memory {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn publish(&self, event: &Event) -> Result<(), String> {
+        let mut events = self.events.lock().map_err(|e| e.to_string())?;
+
+        events.push(event.clone());
+
+        log::debug!(events::publisher::memory, "{} {}, {} held", event.name, event.id, events.len());
+
+        Ok(())
+    }
+}
+
+/// Publisher named by `EVENT_PUBLISHER`, `kafka` when `KAFKA_BROKERS` is set
+/// and `log` otherwise
+pub fn configured() -> Box<dyn Publisher> {
+    let kafka = Kafka::from_env();
+    let name = std::env::var("EVENT_PUBLISHER")
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or(if kafka.is_some() { "kafka" } else { "log" }.to_string());
+
+    match name.trim().to_lowercase().as_str() {
+        "kafka" => match kafka {
+            Some(kafka) => Box::new(kafka),
+            None => {
+                log::error!(events::publisher::configured, "KAFKA_BROKERS must be set, falling back to log");
+
+                Box::new(Log)
+            },
+        },
+        "memory" => Box::new(Memory::default()),
+        "log" => Box::new(Log),
+        name => {
+            log::error!(events::publisher::configured, "unknown publisher {}, falling back to log", name);
+
+            Box::new(Log)
+        },
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use nightmare_common::log;
+use sea_orm::DatabaseConnection;
+
+use crate::services;
+
+/// Seconds between signed checkpoints of the audit chain, from
+/// `AUDIT_CHECKPOINT_SECONDS`
+fn interval() -> Duration {
+    let seconds = std::env::var("AUDIT_CHECKPOINT_SECONDS")
+        .ok()
+        .and_then(|seconds| seconds.trim().parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(3600);
+
+    Duration::from_secs(seconds)
+}
+
+pub async fn run(db: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(interval());
+
+    loop {
+        interval.tick().await;
+
+        checkpoint(&db).await;
+    }
+}
+
+pub async fn checkpoint(db: &DatabaseConnection) {
+    match services::audit::checkpoint(db).await {
+        Err(e) => log::error!(jobs::audit::checkpoint, "{}", e),
+        Ok(Some(checkpoint)) => log::info!(jobs::audit::checkpoint, "signed audit chain at {} {}", checkpoint.sequence, checkpoint.hash),
+        Ok(None) => {},
+    }
+}
@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use nightmare_common::log;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+
+use crate::{dao, events};
+
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// Close elevations that ran out so their end is recorded
+pub async fn run(db: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        expire(&db).await;
+    }
+}
+
+pub async fn expire(db: &DatabaseConnection) {
+    match dao::elevation::lapsed(db).await {
+        Err(e) => log::error!(jobs::elevation::expire, "{}", e),
+        Ok(elevations) => {
+            for elevation in elevations {
+                let ended = async {
+                    let txn = db.begin().await?;
+                    let elevation = dao::elevation::end(&txn, &elevation, None).await?;
+
+                    events::emit(&txn, events::ELEVATION_ENDED, json!({
+                        "id": elevation.id,
+                        "user_id": elevation.user_id,
+                        "role_id": elevation.role_id,
+                        "ended_by": elevation.ended_by,
+                    })).await?;
+
+                    txn.commit().await
+                };
+
+                if let Err(e) = ended.await {
+                    log::error!(jobs::elevation::expire, "{}", e);
+                }
+            }
+        },
+    }
+}
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use nightmare_common::log;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use serde_json::json;
+
+use crate::{dao, events};
+
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// Remove role and permission grants whose window has lapsed
+pub async fn run(db: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        prune(&db).await;
+    }
+}
+
+pub async fn prune(db: &DatabaseConnection) {
+    match dao::authorization::lapsed_roles(db).await {
+        Err(e) => log::error!(jobs::grant::prune, "{}", e),
+        Ok(assignments) => {
+            for assignment in assignments {
+                let revoked = async {
+                    let txn = db.begin().await?;
+
+                    dao::authorization::revoke_role(&txn, assignment.id.clone()).await?;
+                    events::emit(&txn, events::GRANT_EXPIRED, json!({
+                        "id": assignment.id,
+                        "user_id": assignment.user_id,
+                        "role_id": assignment.role_id,
+                        "resource_type": assignment.resource_type,
+                        "resource_id": assignment.resource_id,
+                        "expires_at": assignment.expires_at,
+                    })).await?;
+
+                    txn.commit().await
+                };
+
+                if let Err(e) = revoked.await {
+                    log::error!(jobs::grant::prune, "{}", e);
+                }
+            }
+        },
+    }
+
+    match dao::authorization::lapsed_permissions(db).await {
+        Err(e) => log::error!(jobs::grant::prune, "{}", e),
+        Ok(grants) => {
+            for grant in grants {
+                let revoked = async {
+                    let txn = db.begin().await?;
+
+                    dao::authorization::revoke_permission(&txn, grant.id.clone()).await?;
+                    events::emit(&txn, events::GRANT_EXPIRED, json!({
+                        "id": grant.id,
+                        "user_id": grant.user_id,
+                        "permission_id": grant.permission_id,
+                        "expires_at": grant.expires_at,
+                    })).await?;
+
+                    txn.commit().await
+                };
+
+                if let Err(e) = revoked.await {
+                    log::error!(jobs::grant::prune, "{}", e);
+                }
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nightmare_common::models::{permission_role, permissions, roles, users, Timestamp};
+    use nightmare_common::time;
+    use sea_orm::{ActiveModelTrait, EntityTrait};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{group_permission, group_role, group_user, groups, outbox_events, permission_user, role_elevations, role_user, webhooks};
+    use crate::testing;
+
+    async fn database() -> DatabaseConnection {
+        testing::database(vec![
+            testing::table(users::Entity),
+            testing::table(roles::Entity),
+            testing::table(permissions::Entity),
+            testing::table(permission_role::Entity),
+            testing::table(role_user::Entity),
+            testing::table(permission_user::Entity),
+            testing::table(groups::Entity),
+            testing::table(group_user::Entity),
+            testing::table(group_role::Entity),
+            testing::table(group_permission::Entity),
+            testing::table(role_elevations::Entity),
+            testing::table(outbox_events::Entity),
+            testing::table(webhooks::Entity),
+        ]).await
+    }
+
+    /// alice holds the AUDITOR role and the EXPORT_REPORTS permission until
+    /// the given time
+    async fn granted(db: &DatabaseConnection, expires_at: Timestamp) -> users::Model {
+        let alice = dao::user::store(db, testing::user("alice", "secret")).await.unwrap();
+        let role = dao::role::store(db, "AUDITOR", "auditor").await.unwrap();
+        let permission = dao::permission::store(db, "EXPORT_REPORTS", "export reports").await.unwrap();
+
+        role_user::ActiveModel::from(role_user::Model {
+            id: Uuid::new_v4().into(),
+            role_id: role.id,
+            user_id: alice.id.clone(),
+            resource_type: None,
+            resource_id: None,
+            starts_at: None,
+            expires_at: Some(expires_at),
+        }).insert(db).await.unwrap();
+
+        permission_user::ActiveModel::from(permission_user::Model {
+            id: Uuid::new_v4().into(),
+            permission_id: permission.id,
+            user_id: alice.id.clone(),
+            starts_at: None,
+            expires_at: Some(expires_at),
+        }).insert(db).await.unwrap();
+
+        alice
+    }
+
+    #[actix_web::test]
+    async fn lapsed_grants_stop_counting_before_they_are_pruned() {
+        let db = database().await;
+        let alice = granted(&db, time::now() - chrono::Duration::seconds(1)).await;
+
+        let grants = dao::authorization::grants(&db, &alice, None).await.unwrap();
+
+        assert!(grants.roles.is_empty());
+        assert!(grants.grants.is_empty());
+        assert_eq!(role_user::Entity::find().all(&db).await.unwrap().len(), 1);
+        assert_eq!(permission_user::Entity::find().all(&db).await.unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn grants_count_until_they_lapse() {
+        let db = database().await;
+        let alice = granted(&db, time::now() + chrono::Duration::hours(1)).await;
+
+        let grants = dao::authorization::grants(&db, &alice, None).await.unwrap();
+
+        assert_eq!(grants.roles.len(), 1);
+        assert!(grants.permissions().iter().any(|permission| permission.code.eq("EXPORT_REPORTS")));
+    }
+
+    #[actix_web::test]
+    async fn prune_removes_lapsed_grants() {
+        let db = database().await;
+
+        granted(&db, time::now() - chrono::Duration::seconds(1)).await;
+        prune(&db).await;
+
+        assert!(role_user::Entity::find().all(&db).await.unwrap().is_empty());
+        assert!(permission_user::Entity::find().all(&db).await.unwrap().is_empty());
+        assert_eq!(outbox_events::Entity::find().all(&db).await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Once;
+
+use nightmare_common::log;
+use sea_orm::Database;
+
+pub mod audit;
+pub mod elevation;
+pub mod grant;
+pub mod outbox;
+pub mod webhook;
+
+static START: Once = Once::new();
+
+/// Spawn background jobs once per process, they use their own connection
+/// since the app data isn't available while the service is configured
+pub fn start() {
+    START.call_once(|| {
+        actix_web::rt::spawn(async {
+            let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+            let db = match Database::connect(url).await {
+                Err(e) => {
+                    log::error!(jobs::start, "{}", e);
+
+                    return
+                },
+                Ok(db) => db,
+            };
+
+            actix_web::rt::spawn(grant::run(db.clone()));
+            actix_web::rt::spawn(elevation::run(db.clone()));
+            actix_web::rt::spawn(outbox::run(db.clone()));
+            actix_web::rt::spawn(webhook::run(db.clone()));
+            actix_web::rt::spawn(audit::run(db));
+        });
+    });
+}
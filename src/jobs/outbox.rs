@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use nightmare_common::{log, time};
+use sea_orm::DatabaseConnection;
+
+use crate::dao;
+use crate::events::Event;
+use crate::events::publisher::{self, Publisher};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events relayed per tick
+const BATCH: u64 = 100;
+
+/// Published events are kept this long for inspection
+const RETENTION_DAYS: i64 = 7;
+
+/// Hand committed events to the configured publisher
+pub async fn run(db: DatabaseConnection) {
+    let publisher = publisher::configured();
+    let mut interval = actix_web::rt::time::interval(INTERVAL);
+    let mut ticks = 0u64;
+
+    log::info!(jobs::outbox::run, "publishing events with {}", publisher.name());
+
+    loop {
+        interval.tick().await;
+
+        relay(&db, publisher.as_ref()).await;
+
+        // pruning hourly is plenty
+        if ticks % 3600 == 0 {
+            prune(&db).await;
+        }
+
+        ticks = ticks.wrapping_add(1);
+    }
+}
+
+/// Publish pending events oldest first, stopping at the first failure so
+/// consumers never see an event ahead of the ones before it
+pub async fn relay(db: &DatabaseConnection, publisher: &dyn Publisher) {
+    let pending = match dao::outbox::pending(db, BATCH).await {
+        Err(e) => {
+            log::error!(jobs::outbox::relay, "{}", e);
+
+            return
+        },
+        Ok(pending) => pending,
+    };
+
+    for record in pending {
+        if let Err(e) = publisher.publish(&Event::from(&record)).await {
+            log::error!(jobs::outbox::relay, "{} failed to publish {} {}", publisher.name(), record.id, e);
+
+            if let Err(e) = dao::outbox::failed(db, &record, e).await {
+                log::error!(jobs::outbox::relay, "{}", e);
+            }
+
+            return
+        }
+
+        if let Err(e) = dao::outbox::published(db, &record).await {
+            log::error!(jobs::outbox::relay, "{}", e);
+
+            return
+        }
+    }
+}
+
+pub async fn prune(db: &DatabaseConnection) {
+    let before = time::now() - chrono::Duration::days(RETENTION_DAYS);
+
+    match dao::outbox::prune(db, before).await {
+        Err(e) => log::error!(jobs::outbox::prune, "{}", e),
+        Ok(pruned) => log::debug!(jobs::outbox::prune, "pruned {} events", pruned),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use async_trait::async_trait;
+    use nightmare_common::models::Timestamp;
+    use sea_orm::{ActiveModelTrait, EntityTrait};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::outbox_events;
+    use crate::testing;
+
+    /// Publisher that notes what it published and refuses the named event
+    #[derive(Default)]
+    struct Recorder {
+        published: RefCell<Vec<String>>,
+        refuse: RefCell<Option<String>>,
+    }
+
+    #[async_trait(?Send)]
+    impl Publisher for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        async fn publish(&self, event: &Event) -> Result<(), String> {
+            if self.refuse.borrow().as_ref().is_some_and(|name| name.eq(&event.name)) {
+                return Err("broker down".to_string())
+            }
+
+            self.published.borrow_mut().push(event.name.clone());
+
+            Ok(())
+        }
+    }
+
+    async fn event(db: &DatabaseConnection, name: &str, created_at: Timestamp, published_at: Option<Timestamp>) -> outbox_events::Model {
+        outbox_events::ActiveModel::from(outbox_events::Model {
+            id: Uuid::new_v4().into(),
+            name: name.to_string(),
+            version: 1,
+            payload: json!({}).to_string(),
+            attempts: 0,
+            last_error: None,
+            published_at,
+            created_at,
+        })
+            .insert(db)
+            .await
+            .unwrap()
+    }
+
+    async fn reload(db: &DatabaseConnection, event: &outbox_events::Model) -> Option<outbox_events::Model> {
+        outbox_events::Entity::find_by_id(event.id.clone()).one(db).await.unwrap()
+    }
+
+    #[actix_web::test]
+    async fn relay_publishes_oldest_first() {
+        let db = testing::database(vec![testing::table(outbox_events::Entity)]).await;
+        let now = time::now();
+        let second = event(&db, "second", now - chrono::Duration::seconds(1), None).await;
+        let first = event(&db, "first", now - chrono::Duration::seconds(2), None).await;
+        let third = event(&db, "third", now, None).await;
+        let publisher = Recorder::default();
+
+        relay(&db, &publisher).await;
+
+        assert_eq!(*publisher.published.borrow(), vec!["first", "second", "third"]);
+
+        for record in [first, second, third] {
+            let record = reload(&db, &record).await.unwrap();
+
+            assert!(record.published_at.is_some());
+            assert_eq!(record.attempts, 1);
+        }
+    }
+
+    #[actix_web::test]
+    async fn relay_stops_at_a_failure_and_retries_it_next_tick() {
+        let db = testing::database(vec![testing::table(outbox_events::Entity)]).await;
+        let now = time::now();
+        let first = event(&db, "first", now - chrono::Duration::seconds(2), None).await;
+        let second = event(&db, "second", now - chrono::Duration::seconds(1), None).await;
+        let third = event(&db, "third", now, None).await;
+        let publisher = Recorder::default();
+
+        *publisher.refuse.borrow_mut() = Some("second".to_string());
+        relay(&db, &publisher).await;
+
+        assert_eq!(*publisher.published.borrow(), vec!["first"]);
+
+        let failed = reload(&db, &second).await.unwrap();
+
+        assert!(failed.published_at.is_none());
+        assert_eq!(failed.attempts, 1);
+        assert_eq!(failed.last_error.as_deref(), Some("broker down"));
+        assert!(reload(&db, &third).await.unwrap().published_at.is_none());
+
+        *publisher.refuse.borrow_mut() = None;
+        relay(&db, &publisher).await;
+
+        assert_eq!(*publisher.published.borrow(), vec!["first", "second", "third"]);
+
+        let retried = reload(&db, &second).await.unwrap();
+
+        assert!(retried.published_at.is_some());
+        assert_eq!(retried.attempts, 2);
+        assert!(retried.last_error.is_none());
+        assert_eq!(reload(&db, &first).await.unwrap().attempts, 1);
+    }
+
+    #[actix_web::test]
+    async fn prune_only_removes_events_published_before_the_retention() {
+        let db = testing::database(vec![testing::table(outbox_events::Entity)]).await;
+        let now = time::now();
+        let old = now - chrono::Duration::days(RETENTION_DAYS + 1);
+        let expired = event(&db, "expired", old, Some(old)).await;
+        let recent = event(&db, "recent", now, Some(now)).await;
+        let pending = event(&db, "pending", old, None).await;
+
+        prune(&db).await;
+
+        assert!(reload(&db, &expired).await.is_none());
+        assert!(reload(&db, &recent).await.is_some());
+        assert!(reload(&db, &pending).await.is_some());
+    }
+}
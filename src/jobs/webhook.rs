@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use nightmare_common::log;
+use sea_orm::DatabaseConnection;
+
+use crate::{dao, services};
+
+const INTERVAL: Duration = Duration::from_secs(1);
+
+/// Deliveries attempted per tick
+const BATCH: u64 = 50;
+
+/// Attempt due webhook deliveries
+pub async fn run(db: DatabaseConnection) {
+    let mut interval = actix_web::rt::time::interval(INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        deliver(&db).await;
+    }
+}
+
+pub async fn deliver(db: &DatabaseConnection) {
+    let due = match dao::webhook::due(db, BATCH).await {
+        Err(e) => {
+            log::error!(jobs::webhook::deliver, "{}", e);
+
+            return
+        },
+        Ok(due) => due,
+    };
+
+    for delivery in due {
+        // deliveries go away with their webhook, a miss is a delete racing
+        // the tick
+        let webhook = match dao::webhook::find(db, delivery.webhook_id.clone()).await {
+            None => continue,
+            Some(webhook) => webhook,
+        };
+
+        match services::webhook::deliver(db, &webhook, &delivery, services::webhook::private_allowed()).await {
+            Err(e) => log::error!(jobs::webhook::deliver, "{}", e),
+            Ok(delivery) if delivery.status.eq(dao::webhook::DEAD) => {
+                log::error!(jobs::webhook::deliver, "{} {} to {} is dead, {}", delivery.event, delivery.id, webhook.url, delivery.last_error.unwrap_or_default());
+            },
+            Ok(_) => {},
+        }
+    }
+}
@@ -3,41 +3,196 @@ use actix_web::web;
 #[macro_use] extern crate actix_web;
 
 mod api;
+mod backends;
 mod controllers;
 mod dao;
+mod events;
+mod jobs;
+mod middleware;
+mod models;
 mod requests;
 mod responses;
 mod services;
+#[cfg(test)]
+mod testing;
 
 nightmare_common::main! {
-    web::scope("")
-        .service(api::service())
-        .service(controllers::auth::login)
-        .service(controllers::auth::authenticate)
-        .service(controllers::auth::authenticate_by_token)
-        .service(controllers::auth::logout)
-        .service(
-            web::scope("/api/v1")
-                // user
-                .service(controllers::user::paginate)
-                .service(controllers::user::store)
-                .service(controllers::user::show)
-                .service(controllers::user::update_general_information)
-                .service(controllers::user::update_password)
-                .service(controllers::user::delete)
-                .service(controllers::user::sync_permissions)
-                .service(controllers::user::sync_roles)
-                // permission
-                .service(controllers::permission::paginate)
-                .service(controllers::permission::store)
-                .service(controllers::permission::show)
-                .service(controllers::permission::update)
-                .service(controllers::permission::delete)
-                // role
-                .service(controllers::role::paginate)
-                .service(controllers::role::store)
-                .service(controllers::role::show)
-                .service(controllers::role::update)
-                .service(controllers::role::delete)
-        )
+    {
+        jobs::start();
+        // the breached password list is read once, before serving
+        services::password::policy();
+        // refuse to start without a fixed issuer and signing keys
+        services::oidc::issuer();
+        services::oidc::key_id();
+        services::audit::key_id();
+
+        web::scope("")
+            .service(api::service())
+            .service(controllers::auth::login)
+            .service(controllers::federation::start)
+            .service(controllers::federation::callback)
+            .service(controllers::auth::authenticate)
+            .service(controllers::auth::authenticate_by_token)
+            .service(controllers::auth::logout)
+            .service(controllers::password::policy)
+            .service(controllers::password::expired)
+            .service(controllers::oauth::authorize)
+            .service(controllers::oauth::consent)
+            .service(controllers::oauth::token)
+            .service(controllers::oidc::configuration)
+            .service(controllers::oidc::jwks)
+            .service(controllers::oidc::userinfo)
+            .service(controllers::oidc::logout)
+            .service(
+                web::scope("/saml")
+                    // signed responses easily outgrow the default form limit
+                    .app_data(web::FormConfig::default().limit(1024 * 1024))
+                    .service(controllers::saml::metadata)
+                    .service(controllers::saml::acs)
+            )
+            .service(
+                web::scope("/scim/v2")
+                    // provisioning clients send application/scim+json
+                    .app_data(web::JsonConfig::default().content_type(|mime| {
+                        mime.subtype().as_str().eq("json") || mime.suffix().is_some_and(|suffix| suffix.as_str().eq("json"))
+                    }))
+                    .service(controllers::scim::service_provider_config)
+                    .service(controllers::scim::schemas)
+                    .service(controllers::scim::resource_types)
+                    .service(controllers::scim::users)
+                    .service(controllers::scim::store_user)
+                    .service(controllers::scim::user)
+                    .service(controllers::scim::replace_user)
+                    .service(controllers::scim::patch_user)
+                    .service(controllers::scim::delete_user)
+                    .service(controllers::scim::groups)
+                    .service(controllers::scim::store_group)
+                    .service(controllers::scim::group)
+                    .service(controllers::scim::replace_group)
+                    .service(controllers::scim::patch_group)
+                    .service(controllers::scim::delete_group)
+            )
+            .service(
+                web::scope("/api/v1")
+                    // user
+                    .service(controllers::user::paginate)
+                    .service(controllers::user::store)
+                    .service(controllers::user::show)
+                    .service(controllers::user::update_general_information)
+                    .service(controllers::user::update_password)
+                    .service(controllers::user::delete)
+                    .service(controllers::user::sync_permissions)
+                    .service(controllers::user::sync_roles)
+                    .service(controllers::user::impersonate)
+                    // permission
+                    .service(controllers::permission::paginate)
+                    .service(controllers::permission::store)
+                    .service(controllers::permission::show)
+                    .service(controllers::permission::update)
+                    .service(controllers::permission::delete)
+                    // role
+                    .service(controllers::role::paginate)
+                    .service(controllers::role::store)
+                    .service(controllers::role::show)
+                    .service(controllers::role::update)
+                    .service(controllers::role::delete)
+                    .service(controllers::role::sync_owners)
+                    // organization
+                    .service(controllers::organization::paginate)
+                    .service(controllers::organization::store)
+                    .service(controllers::organization::show)
+                    .service(controllers::organization::update)
+                    .service(controllers::organization::delete)
+                    .service(controllers::organization::sync_users)
+                    .service(controllers::organization::switch)
+                    // group
+                    .service(controllers::group::paginate)
+                    .service(controllers::group::store)
+                    .service(controllers::group::show)
+                    .service(controllers::group::update)
+                    .service(controllers::group::delete)
+                    .service(controllers::group::sync_users)
+                    .service(controllers::group::sync_roles)
+                    .service(controllers::group::sync_permissions)
+                    // policy
+                    .service(controllers::policy::paginate)
+                    .service(controllers::policy::store)
+                    .service(controllers::policy::show)
+                    .service(controllers::policy::update)
+                    .service(controllers::policy::delete)
+                    // authorization
+                    .service(controllers::authorization::authorize)
+                    // access request
+                    .service(controllers::access_request::paginate)
+                    .service(controllers::access_request::store)
+                    .service(controllers::access_request::show)
+                    .service(controllers::access_request::approve)
+                    .service(controllers::access_request::reject)
+                    .service(controllers::access_request::cancel)
+                    // access review
+                    .service(controllers::access_review::paginate)
+                    .service(controllers::access_review::store)
+                    .service(controllers::access_review::show)
+                    .service(controllers::access_review::export)
+                    .service(controllers::access_review::decide)
+                    .service(controllers::access_review::close)
+                    // elevation
+                    .service(controllers::elevation::eligibilities)
+                    .service(controllers::elevation::store_eligibility)
+                    .service(controllers::elevation::delete_eligibility)
+                    .service(controllers::elevation::history)
+                    .service(controllers::elevation::activate)
+                    .service(controllers::elevation::end)
+                    // personal access token
+                    .service(controllers::personal_access_token::all)
+                    .service(controllers::personal_access_token::store)
+                    .service(controllers::personal_access_token::delete)
+                    // service account
+                    .service(controllers::service_account::paginate)
+                    .service(controllers::service_account::store)
+                    .service(controllers::service_account::show)
+                    .service(controllers::service_account::update)
+                    .service(controllers::service_account::delete)
+                    .service(controllers::service_account::rotate_secret)
+                    .service(controllers::service_account::rotate_api_key)
+                    .service(controllers::service_account::sync_roles)
+                    .service(controllers::service_account::sync_permissions)
+                    // oauth client
+                    .service(controllers::oauth_client::paginate)
+                    .service(controllers::oauth_client::store)
+                    .service(controllers::oauth_client::show)
+                    .service(controllers::oauth_client::update)
+                    .service(controllers::oauth_client::delete)
+                    .service(controllers::oauth_client::rotate_secret)
+                    // identity provider
+                    .service(controllers::identity_provider::paginate)
+                    .service(controllers::identity_provider::store)
+                    .service(controllers::identity_provider::show)
+                    .service(controllers::identity_provider::update)
+                    .service(controllers::identity_provider::delete)
+                    .service(controllers::identity_provider::mappings)
+                    .service(controllers::identity_provider::store_mapping)
+                    .service(controllers::identity_provider::delete_mapping)
+                    .service(controllers::identity_provider::identities)
+                    .service(controllers::identity_provider::link)
+                    .service(controllers::identity_provider::unlink)
+                    // ldap
+                    .service(controllers::ldap::mappings)
+                    .service(controllers::ldap::store_mapping)
+                    .service(controllers::ldap::delete_mapping)
+                    // webhook
+                    .service(controllers::webhook::paginate)
+                    .service(controllers::webhook::store)
+                    .service(controllers::webhook::show)
+                    .service(controllers::webhook::update)
+                    .service(controllers::webhook::delete)
+                    .service(controllers::webhook::deliveries)
+                    .service(controllers::webhook::redeliver)
+                    // audit
+                    .service(controllers::audit::paginate)
+                    .service(controllers::audit::verify)
+                    .service(controllers::audit::checkpoints)
+                    .service(controllers::audit::jwks)
+            )
+    }
 }
@@ -0,0 +1,58 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::dev::Payload;
+use actix_web::http::header::USER_AGENT;
+use actix_web::{Error, FromRequest, HttpRequest};
+use nightmare_common::models::Id;
+
+use crate::middleware::tenant::Tenant;
+
+/// Who is making the request and from where, recorded with every audit
+/// entry of the request
+#[derive(Clone, Debug, Default)]
+pub struct Audit {
+    pub actor_id: Option<Id>,
+    pub impersonator_id: Option<Id>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl Audit {
+    /// Same request attributed to the user, for logins where nobody is
+    /// authenticated yet
+    pub fn acting_as(&self, user_id: Id) -> Self {
+        Self {
+            actor_id: Some(user_id),
+            impersonator_id: None,
+            ..self.clone()
+        }
+    }
+}
+
+impl FromRequest for Audit {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let ip = request.connection_info()
+            .realip_remote_addr()
+            .map(|ip| ip.to_string());
+        let user_agent = request.headers()
+            .get(USER_AGENT)
+            .and_then(|header| header.to_str().ok())
+            .map(|header| header.chars().take(512).collect());
+        let tenant = Tenant::from_request(request, payload);
+
+        Box::pin(async move {
+            let token = tenant.await?.token;
+
+            Ok(Self {
+                actor_id: token.as_ref().map(|token| token.user_id.clone()),
+                impersonator_id: token.and_then(|token| token.impersonator_id),
+                ip,
+                user_agent,
+            })
+        })
+    }
+}
@@ -0,0 +1,2 @@
+pub mod tenant;
+pub mod audit;
\ No newline at end of file
@@ -0,0 +1,227 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::dev::Payload;
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::web::Data;
+use actix_web::{Error, FromRequest, HttpRequest};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::Id;
+use sea_orm::DatabaseConnection;
+
+use crate::dao::organization::Scope;
+use crate::models::tokens;
+use crate::{dao, services};
+
+/// Permission that lets a caller outside of any organization reach every
+/// tenant's users, roles and permissions
+pub const PLATFORM_PERMISSION: &str = "MANAGE_PLATFORM";
+
+/// Token of the current request with the organization it's working in
+#[derive(Clone, Debug)]
+pub struct Tenant {
+    /// Session token, personal access tokens and api keys have none
+    pub token: Option<tokens::Model>,
+    organization_id: Option<Id>,
+}
+
+impl Tenant {
+    pub fn organization_id(&self) -> Option<Id> {
+        self.organization_id.clone()
+    }
+
+    /// Tenants the caller reaches, a token without organization only reaches
+    /// them all with the platform permission and nothing otherwise
+    pub fn scope(&self, auth: &Auth) -> Scope {
+        match self.organization_id() {
+            Some(organization_id) => Scope::Organization(organization_id),
+            None if auth.permissions.iter().any(|permission| permission.code.eq(PLATFORM_PERMISSION)) => Scope::Platform,
+            None => Scope::Nowhere,
+        }
+    }
+}
+
+impl FromRequest for Tenant {
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(request: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let db = request.app_data::<Data<DatabaseConnection>>().cloned();
+        let bearer = request.headers()
+            .get(AUTHORIZATION)
+            .and_then(|header| header.to_str().ok())
+            .map(|header| header.trim_start_matches("Bearer").trim().to_string());
+
+        Box::pin(async move {
+            let (db, bearer) = match (db, bearer) {
+                (Some(db), Some(bearer)) => (db, bearer),
+                _ => return Ok(Self { token: None, organization_id: None }),
+            };
+
+            let personal = bearer.starts_with(services::personal_access_token::PREFIX);
+            let api_key = bearer.starts_with(services::service_account::API_KEY_PREFIX);
+
+            if personal || api_key {
+                let organization_id = services::auth::bearer(db.get_ref(), bearer).await
+                    .ok()
+                    .and_then(|bearer| bearer.organization_id);
+
+                return Ok(Self { token: None, organization_id })
+            }
+
+            let token = match services::auth::decode(bearer) {
+                Ok(id) => dao::auth::find(db.get_ref(), id).await,
+                Err(_) => None,
+            };
+
+            Ok(Self {
+                organization_id: token.as_ref().and_then(|token| token.organization_id.clone()),
+                token,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+    use nightmare_common::{base58, hash};
+    use nightmare_common::models::{permission_role, permissions, roles, users};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{
+        external_identities, group_permission, group_role, group_user, groups, ldap_users, organization_role,
+        organization_user, organizations, permission_user, personal_access_tokens, role_elevations, role_user,
+        service_accounts,
+    };
+    use crate::testing;
+
+    async fn database() -> DatabaseConnection {
+        testing::database(vec![
+            testing::table(users::Entity),
+            testing::table(roles::Entity),
+            testing::table(organizations::Entity),
+            testing::table(organization_user::Entity),
+            testing::table(organization_role::Entity),
+            testing::table(tokens::Entity),
+            testing::table(personal_access_tokens::Entity),
+            testing::table(service_accounts::Entity),
+            // bearers go through the password expiry check
+            testing::table(ldap_users::Entity),
+            testing::table(external_identities::Entity),
+            testing::table(permissions::Entity),
+            testing::table(permission_role::Entity),
+            testing::table(role_user::Entity),
+            testing::table(permission_user::Entity),
+            testing::table(groups::Entity),
+            testing::table(group_user::Entity),
+            testing::table(group_role::Entity),
+            testing::table(group_permission::Entity),
+            testing::table(role_elevations::Entity),
+        ]).await
+    }
+
+    async fn tenant(db: &DatabaseConnection, bearer: &str) -> Tenant {
+        let request = TestRequest::default()
+            .app_data(Data::new(db.clone()))
+            .insert_header((AUTHORIZATION, format!("Bearer {}", bearer)))
+            .to_http_request();
+
+        Tenant::extract(&request).await.unwrap()
+    }
+
+    fn auth(user: &users::Model, permissions: Vec<&str>) -> Auth {
+        Auth {
+            user: user.clone(),
+            permissions: permissions.into_iter()
+                .map(|code| permissions::Model {
+                    id: Uuid::new_v4().into(),
+                    code: code.to_string(),
+                    name: code.to_lowercase(),
+                })
+                .collect(),
+            roles: vec![],
+        }
+    }
+
+    /// alice belongs to acme and bob to globex, which owns the GLOBEX_ADMIN
+    /// role
+    async fn tenants(db: &DatabaseConnection) -> (organizations::Model, users::Model, users::Model, roles::Model) {
+        let acme = dao::organization::store(db, "ACME", "acme").await.unwrap();
+        let globex = dao::organization::store(db, "GLOBEX", "globex").await.unwrap();
+        let alice = dao::user::store(db, testing::user("alice", "secret")).await.unwrap();
+        let bob = dao::user::store(db, testing::user("bob", "secret")).await.unwrap();
+        let role = dao::role::store(db, "GLOBEX_ADMIN", "globex admin").await.unwrap();
+
+        dao::organization::join(db, &acme.id, &alice.id).await.unwrap();
+        dao::organization::join(db, &globex.id, &bob.id).await.unwrap();
+        dao::organization::own_role(db, &globex.id, &role).await.unwrap();
+
+        (acme, alice, bob, role)
+    }
+
+    #[actix_web::test]
+    async fn personal_access_tokens_stay_in_their_organization() {
+        let db = database().await;
+        let (acme, alice, bob, role) = tenants(&db).await;
+        let id: Id = Uuid::new_v4().into();
+        let hashed = hash::make(id.clone(), "secret".to_string()).to_string();
+        let token = dao::personal_access_token::store(&db, id, &alice, Some(acme.id.clone()), "ci", hashed, vec![], None).await.unwrap();
+        let bearer = format!("{}{}_secret", services::personal_access_token::PREFIX, base58::to_string(token.id.as_bytes()));
+
+        let tenant = tenant(&db, &bearer).await;
+        let scope = tenant.scope(&auth(&alice, vec![PLATFORM_PERMISSION]));
+
+        assert_eq!(scope, Scope::Organization(acme.id));
+        assert!(dao::user::find_within(&db, alice.id.clone(), dao::organization::members(scope.clone())).await.is_some());
+        assert!(dao::user::find_within(&db, bob.id.clone(), dao::organization::members(scope.clone())).await.is_none());
+        assert!(dao::role::find_within(&db, role.id.clone(), dao::organization::visible_roles(scope)).await.is_none());
+    }
+
+    #[actix_web::test]
+    async fn personal_access_tokens_without_organization_reach_nothing() {
+        let db = database().await;
+        let (_, alice, bob, _) = tenants(&db).await;
+        let id: Id = Uuid::new_v4().into();
+        let hashed = hash::make(id.clone(), "secret".to_string()).to_string();
+        let token = dao::personal_access_token::store(&db, id, &alice, None, "ci", hashed, vec![], None).await.unwrap();
+        let bearer = format!("{}{}_secret", services::personal_access_token::PREFIX, base58::to_string(token.id.as_bytes()));
+
+        let scope = tenant(&db, &bearer).await.scope(&auth(&alice, vec![]));
+
+        assert_eq!(scope, Scope::Nowhere);
+        assert!(dao::user::find_within(&db, alice.id, dao::organization::members(scope.clone())).await.is_none());
+        assert!(dao::user::find_within(&db, bob.id, dao::organization::members(scope)).await.is_none());
+    }
+
+    #[actix_web::test]
+    async fn users_without_membership_reach_nothing() {
+        let db = database().await;
+        let (_, _, bob, role) = tenants(&db).await;
+        let carol = dao::user::store(&db, testing::user("carol", "secret")).await.unwrap();
+        let shared = dao::role::store(&db, "VIEWER", "viewer").await.unwrap();
+        let token = dao::auth::generate(&db, &carol, None, None).await.unwrap();
+
+        let scope = tenant(&db, &base58::to_string(token.id.as_bytes())).await.scope(&auth(&carol, vec![]));
+
+        assert_eq!(scope, Scope::Nowhere);
+        assert!(dao::user::find_within(&db, bob.id, dao::organization::members(scope.clone())).await.is_none());
+        assert!(dao::role::find_within(&db, role.id, dao::organization::visible_roles(scope.clone())).await.is_none());
+        assert!(dao::role::find_within(&db, shared.id, dao::organization::visible_roles(scope)).await.is_none());
+    }
+
+    #[actix_web::test]
+    async fn the_platform_permission_reaches_every_organization() {
+        let db = database().await;
+        let (_, _, bob, role) = tenants(&db).await;
+        let carol = dao::user::store(&db, testing::user("carol", "secret")).await.unwrap();
+        let token = dao::auth::generate(&db, &carol, None, None).await.unwrap();
+
+        let scope = tenant(&db, &base58::to_string(token.id.as_bytes())).await.scope(&auth(&carol, vec![PLATFORM_PERMISSION]));
+
+        assert_eq!(scope, Scope::Platform);
+        assert!(dao::user::find_within(&db, bob.id, dao::organization::members(scope.clone())).await.is_some());
+        assert!(dao::role::find_within(&db, role.id, dao::organization::visible_roles(scope)).await.is_some());
+    }
+}
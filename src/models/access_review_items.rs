@@ -0,0 +1,28 @@
+use nightmare_common::models::{Id, Timestamp};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "access_review_items")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Id,
+    pub campaign_id: Id,
+    pub user_id: Id,
+    pub role_id: Option<Id>,
+    pub permission_id: Option<Id>,
+    pub assignment_id: Id,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub decision: String,
+    pub reviewer_id: Option<Id>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub comment: Option<String>,
+    pub decided_at: Option<Timestamp>,
+    pub created_at: Timestamp,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
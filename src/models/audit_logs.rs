@@ -0,0 +1,30 @@
+use nightmare_common::models::{Id, Timestamp};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "audit_logs")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Id,
+    pub actor_id: Option<Id>,
+    pub impersonator_id: Option<Id>,
+    pub action: String,
+    pub target_type: Option<String>,
+    pub target_id: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub old_values: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub new_values: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: Timestamp,
+    pub sequence: Option<i64>,
+    pub previous_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,34 @@
+use nightmare_common::models::{Id, Timestamp};
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Deserialize, Serialize)]
+#[sea_orm(table_name = "identity_providers")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Id,
+    pub code: String,
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub scopes: String,
+    pub jit_provisioning: bool,
+    pub enabled: bool,
+    pub protocol: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub sso_url: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub certificate: Option<String>,
+    pub email_attribute: Option<String>,
+    pub name_attribute: Option<String>,
+    pub username_attribute: Option<String>,
+    pub algorithm: Option<String>,
+    pub created_at: Timestamp,
+    pub updated_at: Timestamp,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,37 @@
+pub mod access_request_histories;
+pub mod access_requests;
+pub mod access_review_campaigns;
+pub mod access_review_items;
+pub mod audit_chain;
+pub mod audit_checkpoints;
+pub mod audit_logs;
+pub mod external_identities;
+pub mod federated_logins;
+pub mod group_permission;
+pub mod group_role;
+pub mod group_user;
+pub mod groups;
+pub mod identity_provider_role_mappings;
+pub mod identity_providers;
+pub mod ldap_group_mappings;
+pub mod ldap_users;
+pub mod oauth_authorization_codes;
+pub mod oauth_clients;
+pub mod oauth_refresh_tokens;
+pub mod organization_permission;
+pub mod organization_role;
+pub mod organization_user;
+pub mod organizations;
+pub mod outbox_events;
+pub mod password_histories;
+pub mod permission_user;
+pub mod personal_access_tokens;
+pub mod policies;
+pub mod role_elevations;
+pub mod role_eligibilities;
+pub mod role_owner;
+pub mod role_user;
+pub mod service_accounts;
+pub mod tokens;
+pub mod webhook_deliveries;
+pub mod webhooks;
\ No newline at end of file
@@ -0,0 +1,30 @@
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum AccessRequestOrderByColumn {
+    Status,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct AccessRequestStoreRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role: Option<Id>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub permission: Option<Id>,
+    #[schema(example = "need to approve expenses while the manager is on leave")]
+    pub justification: String,
+    #[schema()]
+    pub starts_at: Option<Timestamp>,
+    #[schema()]
+    pub expires_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct AccessRequestDecisionRequest {
+    #[schema(example = "approved until the end of the quarter")]
+    pub comment: Option<String>,
+}
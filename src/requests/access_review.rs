@@ -0,0 +1,38 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum AccessReviewOrderByColumn {
+    Name,
+    Status,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct AccessReviewStoreRequest {
+    #[schema(example = "2026 Q3 privileged access review")]
+    pub name: String,
+    #[schema(example = json!([Uuid::new_v4().to_string()]))]
+    #[serde(default)]
+    pub roles: Vec<Id>,
+    #[schema(example = json!([Uuid::new_v4().to_string()]))]
+    #[serde(default)]
+    pub permissions: Vec<Id>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccessReviewDecision {
+    Approve,
+    Revoke,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct AccessReviewItemDecisionRequest {
+    #[schema()]
+    pub decision: AccessReviewDecision,
+    #[schema(example = "moved to another team")]
+    pub comment: Option<String>,
+}
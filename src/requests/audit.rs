@@ -0,0 +1,35 @@
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum AuditOrderByColumn {
+    Action,
+    CreatedAt,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum AuditCheckpointOrderByColumn {
+    Sequence,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditFilterRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub actor_id: Option<Id>,
+    /// Exact action or a prefix like `user.*`
+    #[schema(example = "user.*")]
+    pub action: Option<String>,
+    #[schema(example = "user")]
+    pub target_type: Option<String>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub target_id: Option<String>,
+    #[schema(example = "127.0.0.1")]
+    pub ip: Option<String>,
+    #[schema(example = "2026-10-01T00:00:00")]
+    pub from: Option<Timestamp>,
+    #[schema(example = "2026-11-01T00:00:00")]
+    pub to: Option<Timestamp>,
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizationSubject {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub user_id: Option<Id>,
+    #[schema()]
+    pub token: Option<String>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub organization_id: Option<Id>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AuthorizationResource {
+    #[schema(example = "project")]
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[schema(example = "42")]
+    pub id: String,
+    #[schema(example = json!({"amount": 2500, "region": "west"}))]
+    #[serde(default)]
+    pub attributes: HashMap<String, Value>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AuthorizationCheck {
+    #[schema(example = "CREATE_USER")]
+    pub permission: String,
+    #[schema()]
+    pub resource: Option<AuthorizationResource>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AuthorizationRequest {
+    #[schema()]
+    pub subject: AuthorizationSubject,
+    #[schema()]
+    pub checks: Vec<AuthorizationCheck>,
+    /// Caller supplied context of the action, subject and environment
+    /// attributes are always the server's own
+    #[schema(example = json!({"channel": "web"}))]
+    #[serde(default)]
+    pub action: HashMap<String, Value>,
+    #[schema(example = false)]
+    #[serde(default)]
+    pub explain: bool,
+}
@@ -0,0 +1,29 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct RoleEligibilityStoreRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub user_id: Option<Id>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub group_id: Option<Id>,
+    #[schema(example = 60)]
+    pub max_minutes: i32,
+    #[schema(example = true)]
+    #[serde(default)]
+    pub require_reauthentication: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ElevationRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role: Id,
+    #[schema(example = 30)]
+    pub minutes: i32,
+    #[schema(example = "rotate production database credentials")]
+    pub reason: String,
+    #[schema(example = "password")]
+    pub password: Option<String>,
+}
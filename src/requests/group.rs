@@ -0,0 +1,30 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum GroupOrderByColumn {
+    Code,
+    Name,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct GroupStoreRequest {
+    #[schema(example = "FINANCE")]
+    pub code: String,
+    #[schema(example = "finance department")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct GroupUpdateRequest {
+    #[schema(example = "finance department")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct GroupUserBulkRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub users: Vec<Id>,
+}
@@ -0,0 +1,138 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum IdentityProviderOrderByColumn {
+    Code,
+    Name,
+    CreatedAt,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IdentityProviderProtocol {
+    #[default]
+    Oidc,
+    Saml,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct IdentityProviderStoreRequest {
+    #[schema(example = "acme")]
+    pub code: String,
+    #[serde(default)]
+    #[schema()]
+    pub protocol: IdentityProviderProtocol,
+    #[schema(example = "Acme Corp")]
+    pub name: String,
+    /// Issuer url for openid connect, entity id for saml
+    #[schema(example = "https://login.acme.example.com")]
+    pub issuer: String,
+    /// Entity id of this service for saml
+    #[schema(example = "nightmare")]
+    pub client_id: String,
+    #[schema(example = "secret")]
+    pub client_secret: Option<String>,
+    #[schema(example = "openid profile email")]
+    pub scopes: Option<String>,
+    #[schema(example = true)]
+    pub jit_provisioning: bool,
+    #[schema(example = true)]
+    pub enabled: bool,
+    /// Single sign-on url of a saml provider, redirect binding
+    #[schema(example = "https://login.acme.example.com/saml/sso")]
+    pub sso_url: Option<String>,
+    /// PEM signing certificate of a saml provider
+    #[schema(example = "-----BEGIN CERTIFICATE-----...")]
+    pub certificate: Option<String>,
+    #[schema(example = "mail")]
+    pub email_attribute: Option<String>,
+    #[schema(example = "displayName")]
+    pub name_attribute: Option<String>,
+    #[schema(example = "uid")]
+    pub username_attribute: Option<String>,
+    /// Algorithm id tokens of an openid connect provider are signed with,
+    /// taken from its keys when left out
+    #[schema(example = "RS256")]
+    pub algorithm: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct IdentityProviderUpdateRequest {
+    #[schema(example = "Acme Corp")]
+    pub name: String,
+    /// Issuer url for openid connect, entity id for saml
+    #[schema(example = "https://login.acme.example.com")]
+    pub issuer: String,
+    /// Entity id of this service for saml
+    #[schema(example = "nightmare")]
+    pub client_id: String,
+    /// Keeps the current secret when left out
+    #[schema(example = "secret")]
+    pub client_secret: Option<String>,
+    #[schema(example = "openid profile email")]
+    pub scopes: Option<String>,
+    #[schema(example = true)]
+    pub jit_provisioning: bool,
+    #[schema(example = true)]
+    pub enabled: bool,
+    /// Single sign-on url of a saml provider, redirect binding
+    #[schema(example = "https://login.acme.example.com/saml/sso")]
+    pub sso_url: Option<String>,
+    /// PEM signing certificate of a saml provider
+    #[schema(example = "-----BEGIN CERTIFICATE-----...")]
+    pub certificate: Option<String>,
+    #[schema(example = "mail")]
+    pub email_attribute: Option<String>,
+    #[schema(example = "displayName")]
+    pub name_attribute: Option<String>,
+    #[schema(example = "uid")]
+    pub username_attribute: Option<String>,
+    /// Algorithm id tokens of an openid connect provider are signed with,
+    /// taken from its keys when left out
+    #[schema(example = "RS256")]
+    pub algorithm: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct RoleMappingStoreRequest {
+    #[schema(example = "groups")]
+    pub claim: String,
+    #[schema(example = "admins")]
+    pub value: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Id,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ExternalIdentityLinkRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub provider_id: Id,
+    #[schema(example = "248289761001")]
+    pub subject: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FederatedCallbackRequest {
+    #[schema(example = "SplxlOBeZQQYbYS6WxSbIA")]
+    pub code: Option<String>,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub state: String,
+    #[schema(example = "access_denied")]
+    pub error: Option<String>,
+    #[schema(example = "the user denied the request")]
+    pub error_description: Option<String>,
+}
+
+/// Response posted back by a saml provider, HTTP-POST binding
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct SamlResponseRequest {
+    #[serde(rename = "SAMLResponse")]
+    #[schema(example = "PHNhbWxwOlJlc3BvbnNlIC4uLg==")]
+    pub saml_response: String,
+    #[serde(rename = "RelayState")]
+    #[schema(example = "5Hq2Vf1x2dD8kBvR7nLwPZ")]
+    pub relay_state: Option<String>,
+}
@@ -0,0 +1,11 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct LdapGroupMappingStoreRequest {
+    #[schema(example = "cn=admins,ou=groups,dc=nightmare,dc=local")]
+    pub group_dn: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Id,
+}
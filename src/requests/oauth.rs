@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct TokenRequest {
+    #[schema(example = "authorization_code")]
+    pub grant_type: String,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: Option<String>,
+    #[schema(example = "secret")]
+    pub client_secret: Option<String>,
+    #[schema(example = "7dRg2bLq9WmYkX4sVpNc1e")]
+    pub code: Option<String>,
+    #[schema(example = "https://dashboard.example.com/callback")]
+    pub redirect_uri: Option<String>,
+    #[schema(example = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk")]
+    pub code_verifier: Option<String>,
+    #[schema(example = "9hTq4nLx2VbYkR8sWpMc3f")]
+    pub refresh_token: Option<String>,
+    #[schema(example = "openid profile READ_USER")]
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuthorizeRequest {
+    #[schema(example = "code")]
+    pub response_type: String,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: String,
+    #[schema(example = "https://dashboard.example.com/callback")]
+    pub redirect_uri: String,
+    #[schema(example = "openid profile READ_USER")]
+    pub scope: Option<String>,
+    #[schema(example = "af0ifjsldkj")]
+    pub state: Option<String>,
+    #[schema(example = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM")]
+    pub code_challenge: Option<String>,
+    #[schema(example = "S256")]
+    pub code_challenge_method: Option<String>,
+    #[schema(example = "n-0S6_WzA2Mj")]
+    pub nonce: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ConsentRequest {
+    #[serde(flatten)]
+    pub authorize: AuthorizeRequest,
+    #[schema(example = "root")]
+    pub email_or_username: String,
+    #[schema(example = "password")]
+    pub password: String,
+    #[schema(example = "approve")]
+    pub decision: String,
+}
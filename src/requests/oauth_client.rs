@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum OauthClientOrderByColumn {
+    Name,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct OauthClientStoreRequest {
+    #[schema(example = "dashboard")]
+    pub name: String,
+    #[schema(example = false)]
+    pub confidential: bool,
+    #[schema(example = json!(["https://dashboard.example.com/callback"]))]
+    pub redirect_uris: Vec<String>,
+    #[schema(example = json!(["openid", "profile", "email", "READ_USER"]))]
+    pub scopes: Vec<String>,
+    #[schema(example = json!(["https://dashboard.example.com/signed-out"]))]
+    #[serde(default)]
+    pub post_logout_redirect_uris: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct OauthClientUpdateRequest {
+    #[schema(example = "dashboard")]
+    pub name: String,
+    #[schema(example = json!(["https://dashboard.example.com/callback"]))]
+    pub redirect_uris: Vec<String>,
+    #[schema(example = json!(["openid", "profile", "email", "READ_USER"]))]
+    pub scopes: Vec<String>,
+    #[schema(example = json!(["https://dashboard.example.com/signed-out"]))]
+    #[serde(default)]
+    pub post_logout_redirect_uris: Vec<String>,
+}
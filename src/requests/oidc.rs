@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogoutRequest {
+    #[schema(example = "eyJhbGciOiJSUzI1NiIsImtpZCI6IjEifQ...")]
+    pub id_token_hint: Option<String>,
+    #[schema(example = "https://dashboard.example.com/signed-out")]
+    pub post_logout_redirect_uri: Option<String>,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: Option<String>,
+    #[schema(example = "af0ifjsldkj")]
+    pub state: Option<String>,
+}
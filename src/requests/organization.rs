@@ -0,0 +1,30 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum OrganizationOrderByColumn {
+    Code,
+    Name,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct OrganizationStoreRequest {
+    #[schema(example = "ACME")]
+    pub code: String,
+    #[schema(example = "acme corporation")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct OrganizationUpdateRequest {
+    #[schema(example = "acme corporation")]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct OrganizationUserBulkRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub users: Vec<Id>,
+}
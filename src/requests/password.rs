@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct ExpiredPasswordRequest {
+    #[schema(example = "john")]
+    pub email_or_username: String,
+    #[schema(example = "Correct-Horse-7")]
+    pub current_password: String,
+    #[schema(example = "Battery-Staple-9")]
+    pub new_password: String,
+    #[schema(example = "Battery-Staple-9")]
+    pub password_confirmation: String,
+}
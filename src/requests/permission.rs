@@ -1,4 +1,4 @@
-use nightmare_common::models::Id;
+use nightmare_common::models::{Id, Timestamp};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -23,8 +23,21 @@ pub struct PermissionUpdateRequest {
     pub name: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct PermissionAssignment {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub permission: Id,
+    #[schema()]
+    pub starts_at: Option<Timestamp>,
+    #[schema()]
+    pub expires_at: Option<Timestamp>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct PermissionBulkRequest {
     #[schema(example = json!(Uuid::new_v4().to_string()))]
     pub permissions: Vec<Id>,
+    #[schema()]
+    #[serde(default)]
+    pub assignments: Vec<PermissionAssignment>,
 }
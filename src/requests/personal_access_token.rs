@@ -0,0 +1,13 @@
+use nightmare_common::models::Timestamp;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct PersonalAccessTokenStoreRequest {
+    #[schema(example = "github actions")]
+    pub name: String,
+    #[schema(example = json!(["CREATE_USER"]))]
+    pub scopes: Vec<String>,
+    #[schema()]
+    pub expires_at: Option<Timestamp>,
+}
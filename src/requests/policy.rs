@@ -0,0 +1,75 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum PolicyOrderByColumn {
+    Name,
+    Effect,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyEffect {
+    Allow,
+    Deny,
+}
+
+impl PolicyEffect {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyOperator {
+    Eq,
+    Ne,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    In,
+    NotIn,
+    Contains,
+    StartsWith,
+    IpIn,
+    Exists,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct PolicyCondition {
+    #[schema(example = "resource.amount")]
+    pub attribute: String,
+    #[schema(example = "lt")]
+    pub operator: PolicyOperator,
+    #[schema(example = json!(10000))]
+    pub value: Option<Value>,
+    #[schema(example = "action.region")]
+    pub reference: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct PolicyStoreRequest {
+    #[schema(example = "managers approve small expenses in their region")]
+    pub name: String,
+    #[schema(example = "allow")]
+    pub effect: PolicyEffect,
+    #[schema(example = "APPROVE_EXPENSE")]
+    pub permission: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Option<Id>,
+    #[schema(example = "expense")]
+    pub resource_type: Option<String>,
+    #[schema()]
+    pub conditions: Vec<PolicyCondition>,
+    #[schema(example = false)]
+    #[serde(default)]
+    pub dry_run: bool,
+}
@@ -1,4 +1,4 @@
-use nightmare_common::models::Id;
+use nightmare_common::models::{Id, Timestamp};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -23,8 +23,31 @@ pub struct RoleUpdateRequest {
     pub name: String,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct RoleAssignment {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role: Id,
+    #[schema(example = "project")]
+    pub resource_type: Option<String>,
+    #[schema(example = "42")]
+    pub resource_id: Option<String>,
+    #[schema()]
+    pub starts_at: Option<Timestamp>,
+    #[schema()]
+    pub expires_at: Option<Timestamp>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 pub struct RoleBulkRequest {
     #[schema(example = json!(Uuid::new_v4().to_string()))]
     pub roles: Vec<Id>,
+    #[schema()]
+    #[serde(default)]
+    pub assignments: Vec<RoleAssignment>,
 }
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct RoleOwnerBulkRequest {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub users: Vec<Id>,
+}
\ No newline at end of file
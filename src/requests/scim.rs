@@ -0,0 +1,105 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScimListRequest {
+    /// Single comparison such as `userName eq "jdoe"`
+    #[param(example = "userName eq \"jdoe\"")]
+    pub filter: Option<String>,
+    /// One based index of the first result
+    #[serde(rename = "startIndex")]
+    #[param(example = 1)]
+    pub start_index: Option<u64>,
+    #[param(example = 100)]
+    pub count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimName {
+    #[schema(example = "John Doe")]
+    pub formatted: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "John")]
+    pub given_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "Doe")]
+    pub family_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ScimEmail {
+    #[schema(example = "jdoe@example.com")]
+    pub value: String,
+    #[schema(example = "work")]
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    #[schema(example = true)]
+    pub primary: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimUserRequest {
+    #[serde(default)]
+    #[schema(example = json!(["urn:ietf:params:scim:schemas:core:2.0:User"]))]
+    pub schemas: Vec<String>,
+    #[schema(example = "jdoe")]
+    pub user_name: String,
+    #[schema()]
+    pub name: Option<ScimName>,
+    #[schema(example = "John Doe")]
+    pub display_name: Option<String>,
+    #[serde(default)]
+    #[schema()]
+    pub emails: Vec<ScimEmail>,
+    /// A random password is set when left out
+    #[schema(example = "Secret123")]
+    pub password: Option<String>,
+    #[schema(example = true)]
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ScimMember {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub value: Id,
+    #[schema(example = "jdoe")]
+    pub display: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimGroupRequest {
+    #[serde(default)]
+    #[schema(example = json!(["urn:ietf:params:scim:schemas:core:2.0:Group"]))]
+    pub schemas: Vec<String>,
+    #[schema(example = "Engineering")]
+    pub display_name: String,
+    #[serde(default)]
+    #[schema()]
+    pub members: Vec<ScimMember>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ScimPatchOperation {
+    #[schema(example = "replace")]
+    pub op: String,
+    #[schema(example = "active")]
+    pub path: Option<String>,
+    #[schema(value_type = Object, example = false)]
+    pub value: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ScimPatchRequest {
+    #[serde(default)]
+    #[schema(example = json!(["urn:ietf:params:scim:api:messages:2.0:PatchOp"]))]
+    pub schemas: Vec<String>,
+    #[serde(rename = "Operations")]
+    #[schema()]
+    pub operations: Vec<ScimPatchOperation>,
+}
@@ -0,0 +1,32 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum ServiceAccountOrderByColumn {
+    Code,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ServiceAccountStoreRequest {
+    #[schema(example = "billing-worker")]
+    pub code: String,
+    #[schema(example = "billing worker")]
+    pub name: String,
+    #[schema(example = "charges subscriptions every night")]
+    pub description: Option<String>,
+    /// Organization the account and its api key are confined to, without
+    /// one it reaches nothing unless it holds the platform permission
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub organization_id: Option<Id>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ServiceAccountUpdateRequest {
+    #[schema(example = "billing worker")]
+    pub name: String,
+    #[schema(example = "charges subscriptions every night")]
+    pub description: Option<String>,
+}
@@ -30,7 +30,7 @@ pub struct UserStoreRequest {
     pub email: String,
     #[schema(example = "john")]
     pub username: String,
-    #[schema(example = "password")]
+    #[schema(example = "Correct-Horse-7")]
     pub password: String,
 }
 
@@ -50,11 +50,11 @@ pub struct UserUpdateGeneralInformationRequest {
 #[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct UserUpdatePasswordRequest {
-    #[schema(example = "Password123")]
+    #[schema(example = "Correct-Horse-7")]
     pub current_password: String,
-    #[schema(example = "Password123")]
+    #[schema(example = "Battery-Staple-9")]
     pub new_password: String,
-    #[schema(example = "Password123")]
+    #[schema(example = "Battery-Staple-9")]
     pub password_confirmation: String,
 }
 
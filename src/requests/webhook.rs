@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum WebhookOrderByColumn {
+    Url,
+    CreatedAt,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub enum WebhookDeliveryOrderByColumn {
+    Event,
+    Status,
+    CreatedAt,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct WebhookStoreRequest {
+    #[schema(example = "https://hooks.example.com/nightmare")]
+    pub url: String,
+    #[schema(example = "provisions accounts in the billing system")]
+    pub description: Option<String>,
+    /// Event types, `*` subscribes to every event and `user.*` to every
+    /// user event
+    #[schema(example = json!(["user.created", "user.deleted", "session.*"]))]
+    pub events: Vec<String>,
+    /// Signing secret, generated when left out
+    #[schema(example = "whsec_3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub secret: Option<String>,
+    #[schema(example = true)]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct WebhookUpdateRequest {
+    #[schema(example = "https://hooks.example.com/nightmare")]
+    pub url: String,
+    #[schema(example = "provisions accounts in the billing system")]
+    pub description: Option<String>,
+    #[schema(example = json!(["user.created", "user.deleted", "session.*"]))]
+    pub events: Vec<String>,
+    /// Keeps the current secret when left out
+    #[schema(example = "whsec_3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub secret: Option<String>,
+    #[schema(example = true)]
+    pub enabled: bool,
+}
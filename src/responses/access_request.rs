@@ -0,0 +1,83 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::{access_request_histories, access_requests};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct AccessRequestOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub user_id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Option<Id>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub permission_id: Option<Id>,
+    #[schema(example = "need to approve expenses while the manager is on leave")]
+    pub justification: String,
+    #[schema(example = "pending")]
+    pub status: String,
+    #[schema()]
+    pub starts_at: Option<Timestamp>,
+    #[schema()]
+    pub expires_at: Option<Timestamp>,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histories: Option<Vec<AccessRequestHistoryOAS>>,
+}
+
+impl From<&access_requests::Model> for AccessRequestOAS {
+    fn from(request: &access_requests::Model) -> Self {
+        Self {
+            id: request.id.clone(),
+            user_id: request.user_id.clone(),
+            role_id: request.role_id.clone(),
+            permission_id: request.permission_id.clone(),
+            justification: request.justification.clone(),
+            status: request.status.clone(),
+            starts_at: request.starts_at,
+            expires_at: request.expires_at,
+            created_at: request.created_at,
+            updated_at: request.updated_at,
+            histories: None,
+        }
+    }
+}
+
+impl From<access_requests::Model> for AccessRequestOAS {
+    fn from(request: access_requests::Model) -> Self {
+        Self::from(&request)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AccessRequestHistoryOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub actor_id: Id,
+    #[schema(example = "approved")]
+    pub action: String,
+    #[schema(example = "approved until the end of the quarter")]
+    pub comment: Option<String>,
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&access_request_histories::Model> for AccessRequestHistoryOAS {
+    fn from(history: &access_request_histories::Model) -> Self {
+        Self {
+            actor_id: history.actor_id.clone(),
+            action: history.action.clone(),
+            comment: history.comment.clone(),
+            created_at: history.created_at,
+        }
+    }
+}
+
+pagination::create!(AccessRequestOAS);
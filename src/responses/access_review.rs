@@ -0,0 +1,92 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::{access_review_campaigns, access_review_items};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct AccessReviewOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "2026 Q3 privileged access review")]
+    pub name: String,
+    #[schema(example = "open")]
+    pub status: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub created_by: Id,
+    #[schema()]
+    pub closed_at: Option<Timestamp>,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<AccessReviewItemOAS>>,
+}
+
+impl From<&access_review_campaigns::Model> for AccessReviewOAS {
+    fn from(campaign: &access_review_campaigns::Model) -> Self {
+        Self {
+            id: campaign.id.clone(),
+            name: campaign.name.clone(),
+            status: campaign.status.clone(),
+            created_by: campaign.created_by.clone(),
+            closed_at: campaign.closed_at,
+            created_at: campaign.created_at,
+            updated_at: campaign.updated_at,
+            items: None,
+        }
+    }
+}
+
+impl From<access_review_campaigns::Model> for AccessReviewOAS {
+    fn from(campaign: access_review_campaigns::Model) -> Self {
+        Self::from(&campaign)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AccessReviewItemOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub user_id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Option<Id>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub permission_id: Option<Id>,
+    #[schema(example = "project")]
+    pub resource_type: Option<String>,
+    #[schema(example = "42")]
+    pub resource_id: Option<String>,
+    #[schema(example = "pending")]
+    pub decision: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub reviewer_id: Option<Id>,
+    #[schema(example = "moved to another team")]
+    pub comment: Option<String>,
+    #[schema()]
+    pub decided_at: Option<Timestamp>,
+}
+
+impl From<&access_review_items::Model> for AccessReviewItemOAS {
+    fn from(item: &access_review_items::Model) -> Self {
+        Self {
+            id: item.id.clone(),
+            user_id: item.user_id.clone(),
+            role_id: item.role_id.clone(),
+            permission_id: item.permission_id.clone(),
+            resource_type: item.resource_type.clone(),
+            resource_id: item.resource_id.clone(),
+            decision: item.decision.clone(),
+            reviewer_id: item.reviewer_id.clone(),
+            comment: item.comment.clone(),
+            decided_at: item.decided_at,
+        }
+    }
+}
+
+pagination::create!(AccessReviewOAS);
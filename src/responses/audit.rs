@@ -0,0 +1,105 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::audit_logs;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct AuditOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    /// Null for failed logins
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub actor_id: Option<Id>,
+    /// Set when the actor was being impersonated
+    #[schema(example = json!(null))]
+    pub impersonator_id: Option<Id>,
+    #[schema(example = "user.updated")]
+    pub action: String,
+    #[schema(example = "user")]
+    pub target_type: Option<String>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub target_id: Option<String>,
+    /// Changed fields before the action
+    #[schema(example = json!({ "name": "john" }))]
+    pub old_values: Option<Value>,
+    /// Changed fields after the action
+    #[schema(example = json!({ "name": "john doe" }))]
+    pub new_values: Option<Value>,
+    #[schema(example = "127.0.0.1")]
+    pub ip: Option<String>,
+    #[schema(example = "Mozilla/5.0")]
+    pub user_agent: Option<String>,
+    #[schema()]
+    pub created_at: Timestamp,
+    /// Position in the hash chain, null for entries written before it
+    #[schema(example = 42)]
+    pub sequence: Option<i64>,
+    #[schema(example = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08")]
+    pub previous_hash: Option<String>,
+    /// Hex sha256 of the entry content, its sequence and the previous hash
+    #[schema(example = "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752")]
+    pub hash: Option<String>,
+}
+
+impl From<&audit_logs::Model> for AuditOAS {
+    fn from(entry: &audit_logs::Model) -> Self {
+        let parse = |values: &Option<String>| values.as_ref()
+            .map(|values| serde_json::from_str(values).unwrap_or(Value::String(values.clone())));
+
+        Self {
+            id: entry.id.clone(),
+            actor_id: entry.actor_id.clone(),
+            impersonator_id: entry.impersonator_id.clone(),
+            action: entry.action.clone(),
+            target_type: entry.target_type.clone(),
+            target_id: entry.target_id.clone(),
+            old_values: parse(&entry.old_values),
+            new_values: parse(&entry.new_values),
+            ip: entry.ip.clone(),
+            user_agent: entry.user_agent.clone(),
+            created_at: entry.created_at,
+            sequence: entry.sequence,
+            previous_hash: entry.previous_hash.clone(),
+            hash: entry.hash.clone(),
+        }
+    }
+}
+
+/// First entry where the chain doesn't hold
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AuditBreak {
+    #[schema(example = 42)]
+    pub sequence: i64,
+    /// Null when the entry is missing
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Option<Id>,
+    #[schema(example = "content doesn't match its hash")]
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct AuditVerification {
+    #[schema(example = true)]
+    pub valid: bool,
+    /// Chained entries that verified before the walk stopped
+    #[schema(example = 42)]
+    pub entries: u64,
+    /// Entries written before the chain existed, they can't be verified
+    #[schema(example = 0)]
+    pub unchained: u64,
+    /// Signed checkpoints matched against the chain
+    #[schema(example = 3)]
+    pub checkpoints: u64,
+    #[schema(example = 42)]
+    pub sequence: i64,
+    #[schema(example = "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752")]
+    pub hash: String,
+    pub first_break: Option<AuditBreak>,
+}
+
+pagination::create!(AuditOAS);
@@ -0,0 +1,41 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::audit_checkpoints;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct AuditCheckpointOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    /// Head of the chain when the checkpoint was taken
+    #[schema(example = 42)]
+    pub sequence: i64,
+    #[schema(example = "60303ae22b998861bce3b28f33eec1be758a213c86c93c076dbe9f558c11c752")]
+    pub hash: String,
+    /// `kid` of the jwks key that signed it
+    #[schema(example = "3Yh9Wm2n1kQeT0cX")]
+    pub key_id: String,
+    /// Compact RS256 jws of `sequence`, `hash` and `iat`
+    #[schema(example = "eyJ0eXAiOiJKV1QiLCJhbGciOiJSUzI1NiJ9...")]
+    pub signature: String,
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&audit_checkpoints::Model> for AuditCheckpointOAS {
+    fn from(checkpoint: &audit_checkpoints::Model) -> Self {
+        Self {
+            id: checkpoint.id.clone(),
+            sequence: checkpoint.sequence,
+            hash: checkpoint.hash.clone(),
+            key_id: checkpoint.key_id.clone(),
+            signature: checkpoint.signature.clone(),
+            created_at: checkpoint.created_at,
+        }
+    }
+}
+
+pagination::create!(AuditCheckpointOAS);
@@ -1,6 +1,12 @@
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{users, Id, Timestamp};
 use serde::{Deserialize, Serialize};
 use utoipa::{ToSchema, IntoResponses};
 
+use crate::dao::authorization::{Grants, Source};
+
+use super::organization::OrganizationOAS;
+use super::role::RoleOAS;
 use super::user::UserOAS;
 
 #[derive(Clone, Deserialize, Serialize, ToSchema, IntoResponses)]
@@ -12,6 +18,17 @@ pub struct Login {
     pub user: UserOAS,
 }
 
+#[derive(Clone, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct Impersonated {
+    #[schema()]
+    pub token: String,
+    #[schema()]
+    pub user: UserOAS,
+    #[schema()]
+    pub expired_at: Timestamp,
+}
+
 #[derive(Clone, Deserialize, Serialize, ToSchema, IntoResponses)]
 #[response(status = 200, description = "Ok")]
 pub struct Authenticated {
@@ -21,4 +38,73 @@ pub struct Authenticated {
     pub permissions: Vec<String>,
     #[schema(example = json!(["SUPERUSER", "MANAGER"]))]
     pub roles: Vec<String>,
+    #[schema()]
+    pub scoped_roles: Vec<ScopedRole>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub organization_id: Option<Id>,
+    #[schema()]
+    pub organizations: Vec<OrganizationOAS>,
+    #[schema()]
+    pub impersonator: Option<UserOAS>,
+    #[schema(example = json!(["CREATE_USER"]))]
+    pub scopes: Option<Vec<String>>,
+    #[schema(example = false)]
+    pub service_account: bool,
+}
+
+#[derive(Clone, Deserialize, Serialize, ToSchema)]
+pub struct ScopedRole {
+    #[schema()]
+    pub role: RoleOAS,
+    #[schema(example = "project")]
+    pub resource_type: String,
+    #[schema(example = "42")]
+    pub resource_id: String,
+    #[schema(example = json!(["UPDATE_PROJECT"]))]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct Session {
+    #[serde(flatten)]
+    pub auth: Auth,
+    pub scoped_roles: Vec<ScopedRole>,
+    pub organization_id: Option<Id>,
+    pub organizations: Vec<OrganizationOAS>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonator: Option<UserOAS>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    pub service_account: bool,
+}
+
+impl Session {
+    pub fn new(user: users::Model, grants: Grants) -> Self {
+        let scoped_roles = grants.scoped.iter()
+            .map(|scope| ScopedRole {
+                role: RoleOAS::from(&scope.role),
+                resource_type: scope.resource.kind.clone(),
+                resource_id: scope.resource.id.clone(),
+                permissions: grants.grants.iter()
+                    .filter(|grant| grant.resource.as_ref() == Some(&scope.resource))
+                    .filter(|grant| matches!(&grant.source, Source::Role(role) if role.id.eq(&scope.role.id)))
+                    .map(|grant| grant.permission.code.clone())
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            auth: Auth {
+                user,
+                permissions: grants.permissions(),
+                roles: grants.roles,
+            },
+            scoped_roles,
+            organization_id: None,
+            organizations: vec![],
+            impersonator: None,
+            scopes: None,
+            service_account: false,
+        }
+    }
 }
@@ -0,0 +1,30 @@
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::requests::authorization::AuthorizationResource;
+use crate::responses::policy::PolicyEvaluation;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct AuthorizationDecision {
+    #[schema(example = "CREATE_USER")]
+    pub permission: String,
+    #[schema()]
+    pub resource: Option<AuthorizationResource>,
+    #[schema(example = true)]
+    pub allowed: bool,
+    #[schema(example = "granted through role SUPERUSER")]
+    pub reason: String,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policies: Option<Vec<PolicyEvaluation>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct Authorized {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub subject: Id,
+    #[schema()]
+    pub decisions: Vec<AuthorizationDecision>,
+}
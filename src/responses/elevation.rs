@@ -0,0 +1,68 @@
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::{role_eligibilities, role_elevations};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct RoleEligibilityOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub user_id: Option<Id>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub group_id: Option<Id>,
+    #[schema(example = 60)]
+    pub max_minutes: i32,
+    #[schema(example = true)]
+    pub require_reauthentication: bool,
+}
+
+impl From<&role_eligibilities::Model> for RoleEligibilityOAS {
+    fn from(eligibility: &role_eligibilities::Model) -> Self {
+        Self {
+            id: eligibility.id.clone(),
+            role_id: eligibility.role_id.clone(),
+            user_id: eligibility.user_id.clone(),
+            group_id: eligibility.group_id.clone(),
+            max_minutes: eligibility.max_minutes,
+            require_reauthentication: eligibility.require_reauthentication,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct RoleElevationOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Id,
+    #[schema(example = "rotate production database credentials")]
+    pub reason: String,
+    #[schema()]
+    pub starts_at: Timestamp,
+    #[schema()]
+    pub expires_at: Timestamp,
+    #[schema()]
+    pub ended_at: Option<Timestamp>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub ended_by: Option<Id>,
+}
+
+impl From<&role_elevations::Model> for RoleElevationOAS {
+    fn from(elevation: &role_elevations::Model) -> Self {
+        Self {
+            id: elevation.id.clone(),
+            role_id: elevation.role_id.clone(),
+            reason: elevation.reason.clone(),
+            starts_at: elevation.starts_at,
+            expires_at: elevation.expires_at,
+            ended_at: elevation.ended_at,
+            ended_by: elevation.ended_by.clone(),
+        }
+    }
+}
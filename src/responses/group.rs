@@ -0,0 +1,39 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::groups;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct GroupOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "FINANCE")]
+    pub code: String,
+    #[schema(example = "finance department")]
+    pub name: String,
+}
+
+impl From<groups::Model> for GroupOAS {
+    fn from(group: groups::Model) -> Self {
+        Self {
+            id: group.id.clone(),
+            code: group.code,
+            name: group.name,
+        }
+    }
+}
+
+impl From<&groups::Model> for GroupOAS {
+    fn from(group: &groups::Model) -> Self {
+        Self {
+            id: group.id.clone(),
+            code: group.code.clone(),
+            name: group.name.clone(),
+        }
+    }
+}
+
+pagination::create!(GroupOAS);
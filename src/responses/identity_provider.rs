@@ -0,0 +1,134 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::{external_identities, identity_provider_role_mappings, identity_providers};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct IdentityProviderOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "acme")]
+    pub code: String,
+    #[schema(example = "oidc")]
+    pub protocol: String,
+    #[schema(example = "Acme Corp")]
+    pub name: String,
+    #[schema(example = "https://login.acme.example.com")]
+    pub issuer: String,
+    #[schema(example = "nightmare")]
+    pub client_id: String,
+    #[schema(example = true)]
+    pub has_client_secret: bool,
+    #[schema(example = "openid profile email")]
+    pub scopes: String,
+    #[schema(example = true)]
+    pub jit_provisioning: bool,
+    #[schema(example = true)]
+    pub enabled: bool,
+    #[schema(example = "https://login.acme.example.com/saml/sso")]
+    pub sso_url: Option<String>,
+    #[schema(example = "-----BEGIN CERTIFICATE-----...")]
+    pub certificate: Option<String>,
+    #[schema(example = "mail")]
+    pub email_attribute: Option<String>,
+    #[schema(example = "displayName")]
+    pub name_attribute: Option<String>,
+    #[schema(example = "uid")]
+    pub username_attribute: Option<String>,
+    #[schema(example = "RS256")]
+    pub algorithm: Option<String>,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<&identity_providers::Model> for IdentityProviderOAS {
+    fn from(provider: &identity_providers::Model) -> Self {
+        Self {
+            id: provider.id.clone(),
+            code: provider.code.clone(),
+            protocol: provider.protocol.clone(),
+            name: provider.name.clone(),
+            issuer: provider.issuer.clone(),
+            client_id: provider.client_id.clone(),
+            has_client_secret: provider.client_secret.is_some(),
+            scopes: provider.scopes.clone(),
+            jit_provisioning: provider.jit_provisioning,
+            enabled: provider.enabled,
+            sso_url: provider.sso_url.clone(),
+            certificate: provider.certificate.clone(),
+            email_attribute: provider.email_attribute.clone(),
+            name_attribute: provider.name_attribute.clone(),
+            username_attribute: provider.username_attribute.clone(),
+            algorithm: provider.algorithm.clone(),
+            created_at: provider.created_at,
+            updated_at: provider.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct RoleMappingOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "groups")]
+    pub claim: String,
+    #[schema(example = "admins")]
+    pub value: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Id,
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&identity_provider_role_mappings::Model> for RoleMappingOAS {
+    fn from(mapping: &identity_provider_role_mappings::Model) -> Self {
+        Self {
+            id: mapping.id.clone(),
+            claim: mapping.claim.clone(),
+            value: mapping.value.clone(),
+            role_id: mapping.role_id.clone(),
+            created_at: mapping.created_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct ExternalIdentityOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub provider_id: Id,
+    #[schema(example = "https://login.acme.example.com")]
+    pub issuer: String,
+    #[schema(example = "248289761001")]
+    pub subject: String,
+    #[schema(example = "john@acme.example.com")]
+    pub email: Option<String>,
+    #[schema()]
+    pub last_login_at: Option<Timestamp>,
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&external_identities::Model> for ExternalIdentityOAS {
+    fn from(identity: &external_identities::Model) -> Self {
+        Self {
+            id: identity.id.clone(),
+            provider_id: identity.provider_id.clone(),
+            issuer: identity.issuer.clone(),
+            subject: identity.subject.clone(),
+            email: identity.email.clone(),
+            last_login_at: identity.last_login_at,
+            created_at: identity.created_at,
+        }
+    }
+}
+
+pagination::create!(IdentityProviderOAS);
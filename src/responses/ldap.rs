@@ -0,0 +1,29 @@
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::ldap_group_mappings;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct LdapGroupMappingOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "cn=admins,ou=groups,dc=nightmare,dc=local")]
+    pub group_dn: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Id,
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&ldap_group_mappings::Model> for LdapGroupMappingOAS {
+    fn from(mapping: &ldap_group_mappings::Model) -> Self {
+        Self {
+            id: mapping.id.clone(),
+            group_dn: mapping.group_dn.clone(),
+            role_id: mapping.role_id.clone(),
+            created_at: mapping.created_at,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct TokenResponse {
+    #[schema()]
+    pub access_token: String,
+    #[schema(example = "Bearer")]
+    pub token_type: String,
+    #[schema(example = 3600)]
+    pub expires_in: i64,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[schema(example = "openid profile READ_USER")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
@@ -0,0 +1,63 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::oauth_clients;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct OauthClientOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "dashboard")]
+    pub name: String,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: String,
+    #[schema(example = false)]
+    pub confidential: bool,
+    #[schema(example = json!(["https://dashboard.example.com/callback"]))]
+    pub redirect_uris: Vec<String>,
+    #[schema(example = json!(["openid", "profile", "email", "READ_USER"]))]
+    pub scopes: Vec<String>,
+    #[schema(example = json!(["https://dashboard.example.com/signed-out"]))]
+    pub post_logout_redirect_uris: Vec<String>,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<&oauth_clients::Model> for OauthClientOAS {
+    fn from(client: &oauth_clients::Model) -> Self {
+        Self {
+            id: client.id.clone(),
+            name: client.name.clone(),
+            client_id: client.client_id.clone(),
+            confidential: client.confidential,
+            redirect_uris: serde_json::from_str(&client.redirect_uris).unwrap_or_default(),
+            scopes: serde_json::from_str(&client.scopes).unwrap_or_default(),
+            post_logout_redirect_uris: client.post_logout_redirect_uris.as_ref()
+                .and_then(|uris| serde_json::from_str(uris).ok())
+                .unwrap_or_default(),
+            created_at: client.created_at,
+            updated_at: client.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 201, description = "Created")]
+pub struct OauthClientCredentials {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: String,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[schema(example = "Copy the credentials now since they won't be shown again")]
+    pub message: String,
+}
+
+pagination::create!(OauthClientOAS);
@@ -0,0 +1,39 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::Id;
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::organizations;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct OrganizationOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "ACME")]
+    pub code: String,
+    #[schema(example = "acme corporation")]
+    pub name: String,
+}
+
+impl From<organizations::Model> for OrganizationOAS {
+    fn from(organization: organizations::Model) -> Self {
+        Self {
+            id: organization.id.clone(),
+            code: organization.code,
+            name: organization.name,
+        }
+    }
+}
+
+impl From<&organizations::Model> for OrganizationOAS {
+    fn from(organization: &organizations::Model) -> Self {
+        Self {
+            id: organization.id.clone(),
+            code: organization.code.clone(),
+            name: organization.name.clone(),
+        }
+    }
+}
+
+pagination::create!(OrganizationOAS);
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::services::password::{BreachedAction, Policy};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct PasswordPolicyOAS {
+    #[schema(example = 6)]
+    pub min_length: usize,
+    #[schema(example = 128)]
+    pub max_length: usize,
+    /// A lower case letter is required
+    #[schema(example = true)]
+    pub lowercase: bool,
+    /// An upper case letter is required
+    #[schema(example = true)]
+    pub uppercase: bool,
+    /// A digit is required
+    #[schema(example = true)]
+    pub digit: bool,
+    /// A character that isn't a letter, digit or space is required
+    #[schema(example = false)]
+    pub symbol: bool,
+    /// The username and the local part of the email can't be in it
+    #[schema(example = true)]
+    pub user_info: bool,
+    /// Common words are refused, ignoring case and surrounding digits or
+    /// symbols
+    #[schema(example = true)]
+    pub dictionary: bool,
+    /// `reject` or `warn` on passwords found in the breached list, null
+    /// when no list is loaded
+    #[schema(example = "reject")]
+    pub breached: Option<String>,
+    /// Previous passwords that can't be reused
+    #[schema(example = 5)]
+    pub history: usize,
+}
+
+impl From<&Policy> for PasswordPolicyOAS {
+    fn from(policy: &Policy) -> Self {
+        Self {
+            min_length: policy.min_length,
+            max_length: policy.max_length,
+            lowercase: policy.lowercase,
+            uppercase: policy.uppercase,
+            digit: policy.digit,
+            symbol: policy.symbol,
+            user_info: policy.user_info,
+            dictionary: policy.dictionary,
+            breached: policy.screening().map(|action| match action {
+                BreachedAction::Reject => "reject".to_string(),
+                BreachedAction::Warn => "warn".to_string(),
+            }),
+            history: policy.history,
+        }
+    }
+}
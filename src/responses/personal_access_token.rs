@@ -0,0 +1,48 @@
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::personal_access_tokens;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct PersonalAccessTokenOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub organization_id: Option<Id>,
+    #[schema(example = "github actions")]
+    pub name: String,
+    #[schema(example = json!(["CREATE_USER"]))]
+    pub scopes: Vec<String>,
+    #[schema()]
+    pub expires_at: Option<Timestamp>,
+    #[schema()]
+    pub last_used_at: Option<Timestamp>,
+    #[schema()]
+    pub created_at: Timestamp,
+}
+
+impl From<&personal_access_tokens::Model> for PersonalAccessTokenOAS {
+    fn from(token: &personal_access_tokens::Model) -> Self {
+        Self {
+            id: token.id.clone(),
+            organization_id: token.organization_id.clone(),
+            name: token.name.clone(),
+            scopes: serde_json::from_str(&token.scopes).unwrap_or_default(),
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+            created_at: token.created_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 201, description = "Created")]
+pub struct PersonalAccessTokenCreated {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema()]
+    pub token: String,
+    #[schema(example = "Token has been created, copy it now since it won't be shown again")]
+    pub message: String,
+}
@@ -0,0 +1,83 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::policies;
+use crate::requests::policy::{PolicyCondition, PolicyOperator};
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct PolicyOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "managers approve small expenses in their region")]
+    pub name: String,
+    #[schema(example = "allow")]
+    pub effect: String,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub permission_id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub role_id: Option<Id>,
+    #[schema(example = "expense")]
+    pub resource_type: Option<String>,
+    #[schema()]
+    pub conditions: Vec<PolicyCondition>,
+    #[schema(example = false)]
+    pub dry_run: bool,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<&policies::Model> for PolicyOAS {
+    fn from(policy: &policies::Model) -> Self {
+        Self {
+            id: policy.id.clone(),
+            name: policy.name.clone(),
+            effect: policy.effect.clone(),
+            permission_id: policy.permission_id.clone(),
+            role_id: policy.role_id.clone(),
+            resource_type: policy.resource_type.clone(),
+            conditions: serde_json::from_str(&policy.conditions).unwrap_or_default(),
+            dry_run: policy.dry_run,
+            created_at: policy.created_at,
+            updated_at: policy.updated_at,
+        }
+    }
+}
+
+impl From<policies::Model> for PolicyOAS {
+    fn from(policy: policies::Model) -> Self {
+        Self::from(&policy)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct ConditionEvaluation {
+    #[schema(example = "resource.amount")]
+    pub attribute: String,
+    #[schema(example = "lt")]
+    pub operator: PolicyOperator,
+    #[schema(example = true)]
+    pub matched: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+pub struct PolicyEvaluation {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "managers approve small expenses in their region")]
+    pub name: String,
+    #[schema(example = "allow")]
+    pub effect: String,
+    #[schema(example = false)]
+    pub dry_run: bool,
+    #[schema(example = true)]
+    pub matched: bool,
+    #[schema()]
+    pub conditions: Vec<ConditionEvaluation>,
+}
+
+pagination::create!(PolicyOAS);
@@ -0,0 +1,155 @@
+use nightmare_common::models::{roles, users, Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::requests::scim::{ScimEmail, ScimMember, ScimName};
+
+pub const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+pub const GROUP_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Group";
+pub const LIST_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScimMeta {
+    #[schema(example = "User")]
+    pub resource_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub created: Option<Timestamp>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema()]
+    pub last_modified: Option<Timestamp>,
+    #[schema(example = "https://auth.example.com/scim/v2/Users/2819c223-7f76-453a-919d-413861904646")]
+    pub location: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct ScimUserOAS {
+    #[schema(example = json!([USER_SCHEMA]))]
+    pub schemas: Vec<String>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "jdoe")]
+    pub user_name: String,
+    #[schema()]
+    pub name: ScimName,
+    #[schema(example = "John Doe")]
+    pub display_name: String,
+    #[schema()]
+    pub emails: Vec<ScimEmail>,
+    #[schema(example = true)]
+    pub active: bool,
+    /// Global roles of the user
+    #[schema()]
+    pub groups: Vec<ScimMember>,
+    #[schema()]
+    pub meta: ScimMeta,
+}
+
+impl ScimUserOAS {
+    pub fn new(user: &users::Model, roles: &[roles::Model], base: &str) -> Self {
+        Self {
+            schemas: vec![USER_SCHEMA.to_string()],
+            id: user.id.clone(),
+            user_name: user.username.clone(),
+            name: ScimName {
+                formatted: Some(user.name.clone()),
+                given_name: None,
+                family_name: None,
+            },
+            display_name: user.name.clone(),
+            emails: vec![ScimEmail {
+                value: user.email.clone(),
+                kind: Some("work".to_string()),
+                primary: Some(true),
+            }],
+            active: user.deleted_at.is_none(),
+            groups: roles.iter()
+                .map(|role| ScimMember {
+                    value: role.id.clone(),
+                    display: Some(role.name.clone()),
+                })
+                .collect(),
+            meta: ScimMeta {
+                resource_type: "User".to_string(),
+                created: Some(user.created_at),
+                last_modified: Some(user.updated_at),
+                location: format!("{}/Users/{}", base, user.id),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct ScimGroupOAS {
+    #[schema(example = json!([GROUP_SCHEMA]))]
+    pub schemas: Vec<String>,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "Engineering")]
+    pub display_name: String,
+    #[schema()]
+    pub members: Vec<ScimMember>,
+    #[schema()]
+    pub meta: ScimMeta,
+}
+
+impl ScimGroupOAS {
+    pub fn new(role: &roles::Model, members: &[users::Model], base: &str) -> Self {
+        Self {
+            schemas: vec![GROUP_SCHEMA.to_string()],
+            id: role.id.clone(),
+            display_name: role.name.clone(),
+            members: members.iter()
+                .map(|user| ScimMember {
+                    value: user.id.clone(),
+                    display: Some(user.username.clone()),
+                })
+                .collect(),
+            meta: ScimMeta {
+                resource_type: "Group".to_string(),
+                created: None,
+                last_modified: None,
+                location: format!("{}/Groups/{}", base, role.id),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct ScimUserList {
+    #[schema(example = json!([LIST_SCHEMA]))]
+    pub schemas: Vec<String>,
+    #[schema(example = 1)]
+    pub total_results: u64,
+    #[schema(example = 1)]
+    pub start_index: u64,
+    #[schema(example = 1)]
+    pub items_per_page: u64,
+    #[serde(rename = "Resources")]
+    #[schema()]
+    pub resources: Vec<ScimUserOAS>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[serde(rename_all = "camelCase")]
+#[response(status = 200, description = "Ok")]
+pub struct ScimGroupList {
+    #[schema(example = json!([LIST_SCHEMA]))]
+    pub schemas: Vec<String>,
+    #[schema(example = 1)]
+    pub total_results: u64,
+    #[schema(example = 1)]
+    pub start_index: u64,
+    #[schema(example = 1)]
+    pub items_per_page: u64,
+    #[serde(rename = "Resources")]
+    #[schema()]
+    pub resources: Vec<ScimGroupOAS>,
+}
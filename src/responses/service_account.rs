@@ -0,0 +1,67 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{users, Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::service_accounts;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct ServiceAccountOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub user_id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub organization_id: Option<Id>,
+    #[schema(example = "billing-worker")]
+    pub code: String,
+    #[schema(example = "billing worker")]
+    pub name: String,
+    #[schema(example = "charges subscriptions every night")]
+    pub description: Option<String>,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: String,
+    #[schema(example = true)]
+    pub has_api_key: bool,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<(&service_accounts::Model, &users::Model)> for ServiceAccountOAS {
+    fn from((account, user): (&service_accounts::Model, &users::Model)) -> Self {
+        Self {
+            id: account.id.clone(),
+            user_id: account.user_id.clone(),
+            organization_id: account.organization_id.clone(),
+            code: account.code.clone(),
+            name: user.name.clone(),
+            description: account.description.clone(),
+            client_id: account.client_id.clone(),
+            has_api_key: account.api_key.is_some(),
+            created_at: account.created_at,
+            updated_at: account.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 201, description = "Created")]
+pub struct ServiceAccountCredentials {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub client_id: String,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    #[schema()]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[schema(example = "Copy the credentials now since they won't be shown again")]
+    pub message: String,
+}
+
+pagination::create!(ServiceAccountOAS);
@@ -11,6 +11,9 @@ pub struct Created {
     pub id: Id,
     #[schema(example = "User has been created")]
     pub message: String,
+    /// Accepted but worth telling the user about, like a breached password
+    #[schema(example = json!([]))]
+    pub warnings: Vec<String>,
 }
 
 #[derive(Clone, Deserialize, Serialize, ToSchema, IntoResponses)]
@@ -0,0 +1,53 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::dao;
+use crate::models::webhooks;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct WebhookOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "https://hooks.example.com/nightmare")]
+    pub url: String,
+    #[schema(example = "provisions accounts in the billing system")]
+    pub description: Option<String>,
+    #[schema(example = json!(["user.created", "user.deleted", "session.*"]))]
+    pub events: Vec<String>,
+    #[schema(example = true)]
+    pub enabled: bool,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<&webhooks::Model> for WebhookOAS {
+    fn from(webhook: &webhooks::Model) -> Self {
+        Self {
+            id: webhook.id.clone(),
+            url: webhook.url.clone(),
+            description: webhook.description.clone(),
+            events: dao::webhook::events(webhook),
+            enabled: webhook.enabled,
+            created_at: webhook.created_at,
+            updated_at: webhook.updated_at,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 201, description = "Created")]
+pub struct WebhookSecret {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = "whsec_3yQ1v9oGdJ8uQ1dYkq3bCt")]
+    pub secret: String,
+    #[schema(example = "Copy the secret now since it won't be shown again")]
+    pub message: String,
+}
+
+pagination::create!(WebhookOAS);
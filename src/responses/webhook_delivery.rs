@@ -0,0 +1,64 @@
+use nightmare_common::response::pagination;
+use nightmare_common::models::{Id, Timestamp};
+use serde::{Deserialize, Serialize};
+use utoipa::{ToSchema, IntoResponses};
+
+use crate::models::webhook_deliveries;
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema, IntoResponses)]
+#[response(status = 200, description = "Ok")]
+pub struct WebhookDeliveryOAS {
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub webhook_id: Id,
+    #[schema(example = json!(Uuid::new_v4().to_string()))]
+    pub event_id: Id,
+    #[schema(example = "user.created")]
+    pub event: String,
+    /// Body sent to the webhook
+    #[schema(example = "{\"id\":\"...\",\"type\":\"user.created\",\"version\":1,\"data\":{}}")]
+    pub payload: String,
+    /// `pending`, `delivered` or `dead`
+    #[schema(example = "delivered")]
+    pub status: String,
+    #[schema(example = 1)]
+    pub attempts: i32,
+    #[schema(example = 200)]
+    pub response_status: Option<i32>,
+    #[schema(example = "ok")]
+    pub response_body: Option<String>,
+    #[schema(example = "connection refused")]
+    pub last_error: Option<String>,
+    #[schema()]
+    pub next_attempt_at: Option<Timestamp>,
+    #[schema()]
+    pub delivered_at: Option<Timestamp>,
+    #[schema()]
+    pub created_at: Timestamp,
+    #[schema()]
+    pub updated_at: Timestamp,
+}
+
+impl From<&webhook_deliveries::Model> for WebhookDeliveryOAS {
+    fn from(delivery: &webhook_deliveries::Model) -> Self {
+        Self {
+            id: delivery.id.clone(),
+            webhook_id: delivery.webhook_id.clone(),
+            event_id: delivery.event_id.clone(),
+            event: delivery.event.clone(),
+            payload: delivery.payload.clone(),
+            status: delivery.status.clone(),
+            attempts: delivery.attempts,
+            response_status: delivery.response_status,
+            response_body: delivery.response_body.clone(),
+            last_error: delivery.last_error.clone(),
+            next_attempt_at: delivery.next_attempt_at,
+            delivered_at: delivery.delivered_at,
+            created_at: delivery.created_at,
+            updated_at: delivery.updated_at,
+        }
+    }
+}
+
+pagination::create!(WebhookDeliveryOAS);
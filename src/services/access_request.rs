@@ -0,0 +1,414 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{permissions, Id};
+use nightmare_common::log;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, DbErr, TransactionTrait};
+use serde_json::json;
+
+use crate::dao;
+use crate::dao::access_request::{APPROVED, CANCELLED, PENDING, REJECTED};
+use crate::dao::authorization::Window;
+use crate::events;
+use crate::models::access_requests;
+use crate::requests::access_request::{AccessRequestDecisionRequest, AccessRequestOrderByColumn, AccessRequestStoreRequest};
+use crate::responses::access_request::{AccessRequestHistoryOAS, AccessRequestOAS};
+use crate::services::user::window;
+
+/// Permission that allows approving any access request, can be
+/// overridden with `ACCESS_REQUEST_APPROVER_PERMISSION`
+pub const APPROVER_PERMISSION: &str = "APPROVE_ACCESS_REQUEST";
+
+fn approver_permission() -> String {
+    std::env::var("ACCESS_REQUEST_APPROVER_PERMISSION")
+        .unwrap_or(APPROVER_PERMISSION.to_string())
+}
+
+fn is_approver(auth: &Auth) -> bool {
+    let code = approver_permission();
+
+    auth.permissions.iter().any(|permission| permission.code.eq(&code))
+}
+
+/// Requesters never decide on their own requests, everyone else needs the
+/// approver permission or to own the requested role
+async fn can_decide(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: &access_requests::Model,
+) -> bool {
+    if request.user_id.eq(&auth.user.id) {
+        return false
+    }
+
+    if is_approver(auth) {
+        return true
+    }
+
+    match &request.role_id {
+        None => false,
+        Some(role_id) => dao::role::is_owner(db, role_id, &auth.user.id).await,
+    }
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: Auth,
+    request: PaginationRequest<AccessRequestOrderByColumn>,
+) -> HttpResponse {
+    let mut query = access_requests::Entity::find()
+        .order_by(match request.order(AccessRequestOrderByColumn::CreatedAt) {
+            AccessRequestOrderByColumn::Status => access_requests::Column::Status,
+            AccessRequestOrderByColumn::CreatedAt => access_requests::Column::CreatedAt,
+        }, request.sort());
+
+    if !is_approver(&auth) {
+        let owned = match dao::role::owned(db, &auth.user.id).await {
+            Err(e) => {
+                log::error!(paginate, "{}", e);
+
+                return HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            Ok(owned) => owned,
+        };
+
+        query = query.filter(
+            Condition::any()
+                .add(access_requests::Column::UserId.eq(auth.user.id.clone()))
+                .add(access_requests::Column::RoleId.is_in(owned))
+        );
+    }
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(access_requests::Column::Status.like(request.search()))
+                .add(access_requests::Column::Justification.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                   .map(|request| request.into())
+                   .collect::<Vec<AccessRequestOAS>>(),
+            }))
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: Auth,
+    request: AccessRequestStoreRequest,
+) -> HttpResponse {
+    let mut validation = HashMap::new();
+    let justification = request.justification.trim().to_string();
+
+    match (&request.role, &request.permission) {
+        (None, None) | (Some(_), Some(_)) => {
+            validation.insert("role", vec!["either role or permission must be requested"]);
+        },
+        (Some(role), None) => {
+            if dao::role::find(db, role.clone()).await.is_none() {
+                validation.insert("role", vec!["role doesn't exists"]);
+            }
+        },
+        (None, Some(permission)) => {
+            let exist = permissions::Entity::find_by_id(permission.clone())
+                .one(db)
+                .await
+                .unwrap_or(None);
+
+            if exist.is_none() {
+                validation.insert("permission", vec!["permission doesn't exists"]);
+            }
+        },
+    }
+
+    if justification.is_empty() {
+        validation.insert("justification", vec!["field justification is required"]);
+    }
+
+    if !window(request.starts_at, request.expires_at) {
+        validation.insert("expires_at", vec!["expires at must be after starts at"]);
+    }
+
+    if validation.is_empty() && dao::access_request::pending(db, &auth.user, request.role.clone(), request.permission.clone()).await {
+        validation.insert("role", vec!["the same access is already requested"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let window = Window {
+        starts_at: request.starts_at,
+        expires_at: request.expires_at,
+    };
+
+    let stored = async {
+        let txn = db.begin().await?;
+        let request = dao::access_request::store(&txn, &auth.user, request.role, request.permission, justification, window).await?;
+
+        events::emit(&txn, events::ACCESS_REQUEST_CREATED, json!({
+            "id": request.id,
+            "user_id": request.user_id,
+            "role_id": request.role_id,
+            "permission_id": request.permission_id,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(request)
+    };
+
+    match stored.await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(request) => {
+            HttpResponse::Created().json(json!({
+                "id": request.id,
+                "message": "Access request has been submitted",
+            }))
+        },
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+) -> HttpResponse {
+    let request = match dao::access_request::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(request) => request,
+    };
+
+    if !request.user_id.eq(&auth.user.id) && !can_decide(db, &auth, &request).await {
+        return HttpResponse::NotFound().finish()
+    }
+
+    match dao::access_request::histories(db, &request).await {
+        Err(e) => {
+            log::error!(show, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(histories) => {
+            let mut response = AccessRequestOAS::from(&request);
+
+            response.histories = Some(histories.iter().map(AccessRequestHistoryOAS::from).collect());
+
+            HttpResponse::Ok().json(response)
+        },
+    }
+}
+
+fn not_pending() -> HttpResponse {
+    HttpResponse::UnprocessableEntity().json(json!({
+        "errors": {
+            "status": ["access request is not pending"],
+        },
+    }))
+}
+
+pub async fn approve<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+    decision: AccessRequestDecisionRequest,
+) -> HttpResponse {
+    let request = match dao::access_request::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(request) => request,
+    };
+
+    if !can_decide(db, &auth, &request).await {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "you are not allowed to decide on this access request",
+        }))
+    }
+
+    if !request.status.eq(PENDING) {
+        return not_pending()
+    }
+
+    let comment = decision.comment
+        .map(|comment| comment.trim().to_string())
+        .filter(|comment| !comment.is_empty());
+
+    // the status moves first so a concurrent decision finds nothing pending
+    // and the grant is never applied twice
+    let approved = async {
+        let txn = db.begin().await?;
+        let request = match dao::access_request::decide(&txn, &request, &auth.user.id, APPROVED, comment).await? {
+            None => return Ok(None),
+            Some(request) => request,
+        };
+
+        dao::access_request::apply(&txn, &request).await?;
+        events::emit(&txn, events::ACCESS_REQUEST_APPROVED, json!({
+            "id": request.id,
+            "user_id": request.user_id,
+            "approver_id": auth.user.id,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(Some(request))
+    };
+
+    match approved.await {
+        Err(e) => {
+            log::error!(approve, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(None) => not_pending(),
+        Ok(Some(request)) => {
+            HttpResponse::Ok().json(json!({
+                "id": request.id,
+                "message": "Access request has been approved",
+            }))
+        },
+    }
+}
+
+pub async fn reject<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+    decision: AccessRequestDecisionRequest,
+) -> HttpResponse {
+    let request = match dao::access_request::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(request) => request,
+    };
+
+    if !can_decide(db, &auth, &request).await {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "you are not allowed to decide on this access request",
+        }))
+    }
+
+    let mut validation = HashMap::new();
+    let comment = decision.comment
+        .map(|comment| comment.trim().to_string())
+        .filter(|comment| !comment.is_empty());
+
+    if !request.status.eq(PENDING) {
+        validation.insert("status", vec!["access request is not pending"]);
+    }
+
+    if comment.is_none() {
+        validation.insert("comment", vec!["field comment is required when rejecting"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let rejected = async {
+        let txn = db.begin().await?;
+        let request = match dao::access_request::decide(&txn, &request, &auth.user.id, REJECTED, comment).await? {
+            None => return Ok(None),
+            Some(request) => request,
+        };
+
+        events::emit(&txn, events::ACCESS_REQUEST_REJECTED, json!({
+            "id": request.id,
+            "user_id": request.user_id,
+            "approver_id": auth.user.id,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(Some(request))
+    };
+
+    match rejected.await {
+        Err(e) => {
+            log::error!(reject, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(None) => not_pending(),
+        Ok(Some(request)) => {
+            HttpResponse::Ok().json(json!({
+                "id": request.id,
+                "message": "Access request has been rejected",
+            }))
+        },
+    }
+}
+
+pub async fn cancel<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+) -> HttpResponse {
+    let request = match dao::access_request::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(request) => request,
+    };
+
+    if !request.user_id.eq(&auth.user.id) {
+        return HttpResponse::NotFound().finish()
+    }
+
+    if !request.status.eq(PENDING) {
+        return not_pending()
+    }
+
+    match dao::access_request::decide(db, &request, &auth.user.id, CANCELLED, None).await {
+        Err(e) => {
+            log::error!(cancel, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(None) => not_pending(),
+        Ok(Some(request)) => {
+            HttpResponse::Ok().json(json!({
+                "id": request.id,
+                "message": "Access request has been cancelled",
+            }))
+        },
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use actix_web::http::header::CONTENT_DISPOSITION;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{permissions, roles, users, Id};
+use nightmare_common::log;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, DbErr, TransactionTrait};
+use serde_json::json;
+
+use crate::dao;
+use crate::dao::access_review::{APPROVED, CLOSED, PENDING, REVOKED};
+use crate::events;
+use crate::models::{access_review_campaigns, access_review_items};
+use crate::requests::access_review::{AccessReviewDecision, AccessReviewItemDecisionRequest, AccessReviewOrderByColumn, AccessReviewStoreRequest};
+use crate::responses::access_review::{AccessReviewItemOAS, AccessReviewOAS};
+
+/// Permission required to run access reviews and decide their items
+pub const REVIEW_PERMISSION: &str = "MANAGE_ACCESS_REVIEWS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(REVIEW_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage access reviews",
+    })))
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<AccessReviewOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = access_review_campaigns::Entity::find()
+        .order_by(match request.order(AccessReviewOrderByColumn::CreatedAt) {
+            AccessReviewOrderByColumn::Name => access_review_campaigns::Column::Name,
+            AccessReviewOrderByColumn::Status => access_review_campaigns::Column::Status,
+            AccessReviewOrderByColumn::CreatedAt => access_review_campaigns::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(access_review_campaigns::Column::Name.like(request.search()))
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                   .map(|campaign| campaign.into())
+                   .collect::<Vec<AccessReviewOAS>>(),
+            }))
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: Auth,
+    request: AccessReviewStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(&auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_string();
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if request.roles.is_empty() && request.permissions.is_empty() {
+        validation.insert("roles", vec!["at least one role or permission must be reviewed"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let roles = roles::Entity::find()
+        .filter(roles::Column::Id.is_in(request.roles));
+
+    log::debug!(store, "{}", roles.build(db.get_database_backend()).to_string());
+
+    let roles = match roles.all(db).await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(roles) => roles,
+    };
+
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Id.is_in(request.permissions));
+
+    log::debug!(store, "{}", permissions.build(db.get_database_backend()).to_string());
+
+    let permissions = match permissions.all(db).await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(permissions) => permissions,
+    };
+
+    let stored = async {
+        let txn = db.begin().await?;
+        let campaign = dao::access_review::store(&txn, name, &auth.user, roles, permissions).await?;
+
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(campaign)
+    };
+
+    match stored.await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(campaign) => {
+            HttpResponse::Created().json(json!({
+                "id": campaign.id,
+                "message": "Access review has been opened",
+            }))
+        },
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let campaign = match dao::access_review::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(campaign) => campaign,
+    };
+
+    match dao::access_review::items(db, &campaign).await {
+        Err(e) => {
+            log::error!(show, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(items) => {
+            let mut response = AccessReviewOAS::from(&campaign);
+
+            response.items = Some(items.iter().map(AccessReviewItemOAS::from).collect());
+
+            HttpResponse::Ok().json(response)
+        },
+    }
+}
+
+pub async fn decide<I: Into<Id>, T: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+    item: T,
+    request: AccessReviewItemDecisionRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(&auth) {
+        return response
+    }
+
+    let campaign = match dao::access_review::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(campaign) => campaign,
+    };
+
+    let item = match dao::access_review::find_item(db, &campaign, item).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(item) => item,
+    };
+
+    if item.user_id.eq(&auth.user.id) {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "you can't review your own access",
+        }))
+    }
+
+    let mut validation = HashMap::new();
+    let comment = request.comment
+        .map(|comment| comment.trim().to_string())
+        .filter(|comment| !comment.is_empty());
+
+    if campaign.status.eq(CLOSED) {
+        validation.insert("campaign", vec!["access review is already closed"]);
+    }
+
+    if !item.decision.eq(PENDING) {
+        validation.insert("decision", vec!["item is already reviewed"]);
+    }
+
+    if request.decision == AccessReviewDecision::Revoke && comment.is_none() {
+        validation.insert("comment", vec!["field comment is required when revoking"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let decision = match request.decision {
+        AccessReviewDecision::Approve => APPROVED,
+        AccessReviewDecision::Revoke => REVOKED,
+    };
+
+    let decided = async {
+        let txn = db.begin().await?;
+        let item = dao::access_review::decide(&txn, &item, &auth.user, decision, comment).await?;
+
+        if item.decision.eq(REVOKED) {
+            events::emit(&txn, events::ACCESS_REVIEW_REVOKED, json!({
+                "id": item.id,
+                "campaign_id": item.campaign_id,
+                "user_id": item.user_id,
+                "role_id": item.role_id,
+                "permission_id": item.permission_id,
+                "reviewer_id": item.reviewer_id,
+            })).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(item)
+    };
+
+    match decided.await {
+        Err(e) => {
+            log::error!(decide, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(item) => {
+            HttpResponse::Ok().json(json!({
+                "id": item.id,
+                "message": format!("Access has been {}", item.decision),
+            }))
+        },
+    }
+}
+
+pub async fn close<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let campaign = match dao::access_review::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(campaign) => campaign,
+    };
+
+    if campaign.status.eq(CLOSED) {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "campaign": ["access review is already closed"],
+            },
+        }))
+    }
+
+    match dao::access_review::close(db, &campaign).await {
+        Err(e) => {
+            log::error!(close, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(campaign) => {
+            HttpResponse::Ok().json(json!({
+                "id": campaign.id,
+                "message": "Access review has been closed",
+            }))
+        },
+    }
+}
+
+pub async fn export<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let campaign = match dao::access_review::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(campaign) => campaign,
+    };
+
+    let items = match dao::access_review::items(db, &campaign).await {
+        Err(e) => {
+            log::error!(export, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(items) => items,
+    };
+
+    match report(db, &items).await {
+        Err(e) => {
+            log::error!(export, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(report) => {
+            HttpResponse::Ok()
+                .content_type("text/csv")
+                .insert_header((CONTENT_DISPOSITION, format!("attachment; filename=\"access-review-{}.csv\"", campaign.id)))
+                .body(report)
+        },
+    }
+}
+
+/// Render review items as csv with the codes and usernames resolved
+async fn report(
+    db: &DatabaseConnection,
+    items: &[access_review_items::Model],
+) -> Result<String, sea_orm::DbErr> {
+    let mut ids = items.iter()
+        .map(|item| item.user_id.clone())
+        .collect::<Vec<Id>>();
+
+    ids.extend(items.iter().filter_map(|item| item.reviewer_id.clone()));
+
+    let users = users::Entity::find()
+        .filter(users::Column::Id.is_in(ids))
+        .all(db)
+        .await?;
+
+    let roles = roles::Entity::find()
+        .filter(roles::Column::Id.is_in(items.iter().filter_map(|item| item.role_id.clone()).collect::<Vec<Id>>()))
+        .all(db)
+        .await?;
+
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Id.is_in(items.iter().filter_map(|item| item.permission_id.clone()).collect::<Vec<Id>>()))
+        .all(db)
+        .await?;
+
+    let username = |id: &Id| users.iter()
+        .find(|user| user.id.eq(id))
+        .map(|user| user.username.clone())
+        .unwrap_or_default();
+
+    let mut report = String::from("user,type,code,resource_type,resource_id,decision,reviewer,comment,decided_at\n");
+
+    for item in items {
+        let (kind, code) = match (&item.role_id, &item.permission_id) {
+            (Some(id), _) => ("role", roles.iter().find(|role| role.id.eq(id)).map(|role| role.code.clone())),
+            (_, Some(id)) => ("permission", permissions.iter().find(|permission| permission.id.eq(id)).map(|permission| permission.code.clone())),
+            _ => ("", None),
+        };
+
+        let row = [
+            username(&item.user_id),
+            kind.to_string(),
+            code.unwrap_or_default(),
+            item.resource_type.clone().unwrap_or_default(),
+            item.resource_id.clone().unwrap_or_default(),
+            item.decision.clone(),
+            item.reviewer_id.as_ref().map(username).unwrap_or_default(),
+            item.comment.clone().unwrap_or_default(),
+            item.decided_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+        ];
+
+        report.push_str(&row.iter().map(|field| escape(field)).collect::<Vec<String>>().join(","));
+        report.push('\n');
+    }
+
+    Ok(report)
+}
+
+fn escape(field: &str) -> String {
+    // spreadsheets run cells starting with these as formulas
+    let field = if field.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", field)
+    } else {
+        field.to_string()
+    };
+
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
@@ -0,0 +1,596 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::OnceLock;
+
+use actix_web::HttpResponse;
+use chrono::SubsecRound;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{log, time};
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, TransactionTrait};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::dao;
+use crate::middleware::audit::Audit;
+use crate::models::{audit_checkpoints, audit_logs};
+use crate::requests::audit::{AuditCheckpointOrderByColumn, AuditFilterRequest, AuditOrderByColumn};
+use crate::responses::audit::{AuditBreak, AuditOAS, AuditVerification};
+use crate::responses::audit_checkpoint::AuditCheckpointOAS;
+use crate::services::oidc::Keys;
+
+/// Actions that aren't domain events, the rest share the event type names
+pub const LOGIN: &str = "auth.login";
+pub const LOGIN_FAILED: &str = "auth.login_failed";
+pub const LOGOUT: &str = "auth.logout";
+
+/// Previous hash of the first chained entry
+const GENESIS: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Entries read at a time while verifying the chain
+const VERIFY_BATCH: u64 = 500;
+
+static KEYS: OnceLock<Keys> = OnceLock::new();
+
+/// Checkpoint signing key from `AUDIT_SIGNING_KEY`, kept apart from the
+/// OIDC key so rotating or leaking that one can't orphan or forge
+/// checkpoints
+fn keys() -> &'static Keys {
+    KEYS.get_or_init(|| Keys::from_env("AUDIT_SIGNING_KEY"))
+}
+
+/// Id of the checkpoint signing key
+pub fn key_id() -> String {
+    keys().kid()
+}
+
+/// Permission required to read and verify the audit log
+pub const VIEW_PERMISSION: &str = "VIEW_AUDIT_LOG";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(VIEW_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to view the audit log",
+    })))
+}
+
+/// Hex sha256 over the position and content of the entry. Timestamps are
+/// hashed at microsecond precision, which is what the database keeps
+pub fn hash(entry: &audit_logs::Model) -> String {
+    let content = json!([
+        entry.sequence,
+        entry.previous_hash,
+        entry.id.to_string(),
+        entry.actor_id.as_ref().map(ToString::to_string),
+        entry.impersonator_id.as_ref().map(ToString::to_string),
+        entry.action,
+        entry.target_type,
+        entry.target_id,
+        entry.old_values,
+        entry.new_values,
+        entry.ip,
+        entry.user_agent,
+        entry.created_at.format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+    ]);
+
+    Sha256::digest(content.to_string().as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Keep only the fields whose value differs, both sides are kept whole
+/// when either isn't an object
+fn diff(old: Value, new: Value) -> (Value, Value) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut before = Map::new();
+            let mut after = Map::new();
+
+            for key in old.keys().chain(new.keys()) {
+                let (old, new) = (old.get(key), new.get(key));
+
+                if old.ne(&new) {
+                    before.insert(key.clone(), old.cloned().unwrap_or(Value::Null));
+                    after.insert(key.clone(), new.cloned().unwrap_or(Value::Null));
+                }
+            }
+
+            (Value::Object(before), Value::Object(after))
+        },
+        sides => sides,
+    }
+}
+
+/// Append an entry for the action, pass the transaction of the change so
+/// the trail never disagrees with the data. The chain head stays locked
+/// until that transaction ends
+pub async fn record<D: ConnectionTrait + TransactionTrait>(
+    db: &D,
+    audit: &Audit,
+    action: &str,
+    target: Option<(&str, &Id)>,
+    old: Value,
+    new: Value,
+) -> Result<(), DbErr> {
+    let (old, new) = diff(old, new);
+    let serialize = |values: Value| match values {
+        Value::Null => None,
+        values => Some(values.to_string()),
+    };
+
+    let txn = db.begin().await?;
+    let head = dao::audit::head(&txn, true).await?;
+    let mut entry = audit_logs::Model {
+        id: Uuid::new_v4().into(),
+        actor_id: audit.actor_id.clone(),
+        impersonator_id: audit.impersonator_id.clone(),
+        action: action.to_string(),
+        target_type: target.map(|(kind, _)| kind.to_string()),
+        target_id: target.map(|(_, id)| id.to_string()),
+        old_values: serialize(old),
+        new_values: serialize(new),
+        ip: audit.ip.clone(),
+        user_agent: audit.user_agent.clone(),
+        created_at: time::now().trunc_subsecs(6),
+        sequence: Some(head.sequence + 1),
+        previous_hash: Some(head.hash.clone()),
+        hash: None,
+    };
+
+    entry.hash = Some(hash(&entry));
+
+    let entry = dao::audit::store(&txn, entry).await?;
+
+    dao::audit::advance(&txn, &head, &entry).await?;
+    txn.commit().await?;
+
+    log::debug!(services::audit::record, "{} {}", entry.action, entry.id);
+
+    Ok(())
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<AuditOrderByColumn>,
+    filter: AuditFilterRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = audit_logs::Entity::find()
+        .order_by(match request.order(AuditOrderByColumn::CreatedAt) {
+            AuditOrderByColumn::Action => audit_logs::Column::Action,
+            AuditOrderByColumn::CreatedAt => audit_logs::Column::CreatedAt,
+        }, request.sort());
+
+    if let Some(actor_id) = filter.actor_id {
+        query = query.filter(audit_logs::Column::ActorId.eq(actor_id));
+    }
+
+    if let Some(action) = filter.action.filter(|action| !action.is_empty()) {
+        query = match action.strip_suffix('*') {
+            None => query.filter(audit_logs::Column::Action.eq(action)),
+            Some(prefix) => query.filter(audit_logs::Column::Action.starts_with(prefix)),
+        };
+    }
+
+    if let Some(target_type) = filter.target_type {
+        query = query.filter(audit_logs::Column::TargetType.eq(target_type));
+    }
+
+    if let Some(target_id) = filter.target_id {
+        query = query.filter(audit_logs::Column::TargetId.eq(target_id));
+    }
+
+    if let Some(ip) = filter.ip {
+        query = query.filter(audit_logs::Column::Ip.eq(ip));
+    }
+
+    if let Some(from) = filter.from {
+        query = query.filter(audit_logs::Column::CreatedAt.gte(from));
+    }
+
+    if let Some(to) = filter.to {
+        query = query.filter(audit_logs::Column::CreatedAt.lt(to));
+    }
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(audit_logs::Column::Action.like(request.search()))
+                .add(audit_logs::Column::TargetId.like(request.search()))
+                .add(audit_logs::Column::UserAgent.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                    .map(AuditOAS::from)
+                    .collect::<Vec<AuditOAS>>(),
+            }))
+        },
+    }
+}
+
+/// Sequence and hash the checkpoint's signature vouches for
+fn signed(keys: &Keys, checkpoint: &audit_checkpoints::Model) -> Result<(i64, String), String> {
+    let claims = keys.verify(&checkpoint.signature)?;
+
+    match (claims.get("sub").and_then(Value::as_str), claims.get("sequence").and_then(Value::as_i64), claims.get("hash").and_then(Value::as_str)) {
+        (Some("audit_logs"), Some(sequence), Some(hash)) => Ok((sequence, hash.to_string())),
+        _ => Err("signature isn't an audit checkpoint".to_string()),
+    }
+}
+
+/// Walk the chain from the genesis and stop at the first entry that is
+/// missing, out of place, edited or disagrees with a signed checkpoint.
+/// Checkpoints only count by what their signature vouches for, the row is
+/// as editable as the entries
+async fn walk(db: &DatabaseConnection, keys: &Keys) -> Result<AuditVerification, DbErr> {
+    let head = dao::audit::head(db, false).await?;
+    let unchained = dao::audit::unchained(db).await?;
+    let mut checkpoints = dao::audit::checkpoints(db).await?
+        .into_iter()
+        .map(|checkpoint| match signed(keys, &checkpoint) {
+            Ok((sequence, hash)) if sequence.eq(&checkpoint.sequence) && hash.eq(&checkpoint.hash) => (sequence, Ok(hash)),
+            Ok((sequence, _)) => (sequence, Err("checkpoint doesn't match its signature".to_string())),
+            Err(_) => (checkpoint.sequence, Err("checkpoint signature is missing or invalid".to_string())),
+        })
+        .collect::<BTreeMap<i64, Result<String, String>>>();
+
+    let mut verification = AuditVerification {
+        valid: true,
+        entries: 0,
+        unchained,
+        checkpoints: 0,
+        sequence: 0,
+        hash: GENESIS.to_string(),
+        first_break: None,
+    };
+
+    'walk: loop {
+        let entries = dao::audit::chained(db, verification.sequence, VERIFY_BATCH).await?;
+
+        if entries.is_empty() {
+            break
+        }
+
+        for entry in entries {
+            let sequence = verification.sequence + 1;
+            let computed = hash(&entry);
+            let reason = if entry.sequence.ne(&Some(sequence)) {
+                Some(format!("entry {} is missing", sequence))
+            } else if entry.previous_hash.as_deref().ne(&Some(verification.hash.as_str())) {
+                Some("previous hash doesn't match the entry before".to_string())
+            } else if entry.hash.as_deref().ne(&Some(computed.as_str())) {
+                Some("content doesn't match its hash".to_string())
+            } else {
+                match checkpoints.remove(&sequence) {
+                    Some(Err(reason)) => Some(reason),
+                    Some(Ok(signed)) if signed.ne(&computed) => Some("hash doesn't match the signed checkpoint".to_string()),
+                    Some(Ok(_)) => {
+                        verification.checkpoints += 1;
+
+                        None
+                    },
+                    None => None,
+                }
+            };
+
+            if let Some(reason) = reason {
+                verification.first_break = Some(AuditBreak {
+                    sequence,
+                    id: entry.sequence.eq(&Some(sequence)).then_some(entry.id),
+                    reason,
+                });
+
+                break 'walk
+            }
+
+            verification.entries += 1;
+            verification.sequence = sequence;
+            verification.hash = computed;
+        }
+    }
+
+    if verification.first_break.is_none() {
+        // entries cut from the end leave the head or a checkpoint behind
+        let end = checkpoints.keys().next_back().copied().unwrap_or_default().max(head.sequence);
+        let first_break = if verification.sequence < end {
+            Some((verification.sequence + 1, format!("chain ends before sequence {}", end)))
+        } else if verification.sequence > end {
+            Some((end + 1, format!("entries were appended after the head at sequence {}", end)))
+        } else if verification.hash.ne(&head.hash) {
+            Some((end, "last entry doesn't match the chain head".to_string()))
+        } else {
+            None
+        };
+
+        verification.first_break = first_break.map(|(sequence, reason)| AuditBreak {
+            sequence,
+            id: None,
+            reason,
+        });
+    }
+
+    verification.valid = verification.first_break.is_none();
+
+    Ok(verification)
+}
+
+pub async fn verify(
+    db: &DatabaseConnection,
+    auth: &Auth,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match walk(db, keys()).await {
+        Err(e) => {
+            log::error!(verify, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(verification) => {
+            if let Some(first_break) = &verification.first_break {
+                log::error!(verify, "audit chain broken at {}, {}", first_break.sequence, first_break.reason);
+            }
+
+            HttpResponse::Ok().json(verification)
+        },
+    }
+}
+
+/// Sign the chain head when it moved since the last checkpoint and append
+/// the jws to `AUDIT_CHECKPOINT_FILE` when set, a copy outside the database
+/// that anyone holding the audit jwks can check the chain against
+pub async fn checkpoint(db: &DatabaseConnection) -> Result<Option<audit_checkpoints::Model>, String> {
+    let head = dao::audit::head(db, false).await.map_err(|e| e.to_string())?;
+    let last = dao::audit::last_checkpoint(db).await.map_err(|e| e.to_string())?;
+
+    if head.sequence.eq(&0) || last.is_some_and(|last| last.sequence >= head.sequence) {
+        return Ok(None)
+    }
+
+    let created_at = time::now();
+    let signature = keys().sign(&json!({
+        "sub": "audit_logs",
+        "sequence": head.sequence,
+        "hash": head.hash,
+        "iat": created_at.timestamp(),
+    }))?;
+
+    let checkpoint = dao::audit::store_checkpoint(db, audit_checkpoints::Model {
+        id: Uuid::new_v4().into(),
+        sequence: head.sequence,
+        hash: head.hash,
+        key_id: keys().kid(),
+        signature,
+        created_at,
+    }).await.map_err(|e| e.to_string())?;
+
+    let file = std::env::var("AUDIT_CHECKPOINT_FILE")
+        .ok()
+        .filter(|file| !file.trim().is_empty());
+
+    if let Some(file) = file {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file)
+            .and_then(|mut file| writeln!(file, "{}", checkpoint.signature))
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(Some(checkpoint))
+}
+
+/// Public key the checkpoints are signed with
+pub fn jwks(auth: &Auth) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    HttpResponse::Ok().json(json!({
+        "keys": [keys().jwk()],
+    }))
+}
+
+pub async fn checkpoints(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<AuditCheckpointOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = audit_checkpoints::Entity::find()
+        .order_by(match request.order(AuditCheckpointOrderByColumn::Sequence) {
+            AuditCheckpointOrderByColumn::Sequence => audit_checkpoints::Column::Sequence,
+            AuditCheckpointOrderByColumn::CreatedAt => audit_checkpoints::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(audit_checkpoints::Column::Hash.like(request.search()))
+                .add(audit_checkpoints::Column::KeyId.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(checkpoints, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(checkpoints, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                    .map(AuditCheckpointOAS::from)
+                    .collect::<Vec<AuditCheckpointOAS>>(),
+            }))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsa::RsaPrivateKey;
+    use sea_orm::{ActiveModelTrait, Set};
+
+    use super::*;
+    use crate::models::audit_chain;
+    use crate::testing;
+
+    fn key() -> Keys {
+        Keys::new(RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap())
+    }
+
+    /// Chain of three entries
+    async fn database() -> DatabaseConnection {
+        let db = testing::database(vec![
+            testing::table(audit_logs::Entity),
+            testing::table(audit_chain::Entity),
+            testing::table(audit_checkpoints::Entity),
+        ]).await;
+
+        audit_chain::ActiveModel::from(audit_chain::Model {
+            id: Uuid::new_v4().into(),
+            sequence: 0,
+            hash: GENESIS.to_string(),
+            updated_at: time::now(),
+        }).insert(&db).await.unwrap();
+
+        for number in 1..=3 {
+            record(&db, &Audit::default(), "user.created", None, Value::Null, json!({ "number": number })).await.unwrap();
+        }
+
+        db
+    }
+
+    /// Checkpoint of the head signed with the key
+    async fn checkpoint(db: &DatabaseConnection, keys: &Keys) -> audit_checkpoints::Model {
+        let head = dao::audit::head(db, false).await.unwrap();
+        let signature = keys.sign(&json!({
+            "sub": "audit_logs",
+            "sequence": head.sequence,
+            "hash": head.hash,
+            "iat": time::now().timestamp(),
+        })).unwrap();
+
+        dao::audit::store_checkpoint(db, audit_checkpoints::Model {
+            id: Uuid::new_v4().into(),
+            sequence: head.sequence,
+            hash: head.hash,
+            key_id: keys.kid(),
+            signature,
+            created_at: time::now(),
+        }).await.unwrap()
+    }
+
+    #[actix_web::test]
+    async fn signed_checkpoints_hold() {
+        let db = database().await;
+        let keys = key();
+
+        checkpoint(&db, &keys).await;
+
+        let verification = walk(&db, &keys).await.unwrap();
+
+        assert!(verification.valid);
+        assert_eq!(verification.entries, 3);
+        assert_eq!(verification.checkpoints, 1);
+    }
+
+    #[actix_web::test]
+    async fn tampered_checkpoint_rows_break_the_chain() {
+        let db = database().await;
+        let keys = key();
+        let mut tampered = audit_checkpoints::ActiveModel::from(checkpoint(&db, &keys).await);
+
+        tampered.hash = Set("f".repeat(64));
+        tampered.update(&db).await.unwrap();
+
+        let verification = walk(&db, &keys).await.unwrap();
+        let first_break = verification.first_break.unwrap();
+
+        assert!(!verification.valid);
+        assert_eq!(first_break.sequence, 3);
+        assert_eq!(first_break.reason, "checkpoint doesn't match its signature");
+    }
+
+    #[actix_web::test]
+    async fn checkpoints_signed_with_a_foreign_key_break_the_chain() {
+        let db = database().await;
+        let keys = key();
+
+        checkpoint(&db, &key()).await;
+
+        let verification = walk(&db, &keys).await.unwrap();
+        let first_break = verification.first_break.unwrap();
+
+        assert!(!verification.valid);
+        assert_eq!(verification.checkpoints, 0);
+        assert_eq!(first_break.sequence, 3);
+        assert_eq!(first_break.reason, "checkpoint signature is missing or invalid");
+    }
+
+    #[actix_web::test]
+    async fn unsigned_checkpoints_break_the_chain() {
+        let db = database().await;
+        let keys = key();
+        let mut unsigned = audit_checkpoints::ActiveModel::from(checkpoint(&db, &keys).await);
+
+        unsigned.signature = Set(String::new());
+        unsigned.update(&db).await.unwrap();
+
+        let first_break = walk(&db, &keys).await.unwrap().first_break.unwrap();
+
+        assert_eq!(first_break.sequence, 3);
+        assert_eq!(first_break.reason, "checkpoint signature is missing or invalid");
+    }
+}
@@ -2,61 +2,113 @@ use std::collections::HashMap;
 use std::str::FromStr;
 
 use actix_web::{HttpResponse, ResponseError};
-use chrono::NaiveDateTime;
-use nightmare_common::{base58, hash, log};
-use nightmare_common::hash::Hash;
+use nightmare_common::{base58, log, time};
 use nightmare_common::middleware::auth::Auth;
-use nightmare_common::models::{Id, QUERY_BUILDER, permission_user, permissions, role_user, roles, tokens, users};
+use nightmare_common::models::{Id, users};
 use nightmare_common::response::http::Unauthorized;
-use sea_orm::{ConnectionTrait, DatabaseConnection, EntityName, FromQueryResult, IntoIdentity, Statement};
-use sea_query::{Expr, Iden, IntoIden, Query, SelectStatement};
-use serde_json::{Value, json};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde_json::{json, Value};
 use uuid::Uuid;
 
-use crate::{requests::auth::Login, dao::{user, self}, responses::user::UserOAS};
+use crate::{backends, events, services};
+use crate::backends::Failure;
+use crate::dao::authorization::Grants;
+use crate::middleware::audit::Audit;
+use crate::middleware::tenant::Tenant;
+use crate::models::tokens;
+use crate::{requests::auth::Login, dao, responses::{auth::Session, organization::OrganizationOAS, user::UserOAS}};
+
+/// Record a refused login, failing to write the entry doesn't change the
+/// answer the caller gets
+async fn refused(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    email_or_username: &str,
+    reason: &str,
+) {
+    let recorded = services::audit::record(db, audit, services::audit::LOGIN_FAILED, None, Value::Null, json!({
+        "email_or_username": email_or_username,
+        "reason": reason,
+    }));
+
+    if let Err(e) = recorded.await {
+        log::error!(services::auth::refused, "{}", e);
+    }
+}
+
+/// Why a password sign in was refused
+pub enum Refusal {
+    Failure(Failure),
+    ServiceAccount,
+    /// Carries the user since only the current password proves who they are
+    Expired(users::Model),
+    Error(DbErr),
+}
+
+/// Check the password against the configured backends, refuse service
+/// accounts and expired local passwords. Every refusal except an unreachable
+/// backend is audited as a failed login
+pub async fn sign_in(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    email_or_username: &str,
+    password: &str,
+) -> Result<users::Model, Refusal> {
+    let (user, method) = match backends::authenticate(db, email_or_username, password).await {
+        Ok(authenticated) => authenticated,
+        Err(Failure::Unavailable(e)) => return Err(Refusal::Failure(Failure::Unavailable(e))),
+        Err(failure) => {
+            let reason = match &failure {
+                Failure::Rejected => "wrong password",
+                Failure::Conflict(message) => message.as_str(),
+                _ => "unknown user",
+            };
+
+            refused(db, audit, email_or_username, reason).await;
+
+            return Err(Refusal::Failure(failure))
+        },
+    };
+
+    if dao::service_account::find_by_user(db, &user).await.is_some() {
+        refused(db, &audit.acting_as(user.id.clone()), email_or_username, "service account").await;
+
+        return Err(Refusal::ServiceAccount)
+    }
+
+    match services::password::policy().expired(db, &user, method).await {
+        Err(e) => {
+            log::error!(services::auth::sign_in, "{}", e);
+
+            return Err(Refusal::Error(e))
+        },
+        Ok(true) => {
+            refused(db, &audit.acting_as(user.id.clone()), email_or_username, "password expired").await;
+
+            return Err(Refusal::Expired(user))
+        },
+        Ok(false) => {},
+    }
+
+    Ok(user)
+}
 
 pub async fn login(
     db: &DatabaseConnection,
+    audit: &Audit,
     request: Login,
 ) -> HttpResponse {
     log::info!(login, "{}", request.email_or_username);
     let mut validation = HashMap::new();
     let email_or_username = request.email_or_username.trim().to_lowercase();
     let password = request.password;
-    let mut user = None;
 
     if email_or_username.is_empty() {
         validation.insert("email_or_username", vec!["field email or username is required"]);
-    } else {
-        let mut errors = vec![];
-
-        user = user::find_by_email_or_username(db, email_or_username.clone()).await;
-
-        if user.is_none() {
-            errors.push("email or username doesn't exist");
-        }
-
-        if !errors.is_empty() {
-            validation.insert("email_or_username", errors);
-        }
     }
 
     if password.is_empty() {
         validation.insert("password", vec!["password field is required"]);
-    } else {
-        let mut errors = vec![];
-
-        if let Some(user) = user.clone() {
-            let hashed = Hash::from(user.password);
-
-            if !hash::verify(hashed, user.id, password) {
-                errors.push("wrong password");
-            }
-        }
-
-        if !errors.is_empty() {
-            validation.insert("password", errors);
-        }
     }
 
     if !validation.is_empty() {
@@ -65,9 +117,60 @@ pub async fn login(
         }))
     }
 
-    let user = user.unwrap();
+    let user = match sign_in(db, audit, &email_or_username, &password).await {
+        Ok(user) => user,
+        Err(Refusal::Failure(Failure::Rejected)) => return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "password": ["wrong password"],
+            },
+        })),
+        Err(Refusal::Failure(Failure::Conflict(message))) => return HttpResponse::Conflict().json(json!({
+            "message": message,
+        })),
+        Err(Refusal::Failure(Failure::Unavailable(_))) => return HttpResponse::ServiceUnavailable().json(json!({
+            "message": "Authentication backend is unavailable",
+        })),
+        Err(Refusal::Failure(Failure::Unknown)) => return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "email_or_username": ["email or username doesn't exist"],
+            },
+        })),
+        Err(Refusal::ServiceAccount) => return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "email_or_username": ["service accounts can't login with password"],
+            },
+        })),
+        Err(Refusal::Expired(_)) => return HttpResponse::Forbidden().json(json!({
+            "status": "password_expired",
+            "message": "Password has expired, change it to continue",
+        })),
+        Err(Refusal::Error(e)) => return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        })),
+    };
+
+    let organization_id = dao::organization::memberships(db, &user).await
+        .unwrap_or_default()
+        .first()
+        .map(|organization| organization.id.clone());
+
+    let generated = async {
+        let txn = db.begin().await?;
+        let token = dao::auth::generate(&txn, &user, None, organization_id).await?;
+        let session = json!({
+            "user_id": token.user_id,
+            "organization_id": token.organization_id,
+            "method": "password",
+        });
+
+        events::emit(&txn, events::SESSION_CREATED, session.clone()).await?;
+        services::audit::record(&txn, &audit.acting_as(user.id.clone()), services::audit::LOGIN, Some(("user", &user.id)), Value::Null, session).await?;
+        txn.commit().await?;
 
-    match dao::auth::generate(db, &user, None).await {
+        Ok::<_, DbErr>(token)
+    };
+
+    match generated.await {
         Err(e) => {
             log::error!(services::auth::login, "{}", e);
 
@@ -92,9 +195,51 @@ pub async fn authenticate(auth: Auth) -> HttpResponse {
 
 pub async fn logout(
     db: &DatabaseConnection,
+    audit: &Audit,
     auth: Auth,
+    tenant: Tenant,
 ) -> HttpResponse {
-    match dao::auth::delete(db, auth.user.id).await {
+    if let Some(token) = tenant.token.filter(|token| token.impersonator_id.is_some()) {
+        let revoked = async {
+            let txn = db.begin().await?;
+
+            let impersonation = json!({
+                "user_id": token.user_id,
+                "impersonator_id": token.impersonator_id,
+            });
+
+            dao::auth::revoke(&txn, &token).await?;
+            events::emit(&txn, events::IMPERSONATION_ENDED, impersonation.clone()).await?;
+            services::audit::record(&txn, audit, events::IMPERSONATION_ENDED, Some(("user", &token.user_id)), impersonation, Value::Null).await?;
+
+            txn.commit().await
+        };
+
+        return match revoked.await {
+            Err(e) => {
+                log::error!(services::auth::logout, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            Ok(_) => HttpResponse::Ok().finish(),
+        }
+    }
+
+    let revoked = async {
+        let txn = db.begin().await?;
+
+        dao::auth::delete(&txn, auth.user.id.clone()).await?;
+        events::emit(&txn, events::SESSION_REVOKED, json!({
+            "user_id": auth.user.id,
+        })).await?;
+        services::audit::record(&txn, audit, services::audit::LOGOUT, Some(("user", &auth.user.id)), Value::Null, Value::Null).await?;
+
+        txn.commit().await
+    };
+
+    match revoked.await {
         Err(e) => {
             log::error!(services::auth::logout, "{}", e);
 
@@ -108,195 +253,172 @@ pub async fn logout(
     }
 }
 
-pub async fn authenticate_by_token(
-    db: &DatabaseConnection,
-    token: String,
-) -> HttpResponse {
-    let token = base58::decode(token);
-    
-    if let Err(e) = token {
-        log::error!(services::auth::authenticate_by_token, "{}", e);
-
-        return Unauthorized {
-            message: e.to_string(),
-        }.error_response();
-    }
+/// Who a bearer token acts as, whatever kind of token it is
+pub struct Bearer {
+    pub user: users::Model,
+    pub organization_id: Option<Id>,
+    /// Permission codes the token is limited to, roles don't carry over
+    pub scopes: Option<Vec<String>>,
+    pub impersonator_id: Option<Id>,
+    pub service_account: bool,
+}
 
-    let token = token.unwrap();
-    #[cfg(feature = "postgres")]
-    let id = Uuid::from_slice(&token);
-    #[cfg(feature = "sqlite")]
-    let id = Uuid::from_str(&String::from_utf8_lossy(&token));
+impl Bearer {
+    pub async fn grants(&self, db: &DatabaseConnection) -> Result<Grants, DbErr> {
+        let mut grants = dao::authorization::grants(db, &self.user, self.organization_id.as_ref()).await?;
 
-    if let Err(e) = id {
-        log::error!(services::auth::authenticate_by_token, "{}", e);
+        if let Some(scopes) = &self.scopes {
+            grants.restrict(scopes);
+        }
 
-        return Unauthorized {
-            message: e.to_string(),
-        }.error_response();
+        Ok(grants)
     }
+}
 
-    let query = query(id.unwrap().into());
-    let statement = Statement::from_string(db.get_database_backend(),query.to_string(QUERY_BUILDER));
-    let rows = Value::find_by_statement(statement).all(db).await;
-
-    if let Err(e) = rows {
-        log::error!(authentication, "{}", e.to_string());
+/// Refuse the tokens of a user whose local password expired since they were
+/// issued, they have to change it the way a refused login does
+async fn unexpired(db: &DatabaseConnection, user: &users::Model) -> Result<(), String> {
+    let policy = services::password::policy();
 
-        return Unauthorized { 
-            message: e.to_string(),
-        }.error_response()
+    if policy.expiry.is_empty() {
+        return Ok(())
     }
 
-    let rows = rows.unwrap();
+    let backend = services::password::backend(db, user).await.map_err(|e| e.to_string())?;
 
-    if rows.is_empty() {
-        return Unauthorized { 
-            message: "Invalid token, record not found".to_string(),
-        }.error_response()
+    match policy.expired(db, user, backend).await {
+        Err(e) => Err(e.to_string()),
+        Ok(true) => Err("Password has expired, change it to continue".to_string()),
+        Ok(false) => Ok(()),
     }
+}
 
-    let mut user= None;
-    let mut permissions = vec![];
-    let mut roles = vec![];
-
-    for row in rows {
-        user = Some(users::Model {
-            id: serde_json::from_value(row["user_id"].clone()).unwrap(),
-            name: serde_json::from_value(row["name"].clone()).unwrap(),
-            email: serde_json::from_value(row["email"].clone()).unwrap(),
-            email_verified_at: serde_json::from_value::<NaiveDateTime>(row["email_verified_at"].clone()).map(|date| Some(date.and_utc())).unwrap_or(None),
-            username: serde_json::from_value(row["username"].clone()).unwrap(),
-            password: serde_json::from_value(row["password"].clone()).unwrap(),
-            profile_photo_id: serde_json::from_value(row["profile_photo_id"].clone()).unwrap(),
-            created_at: serde_json::from_value::<NaiveDateTime>(row["created_at"].clone()).unwrap().and_utc(),
-            updated_at: serde_json::from_value::<NaiveDateTime>(row["updated_at"].clone()).unwrap().and_utc(),
-            deleted_at: serde_json::from_value(row["deleted_at"].clone()).unwrap_or(None),
-        });
+/// Resolve session tokens, personal access tokens and service account api
+/// keys alike. Sessions and personal access tokens stop working once the
+/// user's password expires, impersonation and service accounts don't
+pub async fn bearer(
+    db: &DatabaseConnection,
+    token: String,
+) -> Result<Bearer, String> {
+    if token.starts_with(services::personal_access_token::PREFIX) {
+        let (user, token) = services::personal_access_token::user_by_token(db, token).await?;
+        let service_account = dao::service_account::find_by_user(db, &user).await.is_some();
 
-        if !row["permission_id"].is_null() {
-            permissions.push(permissions::Model {
-                id: serde_json::from_value(row["permission_id"].clone()).unwrap(),
-                code: serde_json::from_value(row["permission_code"].clone()).unwrap(),
-                name: serde_json::from_value(row["permission_name"].clone()).unwrap(),
-            });
+        if !service_account {
+            unexpired(db, &user).await?;
         }
 
-        if !row["role_id"].is_null() {
-            roles.push(roles::Model {
-                id: serde_json::from_value(row["role_id"].clone()).unwrap(),
-                code: serde_json::from_value(row["role_code"].clone()).unwrap(),
-                name: serde_json::from_value(row["role_name"].clone()).unwrap(),
-            });
+        if let Err(e) = dao::personal_access_token::touch(db, &token).await {
+            log::error!(services::auth::bearer, "{}", e);
         }
+
+        return Ok(Bearer {
+            service_account,
+            user,
+            organization_id: token.organization_id.clone(),
+            scopes: Some(serde_json::from_str(&token.scopes).unwrap_or_default()),
+            impersonator_id: None,
+        })
     }
 
-    let auth = Auth { user: user.unwrap(), permissions, roles };
+    if token.starts_with(services::service_account::API_KEY_PREFIX) {
+        let (user, account) = services::service_account::user_by_api_key(db, token).await?;
 
-    HttpResponse::Ok().json(auth)
+        return Ok(Bearer {
+            user,
+            organization_id: account.organization_id,
+            scopes: None,
+            impersonator_id: None,
+            service_account: true,
+        })
+    }
+
+    let (user, token) = user_by_token(db, token).await?;
+    let service_account = dao::service_account::find_by_user(db, &user).await.is_some();
+
+    if !service_account && token.impersonator_id.is_none() {
+        unexpired(db, &user).await?;
+    }
+
+    Ok(Bearer {
+        service_account,
+        user,
+        organization_id: token.organization_id,
+        // tokens issued to oauth clients only carry the granted scopes
+        scopes: token.scopes.map(|scopes| serde_json::from_str(&scopes).unwrap_or_default()),
+        impersonator_id: token.impersonator_id,
+    })
+}
+
+pub async fn authenticate_by_token(
+    db: &DatabaseConnection,
+    token: String,
+) -> HttpResponse {
+    let bearer = match bearer(db, token).await {
+        Err(message) => {
+            log::error!(services::auth::authenticate_by_token, "{}", message);
+
+            return Unauthorized { message }.error_response()
+        },
+        Ok(bearer) => bearer,
+    };
+
+    let organizations = dao::organization::memberships(db, &bearer.user).await;
+    let grants = bearer.grants(db).await;
+
+    match (grants, organizations) {
+        (Err(e), _) | (_, Err(e)) => {
+            log::error!(services::auth::authenticate_by_token, "{}", e);
+
+            Unauthorized {
+                message: e.to_string(),
+            }.error_response()
+        },
+        (Ok(grants), Ok(organizations)) => {
+            let mut session = Session::new(bearer.user, grants);
+
+            session.organization_id = bearer.organization_id;
+            session.organizations = organizations.into_iter()
+                .map(OrganizationOAS::from)
+                .collect();
+            session.service_account = bearer.service_account;
+            session.scopes = bearer.scopes;
+
+            if let Some(impersonator_id) = bearer.impersonator_id {
+                session.impersonator = dao::user::find(db, impersonator_id).await
+                    .map(UserOAS::from);
+            }
+
+            HttpResponse::Ok().json(session)
+        },
+    }
 }
 
-fn query(id: Id) -> SelectStatement {
-    Query::select()
-        .exprs([
-            Expr::col((tokens::Entity.table_name().into_identity(), tokens::Column::UserId.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::Id.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::Name.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::Email.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::EmailVerifiedAt.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::Username.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::Password.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::ProfilePhotoId.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::CreatedAt.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::UpdatedAt.into_iden())),
-            Expr::col((users::Entity.table_name().into_identity(), users::Column::DeletedAt.into_iden())),
-            Expr::col((
-                permission_user::Entity.table_name().into_identity(), 
-                permission_user::Column::PermissionId.into_iden()
-            )),
-            Expr::custom_keyword(format!(
-                "{}.{} as {}", 
-                permissions::Entity.table_name(), 
-                permissions::Column::Code.to_string(), 
-                "permission_code"
-            ).into_identity()),
-            Expr::custom_keyword(format!(
-                "{}.{} as {}", 
-                permissions::Entity.table_name(), 
-                permissions::Column::Name.to_string(), 
-                "permission_name"
-            ).into_identity()),
-            Expr::col((role_user::Entity.table_name().into_identity(), role_user::Column::RoleId.into_iden())),
-            Expr::custom_keyword(format!(
-                "{}.{} as {}", 
-                roles::Entity.table_name(), 
-                roles::Column::Code.to_string(), 
-                "role_code"
-            ).into_identity()),
-            Expr::custom_keyword(format!(
-                "{}.{} as {}", 
-                roles::Entity.table_name(), 
-                roles::Column::Name.to_string(), 
-                "role_name"
-            ).into_identity()),
-        ])
-        .from(tokens::Entity.table_name().into_identity())
-        .inner_join(
-            users::Entity.table_name().into_identity(), 
-            Expr::col((
-                users::Entity.table_name().into_identity(), 
-                users::Column::Id.into_iden(),
-            )).eq(Expr::col((
-                tokens::Entity.table_name().into_identity(), 
-                tokens::Column::UserId.into_iden(),
-            )))
-        )
-        .left_join(
-            permission_user::Entity.table_name().into_identity(), 
-            Expr::col((
-                users::Entity.table_name().into_identity(),
-                users::Column::Id.into_iden(),
-            )).eq(Expr::col((
-                permission_user::Entity.table_name().into_identity(),
-                permission_user::Column::UserId.into_iden(),
-            )))
-        )
-        .left_join(
-            permissions::Entity.table_name().into_identity(), 
-            Expr::col((
-                permissions::Entity.table_name().into_identity(),
-                permissions::Column::Id.into_iden(),
-            )).eq(Expr::col((
-                permission_user::Entity.table_name().into_identity(),
-                permission_user::Column::PermissionId.into_iden(),
-            )))
-        )
-        .left_join(
-            role_user::Entity.table_name().into_identity(), 
-            Expr::col((
-                users::Entity.table_name().into_identity(),
-                users::Column::Id.into_iden(),
-            )).eq(Expr::col((
-                role_user::Entity.table_name().into_identity(),
-                role_user::Column::UserId.into_iden(),
-            )))
-        )
-        .left_join(
-            roles::Entity.table_name().into_identity(), 
-            Expr::col((
-                roles::Entity.table_name().into_identity(),
-                roles::Column::Id.into_iden(),
-            )).eq(Expr::col((
-                role_user::Entity.table_name().into_identity(),
-                role_user::Column::RoleId.into_iden(),
-            )))
-        )
-        .and_where(
-            Expr::col((
-                tokens::Entity.table_name().into_identity(),
-                tokens::Column::Id.into_iden(),
-            )).eq(id)
-        )
-        .take()
+pub fn decode(token: String) -> Result<Id, String> {
+    let token = base58::decode(token).map_err(|e| e.to_string())?;
+    #[cfg(feature = "postgres")]
+    let id = Uuid::from_slice(&token);
+    #[cfg(feature = "sqlite")]
+    let id = Uuid::from_str(&String::from_utf8_lossy(&token));
+
+    id.map(|id| id.into()).map_err(|e| e.to_string())
+}
+
+pub async fn user_by_token(
+    db: &DatabaseConnection,
+    token: String,
+) -> Result<(users::Model, tokens::Model), String> {
+    let token = dao::auth::find(db, decode(token)?).await
+        .ok_or("Invalid token, record not found".to_string())?;
+
+    if let Some(expired_at) = token.expired_at {
+        if expired_at < time::now() {
+            return Err("Token has been expired".to_string())
+        }
+    }
 
+    match dao::user::find(db, token.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => Ok((user, token)),
+        _ => Err("Invalid token, user not found".to_string()),
+    }
 }
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use chrono::{Datelike, Timelike};
+use nightmare_common::{log, time};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::permissions;
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Map, Value};
+
+use crate::dao;
+use crate::dao::authorization::{Grants, Resource, Source};
+use crate::models::policies;
+use crate::requests::authorization::{AuthorizationCheck, AuthorizationRequest, AuthorizationSubject};
+use crate::responses::authorization::{AuthorizationDecision, Authorized};
+use crate::services;
+use crate::services::auth::Bearer;
+
+/// Lets a caller check subjects other than itself
+pub const AUTHORIZE_PERMISSION: &str = "AUTHORIZE_SUBJECT";
+
+pub async fn authorize(
+    db: &DatabaseConnection,
+    auth: Auth,
+    ip: Option<String>,
+    request: AuthorizationRequest,
+) -> HttpResponse {
+    let mut validation = HashMap::new();
+
+    if request.subject.user_id.is_none() && request.subject.token.is_none() {
+        validation.insert("subject", vec!["field subject requires user id or token"]);
+    }
+
+    if request.checks.is_empty() {
+        validation.insert("checks", vec!["field checks is required"]);
+    }
+
+    if request.checks.iter().any(|check| check.permission.trim().is_empty()) {
+        validation.insert("checks", vec!["field permission is required on every check"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    // anyone may check themselves, other subjects need the permission
+    let permitted = auth.permissions.iter().any(|permission| permission.code.eq(AUTHORIZE_PERMISSION));
+    let forbidden = || HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to check other subjects",
+    }));
+
+    let subject = match subject(db, request.subject).await {
+        None if !permitted => return forbidden(),
+        None => return HttpResponse::NotFound().json(json!({
+            "message": "subject not found",
+        })),
+        Some(subject) if !permitted && subject.user.id.ne(&auth.user.id) => return forbidden(),
+        Some(subject) => subject,
+    };
+
+    let grants = subject.grants(db).await;
+
+    if let Err(e) = grants {
+        log::error!(authorize, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    let grants = grants.unwrap();
+    let codes = request.checks.iter()
+        .map(|check| check.permission.trim().to_uppercase())
+        .collect::<Vec<String>>();
+
+    match dao::policy::for_permissions(db, codes).await {
+        Err(e) => {
+            log::error!(authorize, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(policies) => {
+            let environment = environment(ip);
+            let attributes = subject_attributes(&subject, &grants);
+            let action = Value::Object(request.action.into_iter().collect());
+            let explain = request.explain;
+
+            HttpResponse::Ok().json(Authorized {
+                subject: subject.user.id,
+                decisions: request.checks.into_iter()
+                    .map(|check| decide(&grants, &policies, &attributes, &action, &environment, check, explain))
+                    .collect(),
+            })
+        },
+    }
+}
+
+/// Tokens resolve like they do on authentication, scopes included, a
+/// user id is taken with all of the user's grants
+pub async fn subject(
+    db: &DatabaseConnection,
+    subject: AuthorizationSubject,
+) -> Option<Bearer> {
+    if let Some(token) = subject.token {
+        return services::auth::bearer(db, token).await.ok()
+    }
+
+    match dao::user::find(db, subject.user_id?).await {
+        Some(user) if user.deleted_at.is_none() => Some(Bearer {
+            service_account: dao::service_account::find_by_user(db, &user).await.is_some(),
+            user,
+            organization_id: subject.organization_id,
+            scopes: None,
+            impersonator_id: None,
+        }),
+        _ => None,
+    }
+}
+
+/// Subject attributes come from the resolved subject only, a caller can't
+/// vouch for who the subject is
+fn subject_attributes(
+    bearer: &Bearer,
+    grants: &Grants,
+) -> Value {
+    let user = &bearer.user;
+    let mut subject = Map::new();
+
+    subject.insert("id".to_string(), json!(user.id));
+    subject.insert("organization_id".to_string(), json!(bearer.organization_id));
+    subject.insert("name".to_string(), json!(user.name));
+    subject.insert("email".to_string(), json!(user.email));
+    subject.insert("username".to_string(), json!(user.username));
+    subject.insert("roles".to_string(), json!(grants.roles.iter()
+        .map(|role| role.code.clone())
+        .collect::<Vec<String>>()));
+    subject.insert("impersonator_id".to_string(), json!(bearer.impersonator_id));
+    subject.insert("service_account".to_string(), json!(bearer.service_account));
+
+    Value::Object(subject)
+}
+
+/// Environment the server observed, caller supplied context belongs under
+/// `action` or the resource attributes
+fn environment(ip: Option<String>) -> Value {
+    let now = time::now();
+
+    json!({
+        "time": now.to_rfc3339(),
+        "hour": now.hour(),
+        "weekday": now.weekday().number_from_monday(),
+        "ip": ip,
+    })
+}
+
+pub fn decide(
+    grants: &Grants,
+    policies: &[(policies::Model, permissions::Model)],
+    subject: &Value,
+    action: &Value,
+    environment: &Value,
+    check: AuthorizationCheck,
+    explain: bool,
+) -> AuthorizationDecision {
+    let code = check.permission.trim().to_uppercase();
+    let resource = check.resource.as_ref().map(|resource| Resource {
+        kind: resource.kind.trim().to_lowercase(),
+        id: resource.id.trim().to_string(),
+    });
+    let context = json!({
+        "subject": subject,
+        "resource": check.resource.as_ref().map(|check| {
+            let mut attributes = check.attributes.clone().into_iter().collect::<Map<String, Value>>();
+
+            if let Some(resource) = &resource {
+                attributes.insert("type".to_string(), json!(resource.kind));
+                attributes.insert("id".to_string(), json!(resource.id));
+            }
+
+            Value::Object(attributes)
+        }),
+        "action": action,
+        "environment": environment,
+    });
+    let roles = grants.roles.iter()
+        .chain(grants.scoped.iter()
+            .filter(|scope| Some(&scope.resource) == resource.as_ref())
+            .map(|scope| &scope.role))
+        .cloned()
+        .collect::<Vec<_>>();
+    let evaluations = policies.iter()
+        .filter(|(_, permission)| permission.code.eq(&code))
+        .filter(|(policy, _)| services::policy::applies(
+            policy,
+            resource.as_ref().map(|resource| resource.kind.as_str()),
+            &roles,
+        ))
+        .map(|(policy, _)| services::policy::evaluate(policy, &context))
+        .collect::<Vec<_>>();
+    let enforced = |effect: &str| evaluations.iter()
+        .find(|evaluation| evaluation.matched && !evaluation.dry_run && evaluation.effect.eq(effect));
+    let found = grants.find(&code, resource.as_ref());
+
+    let (allowed, reason) = match (enforced("deny"), found.first(), enforced("allow")) {
+        (Some(policy), _, _) => (false, format!("denied by policy {}", policy.name)),
+        (None, Some(grant), _) => match (&grant.source, &grant.resource) {
+            (Source::Direct, _) => (true, "granted directly".to_string()),
+            (Source::Group(group), _) => (true, format!("granted through group {}", group.code)),
+            (Source::Role(role), None) => (true, format!("granted through role {}", role.code)),
+            (Source::Role(role), Some(resource)) => (true, format!(
+                "granted through role {} on {} {}", role.code, resource.kind, resource.id,
+            )),
+        },
+        (None, None, Some(policy)) => (true, format!("allowed by policy {}", policy.name)),
+        (None, None, None) => (false, "permission not granted".to_string()),
+    };
+
+    AuthorizationDecision {
+        permission: code,
+        resource: check.resource,
+        allowed,
+        reason,
+        policies: explain.then_some(evaluations),
+    }
+}
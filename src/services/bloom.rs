@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// `NMBF`, version, hash count as u32 and bit count as u64 little endian,
+/// then the bits
+const MAGIC: &[u8; 4] = b"NMBF";
+const VERSION: u8 = 1;
+
+/// Bloom filter of sha1 digests. The digest is already uniform so the bit
+/// indexes come from double hashing its first 16 bytes
+pub struct Bloom {
+    hashes: u32,
+    bits: u64,
+    data: Vec<u8>,
+}
+
+impl Bloom {
+    fn indexes(&self, digest: &[u8; 20]) -> impl Iterator<Item = u64> + '_ {
+        let mut first = [0u8; 8];
+        let mut second = [0u8; 8];
+
+        first.copy_from_slice(&digest[..8]);
+        second.copy_from_slice(&digest[8..16]);
+
+        let first = u64::from_le_bytes(first);
+        // odd so the step is never zero, the probes only all differ when
+        // the bit count is a power of two and a repeat costs some accuracy
+        let second = u64::from_le_bytes(second) | 1;
+
+        (0..self.hashes as u64).map(move |i| first.wrapping_add(i.wrapping_mul(second)) % self.bits)
+    }
+
+    pub fn contains(&self, digest: &[u8; 20]) -> bool {
+        self.indexes(digest).all(|index| self.data[(index / 8) as usize] & (1 << (index % 8)) != 0)
+    }
+
+    pub fn read<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = [0u8; 17];
+
+        reader.read_exact(&mut header)?;
+
+        if header[..4].ne(MAGIC) || header[4].ne(&VERSION) {
+            return Err(invalid("not a breached password filter"))
+        }
+
+        let mut hashes = [0u8; 4];
+        let mut bits = [0u8; 8];
+
+        hashes.copy_from_slice(&header[5..9]);
+        bits.copy_from_slice(&header[9..17]);
+
+        let hashes = u32::from_le_bytes(hashes);
+        let bits = u64::from_le_bytes(bits);
+
+        if hashes.eq(&0) || bits.eq(&0) {
+            return Err(invalid("filter is empty"))
+        }
+
+        let mut data = Vec::with_capacity(bits.div_ceil(8) as usize);
+
+        reader.read_to_end(&mut data)?;
+
+        if (data.len() as u64).ne(&bits.div_ceil(8)) {
+            return Err(invalid("filter is truncated"))
+        }
+
+        Ok(Self { hashes, bits, data })
+    }
+}
+
+// building only happens in the breach binary
+#[allow(dead_code)]
+impl Bloom {
+    /// Sized for the number of items at the false positive rate
+    pub fn new(items: u64, false_positive: f64) -> Self {
+        let items = items.max(1) as f64;
+        let bits = (-items * false_positive.ln() / 2f64.ln().powi(2)).ceil().max(8.0) as u64;
+        let hashes = (bits as f64 / items * 2f64.ln()).round().max(1.0) as u32;
+
+        Self {
+            hashes,
+            bits,
+            data: vec![0; bits.div_ceil(8) as usize],
+        }
+    }
+
+    pub fn insert(&mut self, digest: &[u8; 20]) {
+        let indexes = self.indexes(digest).collect::<Vec<u64>>();
+
+        for index in indexes {
+            self.data[(index / 8) as usize] |= 1 << (index % 8);
+        }
+    }
+
+    pub fn hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&self.hashes.to_le_bytes())?;
+        writer.write_all(&self.bits.to_le_bytes())?;
+        writer.write_all(&self.data)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::sha::sha1;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn digests(prefix: &str, count: u32) -> Vec<[u8; 20]> {
+        (0..count).map(|i| sha1(format!("{}{}", prefix, i).as_bytes())).collect()
+    }
+
+    #[test]
+    fn inserted_digests_are_found() {
+        let mut bloom = Bloom::new(1_000, 0.001);
+
+        for digest in digests("breached", 1_000) {
+            bloom.insert(&digest);
+        }
+
+        assert!(digests("breached", 1_000).iter().all(|digest| bloom.contains(digest)));
+    }
+
+    #[test]
+    fn false_positives_stay_near_the_rate() {
+        let mut bloom = Bloom::new(10_000, 0.01);
+
+        for digest in digests("breached", 10_000) {
+            bloom.insert(&digest);
+        }
+
+        let positives = digests("clean", 10_000).iter().filter(|digest| bloom.contains(digest)).count();
+
+        assert!(positives < 200, "{} false positives out of 10000", positives);
+    }
+
+    #[test]
+    fn written_filters_read_back_the_same() {
+        let path = std::env::temp_dir().join(format!("{}.bloom", Uuid::new_v4()));
+        let mut bloom = Bloom::new(100, 0.01);
+
+        for digest in digests("breached", 100) {
+            bloom.insert(&digest);
+        }
+
+        bloom.write(&path).unwrap();
+
+        let read = Bloom::read(&path);
+
+        std::fs::remove_file(&path).unwrap();
+
+        let read = read.unwrap();
+
+        assert_eq!(read.hashes(), bloom.hashes());
+        assert_eq!(read.bits(), bloom.bits());
+        assert_eq!(read.data, bloom.data);
+        assert!(digests("breached", 100).iter().all(|digest| read.contains(digest)));
+    }
+
+    #[test]
+    fn damaged_filters_are_refused() {
+        let path = std::env::temp_dir().join(format!("{}.bloom", Uuid::new_v4()));
+        let bloom = Bloom::new(100, 0.01);
+
+        bloom.write(&path).unwrap();
+
+        let mut bytes = std::fs::read(&path).unwrap();
+
+        bytes.pop();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let truncated = Bloom::read(&path).err().map(|e| e.to_string());
+
+        bytes[0] = b'X';
+        std::fs::write(&path, &bytes).unwrap();
+
+        let foreign = Bloom::read(&path).err().map(|e| e.to_string());
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(truncated.as_deref(), Some("filter is truncated"));
+        assert_eq!(foreign.as_deref(), Some("not a breached password filter"));
+    }
+}
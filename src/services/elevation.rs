@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{users, Id};
+use nightmare_common::log;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, TransactionTrait};
+use serde_json::json;
+
+use crate::{backends, dao};
+use crate::events;
+use crate::requests::elevation::{ElevationRequest, RoleEligibilityStoreRequest};
+use crate::responses::elevation::{RoleEligibilityOAS, RoleElevationOAS};
+
+/// Permission required to manage who may activate a role
+pub const ELIGIBILITY_PERMISSION: &str = "MANAGE_ELIGIBILITY";
+
+fn self_grant() -> HttpResponse {
+    HttpResponse::Forbidden().json(json!({
+        "message": "you can't make yourself eligible for a role",
+    }))
+}
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(ELIGIBILITY_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage role eligibilities",
+    })))
+}
+
+pub async fn eligibilities<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(&auth) {
+        return response
+    }
+
+    let role = match dao::role::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(role) => role,
+    };
+
+    match dao::elevation::eligibilities(db, &role).await {
+        Err(e) => {
+            log::error!(eligibilities, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(eligibilities) => {
+            HttpResponse::Ok().json(
+                eligibilities.iter()
+                    .map(RoleEligibilityOAS::from)
+                    .collect::<Vec<RoleEligibilityOAS>>()
+            )
+        },
+    }
+}
+
+pub async fn store_eligibility<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+    request: RoleEligibilityStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(&auth) {
+        return response
+    }
+
+    let role = match dao::role::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(role) => role,
+    };
+
+    let mut validation = HashMap::new();
+
+    match (&request.user_id, &request.group_id) {
+        (None, None) | (Some(_), Some(_)) => {
+            validation.insert("user_id", vec!["either user or group must be eligible"]);
+        },
+        (Some(user_id), None) => {
+            if user_id.eq(&auth.user.id) {
+                return self_grant()
+            }
+
+            let user = users::Entity::find_by_id(user_id.clone())
+                .one(db)
+                .await
+                .unwrap_or(None);
+
+            if user.is_none() {
+                validation.insert("user_id", vec!["user doesn't exists"]);
+            }
+        },
+        (None, Some(group_id)) => match dao::group::find(db, group_id.clone()).await {
+            None => {
+                validation.insert("group_id", vec!["group doesn't exists"]);
+            },
+            Some(group) => {
+                if dao::group::has_member(db, &group, &auth.user).await {
+                    return self_grant()
+                }
+            },
+        },
+    }
+
+    if request.max_minutes <= 0 {
+        validation.insert("max_minutes", vec!["max minutes must be positive"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::elevation::store_eligibility(db, &role, request.user_id, request.group_id, request.max_minutes, request.require_reauthentication).await {
+        Err(e) => {
+            log::error!(store_eligibility, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(eligibility) => {
+            HttpResponse::Created().json(json!({
+                "id": eligibility.id,
+                "message": "Eligibility has been created",
+            }))
+        },
+    }
+}
+
+pub async fn delete_eligibility<I: Into<Id>, E: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+    eligibility: E,
+) -> HttpResponse {
+    if let Some(response) = forbidden(&auth) {
+        return response
+    }
+
+    let role = match dao::role::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(role) => role,
+    };
+
+    match dao::elevation::find_eligibility(db, &role, eligibility).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(eligibility) => match dao::elevation::delete_eligibility(db, eligibility.id.clone()).await {
+            Err(e) => {
+                log::error!(delete_eligibility, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            _ => {
+                HttpResponse::Ok().json(json!({
+                    "id": eligibility.id,
+                    "message": "Eligibility has been deleted",
+                }))
+            },
+        },
+    }
+}
+
+pub async fn history(
+    db: &DatabaseConnection,
+    auth: Auth,
+) -> HttpResponse {
+    match dao::elevation::history(db, &auth.user).await {
+        Err(e) => {
+            log::error!(history, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(elevations) => {
+            HttpResponse::Ok().json(
+                elevations.iter()
+                    .map(RoleElevationOAS::from)
+                    .collect::<Vec<RoleElevationOAS>>()
+            )
+        },
+    }
+}
+
+pub async fn activate(
+    db: &DatabaseConnection,
+    auth: Auth,
+    request: ElevationRequest,
+) -> HttpResponse {
+    let mut validation = HashMap::new();
+    let reason = request.reason.trim().to_string();
+    let role = dao::role::find(db, request.role.clone()).await;
+
+    if reason.is_empty() {
+        validation.insert("reason", vec!["field reason is required"]);
+    }
+
+    if request.minutes <= 0 {
+        validation.insert("minutes", vec!["minutes must be positive"]);
+    }
+
+    let eligibility = match &role {
+        None => {
+            validation.insert("role", vec!["role doesn't exists"]);
+
+            None
+        },
+        Some(role) => match dao::elevation::eligible(db, &auth.user, role).await {
+            Err(e) => {
+                log::error!(activate, "{}", e);
+
+                return HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            Ok(None) => {
+                validation.insert("role", vec!["you are not eligible for this role"]);
+
+                None
+            },
+            Ok(Some(eligibility)) => {
+                if request.minutes > eligibility.max_minutes {
+                    validation.insert("minutes", vec!["minutes exceed the maximum allowed for this role"]);
+                }
+
+                if eligibility.require_reauthentication {
+                    let password = request.password.clone().unwrap_or_default();
+
+                    if password.is_empty() {
+                        validation.insert("password", vec!["password is required to activate this role"]);
+                    } else {
+                        match backends::authenticate(db, &auth.user.username, &password).await {
+                            Ok((user, _)) if user.id.eq(&auth.user.id) => {},
+                            Err(backends::Failure::Unavailable(_)) => return HttpResponse::ServiceUnavailable().json(json!({
+                                "message": "Authentication backend is unavailable",
+                            })),
+                            _ => {
+                                validation.insert("password", vec!["wrong password"]);
+                            },
+                        }
+                    }
+                }
+
+                Some(eligibility)
+            },
+        },
+    };
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let role = role.unwrap();
+    let eligibility = eligibility.unwrap();
+
+    let activated = async {
+        let txn = db.begin().await?;
+        let elevation = dao::elevation::activate(&txn, &auth.user, &role, &eligibility, reason, request.minutes).await?;
+
+        events::emit(&txn, events::ELEVATION_ACTIVATED, json!({
+            "id": elevation.id,
+            "user_id": elevation.user_id,
+            "role_id": elevation.role_id,
+            "reason": elevation.reason,
+            "expires_at": elevation.expires_at,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(elevation)
+    };
+
+    match activated.await {
+        Err(e) => {
+            log::error!(activate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(elevation) => {
+            HttpResponse::Created().json(json!({
+                "id": elevation.id,
+                "message": format!("Role {} is active until {}", role.code, elevation.expires_at),
+            }))
+        },
+    }
+}
+
+pub async fn end<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+) -> HttpResponse {
+    let elevation = match dao::elevation::find(db, id).await {
+        Some(elevation) if elevation.user_id.eq(&auth.user.id) => elevation,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    if elevation.ended_at.is_some() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "id": ["elevation has already ended"],
+            },
+        }))
+    }
+
+    let ended = async {
+        let txn = db.begin().await?;
+        let elevation = dao::elevation::end(&txn, &elevation, Some(auth.user.id.clone())).await?;
+
+        events::emit(&txn, events::ELEVATION_ENDED, json!({
+            "id": elevation.id,
+            "user_id": elevation.user_id,
+            "role_id": elevation.role_id,
+            "ended_by": elevation.ended_by,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(elevation)
+    };
+
+    match ended.await {
+        Err(e) => {
+            log::error!(end, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(elevation) => {
+            HttpResponse::Ok().json(json!({
+                "id": elevation.id,
+                "message": "Elevation has been ended",
+            }))
+        },
+    }
+}
@@ -0,0 +1,670 @@
+use std::str::FromStr;
+
+use actix_web::HttpResponse;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use jsonwebtoken::jwk::JwkSet;
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::models::{users, Id};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{dao, events};
+use crate::middleware::audit::Audit;
+use crate::models::{identity_provider_role_mappings, identity_providers};
+use crate::requests::identity_provider::FederatedCallbackRequest;
+use crate::responses::auth::Login;
+use crate::responses::user::UserOAS;
+use crate::services;
+
+/// How long the user has to come back from the identity provider
+pub const LOGIN_SECONDS: i64 = 600;
+
+#[derive(Debug, Clone, Deserialize)]
+struct Discovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TokenSet {
+    id_token: String,
+}
+
+fn secret() -> String {
+    base58::to_string(Uuid::new_v4().as_bytes())
+}
+
+fn unauthorized<M: ToString>(message: M) -> HttpResponse {
+    HttpResponse::Unauthorized().json(json!({
+        "message": message.to_string(),
+    }))
+}
+
+fn callback_uri(issuer: &str, provider: &identity_providers::Model) -> String {
+    format!("{}/login/{}/callback", issuer, provider.code)
+}
+
+async fn discover(provider: &identity_providers::Model) -> Result<Discovery, String> {
+    let url = format!("{}/.well-known/openid-configuration", provider.issuer.trim_end_matches('/'));
+    let mut response = awc::Client::default()
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("discovery failed with status {}", response.status()))
+    }
+
+    response.json::<Discovery>().await.map_err(|e| e.to_string())
+}
+
+async fn exchange(
+    provider: &identity_providers::Model,
+    discovery: &Discovery,
+    code: &str,
+    redirect_uri: &str,
+    code_verifier: &str,
+) -> Result<TokenSet, String> {
+    let mut form = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", provider.client_id.as_str()),
+        ("code_verifier", code_verifier),
+    ];
+
+    if let Some(client_secret) = &provider.client_secret {
+        form.push(("client_secret", client_secret.as_str()));
+    }
+
+    let mut response = awc::Client::default()
+        .post(&discovery.token_endpoint)
+        .send_form(&form)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("token exchange failed with status {}", response.status()))
+    }
+
+    response.json::<TokenSet>().await.map_err(|e| e.to_string())
+}
+
+/// Asymmetric signing algorithm by name, shared secrets are never accepted
+/// since the key comes from a public jwks
+pub fn algorithm(name: &str) -> Result<Algorithm, String> {
+    match Algorithm::from_str(name) {
+        Ok(algorithm @ (
+            Algorithm::RS256 | Algorithm::RS384 | Algorithm::RS512 |
+            Algorithm::PS256 | Algorithm::PS384 | Algorithm::PS512 |
+            Algorithm::ES256 | Algorithm::ES384 | Algorithm::EdDSA
+        )) => Ok(algorithm),
+        _ => Err(format!("{} isn't an accepted signing algorithm", name)),
+    }
+}
+
+/// Verify the id token signature against the provider's published keys
+async fn verify(
+    provider: &identity_providers::Model,
+    discovery: &Discovery,
+    id_token: &str,
+) -> Result<Map<String, Value>, String> {
+    let mut response = awc::Client::default()
+        .get(&discovery.jwks_uri)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let jwks = response.json::<JwkSet>()
+        .limit(1024 * 1024)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    decode(provider, &jwks, id_token)
+}
+
+/// Check the id token with the key it names. The algorithm is the one
+/// configured for the provider, else the one the key declares, else RS256
+/// as OpenID Connect defaults to, the token header is never trusted for it
+fn decode(
+    provider: &identity_providers::Model,
+    jwks: &JwkSet,
+    id_token: &str,
+) -> Result<Map<String, Value>, String> {
+    let header = jsonwebtoken::decode_header(id_token).map_err(|e| e.to_string())?;
+
+    let jwk = match &header.kid {
+        Some(kid) => jwks.find(kid),
+        None => jwks.keys.first(),
+    };
+
+    let jwk = jwk.ok_or("signing key not found".to_string())?;
+    let expected = match (&provider.algorithm, &jwk.common.key_algorithm) {
+        (Some(configured), _) => algorithm(configured)?,
+        (None, Some(declared)) => algorithm(&format!("{:?}", declared))?,
+        (None, None) => Algorithm::RS256,
+    };
+
+    if header.alg.ne(&expected) {
+        return Err(format!("id token is signed with {:?} instead of {:?}", header.alg, expected))
+    }
+
+    let key = DecodingKey::from_jwk(jwk).map_err(|e| e.to_string())?;
+    let mut validation = Validation::new(expected);
+
+    validation.set_audience(&[&provider.client_id]);
+    validation.set_issuer(&[&provider.issuer]);
+
+    jsonwebtoken::decode::<Map<String, Value>>(id_token, &key, &validation)
+        .map(|token| token.claims)
+        .map_err(|e| e.to_string())
+}
+
+/// A rule matches when the claim equals the value or, for list claims such
+/// as groups, contains it
+fn matches(claims: &Map<String, Value>, mapping: &identity_provider_role_mappings::Model) -> bool {
+    let expected = |value: &Value| match value {
+        Value::String(value) => value.eq(&mapping.value),
+        Value::Bool(_) | Value::Number(_) => value.to_string().eq(&mapping.value),
+        _ => false,
+    };
+
+    match claims.get(&mapping.claim) {
+        Some(Value::Array(values)) => values.iter().any(expected),
+        Some(value) => expected(value),
+        None => false,
+    }
+}
+
+pub async fn start<C: ToString>(
+    db: &DatabaseConnection,
+    issuer: String,
+    code: C,
+) -> HttpResponse {
+    let provider = match dao::identity_provider::find_by_code(db, code).await {
+        Some(provider) if provider.enabled => provider,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    if provider.protocol.eq(dao::identity_provider::SAML) {
+        return services::saml::start(db, issuer, provider).await
+    }
+
+    let discovery = match discover(&provider).await {
+        Err(e) => {
+            log::error!(services::federation::start, "{}", e);
+
+            return HttpResponse::BadGateway().json(json!({
+                "message": e,
+            }))
+        },
+        Ok(discovery) => discovery,
+    };
+
+    let nonce = secret();
+    let code_verifier = format!("{}{}", secret(), secret());
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+    let expires_at = time::now() + chrono::Duration::seconds(LOGIN_SECONDS);
+
+    match dao::identity_provider::start(db, &provider, &nonce, code_verifier, expires_at).await {
+        Err(e) => {
+            log::error!(services::federation::start, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(login) => {
+            let state = base58::to_string(login.id.as_bytes());
+            let redirect_uri = callback_uri(&issuer, &provider);
+
+            services::oauth::redirect(&discovery.authorization_endpoint, &[
+                ("response_type", "code"),
+                ("client_id", provider.client_id.as_str()),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("scope", provider.scopes.as_str()),
+                ("state", state.as_str()),
+                ("nonce", nonce.as_str()),
+                ("code_challenge", code_challenge.as_str()),
+                ("code_challenge_method", "S256"),
+            ])
+        },
+    }
+}
+
+pub async fn callback<C: ToString>(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    issuer: String,
+    code: C,
+    request: FederatedCallbackRequest,
+) -> HttpResponse {
+    let provider = match dao::identity_provider::find_by_code(db, code).await {
+        Some(provider) if provider.enabled && provider.protocol.eq(dao::identity_provider::OIDC) => provider,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    let login = match services::auth::decode(request.state.clone()) {
+        Err(_) => None,
+        Ok(id) => dao::identity_provider::finish(db, id).await,
+    };
+
+    let login = match login {
+        Some(login) if login.provider_id.eq(&provider.id) && login.expires_at > time::now() => login,
+        _ => return unauthorized("Login has been expired, please try again"),
+    };
+
+    if let Some(error) = request.error {
+        return unauthorized(request.error_description.unwrap_or(error))
+    }
+
+    let code = match request.code {
+        None => return unauthorized("Identity provider didn't return a code"),
+        Some(code) => code,
+    };
+
+    let claims = match discover(&provider).await {
+        Err(e) => Err(e),
+        Ok(discovery) => match exchange(&provider, &discovery, &code, &callback_uri(&issuer, &provider), &login.code_verifier).await {
+            Err(e) => Err(e),
+            Ok(tokens) => verify(&provider, &discovery, &tokens.id_token).await,
+        },
+    };
+
+    let claims = match claims {
+        Err(e) => {
+            log::error!(services::federation::callback, "{}", e);
+
+            return unauthorized(format!("Failed to sign in with {}", provider.name))
+        },
+        Ok(claims) => claims,
+    };
+
+    if claims.get("nonce").and_then(Value::as_str).ne(&Some(login.nonce.as_str())) {
+        return unauthorized("Id token nonce doesn't match")
+    }
+
+    let subject = match claims.get("sub").and_then(Value::as_str) {
+        None => return unauthorized("Id token doesn't have a subject"),
+        Some(subject) => subject.to_string(),
+    };
+
+    let email = claims.get("email")
+        .and_then(Value::as_str)
+        .map(|email| email.trim().to_lowercase());
+
+    sign_in(db, audit, &provider, subject, email, &claims).await
+}
+
+/// Resolve the local user of a verified external identity, provisioning it
+/// when allowed, apply the role mappings and hand out a session token
+pub async fn sign_in(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    provider: &identity_providers::Model,
+    subject: String,
+    email: Option<String>,
+    claims: &Map<String, Value>,
+) -> HttpResponse {
+    let user = match dao::external_identity::find_by_subject(db, &provider.issuer, &subject).await {
+        Some(identity) => {
+            if let Err(e) = dao::external_identity::touch(db, &identity, email).await {
+                log::error!(services::federation::sign_in, "{}", e);
+            }
+
+            match dao::user::find(db, identity.user_id.clone()).await {
+                Some(user) if user.deleted_at.is_none() => user,
+                _ => return unauthorized("Linked user no longer exists"),
+            }
+        },
+        None if !provider.jit_provisioning => {
+            return HttpResponse::Forbidden().json(json!({
+                "message": "No local account is linked to this identity",
+            }))
+        },
+        None => match provision(db, provider, &subject, email, claims).await {
+            Err(response) => return response,
+            Ok(user) => user,
+        },
+    };
+
+    let mappings = match dao::identity_provider::mappings(db, provider).await {
+        Err(e) => {
+            log::error!(services::federation::sign_in, "{}", e);
+
+            vec![]
+        },
+        Ok(mappings) => mappings,
+    };
+
+    let managed = mappings.iter()
+        .map(|mapping| mapping.role_id.clone())
+        .collect::<Vec<Id>>();
+    let granted = mappings.iter()
+        .filter(|mapping| matches(claims, mapping))
+        .map(|mapping| mapping.role_id.clone())
+        .collect::<Vec<Id>>();
+
+    if let Err(e) = dao::identity_provider::sync_roles(db, &user, managed, granted).await {
+        log::error!(services::federation::sign_in, "{}", e);
+    }
+
+    let organization_id = dao::organization::memberships(db, &user).await
+        .unwrap_or_default()
+        .first()
+        .map(|organization| organization.id.clone());
+
+    let generated = async {
+        let txn = db.begin().await?;
+        let token = dao::auth::generate(&txn, &user, None, organization_id).await?;
+
+        let session = json!({
+            "user_id": token.user_id,
+            "organization_id": token.organization_id,
+            "method": provider.protocol,
+            "identity_provider": provider.code,
+        });
+
+        events::emit(&txn, events::SESSION_CREATED, session.clone()).await?;
+        services::audit::record(&txn, &audit.acting_as(user.id.clone()), services::audit::LOGIN, Some(("user", &user.id)), Value::Null, session).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(token)
+    };
+
+    match generated.await {
+        Err(e) => {
+            log::error!(services::federation::sign_in, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(token) => {
+            HttpResponse::Ok().json(Login {
+                token: base58::to_string(token.id.as_bytes()),
+                user: UserOAS::from(user),
+            })
+        },
+    }
+}
+
+/// Just in time provisioning, a new local user is created for the identity.
+/// Existing accounts are never claimed by email, an administrator has to
+/// link them
+async fn provision(
+    db: &DatabaseConnection,
+    provider: &identity_providers::Model,
+    subject: &str,
+    email: Option<String>,
+    claims: &Map<String, Value>,
+) -> Result<users::Model, HttpResponse> {
+    let email = match email {
+        None => return Err(unauthorized("Identity provider didn't share an email address")),
+        Some(email) => email,
+    };
+
+    if dao::user::email_exist(db, &email).await {
+        return Err(HttpResponse::Conflict().json(json!({
+            "message": "Email already belongs to a local account, ask an administrator to link it",
+        })))
+    }
+
+    let preferred = claims.get("preferred_username")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .unwrap_or_else(|| email.split('@').next().unwrap_or(subject).to_string())
+        .to_lowercase();
+
+    let mut username = preferred.clone();
+
+    while dao::user::username_exist(db, &username).await {
+        username = format!("{}-{}", preferred, &secret()[..6].to_lowercase());
+    }
+
+    let name = claims.get("name")
+        .and_then(Value::as_str)
+        .map(String::from)
+        .unwrap_or(username.clone());
+
+    // federated users sign in through their provider, the password is
+    // random and never handed out
+    let id = Uuid::new_v4();
+    let verified = claims.get("email_verified").and_then(Value::as_bool).unwrap_or(false);
+    let user = async {
+        let txn = db.begin().await?;
+        let user = dao::user::store(&txn, users::Model {
+            id: id.into(),
+            name,
+            email: email.clone(),
+            username,
+            email_verified_at: verified.then(time::now),
+            password: hash::make(id, secret()).to_string(),
+            profile_photo_id: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+            deleted_at: None,
+        }).await?;
+
+        dao::external_identity::link(&txn, &user, provider, subject, Some(email)).await?;
+        events::emit(&txn, events::USER_CREATED, json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "username": user.username,
+            "identity_provider": provider.code,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(user)
+    };
+
+    user.await.map_err(|e| {
+        log::error!(services::federation::provision, "{}", e);
+
+        HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{EncodingKey, Header};
+    use rsa::RsaPrivateKey;
+    use rsa::pkcs1::EncodeRsaPrivateKey;
+    use rsa::traits::PublicKeyParts;
+
+    use super::*;
+
+    const ISSUER: &str = "https://idp.nightmare.local";
+    const CLIENT_ID: &str = "nightmare";
+
+    /// Identity provider that signs with a key of its own and publishes it
+    struct MockIdp {
+        encoding: EncodingKey,
+        n: String,
+        e: String,
+    }
+
+    impl MockIdp {
+        fn new() -> Self {
+            let key = RsaPrivateKey::new(&mut rand::thread_rng(), 2048).unwrap();
+            let der = key.to_pkcs1_der().unwrap();
+
+            Self {
+                encoding: EncodingKey::from_rsa_der(der.as_bytes()),
+                n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+            }
+        }
+
+        fn jwks(&self, alg: Option<&str>) -> JwkSet {
+            let mut jwk = json!({
+                "kty": "RSA",
+                "use": "sig",
+                "kid": "mock",
+                "n": self.n,
+                "e": self.e,
+            });
+
+            if let Some(alg) = alg {
+                jwk["alg"] = json!(alg);
+            }
+
+            serde_json::from_value(json!({ "keys": [jwk] })).unwrap()
+        }
+
+        fn sign(&self, algorithm: Algorithm, claims: Value) -> String {
+            let mut header = Header::new(algorithm);
+
+            header.kid = Some("mock".to_string());
+
+            jsonwebtoken::encode(&header, &claims, &self.encoding).unwrap()
+        }
+    }
+
+    fn claims() -> Value {
+        json!({
+            "iss": ISSUER,
+            "aud": CLIENT_ID,
+            "sub": "alice",
+            "exp": time::now().timestamp() + 300,
+            "groups": ["admins", "users"],
+        })
+    }
+
+    fn provider(algorithm: Option<&str>) -> identity_providers::Model {
+        identity_providers::Model {
+            id: Uuid::new_v4().into(),
+            code: "mock".to_string(),
+            name: "Mock".to_string(),
+            issuer: ISSUER.to_string(),
+            client_id: CLIENT_ID.to_string(),
+            client_secret: None,
+            scopes: "openid".to_string(),
+            jit_provisioning: false,
+            enabled: true,
+            protocol: dao::identity_provider::OIDC.to_string(),
+            sso_url: None,
+            certificate: None,
+            email_attribute: None,
+            name_attribute: None,
+            username_attribute: None,
+            algorithm: algorithm.map(String::from),
+            created_at: time::now(),
+            updated_at: time::now(),
+        }
+    }
+
+    fn mapping(claim: &str, value: &str) -> identity_provider_role_mappings::Model {
+        identity_provider_role_mappings::Model {
+            id: Uuid::new_v4().into(),
+            provider_id: Uuid::new_v4().into(),
+            claim: claim.to_string(),
+            value: value.to_string(),
+            role_id: Uuid::new_v4().into(),
+            created_at: time::now(),
+        }
+    }
+
+    #[test]
+    fn accepts_token_signed_with_the_declared_algorithm() {
+        let idp = MockIdp::new();
+        let token = idp.sign(Algorithm::RS256, claims());
+        let claims = decode(&provider(None), &idp.jwks(Some("RS256")), &token).unwrap();
+
+        assert_eq!(claims.get("sub").and_then(Value::as_str), Some("alice"));
+    }
+
+    #[test]
+    fn defaults_to_rs256_when_nothing_declares_an_algorithm() {
+        let idp = MockIdp::new();
+
+        assert!(decode(&provider(None), &idp.jwks(None), &idp.sign(Algorithm::RS256, claims())).is_ok());
+        assert!(decode(&provider(None), &idp.jwks(None), &idp.sign(Algorithm::RS384, claims())).is_err());
+    }
+
+    #[test]
+    fn rejects_algorithm_the_key_doesnt_declare() {
+        let idp = MockIdp::new();
+        let token = idp.sign(Algorithm::PS256, claims());
+
+        assert!(decode(&provider(None), &idp.jwks(Some("RS256")), &token).is_err());
+    }
+
+    #[test]
+    fn configured_algorithm_overrides_the_key() {
+        let idp = MockIdp::new();
+        let jwks = idp.jwks(Some("RS256"));
+
+        assert!(decode(&provider(Some("RS384")), &jwks, &idp.sign(Algorithm::RS384, claims())).is_ok());
+        assert!(decode(&provider(Some("RS384")), &jwks, &idp.sign(Algorithm::RS256, claims())).is_err());
+    }
+
+    #[test]
+    fn rejects_token_signed_with_a_shared_secret() {
+        let idp = MockIdp::new();
+        let token = jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims(), &EncodingKey::from_secret(idp.n.as_bytes())).unwrap();
+
+        assert!(decode(&provider(None), &idp.jwks(None), &token).is_err());
+    }
+
+    #[test]
+    fn rejects_token_of_another_key() {
+        let idp = MockIdp::new();
+        let token = MockIdp::new().sign(Algorithm::RS256, claims());
+
+        assert!(decode(&provider(None), &idp.jwks(Some("RS256")), &token).is_err());
+    }
+
+    #[test]
+    fn rejects_token_for_another_audience_or_issuer() {
+        let idp = MockIdp::new();
+        let jwks = idp.jwks(Some("RS256"));
+        let mut audience = claims();
+        let mut issuer = claims();
+
+        audience["aud"] = json!("someone-else");
+        issuer["iss"] = json!("https://evil.example.com");
+
+        assert!(decode(&provider(None), &jwks, &idp.sign(Algorithm::RS256, audience)).is_err());
+        assert!(decode(&provider(None), &jwks, &idp.sign(Algorithm::RS256, issuer)).is_err());
+    }
+
+    #[test]
+    fn rejects_expired_token() {
+        let idp = MockIdp::new();
+        let mut expired = claims();
+
+        expired["exp"] = json!(time::now().timestamp() - 3600);
+
+        assert!(decode(&provider(None), &idp.jwks(Some("RS256")), &idp.sign(Algorithm::RS256, expired)).is_err());
+    }
+
+    #[test]
+    fn only_asymmetric_algorithms_are_accepted() {
+        assert!(algorithm("RS256").is_ok());
+        assert!(algorithm("ES256").is_ok());
+        assert!(algorithm("HS256").is_err());
+        assert!(algorithm("none").is_err());
+    }
+
+    #[test]
+    fn mappings_match_string_and_list_claims() {
+        let claims = claims();
+        let claims = claims.as_object().unwrap();
+
+        assert!(matches(claims, &mapping("groups", "admins")));
+        assert!(matches(claims, &mapping("sub", "alice")));
+        assert!(!matches(claims, &mapping("groups", "auditors")));
+        assert!(!matches(claims, &mapping("department", "admins")));
+    }
+}
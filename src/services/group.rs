@@ -0,0 +1,431 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{permissions, roles, users, Id};
+use nightmare_common::log;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, TransactionTrait};
+use serde_json::json;
+
+use crate::{dao, events, services};
+use crate::middleware::audit::Audit;
+use crate::models::groups;
+use crate::requests::group::{GroupOrderByColumn, GroupStoreRequest, GroupUpdateRequest, GroupUserBulkRequest};
+use crate::requests::permission::PermissionBulkRequest;
+use crate::requests::role::RoleBulkRequest;
+use crate::responses::group::GroupOAS;
+
+/// Permission required to manage groups and what they grant
+pub const MANAGE_PERMISSION: &str = "MANAGE_GROUPS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage groups",
+    })))
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<GroupOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = groups::Entity::find()
+        .order_by(match request.order(GroupOrderByColumn::Name) {
+            GroupOrderByColumn::Code => groups::Column::Code,
+            GroupOrderByColumn::Name => groups::Column::Name,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(groups::Column::Code.like(request.search()))
+                .add(groups::Column::Name.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                   .map(|group| group.into())
+                   .collect::<Vec<GroupOAS>>(),
+            }))
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: GroupStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let code = request.code.trim().to_uppercase();
+    let name = request.name.trim().to_lowercase();
+
+    if code.is_empty() {
+        validation.insert("code", vec!["field code is required"]);
+    }
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if dao::group::exist(db, &code).await {
+        validation.insert("code", vec!["code already exists"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::group::store(db, code, name).await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(group) => {
+            HttpResponse::Created().json(json!({
+                "id": group.id,
+                "message": "Group has been created",
+            }))
+        }
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::group::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(group) => HttpResponse::Ok().json(GroupOAS::from(group)),
+    }
+}
+
+pub async fn update<I: Into<Id> + Clone>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: GroupUpdateRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    if dao::group::find(db, id.clone()).await.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_lowercase();
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::group::update(db, id, name).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(group) => {
+            HttpResponse::Ok().json(json!({
+                "id": group.id,
+                "message": "Group has been updated",
+            }))
+        }
+    }
+}
+
+pub async fn delete<I: Into<Id> + Clone>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::group::find(db, id.clone()).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(group) => match dao::group::delete(db, id).await {
+            Err(e) => {
+                log::error!(delete, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            _ => {
+                HttpResponse::Ok().json(json!({
+                    "id": group.id,
+                    "message": "Group has been deleted",
+                }))
+            },
+        }
+    }
+}
+
+pub async fn sync_users<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    audit: &Audit,
+    id: I,
+    request: GroupUserBulkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let group = dao::group::find(db, id).await;
+
+    if group.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let group = group.unwrap();
+    let users = users::Entity::find()
+        .filter(users::Column::Id.is_in(request.users))
+        .filter(users::Column::DeletedAt.is_null());
+
+    log::debug!(sync_users, "{}", users.build(db.get_database_backend()).to_string());
+
+    let users = users.all(db).await;
+
+    if let Err(e) = users {
+        log::error!(sync_users, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    let users = users.unwrap();
+    let after = users.iter().map(|user| user.id.clone()).collect::<Vec<Id>>();
+    let payload = json!({
+        "group_id": group.id,
+        "users": users.iter()
+            .map(|user| json!({
+                "id": user.id,
+                "username": user.username,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let synced = async {
+        let txn = db.begin().await?;
+        let before = dao::group::sync_users(&txn, &group, users).await?;
+
+        events::emit(&txn, events::GROUP_USERS_SYNCED, payload).await?;
+        services::audit::record(&txn, audit, events::GROUP_USERS_SYNCED, Some(("group", &group.id)), json!({
+            "users": before,
+        }), json!({
+            "users": after,
+        })).await?;
+
+        txn.commit().await
+    };
+
+    match synced.await {
+        Err(e) => {
+            log::error!(sync_users, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => HttpResponse::Ok().finish(),
+    }
+}
+
+pub async fn sync_roles<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    audit: &Audit,
+    id: I,
+    request: RoleBulkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let group = dao::group::find(db, id).await;
+
+    if group.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let group = group.unwrap();
+    let roles = roles::Entity::find()
+        .filter(roles::Column::Id.is_in(request.roles));
+
+    log::debug!(sync_roles, "{}", roles.build(db.get_database_backend()).to_string());
+
+    let roles = roles.all(db).await;
+
+    if let Err(e) = roles {
+        log::error!(sync_roles, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    let roles = roles.unwrap();
+    let after = roles.iter().map(|role| role.id.clone()).collect::<Vec<Id>>();
+    let payload = json!({
+        "group_id": group.id,
+        "roles": roles.iter()
+            .map(|role| json!({
+                "id": role.id,
+                "code": role.code,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let synced = async {
+        let txn = db.begin().await?;
+        let before = dao::group::sync_roles(&txn, &group, roles).await?;
+
+        events::emit(&txn, events::GROUP_ROLES_SYNCED, payload).await?;
+        services::audit::record(&txn, audit, events::GROUP_ROLES_SYNCED, Some(("group", &group.id)), json!({
+            "roles": before,
+        }), json!({
+            "roles": after,
+        })).await?;
+
+        txn.commit().await
+    };
+
+    match synced.await {
+        Err(e) => {
+            log::error!(sync_roles, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => HttpResponse::Ok().finish(),
+    }
+}
+
+pub async fn sync_permissions<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    audit: &Audit,
+    id: I,
+    request: PermissionBulkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let group = dao::group::find(db, id).await;
+
+    if group.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let group = group.unwrap();
+    let permissions = permissions::Entity::find()
+        .filter(permissions::Column::Id.is_in(request.permissions));
+
+    log::debug!(sync_permissions, "{}", permissions.build(db.get_database_backend()).to_string());
+
+    let permissions = permissions.all(db).await;
+
+    if let Err(e) = permissions {
+        log::error!(sync_permissions, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    let permissions = permissions.unwrap();
+    let after = permissions.iter().map(|permission| permission.id.clone()).collect::<Vec<Id>>();
+    let payload = json!({
+        "group_id": group.id,
+        "permissions": permissions.iter()
+            .map(|permission| json!({
+                "id": permission.id,
+                "code": permission.code,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let synced = async {
+        let txn = db.begin().await?;
+        let before = dao::group::sync_permissions(&txn, &group, permissions).await?;
+
+        events::emit(&txn, events::GROUP_PERMISSIONS_SYNCED, payload).await?;
+        services::audit::record(&txn, audit, events::GROUP_PERMISSIONS_SYNCED, Some(("group", &group.id)), json!({
+            "permissions": before,
+        }), json!({
+            "permissions": after,
+        })).await?;
+
+        txn.commit().await
+    };
+
+    match synced.await {
+        Err(e) => {
+            log::error!(sync_permissions, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => HttpResponse::Ok().finish(),
+    }
+}
@@ -0,0 +1,586 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{log, time};
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{dao, services};
+use crate::models::identity_providers;
+use crate::requests::identity_provider::{ExternalIdentityLinkRequest, IdentityProviderOrderByColumn, IdentityProviderProtocol, IdentityProviderStoreRequest, IdentityProviderUpdateRequest, RoleMappingStoreRequest};
+use crate::responses::identity_provider::{ExternalIdentityOAS, IdentityProviderOAS, RoleMappingOAS};
+
+/// Permission required to manage identity providers, their mappings and linked identities
+pub const MANAGE_PERMISSION: &str = "MANAGE_IDENTITY_PROVIDERS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage identity providers",
+    })))
+}
+
+/// Scopes requested from the provider when none are configured
+pub const DEFAULT_SCOPES: &str = "openid profile email";
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<IdentityProviderOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = identity_providers::Entity::find()
+        .order_by(match request.order(IdentityProviderOrderByColumn::Code) {
+            IdentityProviderOrderByColumn::Code => identity_providers::Column::Code,
+            IdentityProviderOrderByColumn::Name => identity_providers::Column::Name,
+            IdentityProviderOrderByColumn::CreatedAt => identity_providers::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(identity_providers::Column::Code.like(request.search()))
+                .add(identity_providers::Column::Name.like(request.search()))
+                .add(identity_providers::Column::Issuer.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                    .map(IdentityProviderOAS::from)
+                    .collect::<Vec<IdentityProviderOAS>>(),
+            }))
+        },
+    }
+}
+
+/// Shared checks of the provider connection settings
+fn validate<'a>(
+    validation: &mut HashMap<&'a str, Vec<&'a str>>,
+    provider: &identity_providers::Model,
+) {
+    let url = |value: &str| value.starts_with("https://") || value.starts_with("http://");
+
+    if provider.name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if provider.client_id.is_empty() {
+        validation.insert("client_id", vec!["field client id is required"]);
+    }
+
+    if provider.protocol.eq(dao::identity_provider::SAML) {
+        if provider.issuer.is_empty() {
+            validation.insert("issuer", vec!["field issuer is required"]);
+        }
+
+        if !provider.sso_url.as_deref().is_some_and(url) {
+            validation.insert("sso_url", vec!["sso url must be an http or https url"]);
+        }
+
+        match &provider.certificate {
+            None => {
+                validation.insert("certificate", vec!["field certificate is required"]);
+            },
+            Some(certificate) if services::saml::certificate(certificate).is_err() => {
+                validation.insert("certificate", vec!["certificate must be a PEM encoded x509 certificate"]);
+            },
+            _ => {},
+        }
+
+        return
+    }
+
+    if !url(&provider.issuer) {
+        validation.insert("issuer", vec!["issuer must be an http or https url"]);
+    }
+
+    if !provider.scopes.split_whitespace().any(|scope| scope.eq("openid")) {
+        validation.insert("scopes", vec!["scopes must include openid"]);
+    }
+
+    if provider.algorithm.as_deref().is_some_and(|algorithm| services::federation::algorithm(algorithm).is_err()) {
+        validation.insert("algorithm", vec!["algorithm must be one of RS256, RS384, RS512, PS256, PS384, PS512, ES256, ES384 or EdDSA"]);
+    }
+}
+
+/// Saml entity ids are compared as is, issuer urls lose the trailing slash
+fn issuer(protocol: &str, issuer: &str) -> String {
+    match protocol {
+        dao::identity_provider::SAML => issuer.trim().to_string(),
+        _ => issuer.trim().trim_end_matches('/').to_string(),
+    }
+}
+
+/// Blank optional settings are stored as null
+fn optional(value: Option<String>) -> Option<String> {
+    value.map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: IdentityProviderStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let code = request.code.trim().to_lowercase();
+    let protocol = match request.protocol {
+        IdentityProviderProtocol::Oidc => dao::identity_provider::OIDC,
+        IdentityProviderProtocol::Saml => dao::identity_provider::SAML,
+    };
+    let scopes = match request.protocol {
+        IdentityProviderProtocol::Saml => String::new(),
+        IdentityProviderProtocol::Oidc => request.scopes
+            .map(|scopes| scopes.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|scopes| !scopes.is_empty())
+            .unwrap_or(DEFAULT_SCOPES.to_string()),
+    };
+
+    if code.is_empty() {
+        validation.insert("code", vec!["field code is required"]);
+    } else if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        validation.insert("code", vec!["code may only contain letters, numbers, dash and underscore"]);
+    } else if dao::identity_provider::exist(db, &code).await {
+        validation.insert("code", vec!["code already exists"]);
+    }
+
+    let provider = identity_providers::Model {
+        id: Uuid::new_v4().into(),
+        code,
+        name: request.name.trim().to_string(),
+        issuer: issuer(protocol, &request.issuer),
+        client_id: request.client_id.trim().to_string(),
+        client_secret: request.client_secret.filter(|secret| !secret.is_empty()),
+        scopes,
+        jit_provisioning: request.jit_provisioning,
+        enabled: request.enabled,
+        protocol: protocol.to_string(),
+        sso_url: optional(request.sso_url),
+        certificate: optional(request.certificate).map(services::saml::pem),
+        email_attribute: optional(request.email_attribute),
+        name_attribute: optional(request.name_attribute),
+        username_attribute: optional(request.username_attribute),
+        algorithm: optional(request.algorithm).map(|algorithm| algorithm.to_uppercase()),
+        created_at: time::now(),
+        updated_at: time::now(),
+    };
+
+    validate(&mut validation, &provider);
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let provider = dao::identity_provider::store(db, provider).await;
+
+    match provider {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(provider) => {
+            HttpResponse::Created().json(json!({
+                "id": provider.id,
+                "message": "Identity provider has been created",
+            }))
+        },
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::identity_provider::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(provider) => HttpResponse::Ok().json(IdentityProviderOAS::from(&provider)),
+    }
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: IdentityProviderUpdateRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut provider = match dao::identity_provider::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    let mut validation = HashMap::new();
+
+    provider.name = request.name.trim().to_string();
+    provider.issuer = issuer(&provider.protocol, &request.issuer);
+    provider.client_id = request.client_id.trim().to_string();
+    provider.jit_provisioning = request.jit_provisioning;
+    provider.enabled = request.enabled;
+    provider.sso_url = optional(request.sso_url);
+    provider.certificate = optional(request.certificate).map(services::saml::pem);
+    provider.email_attribute = optional(request.email_attribute);
+    provider.name_attribute = optional(request.name_attribute);
+    provider.username_attribute = optional(request.username_attribute);
+    provider.algorithm = optional(request.algorithm).map(|algorithm| algorithm.to_uppercase());
+
+    if provider.protocol.eq(dao::identity_provider::OIDC) {
+        provider.scopes = request.scopes
+            .map(|scopes| scopes.split_whitespace().collect::<Vec<&str>>().join(" "))
+            .filter(|scopes| !scopes.is_empty())
+            .unwrap_or(DEFAULT_SCOPES.to_string());
+    }
+
+    if let Some(client_secret) = request.client_secret {
+        provider.client_secret = Some(client_secret).filter(|secret| !secret.is_empty());
+    }
+
+    validate(&mut validation, &provider);
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::identity_provider::update(db, provider).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(provider) => {
+            HttpResponse::Ok().json(json!({
+                "id": provider.id,
+                "message": "Identity provider has been updated",
+            }))
+        },
+    }
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let provider = match dao::identity_provider::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    match dao::identity_provider::delete(db, provider.id.clone()).await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": provider.id,
+                "message": "Identity provider has been deleted",
+            }))
+        },
+    }
+}
+
+pub async fn mappings<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let provider = match dao::identity_provider::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    match dao::identity_provider::mappings(db, &provider).await {
+        Err(e) => {
+            log::error!(mappings, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(mappings) => {
+            HttpResponse::Ok().json(
+                mappings.iter()
+                    .map(RoleMappingOAS::from)
+                    .collect::<Vec<RoleMappingOAS>>()
+            )
+        },
+    }
+}
+
+pub async fn store_mapping<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: RoleMappingStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let provider = match dao::identity_provider::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    let mut validation = HashMap::new();
+    let claim = request.claim.trim().to_string();
+    let value = request.value.trim().to_string();
+    let role = dao::role::find(db, request.role_id).await;
+
+    if claim.is_empty() {
+        validation.insert("claim", vec!["field claim is required"]);
+    }
+
+    if value.is_empty() {
+        validation.insert("value", vec!["field value is required"]);
+    }
+
+    if role.is_none() {
+        validation.insert("role_id", vec!["role doesn't exists"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::identity_provider::store_mapping(db, &provider, claim, value, &role.unwrap()).await {
+        Err(e) => {
+            log::error!(store_mapping, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(mapping) => {
+            HttpResponse::Created().json(json!({
+                "id": mapping.id,
+                "message": "Role mapping has been created",
+            }))
+        },
+    }
+}
+
+pub async fn delete_mapping<I: Into<Id>, M: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    mapping: M,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let provider = match dao::identity_provider::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    match dao::identity_provider::find_mapping(db, &provider, mapping).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(mapping) => match dao::identity_provider::delete_mapping(db, mapping.id.clone()).await {
+            Err(e) => {
+                log::error!(delete_mapping, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            _ => {
+                HttpResponse::Ok().json(json!({
+                    "id": mapping.id,
+                    "message": "Role mapping has been deleted",
+                }))
+            },
+        },
+    }
+}
+
+pub async fn identities<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let user = match dao::user::find(db, id).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    match dao::external_identity::all(db, &user).await {
+        Err(e) => {
+            log::error!(identities, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(identities) => {
+            HttpResponse::Ok().json(
+                identities.iter()
+                    .map(ExternalIdentityOAS::from)
+                    .collect::<Vec<ExternalIdentityOAS>>()
+            )
+        },
+    }
+}
+
+pub async fn link<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: ExternalIdentityLinkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let user = match dao::user::find(db, id).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    let mut validation = HashMap::new();
+    let subject = request.subject.trim().to_string();
+    let provider = dao::identity_provider::find(db, request.provider_id).await;
+
+    if subject.is_empty() {
+        validation.insert("subject", vec!["field subject is required"]);
+    }
+
+    match &provider {
+        None => {
+            validation.insert("provider_id", vec!["identity provider doesn't exists"]);
+        },
+        Some(provider) => {
+            if dao::external_identity::find_by_subject(db, &provider.issuer, &subject).await.is_some() {
+                validation.insert("subject", vec!["identity is already linked"]);
+            }
+        },
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::external_identity::link(db, &user, &provider.unwrap(), subject, None).await {
+        Err(e) => {
+            log::error!(link, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(identity) => {
+            HttpResponse::Created().json(json!({
+                "id": identity.id,
+                "message": "Identity has been linked",
+            }))
+        },
+    }
+}
+
+pub async fn unlink<I: Into<Id>, E: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    identity: E,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let user = match dao::user::find(db, id).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    match dao::external_identity::find(db, identity).await {
+        Some(identity) if identity.user_id.eq(&user.id) => {
+            match dao::external_identity::unlink(db, identity.id.clone()).await {
+                Err(e) => {
+                    log::error!(unlink, "{}", e);
+
+                    HttpResponse::InternalServerError().json(json!({
+                        "message": e.to_string(),
+                    }))
+                },
+                _ => {
+                    HttpResponse::Ok().json(json!({
+                        "id": identity.id,
+                        "message": "Identity has been unlinked",
+                    }))
+                },
+            }
+        },
+        _ => HttpResponse::NotFound().finish(),
+    }
+}
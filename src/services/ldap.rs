@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::log;
+use nightmare_common::models::Id;
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+
+use crate::dao;
+use crate::requests::ldap::LdapGroupMappingStoreRequest;
+use crate::responses::ldap::LdapGroupMappingOAS;
+
+/// Permission required to manage ldap group mappings
+pub const MANAGE_PERMISSION: &str = "MANAGE_LDAP_MAPPINGS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage ldap mappings",
+    })))
+}
+
+pub async fn mappings(
+    db: &DatabaseConnection,
+    auth: &Auth,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::ldap::mappings(db).await {
+        Err(e) => {
+            log::error!(mappings, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(mappings) => {
+            HttpResponse::Ok().json(
+                mappings.iter()
+                    .map(LdapGroupMappingOAS::from)
+                    .collect::<Vec<LdapGroupMappingOAS>>()
+            )
+        },
+    }
+}
+
+pub async fn store_mapping(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: LdapGroupMappingStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let group_dn = request.group_dn.trim().to_string();
+    let role = dao::role::find(db, request.role_id).await;
+
+    if group_dn.is_empty() {
+        validation.insert("group_dn", vec!["field group dn is required"]);
+    } else if !group_dn.contains('=') {
+        validation.insert("group_dn", vec!["group dn must be a distinguished name"]);
+    }
+
+    if role.is_none() {
+        validation.insert("role_id", vec!["role doesn't exists"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::ldap::store_mapping(db, group_dn, &role.unwrap()).await {
+        Err(e) => {
+            log::error!(store_mapping, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(mapping) => {
+            HttpResponse::Created().json(json!({
+                "id": mapping.id,
+                "message": "Group mapping has been created",
+            }))
+        },
+    }
+}
+
+pub async fn delete_mapping<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::ldap::find_mapping(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(mapping) => match dao::ldap::delete_mapping(db, mapping.id.clone()).await {
+            Err(e) => {
+                log::error!(delete_mapping, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            _ => {
+                HttpResponse::Ok().json(json!({
+                    "id": mapping.id,
+                    "message": "Group mapping has been deleted",
+                }))
+            },
+        },
+    }
+}
@@ -1,4 +1,26 @@
 pub mod user;
 pub mod permission;
 pub mod role;
-pub mod auth;
\ No newline at end of file
+pub mod auth;
+pub mod authorization;
+pub mod policy;
+pub mod organization;
+pub mod group;
+pub mod access_request;
+pub mod access_review;
+pub mod elevation;
+pub mod personal_access_token;
+pub mod service_account;
+pub mod oauth;
+pub mod oauth_client;
+pub mod oidc;
+pub mod identity_provider;
+pub mod federation;
+pub mod ldap;
+pub mod xmldsig;
+pub mod saml;
+pub mod scim;
+pub mod webhook;
+pub mod audit;
+pub mod password;
+pub mod bloom;
\ No newline at end of file
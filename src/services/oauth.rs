@@ -0,0 +1,550 @@
+use actix_web::HttpResponse;
+use actix_web::http::header::LOCATION;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::hash::Hash;
+use nightmare_common::models::users;
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::backends::Failure;
+use crate::dao;
+use crate::middleware::audit::Audit;
+use crate::models::{oauth_authorization_codes, oauth_clients, oauth_refresh_tokens};
+use crate::requests::oauth::{AuthorizeRequest, ConsentRequest, TokenRequest};
+use crate::responses::oauth::TokenResponse;
+use crate::services;
+use crate::services::auth::Refusal;
+
+/// Lifetime of access tokens issued through the token endpoint
+pub const ACCESS_TOKEN_SECONDS: i64 = 3600;
+
+/// Lifetime of authorization codes, they are exchanged right away
+pub const AUTHORIZATION_CODE_SECONDS: i64 = 300;
+
+/// Lifetime of refresh tokens, each use rotates them
+pub const REFRESH_TOKEN_DAYS: i64 = 30;
+
+/// Error body as described in RFC 6749 section 5.2
+fn error(error: &str, description: &str) -> HttpResponse {
+    let body = json!({
+        "error": error,
+        "error_description": description,
+    });
+
+    match error {
+        "invalid_client" => HttpResponse::Unauthorized().json(body),
+        _ => HttpResponse::BadRequest().json(body),
+    }
+}
+
+/// Send the user agent back to the client with the given parameters
+pub fn redirect(uri: &str, params: &[(&str, &str)]) -> HttpResponse {
+    let location = match serde_urlencoded::to_string(params).unwrap_or_default() {
+        query if query.is_empty() => uri.to_string(),
+        query if uri.contains('?') => format!("{}&{}", uri, query),
+        query => format!("{}?{}", uri, query),
+    };
+
+    HttpResponse::Found()
+        .insert_header((LOCATION, location))
+        .finish()
+}
+
+/// Authorization error delivered to the redirect uri, RFC 6749 section 4.1.2.1
+fn redirect_error(request: &AuthorizeRequest, error: &str, description: &str) -> HttpResponse {
+    let mut params = vec![("error", error), ("error_description", description)];
+
+    if let Some(state) = &request.state {
+        params.push(("state", state));
+    }
+
+    redirect(&request.redirect_uri, &params)
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Minimal consent page, the user signs in and approves in one step since
+/// there is no browser session to rely on
+fn consent_page(
+    client: &oauth_clients::Model,
+    request: &AuthorizeRequest,
+    scopes: &[String],
+    message: Option<&str>,
+) -> HttpResponse {
+    let hidden = [
+        ("response_type", Some(request.response_type.clone())),
+        ("client_id", Some(request.client_id.clone())),
+        ("redirect_uri", Some(request.redirect_uri.clone())),
+        ("scope", request.scope.clone()),
+        ("state", request.state.clone()),
+        ("code_challenge", request.code_challenge.clone()),
+        ("code_challenge_method", request.code_challenge_method.clone()),
+        ("nonce", request.nonce.clone()),
+    ];
+
+    let hidden = hidden.iter()
+        .filter_map(|(name, value)| value.as_ref().map(|value| format!(
+            "<input type=\"hidden\" name=\"{}\" value=\"{}\">", name, escape(value),
+        )))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let scopes = scopes.iter()
+        .map(|scope| format!("<li>{}</li>", escape(scope)))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    let message = message
+        .map(|message| format!("<p class=\"error\">{}</p>", escape(message)))
+        .unwrap_or_default();
+
+    HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .body(format!(
+            r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Authorize {name}</title></head>
+<body>
+<h1>{name} wants to access your account</h1>
+<p>It will be able to:</p>
+<ul>
+{scopes}
+</ul>
+{message}
+<form method="post" action="/oauth/authorize">
+{hidden}
+<input type="text" name="email_or_username" placeholder="Email or username" required>
+<input type="password" name="password" placeholder="Password" required>
+<button type="submit" name="decision" value="approve">Approve</button>
+<button type="submit" name="decision" value="deny" formnovalidate>Deny</button>
+</form>
+</body>
+</html>"#,
+            name = escape(&client.name),
+            scopes = scopes,
+            message = message,
+            hidden = hidden,
+        ))
+}
+
+/// Validate the authorization request, errors about the client or redirect
+/// uri are shown to the user instead of being redirected
+async fn check(
+    db: &DatabaseConnection,
+    request: &AuthorizeRequest,
+) -> Result<(oauth_clients::Model, Vec<String>), HttpResponse> {
+    let client = dao::oauth_client::find_by_client_id(db, &request.client_id).await
+        .ok_or_else(|| HttpResponse::BadRequest().body("Unknown client"))?;
+
+    let redirect_uris: Vec<String> = serde_json::from_str(&client.redirect_uris).unwrap_or_default();
+
+    if !redirect_uris.contains(&request.redirect_uri) {
+        return Err(HttpResponse::BadRequest().body("Redirect uri is not registered for this client"))
+    }
+
+    if request.response_type.ne("code") {
+        return Err(redirect_error(request, "unsupported_response_type", "only the code response type is supported"))
+    }
+
+    if request.code_challenge.as_deref().unwrap_or_default().is_empty() {
+        return Err(redirect_error(request, "invalid_request", "code challenge is required"))
+    }
+
+    if request.code_challenge_method.as_deref().ne(&Some("S256")) {
+        return Err(redirect_error(request, "invalid_request", "code challenge method must be S256"))
+    }
+
+    let allowed: Vec<String> = serde_json::from_str(&client.scopes).unwrap_or_default();
+    let scopes = match scopes(request.scope.as_deref(), &allowed) {
+        None => return Err(redirect_error(request, "invalid_scope", "requested scope is not allowed for this client")),
+        Some(scopes) => scopes,
+    };
+
+    Ok((client, scopes))
+}
+
+/// Space delimited scope narrowed to `allowed`, everything allowed when
+/// no scope is requested
+fn scopes(scope: Option<&str>, allowed: &[String]) -> Option<Vec<String>> {
+    let mut scopes = match scope {
+        None => allowed.to_vec(),
+        Some(scope) => scope.split_whitespace()
+            .map(services::oidc::normalize)
+            .collect(),
+    };
+
+    scopes.sort();
+    scopes.dedup();
+
+    scopes.iter()
+        .all(|scope| allowed.contains(scope))
+        .then_some(scopes)
+}
+
+pub async fn authorize(
+    db: &DatabaseConnection,
+    request: AuthorizeRequest,
+) -> HttpResponse {
+    match check(db, &request).await {
+        Err(response) => response,
+        Ok((client, scopes)) => consent_page(&client, &request, &scopes, None),
+    }
+}
+
+pub async fn consent(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    request: ConsentRequest,
+) -> HttpResponse {
+    let (client, scopes) = match check(db, &request.authorize).await {
+        Err(response) => return response,
+        Ok(checked) => checked,
+    };
+
+    if request.decision.ne("approve") {
+        return redirect_error(&request.authorize, "access_denied", "the user denied the request")
+    }
+
+    let email_or_username = request.email_or_username.trim().to_lowercase();
+    let user = match services::auth::sign_in(db, audit, &email_or_username, &request.password).await {
+        Ok(user) => user,
+        Err(Refusal::Failure(Failure::Unavailable(_))) => return consent_page(&client, &request.authorize, &scopes, Some("authentication backend is unavailable")),
+        Err(Refusal::Expired(_)) => return consent_page(&client, &request.authorize, &scopes, Some("password has expired, change it to continue")),
+        Err(Refusal::Error(e)) => {
+            log::error!(services::oauth::consent, "{}", e);
+
+            return redirect_error(&request.authorize, "server_error", "failed to authenticate the user")
+        },
+        Err(_) => return consent_page(&client, &request.authorize, &scopes, Some("wrong email, username or password")),
+    };
+
+    let code = async {
+        let txn = db.begin().await?;
+        let code = dao::oauth::store_code(&txn, oauth_authorization_codes::Model {
+            id: Uuid::new_v4().into(),
+            oauth_client_id: client.id.clone(),
+            user_id: user.id.clone(),
+            redirect_uri: request.authorize.redirect_uri.clone(),
+            scopes: serde_json::to_string(&scopes).unwrap(),
+            code_challenge: request.authorize.code_challenge.clone().unwrap_or_default(),
+            code_challenge_method: request.authorize.code_challenge_method.clone().unwrap_or_default(),
+            expires_at: time::now() + chrono::Duration::seconds(AUTHORIZATION_CODE_SECONDS),
+            used_at: None,
+            created_at: time::now(),
+            nonce: request.authorize.nonce.clone(),
+        }).await?;
+
+        services::audit::record(&txn, &audit.acting_as(user.id.clone()), services::audit::LOGIN, Some(("user", &user.id)), Value::Null, json!({
+            "user_id": user.id,
+            "client_id": client.client_id,
+            "method": "oauth",
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(code)
+    };
+
+    match code.await {
+        Err(e) => {
+            log::error!(services::oauth::consent, "{}", e);
+
+            redirect_error(&request.authorize, "server_error", "failed to issue authorization code")
+        },
+        Ok(code) => {
+            let code = base58::to_string(code.id.as_bytes());
+            let mut params = vec![("code", code.as_str())];
+
+            if let Some(state) = &request.authorize.state {
+                params.push(("state", state));
+            }
+
+            redirect(&request.authorize.redirect_uri, &params)
+        },
+    }
+}
+
+pub async fn token(
+    db: &DatabaseConnection,
+    issuer: String,
+    request: TokenRequest,
+) -> HttpResponse {
+    match request.grant_type.as_str() {
+        "authorization_code" => authorization_code(db, issuer, request).await,
+        "refresh_token" => refresh_token(db, issuer, request).await,
+        "client_credentials" => client_credentials(db, request).await,
+        _ => error("unsupported_grant_type", "grant type is not supported"),
+    }
+}
+
+/// Authenticate the oauth client, public clients only identify themselves
+async fn client(
+    db: &DatabaseConnection,
+    request: &TokenRequest,
+) -> Result<oauth_clients::Model, HttpResponse> {
+    let client_id = request.client_id.as_ref()
+        .ok_or_else(|| error("invalid_request", "client id is required"))?;
+
+    let client = dao::oauth_client::find_by_client_id(db, client_id).await
+        .ok_or_else(|| error("invalid_client", "client authentication failed"))?;
+
+    if let Some(hashed) = &client.client_secret {
+        let authenticated = request.client_secret.as_ref()
+            .is_some_and(|secret| hash::verify(Hash::from(hashed.clone()), client.id.clone(), secret.clone()));
+
+        if !authenticated {
+            return Err(error("invalid_client", "client authentication failed"))
+        }
+    }
+
+    Ok(client)
+}
+
+/// PKCE S256 check from RFC 7636 section 4.6
+fn verify_challenge(verifier: &str, challenge: &str) -> bool {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())).eq(challenge)
+}
+
+async fn authorization_code(
+    db: &DatabaseConnection,
+    issuer: String,
+    request: TokenRequest,
+) -> HttpResponse {
+    let client = match client(db, &request).await {
+        Err(response) => return response,
+        Ok(client) => client,
+    };
+
+    let (code, redirect_uri, code_verifier) = match (&request.code, &request.redirect_uri, &request.code_verifier) {
+        (Some(code), Some(redirect_uri), Some(code_verifier)) => (code, redirect_uri, code_verifier),
+        _ => return error("invalid_request", "code, redirect uri and code verifier are required"),
+    };
+
+    let code = match services::auth::decode(code.clone()) {
+        Err(_) => None,
+        Ok(id) => dao::oauth::find_code(db, id).await,
+    };
+
+    let code = match code {
+        Some(code) if code.oauth_client_id.eq(&client.id) => code,
+        _ => return error("invalid_grant", "authorization code is invalid"),
+    };
+
+    // a replayed code means it leaked, drop whatever was issued with it
+    if code.used_at.is_some() {
+        if let Err(e) = dao::oauth::revoke_refresh_tokens(db, &client.id, &code.user_id).await {
+            log::error!(services::oauth::authorization_code, "{}", e);
+        }
+
+        return error("invalid_grant", "authorization code has already been used")
+    }
+
+    if code.expires_at < time::now() {
+        return error("invalid_grant", "authorization code has been expired")
+    }
+
+    if code.redirect_uri.ne(redirect_uri) {
+        return error("invalid_grant", "redirect uri doesn't match the authorization request")
+    }
+
+    if !verify_challenge(code_verifier, &code.code_challenge) {
+        return error("invalid_grant", "code verifier doesn't match the code challenge")
+    }
+
+    match dao::oauth::consume_code(db, &code).await {
+        Err(e) => {
+            log::error!(services::oauth::authorization_code, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(false) => {
+            if let Err(e) = dao::oauth::revoke_refresh_tokens(db, &client.id, &code.user_id).await {
+                log::error!(services::oauth::authorization_code, "{}", e);
+            }
+
+            return error("invalid_grant", "authorization code has already been used")
+        },
+        Ok(true) => {},
+    }
+
+    let user = match dao::user::find(db, code.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return error("invalid_grant", "user no longer exists"),
+    };
+
+    let scopes: Vec<String> = serde_json::from_str(&code.scopes).unwrap_or_default();
+
+    issue(db, &issuer, &client, &user, scopes, code.nonce, None).await
+}
+
+async fn refresh_token(
+    db: &DatabaseConnection,
+    issuer: String,
+    request: TokenRequest,
+) -> HttpResponse {
+    let client = match client(db, &request).await {
+        Err(response) => return response,
+        Ok(client) => client,
+    };
+
+    let token = match &request.refresh_token {
+        None => return error("invalid_request", "refresh token is required"),
+        Some(token) => match services::auth::decode(token.clone()) {
+            Err(_) => None,
+            Ok(id) => dao::oauth::find_refresh_token(db, id).await,
+        },
+    };
+
+    let token = match token {
+        Some(token) if token.oauth_client_id.eq(&client.id) => token,
+        _ => return error("invalid_grant", "refresh token is invalid"),
+    };
+
+    // refresh tokens rotate, reuse of a revoked one means it leaked
+    if token.revoked_at.is_some() {
+        if let Err(e) = dao::oauth::revoke_refresh_tokens(db, &client.id, &token.user_id).await {
+            log::error!(services::oauth::refresh_token, "{}", e);
+        }
+
+        return error("invalid_grant", "refresh token has been revoked")
+    }
+
+    if token.expires_at < time::now() {
+        return error("invalid_grant", "refresh token has been expired")
+    }
+
+    let granted: Vec<String> = serde_json::from_str(&token.scopes).unwrap_or_default();
+    let scopes = match scopes(request.scope.as_deref(), &granted) {
+        None => return error("invalid_scope", "requested scope exceeds the original grant"),
+        Some(scopes) => scopes,
+    };
+
+    let user = match dao::user::find(db, token.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return error("invalid_grant", "user no longer exists"),
+    };
+
+    issue(db, &issuer, &client, &user, scopes, None, Some(&token)).await
+}
+
+/// Issue an access token tied to the tokens table along with a refresh token,
+/// and an id token when the openid scope was granted. The rotated refresh
+/// token is revoked in the same transaction, only one request can trade it
+async fn issue(
+    db: &DatabaseConnection,
+    issuer: &str,
+    client: &oauth_clients::Model,
+    user: &users::Model,
+    scopes: Vec<String>,
+    nonce: Option<String>,
+    rotated: Option<&oauth_refresh_tokens::Model>,
+) -> HttpResponse {
+    let id_token = match scopes.iter().any(|scope| scope.eq("openid")) {
+        false => None,
+        true => match services::oidc::id_token(db, issuer, client, user, &scopes, nonce).await {
+            Err(e) => {
+                log::error!(services::oauth::issue, "{}", e);
+
+                return HttpResponse::InternalServerError().json(json!({
+                    "message": e,
+                }))
+            },
+            Ok(id_token) => Some(id_token),
+        },
+    };
+
+    let expired_at = time::now() + chrono::Duration::seconds(ACCESS_TOKEN_SECONDS);
+    let expires_at = time::now() + chrono::Duration::days(REFRESH_TOKEN_DAYS);
+
+    let issued = async {
+        let txn = db.begin().await?;
+
+        if let Some(rotated) = rotated {
+            if !dao::oauth::revoke_refresh_token(&txn, rotated).await? {
+                return Ok(None)
+            }
+        }
+
+        let token = dao::auth::delegate(&txn, user, client, &scopes, expired_at).await?;
+        let refresh_token = dao::oauth::store_refresh_token(&txn, client, user, &scopes, expires_at).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(Some((token, refresh_token)))
+    };
+
+    match issued.await {
+        Err(e) => {
+            log::error!(services::oauth::issue, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        // a concurrent request traded the same refresh token first
+        Ok(None) => {
+            if let Err(e) = dao::oauth::revoke_refresh_tokens(db, &client.id, &user.id).await {
+                log::error!(services::oauth::issue, "{}", e);
+            }
+
+            error("invalid_grant", "refresh token has been revoked")
+        },
+        Ok(Some((token, refresh_token))) => {
+            HttpResponse::Ok().json(TokenResponse {
+                access_token: base58::to_string(token.id.as_bytes()),
+                token_type: "Bearer".to_string(),
+                expires_in: ACCESS_TOKEN_SECONDS,
+                refresh_token: Some(base58::to_string(refresh_token.id.as_bytes())),
+                scope: Some(scopes.join(" ")),
+                id_token,
+            })
+        },
+    }
+}
+
+async fn client_credentials(
+    db: &DatabaseConnection,
+    request: TokenRequest,
+) -> HttpResponse {
+    let (client_id, client_secret) = match (request.client_id, request.client_secret) {
+        (Some(client_id), Some(client_secret)) => (client_id, client_secret),
+        _ => return error("invalid_request", "client id and client secret are required"),
+    };
+
+    let (account, user) = match services::service_account::by_credentials(db, &client_id, &client_secret).await {
+        None => return error("invalid_client", "client authentication failed"),
+        Some(account) => account,
+    };
+
+    let expired_at = time::now() + chrono::Duration::seconds(ACCESS_TOKEN_SECONDS);
+
+    match dao::auth::generate(db, &user, Some(expired_at), account.organization_id).await {
+        Err(e) => {
+            log::error!(services::oauth::client_credentials, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(token) => {
+            HttpResponse::Ok().json(TokenResponse {
+                access_token: base58::to_string(token.id.as_bytes()),
+                token_type: "Bearer".to_string(),
+                expires_in: ACCESS_TOKEN_SECONDS,
+                refresh_token: None,
+                scope: None,
+                id_token: None,
+            })
+        },
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{dao, services};
+use crate::models::oauth_clients;
+use crate::requests::oauth_client::{OauthClientOrderByColumn, OauthClientStoreRequest, OauthClientUpdateRequest};
+use crate::responses::oauth_client::{OauthClientCredentials, OauthClientOAS};
+
+/// Permission required to register oauth clients and manage their secrets
+pub const MANAGE_PERMISSION: &str = "MANAGE_OAUTH_CLIENTS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage oauth clients",
+    })))
+}
+
+fn secret() -> String {
+    base58::to_string(Uuid::new_v4().as_bytes())
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<OauthClientOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = oauth_clients::Entity::find()
+        .order_by(match request.order(OauthClientOrderByColumn::Name) {
+            OauthClientOrderByColumn::Name => oauth_clients::Column::Name,
+            OauthClientOrderByColumn::CreatedAt => oauth_clients::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(oauth_clients::Column::Name.like(request.search()))
+                .add(oauth_clients::Column::ClientId.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                    .map(OauthClientOAS::from)
+                    .collect::<Vec<OauthClientOAS>>(),
+            }))
+        },
+    }
+}
+
+fn absolute(uri: &str) -> bool {
+    uri.contains("://") && !uri.contains('#')
+}
+
+/// Redirect uris must be absolute and matched exactly, scopes must be
+/// OpenID Connect scopes or existing permission codes
+async fn validate(
+    db: &DatabaseConnection,
+    validation: &mut HashMap<&str, Vec<&str>>,
+    redirect_uris: Vec<String>,
+    post_logout_redirect_uris: Vec<String>,
+    scopes: Vec<String>,
+) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let redirect_uris = redirect_uris.iter()
+        .map(|uri| uri.trim().to_string())
+        .collect::<Vec<String>>();
+
+    let post_logout_redirect_uris = post_logout_redirect_uris.iter()
+        .map(|uri| uri.trim().to_string())
+        .collect::<Vec<String>>();
+
+    let mut scopes = scopes.iter()
+        .map(|scope| services::oidc::normalize(scope))
+        .collect::<Vec<String>>();
+
+    scopes.sort();
+    scopes.dedup();
+
+    if redirect_uris.is_empty() {
+        validation.insert("redirect_uris", vec!["at least one redirect uri is required"]);
+    } else if !redirect_uris.iter().all(|uri| absolute(uri)) {
+        validation.insert("redirect_uris", vec!["redirect uris must be absolute and can't contain a fragment"]);
+    }
+
+    if !post_logout_redirect_uris.iter().all(|uri| absolute(uri)) {
+        validation.insert("post_logout_redirect_uris", vec!["redirect uris must be absolute and can't contain a fragment"]);
+    }
+
+    for scope in scopes.iter().filter(|scope| !services::oidc::SCOPES.contains(&scope.as_str())) {
+        if !dao::permission::exist(db, scope).await {
+            validation.insert("scopes", vec!["scopes must be existing permissions"]);
+
+            break
+        }
+    }
+
+    (redirect_uris, post_logout_redirect_uris, scopes)
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: OauthClientStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_string();
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    let (redirect_uris, post_logout_redirect_uris, scopes) = validate(db, &mut validation, request.redirect_uris, request.post_logout_redirect_uris, request.scopes).await;
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    // public clients such as spas can't keep a secret, they rely on pkce
+    let id: Id = Uuid::new_v4().into();
+    let client_secret = request.confidential.then(secret);
+    let client = dao::oauth_client::store(db, oauth_clients::Model {
+        id: id.clone(),
+        name,
+        client_id: secret(),
+        client_secret: client_secret.clone().map(|client_secret| hash::make(id, client_secret).to_string()),
+        confidential: request.confidential,
+        redirect_uris: serde_json::to_string(&redirect_uris).unwrap(),
+        scopes: serde_json::to_string(&scopes).unwrap(),
+        created_at: time::now(),
+        updated_at: time::now(),
+        post_logout_redirect_uris: Some(serde_json::to_string(&post_logout_redirect_uris).unwrap()),
+    }).await;
+
+    match client {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(client) => {
+            HttpResponse::Created().json(OauthClientCredentials {
+                id: client.id,
+                client_id: client.client_id,
+                client_secret,
+                message: "Copy the credentials now since they won't be shown again".to_string(),
+            })
+        },
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::oauth_client::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(client) => HttpResponse::Ok().json(OauthClientOAS::from(&client)),
+    }
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: OauthClientUpdateRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let client = match dao::oauth_client::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(client) => client,
+    };
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_string();
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    let (redirect_uris, post_logout_redirect_uris, scopes) = validate(db, &mut validation, request.redirect_uris, request.post_logout_redirect_uris, request.scopes).await;
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::oauth_client::update(db, client.id.clone(), name, redirect_uris, scopes, post_logout_redirect_uris).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(client) => {
+            HttpResponse::Ok().json(json!({
+                "id": client.id,
+                "message": "OAuth client has been updated",
+            }))
+        },
+    }
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let client = match dao::oauth_client::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(client) => client,
+    };
+
+    match dao::oauth_client::delete(db, client.id.clone()).await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": client.id,
+                "message": "OAuth client has been deleted",
+            }))
+        },
+    }
+}
+
+pub async fn rotate_secret<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let client = match dao::oauth_client::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(client) => client,
+    };
+
+    if !client.confidential {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "id": ["public clients don't have a secret"],
+            },
+        }))
+    }
+
+    let client_secret = secret();
+    let hashed = hash::make(client.id.clone(), client_secret.clone()).to_string();
+
+    match dao::oauth_client::rotate_secret(db, &client, hashed).await {
+        Err(e) => {
+            log::error!(rotate_secret, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(client) => {
+            HttpResponse::Created().json(OauthClientCredentials {
+                id: client.id,
+                client_id: client.client_id,
+                client_secret: Some(client_secret),
+                message: "Copy the credentials now since they won't be shown again".to_string(),
+            })
+        },
+    }
+}
@@ -0,0 +1,346 @@
+use std::sync::OnceLock;
+
+use actix_web::HttpResponse;
+use actix_web::http::header::WWW_AUTHENTICATE;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{users, Id};
+use nightmare_common::{log, time};
+use rsa::RsaPrivateKey;
+use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPrivateKey};
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::traits::PublicKeyParts;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::dao;
+use crate::middleware::tenant::Tenant;
+use crate::models::oauth_clients;
+use crate::requests::oidc::LogoutRequest;
+use crate::services;
+
+/// Scopes defined by OpenID Connect, they select claims instead of
+/// permissions and keep their lowercase spelling
+pub const SCOPES: [&str; 3] = ["openid", "profile", "email"];
+
+/// Rsa signing key read from a pem in the environment
+pub struct Keys {
+    kid: String,
+    n: String,
+    e: String,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+}
+
+impl Keys {
+    /// Pkcs8 or pkcs1 rsa private key from the variable, panics when it's
+    /// missing or unreadable so the service refuses to start
+    pub fn from_env(variable: &str) -> Self {
+        let pem = std::env::var(variable).unwrap_or_else(|_| panic!("{} must be set", variable));
+        let key = RsaPrivateKey::from_pkcs8_pem(&pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(&pem))
+            .unwrap_or_else(|_| panic!("{} must be a pkcs8 or pkcs1 rsa private key", variable));
+
+        Self::new(key)
+    }
+
+    pub fn new(key: RsaPrivateKey) -> Self {
+        let n = URL_SAFE_NO_PAD.encode(key.n().to_bytes_be());
+        let e = URL_SAFE_NO_PAD.encode(key.e().to_bytes_be());
+        let der = key.to_pkcs1_der().expect("failed to encode rsa key");
+
+        Self {
+            kid: URL_SAFE_NO_PAD.encode(&Sha256::digest(n.as_bytes())[..12]),
+            encoding: EncodingKey::from_rsa_der(der.as_bytes()),
+            decoding: DecodingKey::from_rsa_components(&n, &e).expect("failed to read rsa public key"),
+            n,
+            e,
+        }
+    }
+
+    pub fn kid(&self) -> String {
+        self.kid.clone()
+    }
+
+    /// Public half as a jwk
+    pub fn jwk(&self) -> Value {
+        json!({
+            "kty": "RSA",
+            "use": "sig",
+            "alg": "RS256",
+            "kid": self.kid,
+            "n": self.n,
+            "e": self.e,
+        })
+    }
+
+    /// Compact RS256 jws of the claims
+    pub fn sign<C: Serialize>(&self, claims: &C) -> Result<String, String> {
+        let mut header = Header::new(Algorithm::RS256);
+
+        header.kid = Some(self.kid.clone());
+
+        jsonwebtoken::encode(&header, claims, &self.encoding).map_err(|e| e.to_string())
+    }
+
+    /// Claims of a compact RS256 jws, refused unless this key signed it
+    pub fn verify(&self, token: &str) -> Result<Map<String, Value>, String> {
+        let mut validation = Validation::new(Algorithm::RS256);
+
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+
+        jsonwebtoken::decode::<Map<String, Value>>(token, &self.decoding, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| e.to_string())
+    }
+}
+
+static KEYS: OnceLock<Keys> = OnceLock::new();
+static ISSUER: OnceLock<String> = OnceLock::new();
+
+/// Signing key from `OIDC_PRIVATE_KEY`, required so id tokens stay
+/// verifiable across restarts
+fn keys() -> &'static Keys {
+    KEYS.get_or_init(|| Keys::from_env("OIDC_PRIVATE_KEY"))
+}
+
+/// Issuer from `OIDC_ISSUER`, required since the host header is chosen by
+/// the caller
+pub fn issuer() -> String {
+    ISSUER.get_or_init(|| {
+        std::env::var("OIDC_ISSUER")
+            .expect("OIDC_ISSUER must be set")
+            .trim_end_matches('/')
+            .to_string()
+    }).clone()
+}
+
+/// OpenID Connect scopes are kept as is, anything else is a permission code
+pub fn normalize(scope: &str) -> String {
+    let scope = scope.trim();
+
+    if SCOPES.contains(&scope.to_lowercase().as_str()) {
+        scope.to_lowercase()
+    } else {
+        scope.to_uppercase()
+    }
+}
+
+pub fn configuration(issuer: String) -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{}/oauth/authorize", issuer),
+        "token_endpoint": format!("{}/oauth/token", issuer),
+        "userinfo_endpoint": format!("{}/userinfo", issuer),
+        "jwks_uri": format!("{}/.well-known/jwks.json", issuer),
+        "end_session_endpoint": format!("{}/oauth/logout", issuer),
+        "response_types_supported": ["code"],
+        "grant_types_supported": ["authorization_code", "refresh_token", "client_credentials"],
+        "subject_types_supported": ["public"],
+        "id_token_signing_alg_values_supported": ["RS256"],
+        "scopes_supported": SCOPES,
+        "token_endpoint_auth_methods_supported": ["client_secret_post", "none"],
+        "code_challenge_methods_supported": ["S256"],
+        "claims_supported": [
+            "iss", "sub", "aud", "exp", "iat", "nonce",
+            "name", "preferred_username", "updated_at",
+            "email", "email_verified",
+            "roles", "permissions",
+        ],
+    }))
+}
+
+pub fn jwks() -> HttpResponse {
+    HttpResponse::Ok().json(json!({
+        "keys": [keys().jwk()],
+    }))
+}
+
+/// Standard claims selected by scope, roles and permissions are custom
+/// claims limited to the granted scopes like any other delegated token
+pub async fn claims(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    scopes: &[String],
+) -> Result<Map<String, Value>, DbErr> {
+    let mut grants = dao::authorization::grants(db, user, None).await?;
+
+    grants.restrict(scopes);
+
+    let mut claims = Map::new();
+
+    claims.insert("sub".to_string(), json!(user.id));
+
+    if scopes.iter().any(|scope| scope.eq("profile")) {
+        claims.insert("name".to_string(), json!(user.name));
+        claims.insert("preferred_username".to_string(), json!(user.username));
+        claims.insert("updated_at".to_string(), json!(user.updated_at.timestamp()));
+    }
+
+    if scopes.iter().any(|scope| scope.eq("email")) {
+        claims.insert("email".to_string(), json!(user.email));
+        claims.insert("email_verified".to_string(), json!(user.email_verified_at.is_some()));
+    }
+
+    claims.insert("roles".to_string(), json!(grants.roles.iter()
+        .map(|role| role.code.clone())
+        .collect::<Vec<String>>()));
+    claims.insert("permissions".to_string(), json!(grants.permissions().iter()
+        .map(|permission| permission.code.clone())
+        .collect::<Vec<String>>()));
+
+    Ok(claims)
+}
+
+pub async fn id_token(
+    db: &DatabaseConnection,
+    issuer: &str,
+    client: &oauth_clients::Model,
+    user: &users::Model,
+    scopes: &[String],
+    nonce: Option<String>,
+) -> Result<String, String> {
+    let mut claims = claims(db, user, scopes).await.map_err(|e| e.to_string())?;
+    let now = time::now().timestamp();
+
+    claims.insert("iss".to_string(), json!(issuer));
+    claims.insert("aud".to_string(), json!(client.client_id));
+    claims.insert("iat".to_string(), json!(now));
+    claims.insert("exp".to_string(), json!(now + services::oauth::ACCESS_TOKEN_SECONDS));
+
+    if let Some(nonce) = nonce {
+        claims.insert("nonce".to_string(), json!(nonce));
+    }
+
+    sign(&claims)
+}
+
+/// Id of the signing key, published in the jwks
+pub fn key_id() -> String {
+    keys().kid()
+}
+
+/// Compact jws of the claims with the signing key
+pub fn sign<C: Serialize>(claims: &C) -> Result<String, String> {
+    keys().sign(claims)
+}
+
+pub async fn userinfo(
+    db: &DatabaseConnection,
+    auth: Auth,
+    tenant: Tenant,
+) -> HttpResponse {
+    let scopes: Vec<String> = tenant.token
+        .and_then(|token| token.scopes)
+        .and_then(|scopes| serde_json::from_str(&scopes).ok())
+        .unwrap_or_default();
+
+    if !scopes.iter().any(|scope| scope.eq("openid")) {
+        return HttpResponse::Forbidden()
+            .insert_header((WWW_AUTHENTICATE, "Bearer error=\"insufficient_scope\", scope=\"openid\""))
+            .json(json!({
+                "error": "insufficient_scope",
+                "error_description": "token wasn't issued with the openid scope",
+            }))
+    }
+
+    match claims(db, &auth.user, &scopes).await {
+        Err(e) => {
+            log::error!(services::oidc::userinfo, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(claims) => HttpResponse::Ok().json(claims),
+    }
+}
+
+/// RP-initiated logout, tokens the client holds for the user in the id
+/// token hint are revoked since there is no browser session to end
+pub async fn logout(
+    db: &DatabaseConnection,
+    issuer: String,
+    request: LogoutRequest,
+) -> HttpResponse {
+    let hint = match &request.id_token_hint {
+        None => None,
+        Some(token) => {
+            let mut validation = Validation::new(Algorithm::RS256);
+
+            validation.validate_exp = false;
+            validation.validate_aud = false;
+            validation.set_issuer(&[issuer]);
+
+            match jsonwebtoken::decode::<Map<String, Value>>(token, &keys().decoding, &validation) {
+                Err(_) => return HttpResponse::BadRequest().body("Invalid id token hint"),
+                Ok(token) => Some(token.claims),
+            }
+        },
+    };
+
+    let audience = hint.as_ref()
+        .and_then(|claims| claims.get("aud"))
+        .and_then(Value::as_str)
+        .map(String::from);
+
+    let client_id = match (request.client_id.clone(), audience) {
+        (Some(client_id), Some(audience)) if client_id.ne(&audience) => {
+            return HttpResponse::BadRequest().body("Client id doesn't match the id token hint")
+        },
+        (client_id, audience) => client_id.or(audience),
+    };
+
+    let client = match &client_id {
+        None => None,
+        Some(client_id) => match dao::oauth_client::find_by_client_id(db, client_id).await {
+            None => return HttpResponse::BadRequest().body("Unknown client"),
+            client => client,
+        },
+    };
+
+    let subject = hint.as_ref()
+        .and_then(|claims| claims.get("sub"))
+        .and_then(Value::as_str)
+        .and_then(|sub| Uuid::parse_str(sub).ok())
+        .map(Id::from);
+
+    if let (Some(client), Some(user_id)) = (&client, subject) {
+        if let Err(e) = dao::auth::revoke_delegated(db, &client.id, &user_id).await {
+            log::error!(services::oidc::logout, "{}", e);
+        }
+
+        if let Err(e) = dao::oauth::revoke_refresh_tokens(db, &client.id, &user_id).await {
+            log::error!(services::oidc::logout, "{}", e);
+        }
+    }
+
+    match (&request.post_logout_redirect_uri, &client) {
+        (None, _) => {
+            HttpResponse::Ok()
+                .content_type("text/html; charset=utf-8")
+                .body("<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>Signed out</title></head>\n<body><h1>You have been signed out</h1></body>\n</html>")
+        },
+        (Some(uri), Some(client)) => {
+            let registered: Vec<String> = client.post_logout_redirect_uris.as_ref()
+                .and_then(|uris| serde_json::from_str(uris).ok())
+                .unwrap_or_default();
+
+            if !registered.contains(uri) {
+                return HttpResponse::BadRequest().body("Post logout redirect uri is not registered for this client")
+            }
+
+            match &request.state {
+                None => services::oauth::redirect(uri, &[]),
+                Some(state) => services::oauth::redirect(uri, &[("state", state.as_str())]),
+            }
+        },
+        (Some(_), None) => HttpResponse::BadRequest().body("Client id or id token hint is required to redirect"),
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{users, Id};
+use nightmare_common::log;
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
+use serde_json::json;
+
+use crate::dao;
+use crate::middleware::tenant::Tenant;
+use crate::models::organizations;
+use crate::requests::organization::{OrganizationOrderByColumn, OrganizationStoreRequest, OrganizationUpdateRequest, OrganizationUserBulkRequest};
+use crate::responses::organization::OrganizationOAS;
+
+/// Permission required to manage organizations and their members
+pub const MANAGE_PERMISSION: &str = "MANAGE_ORGANIZATIONS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage organizations",
+    })))
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<OrganizationOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = organizations::Entity::find()
+        .order_by(match request.order(OrganizationOrderByColumn::Name) {
+            OrganizationOrderByColumn::Code => organizations::Column::Code,
+            OrganizationOrderByColumn::Name => organizations::Column::Name,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(organizations::Column::Code.like(request.search()))
+                .add(organizations::Column::Name.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                   .map(|organization| organization.into())
+                   .collect::<Vec<OrganizationOAS>>(),
+            }))
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: OrganizationStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let code = request.code.trim().to_uppercase();
+    let name = request.name.trim().to_lowercase();
+
+    if code.is_empty() {
+        validation.insert("code", vec!["field code is required"]);
+    }
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if dao::organization::exist(db, &code).await {
+        validation.insert("code", vec!["code already exists"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::organization::store(db, code, name).await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(organization) => {
+            HttpResponse::Created().json(json!({
+                "id": organization.id,
+                "message": "Organization has been created",
+            }))
+        }
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::organization::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(organization) => HttpResponse::Ok().json(OrganizationOAS::from(organization)),
+    }
+}
+
+pub async fn update<I: Into<Id> + Clone>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: OrganizationUpdateRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    if dao::organization::find(db, id.clone()).await.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_lowercase();
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::organization::update(db, id, name).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(organization) => {
+            HttpResponse::Ok().json(json!({
+                "id": organization.id,
+                "message": "Organization has been updated",
+            }))
+        }
+    }
+}
+
+pub async fn delete<I: Into<Id> + Clone>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::organization::find(db, id.clone()).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(organization) => match dao::organization::delete(db, id).await {
+            Err(e) => {
+                log::error!(delete, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            _ => {
+                HttpResponse::Ok().json(json!({
+                    "id": organization.id,
+                    "message": "Organization has been deleted",
+                }))
+            },
+        }
+    }
+}
+
+pub async fn sync_users<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: OrganizationUserBulkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let organization = dao::organization::find(db, id).await;
+
+    if organization.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let organization = organization.unwrap();
+    let users = users::Entity::find()
+        .filter(users::Column::Id.is_in(request.users))
+        .filter(users::Column::DeletedAt.is_null());
+
+    log::debug!(sync_users, "{}", users.build(db.get_database_backend()).to_string());
+
+    let users = users.all(db).await;
+
+    if let Err(e) = users {
+        log::error!(sync_users, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    match dao::organization::sync_users(db, &organization, users.unwrap()).await {
+        Err(e) => {
+            log::error!(sync_users, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => HttpResponse::Ok().finish(),
+    }
+}
+
+pub async fn switch<I: Into<Id>>(
+    db: &DatabaseConnection,
+    tenant: Tenant,
+    id: I,
+) -> HttpResponse {
+    let organization = dao::organization::find(db, id).await;
+
+    if organization.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let organization = organization.unwrap();
+    let token = match tenant.token {
+        None => return HttpResponse::Unauthorized().finish(),
+        Some(token) => token,
+    };
+
+    if !dao::organization::is_member(db, &organization.id, &token.user_id).await {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "organization": ["user is not a member of this organization"],
+            },
+        }))
+    }
+
+    match dao::auth::switch(db, &token, Some(organization.id.clone())).await {
+        Err(e) => {
+            log::error!(switch, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(_) => {
+            HttpResponse::Ok().json(json!({
+                "id": organization.id,
+                "message": "Organization has been switched",
+            }))
+        },
+    }
+}
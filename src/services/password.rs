@@ -0,0 +1,795 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use actix_web::HttpResponse;
+use nightmare_common::{hash, log, time};
+use nightmare_common::hash::Hash;
+use nightmare_common::models::users;
+use openssl::sha::sha1;
+use sea_orm::{DatabaseConnection, DbErr};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{dao, services};
+use crate::backends::Failure;
+use crate::dao::organization::Scope;
+use crate::middleware::audit::Audit;
+use crate::requests::password::ExpiredPasswordRequest;
+use crate::requests::user::UserUpdatePasswordRequest;
+use crate::responses::password::PasswordPolicyOAS;
+use crate::services::auth::Refusal;
+use crate::services::bloom::Bloom;
+
+/// Refused even without a dictionary file
+const COMMON: [&str; 24] = [
+    "password", "passw0rd", "qwerty", "qwertyuiop", "asdfgh", "letmein",
+    "welcome", "admin", "administrator", "root", "iloveyou", "monkey",
+    "dragon", "sunshine", "princess", "football", "baseball", "master",
+    "shadow", "superman", "trustno", "changeme", "secret", "nightmare",
+];
+
+/// What happens to a password found in the breached list
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BreachedAction {
+    #[default]
+    Reject,
+    /// Accepted, the response carries a warning
+    Warn,
+}
+
+/// Breached sha1 hashes loaded at startup, nothing leaves the host
+pub enum Breached {
+    /// Filter built by `cargo run --bin breach -- build`
+    Bloom(Bloom),
+    /// Directory of range files named by the first 5 hex characters of
+    /// the hash, holding `SUFFIX:COUNT` lines
+    Ranges(PathBuf),
+}
+
+impl Breached {
+    pub fn contains(&self, password: &str) -> bool {
+        let digest = sha1(password.as_bytes());
+
+        match self {
+            Self::Bloom(bloom) => bloom.contains(&digest),
+            Self::Ranges(directory) => {
+                let hex = digest.iter().map(|byte| format!("{:02X}", byte)).collect::<String>();
+                let (prefix, suffix) = hex.split_at(5);
+
+                match std::fs::read_to_string(directory.join(format!("{}.txt", prefix))) {
+                    Err(e) => {
+                        log::error!(services::password::breached, "range {} {}", prefix, e);
+
+                        false
+                    },
+                    Ok(range) => range.lines()
+                        .filter_map(|line| line.split(':').next())
+                        .any(|hash| hash.trim().eq_ignore_ascii_case(suffix)),
+                }
+            },
+        }
+    }
+}
+
+/// Rules every new password goes through, whoever sets it
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digit: bool,
+    pub symbol: bool,
+    /// Refuse passwords containing the username or the local part of the
+    /// email
+    pub user_info: bool,
+    /// Refuse common words, ignoring case and the digits or symbols around
+    /// them
+    pub dictionary: bool,
+    /// Newline separated words added to the common ones
+    pub dictionary_file: Option<String>,
+    pub breached: BreachedAction,
+    /// Bloom filter of breached sha1 hashes, takes precedence over ranges
+    pub breached_filter: Option<String>,
+    /// Directory of breached hash range files
+    pub breached_ranges: Option<String>,
+    /// Previous passwords a user can't reuse, 0 turns the history off
+    pub history: usize,
+    /// Days a password lasts for holders of the role code, `*` applies to
+    /// everyone and the shortest matching period wins
+    pub expiry: HashMap<String, i64>,
+    #[serde(skip)]
+    words: HashSet<String>,
+    #[serde(skip)]
+    list: Option<Breached>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            min_length: 6,
+            max_length: 128,
+            lowercase: true,
+            uppercase: true,
+            digit: true,
+            symbol: false,
+            user_info: true,
+            dictionary: true,
+            dictionary_file: None,
+            breached: BreachedAction::default(),
+            breached_filter: None,
+            breached_ranges: None,
+            history: 5,
+            expiry: HashMap::new(),
+            words: HashSet::new(),
+            list: None,
+        }
+    }
+}
+
+impl Policy {
+    /// `PASSWORD_POLICY_FILE` is a json of the fields, `PASSWORD_*`
+    /// variables override it and the defaults keep the rules that were
+    /// hard coded before
+    pub fn from_env() -> Self {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Same as `from_env` with the variables looked up through `var`
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let env = |key: &str| var(key).filter(|value| !value.trim().is_empty());
+        let flag = |key: &str| env(key).map(|value| matches!(value.trim().to_lowercase().as_str(), "true" | "1" | "yes"));
+        let number = |key: &str| env(key).map(|value| value.trim().parse::<usize>().unwrap_or_else(|_| panic!("{} must be a number", key)));
+        let mut policy = match env("PASSWORD_POLICY_FILE") {
+            None => Self::default(),
+            Some(file) => std::fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str::<Self>(&json).map_err(|e| e.to_string()))
+                .unwrap_or_else(|e| panic!("PASSWORD_POLICY_FILE {} is invalid, {}", file, e)),
+        };
+
+        policy.min_length = number("PASSWORD_MIN_LENGTH").unwrap_or(policy.min_length);
+        policy.max_length = number("PASSWORD_MAX_LENGTH").unwrap_or(policy.max_length);
+        policy.lowercase = flag("PASSWORD_REQUIRE_LOWERCASE").unwrap_or(policy.lowercase);
+        policy.uppercase = flag("PASSWORD_REQUIRE_UPPERCASE").unwrap_or(policy.uppercase);
+        policy.digit = flag("PASSWORD_REQUIRE_DIGIT").unwrap_or(policy.digit);
+        policy.symbol = flag("PASSWORD_REQUIRE_SYMBOL").unwrap_or(policy.symbol);
+        policy.user_info = flag("PASSWORD_REJECT_USER_INFO").unwrap_or(policy.user_info);
+        policy.dictionary = flag("PASSWORD_REJECT_COMMON").unwrap_or(policy.dictionary);
+        policy.dictionary_file = env("PASSWORD_DICTIONARY_FILE").or(policy.dictionary_file);
+        policy.breached_filter = env("PASSWORD_BREACHED_FILTER").or(policy.breached_filter);
+        policy.breached_ranges = env("PASSWORD_BREACHED_RANGES").or(policy.breached_ranges);
+        policy.history = number("PASSWORD_HISTORY").unwrap_or(policy.history);
+        policy.expiry = match env("PASSWORD_EXPIRY") {
+            None => policy.expiry,
+            Some(expiry) => expiry.split(',')
+                .filter(|pair| !pair.trim().is_empty())
+                .map(|pair| pair.split_once(':')
+                    .and_then(|(code, days)| days.trim().parse().ok().map(|days| (code.trim().to_string(), days)))
+                    .unwrap_or_else(|| panic!("PASSWORD_EXPIRY must be ROLE:DAYS pairs, got {}", pair)))
+                .collect(),
+        };
+        policy.expiry = policy.expiry.into_iter()
+            .map(|(code, days)| (code.to_uppercase(), days))
+            .collect();
+        policy.breached = match env("PASSWORD_BREACHED_ACTION").map(|action| action.trim().to_lowercase()) {
+            None => policy.breached,
+            Some(action) if action.eq("warn") => BreachedAction::Warn,
+            Some(action) if action.eq("reject") => BreachedAction::Reject,
+            Some(action) => panic!("PASSWORD_BREACHED_ACTION must be reject or warn, got {}", action),
+        };
+
+        if policy.dictionary {
+            policy.words = COMMON.iter().map(|word| word.to_string()).collect();
+
+            if let Some(file) = &policy.dictionary_file {
+                let words = std::fs::read_to_string(file)
+                    .unwrap_or_else(|e| panic!("PASSWORD_DICTIONARY_FILE {} is unreadable, {}", file, e));
+
+                policy.words.extend(
+                    words.lines()
+                        .map(|word| word.trim().to_lowercase())
+                        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+                );
+            }
+        }
+
+        policy.list = match (&policy.breached_filter, &policy.breached_ranges) {
+            (Some(file), _) => {
+                let bloom = Bloom::read(file)
+                    .unwrap_or_else(|e| panic!("PASSWORD_BREACHED_FILTER {} is invalid, {}", file, e));
+
+                log::info!(services::password, "screening passwords against {}", file);
+
+                Some(Breached::Bloom(bloom))
+            },
+            (None, Some(directory)) => {
+                if !PathBuf::from(directory).is_dir() {
+                    panic!("PASSWORD_BREACHED_RANGES {} must be a directory", directory)
+                }
+
+                log::info!(services::password, "screening passwords against ranges in {}", directory);
+
+                Some(Breached::Ranges(PathBuf::from(directory)))
+            },
+            (None, None) => None,
+        };
+
+        policy
+    }
+
+    /// Action applied to breached passwords, none without a list
+    pub fn screening(&self) -> Option<BreachedAction> {
+        self.list.as_ref().map(|_| self.breached)
+    }
+
+    fn breached(&self, password: &str) -> bool {
+        self.list.as_ref().is_some_and(|list| list.contains(password))
+    }
+
+    /// Notes on a password that was accepted anyway
+    pub fn warnings(&self, password: &str) -> Vec<String> {
+        if self.breached.eq(&BreachedAction::Warn) && self.breached(password) {
+            vec!["password appeared in a data breach, consider changing it".to_string()]
+        } else {
+            vec![]
+        }
+    }
+
+    /// Whether the password matches one of the user's previous ones
+    pub async fn reused(&self, db: &DatabaseConnection, user: &users::Model, password: &str) -> Result<bool, DbErr> {
+        if self.history.eq(&0) {
+            return Ok(false)
+        }
+
+        let histories = dao::password::histories(db, user, self.history as u64).await?;
+
+        Ok(histories.into_iter().any(|history| {
+            Hash::from(history.password).verify(hash::make(user.id.clone(), password))
+        }))
+    }
+
+    /// Whether the user's roles give the password a lifetime and it has
+    /// run out, only passwords checked by the local backend expire here
+    pub async fn expired(&self, db: &DatabaseConnection, user: &users::Model, method: &str) -> Result<bool, DbErr> {
+        // directory passwords expire on the directory's own terms
+        if self.expiry.is_empty() || method.ne("local") {
+            return Ok(false)
+        }
+
+        let grants = dao::authorization::grants(db, user, None).await?;
+        let codes = grants.roles.iter()
+            .chain(grants.scoped.iter().map(|scoped| &scoped.role))
+            .map(|role| role.code.to_uppercase())
+            .chain(["*".to_string()]);
+        let days = codes.filter_map(|code| self.expiry.get(&code).copied()).min();
+
+        let Some(days) = days else {
+            return Ok(false)
+        };
+
+        let changed_at = dao::password::changed_at(db, user).await?.unwrap_or(user.created_at);
+
+        Ok(changed_at + chrono::Duration::days(days) <= time::now())
+    }
+
+    /// Reasons the password is refused for the user, empty when it passes
+    pub fn check(&self, password: &str, username: &str, email: &str) -> Vec<String> {
+        let mut errors = vec![];
+        let length = password.chars().count();
+
+        if length < self.min_length {
+            errors.push(format!("min length for password is {}", self.min_length));
+        }
+
+        if length > self.max_length {
+            errors.push(format!("max length for password is {}", self.max_length));
+        }
+
+        if self.lowercase && !password.chars().any(char::is_lowercase) {
+            errors.push("password must have lower case letter".to_string());
+        }
+
+        if self.uppercase && !password.chars().any(char::is_uppercase) {
+            errors.push("password must have upper case letter".to_string());
+        }
+
+        if self.digit && !password.chars().any(char::is_numeric) {
+            errors.push("password must have numeric value".to_string());
+        }
+
+        if self.symbol && !password.chars().any(|c| !c.is_alphanumeric() && !c.is_whitespace()) {
+            errors.push("password must have symbol".to_string());
+        }
+
+        let lowercase = password.to_lowercase();
+
+        if self.user_info {
+            let local = email.split('@').next().unwrap_or_default();
+            let similar = [username, local].iter()
+                .map(|info| info.trim().to_lowercase())
+                .any(|info| info.chars().count() >= 3 && lowercase.contains(&info));
+
+            if similar {
+                errors.push("password can't contain the username or email".to_string());
+            }
+        }
+
+        let word = lowercase.trim_matches(|c: char| !c.is_alphabetic());
+
+        if self.words.contains(&lowercase) || self.words.contains(word) {
+            errors.push("password is a common word".to_string());
+        }
+
+        if self.breached.eq(&BreachedAction::Reject) && self.breached(password) {
+            errors.push("password appeared in a data breach".to_string());
+        }
+
+        errors
+    }
+}
+
+static POLICY: OnceLock<Policy> = OnceLock::new();
+
+#[cfg(not(test))]
+pub fn policy() -> &'static Policy {
+    POLICY.get_or_init(Policy::from_env)
+}
+
+/// Tests leave the environment alone, passwords only expire for holders of
+/// the role they set aside for it
+#[cfg(test)]
+pub fn policy() -> &'static Policy {
+    POLICY.get_or_init(|| Policy::from_vars(|key| key.eq("PASSWORD_EXPIRY").then(|| format!("{}:30", tests::EXPIRING))))
+}
+
+/// Backend checking the user's password, accounts kept by a directory or
+/// linked to an identity provider may sign in elsewhere and aren't local
+pub async fn backend(db: &DatabaseConnection, user: &users::Model) -> Result<&'static str, DbErr> {
+    if dao::ldap::find_by_user(db, user).await?.is_some() {
+        return Ok("ldap")
+    }
+
+    if !dao::external_identity::all(db, user).await?.is_empty() {
+        return Ok("federated")
+    }
+
+    Ok("local")
+}
+
+pub fn show() -> HttpResponse {
+    HttpResponse::Ok().json(PasswordPolicyOAS::from(policy()))
+}
+
+/// Password change for users login refused, the current password stands in
+/// for the session they can't get
+pub async fn change_expired(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    request: ExpiredPasswordRequest,
+) -> HttpResponse {
+    let email_or_username = request.email_or_username.trim().to_lowercase();
+
+    // credentials are checked the way login does before anything about the
+    // account is revealed, every refusal gets the same answer
+    let user = match services::auth::sign_in(db, audit, &email_or_username, &request.current_password).await {
+        Err(Refusal::Expired(user)) => user,
+        Ok(_) => return HttpResponse::Conflict().json(json!({
+            "message": "Password hasn't expired, change it from the account",
+        })),
+        Err(Refusal::Failure(Failure::Unavailable(_))) => return HttpResponse::ServiceUnavailable().json(json!({
+            "message": "Authentication backend is unavailable",
+        })),
+        Err(Refusal::Error(e)) => return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        })),
+        Err(Refusal::Failure(_) | Refusal::ServiceAccount) => return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "credentials": ["wrong email, username or password"],
+            },
+        })),
+    };
+
+    // the current password already proved who they are, wherever they belong
+    services::user::update_password(db, &audit.acting_as(user.id.clone()), Scope::Platform, user.id.clone(), UserUpdatePasswordRequest {
+        current_password: request.current_password,
+        new_password: request.new_password,
+        password_confirmation: request.password_confirmation,
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::to_bytes;
+    use actix_web::http::StatusCode;
+    use nightmare_common::base58;
+    use nightmare_common::middleware::auth::Auth;
+    use nightmare_common::models::{permission_role, permissions, roles, Id};
+    use sea_orm::{ActiveModelTrait, ConnectionTrait};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::models::{
+        audit_chain, audit_logs, external_identities, group_permission, group_role, group_user, groups, ldap_users,
+        outbox_events, password_histories, permission_user, personal_access_tokens, role_elevations, role_user,
+        service_accounts, tokens, webhooks,
+    };
+    use crate::requests::scim::{ScimEmail, ScimUserRequest};
+    use crate::requests::user::UserStoreRequest;
+    use crate::testing;
+
+    /// Role the shared test policy gives 30 day passwords
+    pub const EXPIRING: &str = "ROTATED";
+
+    async fn database() -> DatabaseConnection {
+        let db = testing::database(vec![
+            testing::table(users::Entity),
+            testing::table(password_histories::Entity),
+            testing::table(roles::Entity),
+            testing::table(permissions::Entity),
+            testing::table(permission_role::Entity),
+            testing::table(role_user::Entity),
+            testing::table(permission_user::Entity),
+            testing::table(groups::Entity),
+            testing::table(group_user::Entity),
+            testing::table(group_role::Entity),
+            testing::table(group_permission::Entity),
+            testing::table(role_elevations::Entity),
+            testing::table(service_accounts::Entity),
+            testing::table(ldap_users::Entity),
+            testing::table(external_identities::Entity),
+            testing::table(tokens::Entity),
+            testing::table(personal_access_tokens::Entity),
+            testing::table(outbox_events::Entity),
+            testing::table(webhooks::Entity),
+            testing::table(audit_logs::Entity),
+            testing::table(audit_chain::Entity),
+        ]).await;
+
+        // not part of the shared users model
+        db.execute_unprepared("ALTER TABLE users ADD COLUMN password_changed_at TEXT").await.unwrap();
+
+        audit_chain::ActiveModel::from(audit_chain::Model {
+            id: Uuid::new_v4().into(),
+            sequence: 0,
+            hash: "0".repeat(64),
+            updated_at: time::now(),
+        }).insert(&db).await.unwrap();
+
+        db
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = pairs.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+
+        move |key| vars.get(key).cloned()
+    }
+
+    /// alice whose passwords were `passwords`, the last one is current
+    async fn alice(db: &DatabaseConnection, passwords: &[&str]) -> users::Model {
+        let mut user = dao::user::store(db, testing::user("alice", passwords[0])).await.unwrap();
+
+        for password in passwords {
+            user.password = hash::make(user.id.clone(), password.to_string()).to_string();
+            user = dao::user::update(db, &user).await.unwrap();
+            dao::password::changed(db, &user, 5).await.unwrap();
+        }
+
+        user
+    }
+
+    /// User whose password `First-Horse-1` was set `days` ago, holding the
+    /// expiring role when `rotated`
+    async fn aged(db: &DatabaseConnection, username: &str, days: i64, rotated: bool) -> users::Model {
+        let mut user = testing::user(username, "First-Horse-1");
+
+        user.created_at = time::now() - chrono::Duration::days(days);
+
+        let user = dao::user::store(db, user).await.unwrap();
+
+        if rotated {
+            let role = dao::role::store(db, EXPIRING, "rotated").await.unwrap();
+
+            role_user::ActiveModel::from(role_user::Model {
+                id: Uuid::new_v4().into(),
+                role_id: role.id,
+                user_id: user.id.clone(),
+                resource_type: None,
+                resource_id: None,
+                starts_at: None,
+                expires_at: None,
+            }).insert(db).await.unwrap();
+        }
+
+        user
+    }
+
+    fn expired(email_or_username: &str, current: &str, new: &str) -> ExpiredPasswordRequest {
+        ExpiredPasswordRequest {
+            email_or_username: email_or_username.to_string(),
+            current_password: current.to_string(),
+            new_password: new.to_string(),
+            password_confirmation: new.to_string(),
+        }
+    }
+
+    async fn body(response: HttpResponse) -> String {
+        String::from_utf8(to_bytes(response.into_body()).await.unwrap().to_vec()).unwrap()
+    }
+
+    #[test]
+    fn check_applies_every_rule() {
+        let policy = Policy::from_vars(vars(&[("PASSWORD_REQUIRE_SYMBOL", "true")]));
+
+        assert!(policy.check("Correct-Horse-7", "alice", "alice@nightmare.local").is_empty());
+        assert_eq!(policy.check("Ab-1", "alice", "alice@nightmare.local"), vec!["min length for password is 6"]);
+        assert_eq!(policy.check("CORRECT-HORSE-7", "alice", "alice@nightmare.local"), vec!["password must have lower case letter"]);
+        assert_eq!(policy.check("correct-horse-7", "alice", "alice@nightmare.local"), vec!["password must have upper case letter"]);
+        assert_eq!(policy.check("Correct-Horse", "alice", "alice@nightmare.local"), vec!["password must have numeric value"]);
+        assert_eq!(policy.check("CorrectHorse7", "alice", "alice@nightmare.local"), vec!["password must have symbol"]);
+        assert_eq!(policy.check(&format!("Aa-1{}", "a".repeat(128)), "bob", "bob@nightmare.local"), vec!["max length for password is 128"]);
+    }
+
+    #[test]
+    fn check_refuses_user_info_and_common_words() {
+        let policy = Policy::from_vars(vars(&[]));
+
+        assert_eq!(policy.check("Alice-2024", "alice", "bob@nightmare.local"), vec!["password can't contain the username or email"]);
+        assert_eq!(policy.check("Jdoe-2024", "alice", "jdoe@nightmare.local"), vec!["password can't contain the username or email"]);
+        assert_eq!(policy.check("!!Password1", "bob", "bob@nightmare.local"), vec!["password is a common word"]);
+        assert!(policy.check("Al-2024x", "al", "al@nightmare.local").is_empty());
+    }
+
+    #[test]
+    fn variables_override_the_defaults() {
+        let policy = Policy::from_vars(vars(&[
+            ("PASSWORD_MIN_LENGTH", "12"),
+            ("PASSWORD_REQUIRE_UPPERCASE", "false"),
+            ("PASSWORD_REJECT_COMMON", "no"),
+            ("PASSWORD_HISTORY", "0"),
+            ("PASSWORD_EXPIRY", "admin:30, *:90"),
+            ("PASSWORD_BREACHED_ACTION", "warn"),
+        ]));
+
+        assert_eq!(policy.min_length, 12);
+        assert!(!policy.uppercase);
+        assert!(policy.lowercase);
+        assert!(!policy.dictionary);
+        assert_eq!(policy.history, 0);
+        assert_eq!(policy.expiry, HashMap::from([("ADMIN".to_string(), 30), ("*".to_string(), 90)]));
+        assert_eq!(policy.breached, BreachedAction::Warn);
+        assert!(policy.check("password-2024", "bob", "bob@nightmare.local").is_empty());
+    }
+
+    #[test]
+    fn variables_override_the_policy_file() {
+        let directory = std::env::temp_dir();
+        let file = directory.join(format!("{}.json", Uuid::new_v4()));
+        let dictionary = directory.join(format!("{}.txt", Uuid::new_v4()));
+
+        std::fs::write(&dictionary, "# team words\nNightowl\n\n").unwrap();
+        std::fs::write(&file, json!({
+            "min_length": 10,
+            "symbol": true,
+            "history": 2,
+            "expiry": { "admin": 60 },
+            "dictionary_file": dictionary.to_str(),
+        }).to_string()).unwrap();
+
+        let policy = Policy::from_vars(vars(&[
+            ("PASSWORD_POLICY_FILE", file.to_str().unwrap()),
+            ("PASSWORD_MIN_LENGTH", "8"),
+        ]));
+
+        std::fs::remove_file(file).unwrap();
+        std::fs::remove_file(dictionary).unwrap();
+
+        assert_eq!(policy.min_length, 8);
+        assert!(policy.symbol);
+        assert_eq!(policy.history, 2);
+        assert_eq!(policy.expiry, HashMap::from([("ADMIN".to_string(), 60)]));
+        assert_eq!(policy.check("Nightowl-7", "bob", "bob@nightmare.local"), vec!["password is a common word"]);
+        assert_eq!(policy.check("Shadow-7!", "bob", "bob@nightmare.local"), vec!["password is a common word"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "PASSWORD_HISTORY must be a number")]
+    fn invalid_variables_stop_the_startup() {
+        Policy::from_vars(vars(&[("PASSWORD_HISTORY", "five")]));
+    }
+
+    #[actix_web::test]
+    async fn reused_looks_back_as_far_as_the_history() {
+        let db = database().await;
+        let user = alice(&db, &["First-Horse-1", "Second-Horse-2", "Third-Horse-3"]).await;
+        let policy = |history| Policy { history, ..Policy::default() };
+
+        assert!(policy(5).reused(&db, &user, "First-Horse-1").await.unwrap());
+        assert!(policy(5).reused(&db, &user, "Third-Horse-3").await.unwrap());
+        assert!(!policy(5).reused(&db, &user, "Fourth-Horse-4").await.unwrap());
+        assert!(!policy(2).reused(&db, &user, "First-Horse-1").await.unwrap());
+        assert!(policy(2).reused(&db, &user, "Second-Horse-2").await.unwrap());
+        assert!(!policy(0).reused(&db, &user, "Third-Horse-3").await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn created_users_go_through_the_policy() {
+        let db = database().await;
+        let response = services::user::store(&db, &Audit::default(), Scope::Platform, UserStoreRequest {
+            name: "Bob".to_string(),
+            email: "bob@nightmare.local".to_string(),
+            username: "bob".to_string(),
+            password: "letmein".to_string(),
+        }).await;
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body(response).await.contains("password must have upper case letter"));
+    }
+
+    #[actix_web::test]
+    async fn password_updates_go_through_the_policy() {
+        let db = database().await;
+        let user = alice(&db, &["First-Horse-1", "Second-Horse-2"]).await;
+        let update = |new: &str| UserUpdatePasswordRequest {
+            current_password: "Second-Horse-2".to_string(),
+            new_password: new.to_string(),
+            password_confirmation: new.to_string(),
+        };
+
+        let weak = services::user::update_password(&db, &Audit::default(), Scope::Platform, user.id.clone(), update("short")).await;
+
+        assert_eq!(weak.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body(weak).await.contains("min length for password is 6"));
+
+        let reused = services::user::update_password(&db, &Audit::default(), Scope::Platform, user.id.clone(), update("First-Horse-1")).await;
+
+        assert_eq!(reused.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body(reused).await.contains("password was used recently"));
+    }
+
+    #[actix_web::test]
+    async fn provisioned_passwords_go_through_the_policy() {
+        let db = database().await;
+        let user = alice(&db, &["First-Horse-1", "Second-Horse-2"]).await;
+        let auth = Auth {
+            user: user.clone(),
+            permissions: vec![permissions::Model {
+                id: Uuid::new_v4().into(),
+                code: services::scim::PROVISIONING_PERMISSION.to_string(),
+                name: "scim provisioning".to_string(),
+            }],
+            roles: vec![],
+        };
+        let request = |username: &str, password: &str| ScimUserRequest {
+            schemas: vec![],
+            user_name: username.to_string(),
+            name: None,
+            display_name: None,
+            emails: vec![ScimEmail {
+                value: format!("{}@nightmare.local", username),
+                kind: None,
+                primary: Some(true),
+            }],
+            password: Some(password.to_string()),
+            active: Some(true),
+        };
+        let base = "http://localhost/scim/v2".to_string();
+
+        let weak = services::scim::store_user(&db, &auth, base.clone(), request("bob", "letmein")).await;
+
+        assert_eq!(weak.status(), StatusCode::BAD_REQUEST);
+        assert!(body(weak).await.contains("password must have upper case letter"));
+
+        let reused = services::scim::replace_user(&db, &auth, base, user.id.clone(), request("alice", "First-Horse-1")).await;
+
+        assert_eq!(reused.status(), StatusCode::BAD_REQUEST);
+        assert!(body(reused).await.contains("password was used recently"));
+    }
+
+    #[actix_web::test]
+    async fn expired_counts_from_the_last_change() {
+        let db = database().await;
+        let bob = aged(&db, "bob", 40, true).await;
+        let carol = aged(&db, "carol", 40, false).await;
+        let policy = |expiry: &[(&str, i64)]| Policy {
+            expiry: expiry.iter().map(|(code, days)| (code.to_string(), *days)).collect(),
+            ..Policy::default()
+        };
+
+        assert!(policy(&[(EXPIRING, 30)]).expired(&db, &bob, "local").await.unwrap());
+        assert!(!policy(&[(EXPIRING, 50)]).expired(&db, &bob, "local").await.unwrap());
+        assert!(policy(&[(EXPIRING, 50), ("*", 30)]).expired(&db, &bob, "local").await.unwrap());
+        assert!(!policy(&[(EXPIRING, 30)]).expired(&db, &carol, "local").await.unwrap());
+        assert!(!policy(&[]).expired(&db, &bob, "local").await.unwrap());
+
+        dao::password::changed(&db, &bob, 5).await.unwrap();
+
+        assert!(!policy(&[(EXPIRING, 30)]).expired(&db, &bob, "local").await.unwrap());
+    }
+
+    #[actix_web::test]
+    async fn directory_passwords_expire_on_their_own_terms() {
+        let db = database().await;
+        let bob = aged(&db, "bob", 40, true).await;
+        let policy = Policy {
+            expiry: HashMap::from([(EXPIRING.to_string(), 30)]),
+            ..Policy::default()
+        };
+
+        assert!(!policy.expired(&db, &bob, "ldap").await.unwrap());
+        assert_eq!(backend(&db, &bob).await.unwrap(), "local");
+
+        dao::ldap::link(&db, &bob, "uid=bob,ou=people,dc=nightmare,dc=local").await.unwrap();
+
+        let token = dao::auth::generate(&db, &bob, None, None).await.unwrap();
+
+        assert_eq!(backend(&db, &bob).await.unwrap(), "ldap");
+        assert!(services::auth::bearer(&db, base58::to_string(token.id.as_bytes())).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn expired_passwords_refuse_the_tokens_issued_before() {
+        let db = database().await;
+        let bob = aged(&db, "bob", 40, true).await;
+        let id: Id = Uuid::new_v4().into();
+        let hashed = hash::make(id.clone(), "secret".to_string()).to_string();
+        let personal = dao::personal_access_token::store(&db, id, &bob, None, "ci", hashed, vec![], None).await.unwrap();
+        let personal = format!("{}{}_secret", services::personal_access_token::PREFIX, base58::to_string(personal.id.as_bytes()));
+        let session = dao::auth::generate(&db, &bob, None, None).await.unwrap();
+        let session = base58::to_string(session.id.as_bytes());
+        let refusal = Some("Password has expired, change it to continue".to_string());
+
+        assert_eq!(services::auth::bearer(&db, session.clone()).await.err(), refusal);
+        assert_eq!(services::auth::bearer(&db, personal.clone()).await.err(), refusal);
+
+        let changed = change_expired(&db, &Audit::default(), expired("bob", "First-Horse-1", "Second-Horse-2")).await;
+
+        assert_eq!(changed.status(), StatusCode::OK);
+        assert!(services::auth::bearer(&db, session).await.is_ok());
+        assert!(services::auth::bearer(&db, personal).await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn change_expired_lets_the_user_sign_in_again() {
+        let db = database().await;
+
+        aged(&db, "bob", 40, true).await;
+
+        assert!(matches!(
+            services::auth::sign_in(&db, &Audit::default(), "bob", "First-Horse-1").await,
+            Err(Refusal::Expired(_)),
+        ));
+
+        let weak = change_expired(&db, &Audit::default(), expired("bob", "First-Horse-1", "short")).await;
+
+        assert_eq!(weak.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let changed = change_expired(&db, &Audit::default(), expired("bob", "First-Horse-1", "Second-Horse-2")).await;
+
+        assert_eq!(changed.status(), StatusCode::OK);
+        assert!(services::auth::sign_in(&db, &Audit::default(), "bob", "First-Horse-1").await.is_err());
+        assert!(services::auth::sign_in(&db, &Audit::default(), "bob", "Second-Horse-2").await.is_ok());
+    }
+
+    #[actix_web::test]
+    async fn change_expired_answers_like_login_first() {
+        let db = database().await;
+
+        aged(&db, "bob", 40, true).await;
+        aged(&db, "carol", 40, false).await;
+
+        let unknown = change_expired(&db, &Audit::default(), expired("dave", "First-Horse-1", "Second-Horse-2")).await;
+        let wrong = change_expired(&db, &Audit::default(), expired("bob", "Wrong-Horse-1", "Second-Horse-2")).await;
+        let current = change_expired(&db, &Audit::default(), expired("carol", "First-Horse-1", "Second-Horse-2")).await;
+
+        assert_eq!(unknown.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(wrong.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body(unknown).await, body(wrong).await);
+        assert_eq!(current.status(), StatusCode::CONFLICT);
+    }
+}
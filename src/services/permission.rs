@@ -3,18 +3,22 @@ use std::collections::HashMap;
 use actix_web::HttpResponse;
 use nightmare_common::{request::pagination::PaginationRequest, log};
 use nightmare_common::models::{permissions, Id};
-use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
-use serde_json::json;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, DbErr, TransactionTrait};
+use serde_json::{json, Value};
 
-use crate::dao;
+use crate::{dao, events, services};
+use crate::dao::organization::Scope;
+use crate::middleware::audit::Audit;
 use crate::requests::permission::{PermissionOrderByColumn, PermissionStoreRequest, PermissionUpdateRequest};
 use crate::responses::permission::PermissionOAS;
 
 pub async fn paginate(
     db: &DatabaseConnection,
+    scope: Scope,
     request: PaginationRequest<PermissionOrderByColumn>,
 ) -> HttpResponse {
     let mut query = permissions::Entity::find()
+        .filter(dao::organization::visible_permissions(scope))
         .order_by(match request.order(PermissionOrderByColumn::Name) {
             PermissionOrderByColumn::Code => permissions::Column::Code,
             PermissionOrderByColumn::Name => permissions::Column::Name,
@@ -56,8 +60,16 @@ pub async fn paginate(
 
 pub async fn store(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     request: PermissionStoreRequest,
 ) -> HttpResponse {
+    if scope == Scope::Nowhere {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "permissions can only be created within an organization",
+        }))
+    }
+
     let mut validation = HashMap::new();
     let code = request.code.trim().to_uppercase();
     let name = request.name.trim().to_lowercase();
@@ -80,9 +92,33 @@ pub async fn store(
         }))
     }
 
-    match dao::permission::store(db, code, name).await {
+    let stored = async {
+        let txn = db.begin().await?;
+        let permission = dao::permission::store(&txn, code, name).await?;
+
+        let organization_id = scope.organization_id();
+
+        if let Some(organization_id) = &organization_id {
+            dao::organization::own_permission(&txn, organization_id, &permission).await?;
+        }
+
+        let created = json!({
+            "id": permission.id,
+            "code": permission.code,
+            "name": permission.name,
+            "organization_id": organization_id,
+        });
+
+        events::emit(&txn, events::PERMISSION_CREATED, created.clone()).await?;
+        services::audit::record(&txn, audit, events::PERMISSION_CREATED, Some(("permission", &permission.id)), Value::Null, created).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(permission)
+    };
+
+    match stored.await {
         Err(e) => {
-            log::error!(paginate, "{}", e);
+            log::error!(store, "{}", e);
 
             HttpResponse::InternalServerError().json(json!({
                 "message": e.to_string(),
@@ -99,9 +135,10 @@ pub async fn store(
 
 pub async fn show<I: Into<Id>>(
     db: &DatabaseConnection,
+    scope: Scope,
     id: I,
 ) -> HttpResponse {
-    match dao::permission::find(db, id).await {
+    match dao::permission::find_within(db, id, dao::organization::visible_permissions(scope)).await {
         None => HttpResponse::NotFound().finish(),
         Some(permission) => HttpResponse::Ok().json(json!(
             PermissionOAS::from(permission)
@@ -111,9 +148,16 @@ pub async fn show<I: Into<Id>>(
 
 pub async fn update<I: Into<Id>>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
     request: PermissionUpdateRequest,
 ) -> HttpResponse {
+    let permission = match dao::permission::find_within(db, id, dao::organization::owned_permissions(scope)).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(permission) => permission,
+    };
+
     let mut validation = HashMap::new();
     let name = request.name.trim().to_lowercase();
 
@@ -127,7 +171,26 @@ pub async fn update<I: Into<Id>>(
         }))
     }
 
-    match dao::permission::update(db, id, name).await {
+    let updated = async {
+        let txn = db.begin().await?;
+        let before = json!({
+            "name": permission.name,
+        });
+        let permission = dao::permission::update(&txn, permission.id.clone(), name).await?;
+
+        events::emit(&txn, events::PERMISSION_UPDATED, json!({
+            "id": permission.id,
+            "name": permission.name,
+        })).await?;
+        services::audit::record(&txn, audit, events::PERMISSION_UPDATED, Some(("permission", &permission.id)), before, json!({
+            "name": permission.name,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(permission)
+    };
+
+    match updated.await {
         Err(e) => {
             log::error!(update, "{}", e);
 
@@ -146,24 +209,44 @@ pub async fn update<I: Into<Id>>(
 
 pub async fn delete<I: Into<Id> + Clone>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
 ) -> HttpResponse {
-    match dao::permission::find(db, id.clone()).await {
-        None => HttpResponse::NotFound().finish(),
-        Some(permission) => match dao::permission::delete(db, id).await {
-            Err(e) => {
-                log::error!(delete, "{}", e);
+    let permission = match dao::permission::find_within(db, id, dao::organization::owned_permissions(scope)).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(permission) => permission,
+    };
+
+    let deleted = async {
+        let txn = db.begin().await?;
+
+        dao::permission::delete(&txn, permission.id.clone()).await?;
+        events::emit(&txn, events::PERMISSION_DELETED, json!({
+            "id": permission.id,
+            "code": permission.code,
+        })).await?;
+        services::audit::record(&txn, audit, events::PERMISSION_DELETED, Some(("permission", &permission.id)), json!({
+            "code": permission.code,
+            "name": permission.name,
+        }), Value::Null).await?;
+
+        txn.commit().await
+    };
+
+    match deleted.await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
 
-                HttpResponse::InternalServerError().json(json!({
-                    "message": e.to_string(),
-                }))
-            },
-            _ => {
-                HttpResponse::Ok().json(json!({
-                    "id": permission.id,
-                    "message": "Permission has been deleted",
-                }))
-            },
-        }
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": permission.id,
+                "message": "Permission has been deleted",
+            }))
+        },
     }
 }
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::hash::Hash;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{users, Id};
+use sea_orm::{DatabaseConnection, DbErr, TransactionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{dao, events};
+use crate::middleware::tenant::Tenant;
+use crate::models::personal_access_tokens;
+use crate::requests::personal_access_token::PersonalAccessTokenStoreRequest;
+use crate::responses::personal_access_token::{PersonalAccessTokenCreated, PersonalAccessTokenOAS};
+use crate::services;
+
+/// Personal access tokens look like `pat_<id>_<secret>`
+pub const PREFIX: &str = "pat_";
+
+pub async fn all(
+    db: &DatabaseConnection,
+    auth: Auth,
+) -> HttpResponse {
+    match dao::personal_access_token::all(db, &auth.user).await {
+        Err(e) => {
+            log::error!(all, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(tokens) => {
+            HttpResponse::Ok().json(
+                tokens.iter()
+                    .map(PersonalAccessTokenOAS::from)
+                    .collect::<Vec<PersonalAccessTokenOAS>>()
+            )
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: Auth,
+    tenant: Tenant,
+    request: PersonalAccessTokenStoreRequest,
+) -> HttpResponse {
+    if tenant.token.as_ref().is_some_and(|token| token.impersonator_id.is_some()) {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "personal access tokens can't be created while impersonating",
+        }))
+    }
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_string();
+    let mut scopes = request.scopes.iter()
+        .map(|scope| scope.trim().to_uppercase())
+        .collect::<Vec<String>>();
+
+    scopes.sort();
+    scopes.dedup();
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if scopes.is_empty() {
+        validation.insert("scopes", vec!["at least one scope is required"]);
+    } else if scopes.iter().any(|scope| !auth.permissions.iter().any(|permission| permission.code.eq(scope))) {
+        validation.insert("scopes", vec!["scopes must be a subset of your permissions"]);
+    }
+
+    if request.expires_at.is_some_and(|expires_at| expires_at <= time::now()) {
+        validation.insert("expires_at", vec!["expires at must be in the future"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let id: Id = Uuid::new_v4().into();
+    let secret = base58::to_string(Uuid::new_v4().as_bytes());
+    let hashed = hash::make(id.clone(), secret.clone()).to_string();
+
+    let stored = async {
+        let txn = db.begin().await?;
+        let token = dao::personal_access_token::store(&txn, id, &auth.user, tenant.organization_id(), name, hashed, scopes, request.expires_at).await?;
+
+        events::emit(&txn, events::TOKEN_CREATED, json!({
+            "id": token.id,
+            "user_id": token.user_id,
+            "organization_id": token.organization_id,
+            "name": token.name,
+            "expires_at": token.expires_at,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(token)
+    };
+
+    match stored.await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(token) => {
+            HttpResponse::Created().json(PersonalAccessTokenCreated {
+                id: token.id.clone(),
+                token: format!("{}{}_{}", PREFIX, base58::to_string(token.id.as_bytes()), secret),
+                message: "Token has been created, copy it now since it won't be shown again".to_string(),
+            })
+        },
+    }
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: Auth,
+    id: I,
+) -> HttpResponse {
+    let token = match dao::personal_access_token::find(db, id).await {
+        Some(token) if token.user_id.eq(&auth.user.id) => token,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    let revoked = async {
+        let txn = db.begin().await?;
+
+        dao::personal_access_token::delete(&txn, token.id.clone()).await?;
+        events::emit(&txn, events::TOKEN_REVOKED, json!({
+            "id": token.id,
+            "user_id": token.user_id,
+        })).await?;
+
+        txn.commit().await
+    };
+
+    match revoked.await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": token.id,
+                "message": "Token has been revoked",
+            }))
+        },
+    }
+}
+
+pub async fn user_by_token(
+    db: &DatabaseConnection,
+    token: String,
+) -> Result<(users::Model, personal_access_tokens::Model), String> {
+    let (id, secret) = token.strip_prefix(PREFIX)
+        .and_then(|token| token.split_once('_'))
+        .ok_or("Invalid token, malformed personal access token".to_string())?;
+
+    let id = services::auth::decode(id.to_string())?;
+    let token = dao::personal_access_token::find(db, id).await
+        .ok_or("Invalid token, record not found".to_string())?;
+
+    if !hash::verify(Hash::from(token.secret.clone()), token.id.clone(), secret.to_string()) {
+        return Err("Invalid token, record not found".to_string())
+    }
+
+    if token.expires_at.is_some_and(|expires_at| expires_at < time::now()) {
+        return Err("Token has been expired".to_string())
+    }
+
+    match dao::user::find(db, token.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => Ok((user, token)),
+        _ => Err("Invalid token, user not found".to_string()),
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{log, time};
+use nightmare_common::models::{permissions, roles, Id};
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::dao;
+use crate::models::policies;
+use crate::requests::policy::{PolicyCondition, PolicyOperator, PolicyOrderByColumn, PolicyStoreRequest};
+use crate::responses::policy::{ConditionEvaluation, PolicyEvaluation, PolicyOAS};
+
+/// Permission required to manage authorization policies
+pub const MANAGE_PERMISSION: &str = "MANAGE_POLICIES";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage policies",
+    })))
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<PolicyOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = policies::Entity::find()
+        .order_by(match request.order(PolicyOrderByColumn::Name) {
+            PolicyOrderByColumn::Name => policies::Column::Name,
+            PolicyOrderByColumn::Effect => policies::Column::Effect,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(policies::Column::Name.like(request.search()))
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                   .map(|policy| policy.into())
+                   .collect::<Vec<PolicyOAS>>(),
+            }))
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PolicyStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let policy = match validate(db, None, request).await {
+        Err(response) => return response,
+        Ok(policy) => policy,
+    };
+
+    match dao::policy::store(db, policy).await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(policy) => {
+            HttpResponse::Created().json(json!({
+                "id": policy.id,
+                "message": "Policy has been created",
+            }))
+        }
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::policy::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(policy) => HttpResponse::Ok().json(PolicyOAS::from(policy)),
+    }
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: PolicyStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let policy = match dao::policy::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(policy) => policy,
+    };
+
+    let policy = match validate(db, Some(policy), request).await {
+        Err(response) => return response,
+        Ok(policy) => policy,
+    };
+
+    match dao::policy::update(db, &policy).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(policy) => {
+            HttpResponse::Ok().json(json!({
+                "id": policy.id,
+                "message": "Policy has been updated",
+            }))
+        }
+    }
+}
+
+pub async fn delete<I: Into<Id> + Clone>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::policy::find(db, id.clone()).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(policy) => match dao::policy::delete(db, id).await {
+            Err(e) => {
+                log::error!(delete, "{}", e);
+
+                HttpResponse::InternalServerError().json(json!({
+                    "message": e.to_string(),
+                }))
+            },
+            _ => {
+                HttpResponse::Ok().json(json!({
+                    "id": policy.id,
+                    "message": "Policy has been deleted",
+                }))
+            },
+        }
+    }
+}
+
+async fn validate(
+    db: &DatabaseConnection,
+    policy: Option<policies::Model>,
+    request: PolicyStoreRequest,
+) -> Result<policies::Model, HttpResponse> {
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_lowercase();
+    let code = request.permission.trim().to_uppercase();
+    let resource_type = request.resource_type
+        .map(|resource_type| resource_type.trim().to_lowercase())
+        .filter(|resource_type| !resource_type.is_empty());
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    let permission = permissions::Entity::find()
+        .filter(permissions::Column::Code.eq(code.clone()))
+        .one(db)
+        .await
+        .unwrap_or(None);
+
+    if code.is_empty() {
+        validation.insert("permission", vec!["field permission is required"]);
+    } else if permission.is_none() {
+        validation.insert("permission", vec!["permission doesn't exist"]);
+    }
+
+    if let Some(role_id) = request.role_id.clone() {
+        if dao::role::find(db, role_id).await.is_none() {
+            validation.insert("roleId", vec!["role doesn't exist"]);
+        }
+    }
+
+    let mut errors = vec![];
+
+    for condition in &request.conditions {
+        if !attribute(&condition.attribute) {
+            errors.push("attribute must start with subject., resource., action. or environment.");
+        }
+
+        if let Some(reference) = &condition.reference {
+            if !attribute(reference) {
+                errors.push("reference must start with subject., resource., action. or environment.");
+            }
+        } else if condition.value.is_none() && condition.operator != PolicyOperator::Exists {
+            errors.push("condition requires value or reference");
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.dedup();
+        validation.insert("conditions", errors);
+    }
+
+    if !validation.is_empty() {
+        return Err(HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        })))
+    }
+
+    let conditions = serde_json::to_string(&request.conditions).unwrap();
+    let permission_id = permission.unwrap().id;
+
+    Ok(match policy {
+        None => policies::Model {
+            id: Uuid::new_v4().into(),
+            name,
+            effect: request.effect.as_str().to_string(),
+            permission_id,
+            role_id: request.role_id,
+            resource_type,
+            conditions,
+            dry_run: request.dry_run,
+            created_at: time::now(),
+            updated_at: time::now(),
+        },
+        Some(policy) => policies::Model {
+            name,
+            effect: request.effect.as_str().to_string(),
+            permission_id,
+            role_id: request.role_id,
+            resource_type,
+            conditions,
+            dry_run: request.dry_run,
+            ..policy
+        },
+    })
+}
+
+fn attribute(path: &str) -> bool {
+    ["subject.", "resource.", "action.", "environment."].iter()
+        .any(|prefix| path.starts_with(prefix) && path.len() > prefix.len())
+}
+
+/// Whether the policy targets the resource type and role held by the subject
+pub fn applies(
+    policy: &policies::Model,
+    resource_type: Option<&str>,
+    roles: &[roles::Model],
+) -> bool {
+    if let Some(expected) = &policy.resource_type {
+        if resource_type != Some(expected.as_str()) {
+            return false
+        }
+    }
+
+    match &policy.role_id {
+        None => true,
+        Some(role_id) => roles.iter().any(|role| role.id.eq(role_id)),
+    }
+}
+
+pub fn evaluate(
+    policy: &policies::Model,
+    context: &Value,
+) -> PolicyEvaluation {
+    let conditions = serde_json::from_str::<Vec<PolicyCondition>>(&policy.conditions)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|condition| ConditionEvaluation {
+            matched: matches(&condition, context),
+            attribute: condition.attribute,
+            operator: condition.operator,
+        })
+        .collect::<Vec<ConditionEvaluation>>();
+
+    PolicyEvaluation {
+        id: policy.id.clone(),
+        name: policy.name.clone(),
+        effect: policy.effect.clone(),
+        dry_run: policy.dry_run,
+        matched: conditions.iter().all(|condition| condition.matched),
+        conditions,
+    }
+}
+
+fn lookup<'a>(context: &'a Value, path: &str) -> Option<&'a Value> {
+    context.pointer(&format!("/{}", path.replace('.', "/")))
+        .filter(|value| !value.is_null())
+}
+
+fn matches(condition: &PolicyCondition, context: &Value) -> bool {
+    let left = lookup(context, &condition.attribute);
+    let right = match &condition.reference {
+        Some(reference) => lookup(context, reference),
+        None => condition.value.as_ref(),
+    };
+
+    match (condition.operator, left, right) {
+        (PolicyOperator::Exists, left, _) => left.is_some(),
+        (operator, Some(left), Some(right)) => compare(operator, left, right),
+        _ => false,
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left.as_f64(), right.as_f64()) {
+        (Some(left), Some(right)) => left == right,
+        _ => left.eq(right),
+    }
+}
+
+fn order(left: &Value, right: &Value) -> Option<std::cmp::Ordering> {
+    match (left.as_f64(), right.as_f64(), left.as_str(), right.as_str()) {
+        (Some(left), Some(right), _, _) => left.partial_cmp(&right),
+        (_, _, Some(left), Some(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+fn compare(operator: PolicyOperator, left: &Value, right: &Value) -> bool {
+    use std::cmp::Ordering::*;
+
+    match operator {
+        PolicyOperator::Eq => equals(left, right),
+        PolicyOperator::Ne => !equals(left, right),
+        PolicyOperator::Lt => order(left, right) == Some(Less),
+        PolicyOperator::Lte => matches!(order(left, right), Some(Less | Equal)),
+        PolicyOperator::Gt => order(left, right) == Some(Greater),
+        PolicyOperator::Gte => matches!(order(left, right), Some(Greater | Equal)),
+        PolicyOperator::In => right.as_array()
+            .map(|items| items.iter().any(|item| equals(left, item)))
+            .unwrap_or(false),
+        PolicyOperator::NotIn => right.as_array()
+            .map(|items| !items.iter().any(|item| equals(left, item)))
+            .unwrap_or(false),
+        PolicyOperator::Contains => match (left, right) {
+            (Value::Array(items), right) => items.iter().any(|item| equals(item, right)),
+            (Value::String(left), Value::String(right)) => left.contains(right.as_str()),
+            _ => false,
+        },
+        PolicyOperator::StartsWith => match (left.as_str(), right.as_str()) {
+            (Some(left), Some(right)) => left.starts_with(right),
+            _ => false,
+        },
+        PolicyOperator::IpIn => match (left.as_str(), right) {
+            (Some(ip), Value::String(cidr)) => ip_in(ip, cidr),
+            (Some(ip), Value::Array(cidrs)) => cidrs.iter()
+                .filter_map(|cidr| cidr.as_str())
+                .any(|cidr| ip_in(ip, cidr)),
+            _ => false,
+        },
+        PolicyOperator::Exists => true,
+    }
+}
+
+fn ip_in(ip: &str, cidr: &str) -> bool {
+    let (network, prefix) = cidr.split_once('/').unwrap_or((cidr, ""));
+
+    match (ip.parse::<IpAddr>(), network.parse::<IpAddr>()) {
+        (Ok(IpAddr::V4(ip)), Ok(IpAddr::V4(network))) => {
+            let prefix = prefix.parse::<u32>().unwrap_or(32).min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+
+            u32::from(ip) & mask == u32::from(network) & mask
+        },
+        (Ok(IpAddr::V6(ip)), Ok(IpAddr::V6(network))) => {
+            let prefix = prefix.parse::<u32>().unwrap_or(128).min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+
+            u128::from(ip) & mask == u128::from(network) & mask
+        },
+        _ => false,
+    }
+}
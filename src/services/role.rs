@@ -1,21 +1,29 @@
 use std::collections::HashMap;
 
 use actix_web::HttpResponse;
-use nightmare_common::models::{roles, Id};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::models::{roles, users, Id};
 use nightmare_common::log;
 use nightmare_common::request::pagination::PaginationRequest;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
-use serde_json::json;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, DbErr, TransactionTrait};
+use serde_json::{json, Value};
 
-use crate::dao;
-use crate::requests::role::{RoleOrderByColumn, RoleStoreRequest, RoleUpdateRequest};
+use crate::{dao, events, services};
+use crate::dao::organization::Scope;
+use crate::middleware::audit::Audit;
+use crate::requests::role::{RoleOrderByColumn, RoleOwnerBulkRequest, RoleStoreRequest, RoleUpdateRequest};
 use crate::responses::role::RoleOAS;
 
+/// Permission required to pick who approves access requests for a role
+pub const OWNERS_PERMISSION: &str = "MANAGE_ROLE_OWNERS";
+
 pub async fn paginate(
     db: &DatabaseConnection,
+    scope: Scope,
     request: PaginationRequest<RoleOrderByColumn>,
 ) -> HttpResponse {
     let mut query = roles::Entity::find()
+        .filter(dao::organization::visible_roles(scope))
         .order_by(match request.order(RoleOrderByColumn::Name) {
             RoleOrderByColumn::Code => roles::Column::Code,
             RoleOrderByColumn::Name => roles::Column::Name,
@@ -59,8 +67,16 @@ pub async fn paginate(
 
 pub async fn store(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     request: RoleStoreRequest,
 ) -> HttpResponse {
+    if scope == Scope::Nowhere {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "roles can only be created within an organization",
+        }))
+    }
+
     let mut validation = HashMap::new();
     let code = request.code.trim().to_uppercase();
     let name = request.name.trim().to_lowercase();
@@ -83,9 +99,33 @@ pub async fn store(
         }))
     }
 
-    match dao::role::store(db, code, name).await {
+    let stored = async {
+        let txn = db.begin().await?;
+        let role = dao::role::store(&txn, code, name).await?;
+
+        let organization_id = scope.organization_id();
+
+        if let Some(organization_id) = &organization_id {
+            dao::organization::own_role(&txn, organization_id, &role).await?;
+        }
+
+        let created = json!({
+            "id": role.id,
+            "code": role.code,
+            "name": role.name,
+            "organization_id": organization_id,
+        });
+
+        events::emit(&txn, events::ROLE_CREATED, created.clone()).await?;
+        services::audit::record(&txn, audit, events::ROLE_CREATED, Some(("role", &role.id)), Value::Null, created).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(role)
+    };
+
+    match stored.await {
         Err(e) => {
-            log::error!(paginate, "{}", e);
+            log::error!(store, "{}", e);
 
             HttpResponse::InternalServerError().json(json!({
                 "message": e.to_string(),
@@ -102,9 +142,10 @@ pub async fn store(
 
 pub async fn show<I: Into<Id>>(
     db: &DatabaseConnection,
+    scope: Scope,
     id: I,
 ) -> HttpResponse {
-    match dao::role::find(db, id).await {
+    match dao::role::find_within(db, id, dao::organization::visible_roles(scope)).await {
         None => HttpResponse::NotFound().finish(),
         Some(role) => HttpResponse::Ok().json(RoleOAS::from(role)),
     }
@@ -112,9 +153,16 @@ pub async fn show<I: Into<Id>>(
 
 pub async fn update<I: Into<Id>>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
     request: RoleUpdateRequest,
 ) -> HttpResponse {
+    let role = match dao::role::find_within(db, id, dao::organization::owned_roles(scope)).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(role) => role,
+    };
+
     let mut validation = HashMap::new();
     let name = request.name.trim().to_lowercase();
 
@@ -128,7 +176,26 @@ pub async fn update<I: Into<Id>>(
         }))
     }
 
-    match dao::role::update(db, id, name).await {
+    let updated = async {
+        let txn = db.begin().await?;
+        let before = json!({
+            "name": role.name,
+        });
+        let role = dao::role::update(&txn, role.id.clone(), name).await?;
+
+        events::emit(&txn, events::ROLE_UPDATED, json!({
+            "id": role.id,
+            "name": role.name,
+        })).await?;
+        services::audit::record(&txn, audit, events::ROLE_UPDATED, Some(("role", &role.id)), before, json!({
+            "name": role.name,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(role)
+    };
+
+    match updated.await {
         Err(e) => {
             log::error!(update, "{}", e);
 
@@ -147,24 +214,93 @@ pub async fn update<I: Into<Id>>(
 
 pub async fn delete<I: Into<Id> + Clone>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
 ) -> HttpResponse {
-    match dao::role::find(db, id.clone()).await {
-        None => HttpResponse::NotFound().finish(),
-        Some(role) => match dao::role::delete(db, id).await {
-            Err(e) => {
-                log::error!(delete, "{}", e);
-
-                HttpResponse::InternalServerError().json(json!({
-                    "message": e.to_string(),
-                }))
-            },
-            _ => {
-                HttpResponse::Ok().json(json!({
-                    "id": role.id,
-                    "message": "Role has been deleted",
-                }))
-            },
-        }
+    let role = match dao::role::find_within(db, id, dao::organization::owned_roles(scope)).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(role) => role,
+    };
+
+    let deleted = async {
+        let txn = db.begin().await?;
+
+        dao::role::delete(&txn, role.id.clone()).await?;
+        events::emit(&txn, events::ROLE_DELETED, json!({
+            "id": role.id,
+            "code": role.code,
+        })).await?;
+        services::audit::record(&txn, audit, events::ROLE_DELETED, Some(("role", &role.id)), json!({
+            "code": role.code,
+            "name": role.name,
+        }), Value::Null).await?;
+
+        txn.commit().await
+    };
+
+    match deleted.await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": role.id,
+                "message": "Role has been deleted",
+            }))
+        },
     }
 }
+
+pub async fn sync_owners<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    scope: Scope,
+    id: I,
+    request: RoleOwnerBulkRequest,
+) -> HttpResponse {
+    if !auth.permissions.iter().any(|permission| permission.code.eq(OWNERS_PERMISSION)) {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "you are not allowed to manage role owners",
+        }))
+    }
+
+    let role = dao::role::find_within(db, id, dao::organization::owned_roles(scope.clone())).await;
+
+    if role.is_none() {
+        return HttpResponse::NotFound().finish()
+    }
+
+    let role = role.unwrap();
+    let users = users::Entity::find()
+        .filter(users::Column::Id.is_in(request.users))
+        .filter(users::Column::DeletedAt.is_null())
+        .filter(dao::organization::members(scope));
+
+    log::debug!(sync_owners, "{}", users.build(db.get_database_backend()).to_string());
+
+    let users = users.all(db).await;
+
+    if let Err(e) = users {
+        log::error!(sync_owners, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    match dao::role::sync_owners(db, &role, users.unwrap()).await {
+        Err(e) => {
+            log::error!(sync_owners, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => HttpResponse::Ok().finish(),
+    }
+}
\ No newline at end of file
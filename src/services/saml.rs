@@ -0,0 +1,729 @@
+use std::io::Write;
+
+use actix_web::HttpResponse;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+use nightmare_common::{base58, log, time};
+use openssl::x509::X509;
+use roxmltree::{Document, Node};
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::{dao, services};
+use crate::middleware::audit::Audit;
+use crate::models::identity_providers;
+use crate::requests::identity_provider::SamlResponseRequest;
+use crate::services::federation::LOGIN_SECONDS;
+use crate::services::xmldsig::{self, child, children};
+
+pub const PROTOCOL: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+pub const ASSERTION: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+
+const METADATA: &str = "urn:oasis:names:tc:SAML:2.0:metadata";
+const SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+const BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+const HTTP_POST: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+const UNSPECIFIED: &str = "urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified";
+const EMAIL_ADDRESS: &str = "urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress";
+
+/// Tolerated clock difference with the identity provider
+const CLOCK_SKEW_SECONDS: i64 = 120;
+
+/// Attributes looked at when the provider doesn't configure one, friendly
+/// names first then their oid
+const EMAIL_ATTRIBUTES: [&str; 3] = ["email", "mail", "urn:oid:0.9.2342.19200300.100.1.3"];
+const NAME_ATTRIBUTES: [&str; 3] = ["displayName", "name", "urn:oid:2.16.840.1.113730.3.1.241"];
+const USERNAME_ATTRIBUTES: [&str; 3] = ["uid", "username", "urn:oid:0.9.2342.19200300.100.1.1"];
+
+/// What a verified assertion says about the user
+#[derive(Debug, Clone)]
+struct Assertion {
+    subject: String,
+    format: Option<String>,
+    attributes: Map<String, Value>,
+}
+
+fn secret() -> String {
+    base58::to_string(Uuid::new_v4().as_bytes())
+}
+
+fn unauthorized<M: ToString>(message: M) -> HttpResponse {
+    HttpResponse::Unauthorized().json(json!({
+        "message": message.to_string(),
+    }))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn acs_uri(issuer: &str, provider: &identity_providers::Model) -> String {
+    format!("{}/saml/{}/acs", issuer, provider.code)
+}
+
+/// Wrap a bare base64 certificate, as copied out of idp metadata, in PEM
+pub fn pem(certificate: String) -> String {
+    if certificate.contains("-----BEGIN") {
+        return certificate
+    }
+
+    let body = certificate.split_whitespace().collect::<String>();
+    let lines = body.as_bytes()
+        .chunks(64)
+        .map(|line| String::from_utf8_lossy(line).to_string())
+        .collect::<Vec<String>>();
+
+    format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", lines.join("\n"))
+}
+
+pub fn certificate(pem: &str) -> Result<X509, String> {
+    X509::from_pem(pem.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn timestamp(value: &str) -> Result<i64, String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.timestamp())
+        .map_err(|e| format!("invalid timestamp {}: {}", value, e))
+}
+
+/// Text content of a leaf element. Canonicalization drops comments, so
+/// reading only the first text node would turn a signed
+/// `alice@example.com<!---->.evil.com` into `alice@example.com`
+/// (CVE-2017-11427), anything but text inside the element is refused
+fn text(node: Option<Node>) -> Result<Option<String>, String> {
+    let node = match node {
+        None => return Ok(None),
+        Some(node) => node,
+    };
+
+    if node.children().any(|child| !child.is_text()) {
+        return Err(format!("{} must only contain text", node.tag_name().name()))
+    }
+
+    let text = node.children()
+        .filter_map(|child| child.text())
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    Ok(Some(text).filter(|text| !text.is_empty()))
+}
+
+async fn provider<C: ToString>(
+    db: &DatabaseConnection,
+    code: C,
+) -> Option<identity_providers::Model> {
+    dao::identity_provider::find_by_code(db, code).await
+        .filter(|provider| provider.enabled && provider.protocol.eq(dao::identity_provider::SAML))
+}
+
+pub async fn metadata<C: ToString>(
+    db: &DatabaseConnection,
+    issuer: String,
+    code: C,
+) -> HttpResponse {
+    let provider = match provider(db, code).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    let metadata = format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<md:EntityDescriptor xmlns:md=\"{}\" entityID=\"{}\">\n",
+            "  <md:SPSSODescriptor AuthnRequestsSigned=\"false\" WantAssertionsSigned=\"true\" protocolSupportEnumeration=\"{}\">\n",
+            "    <md:NameIDFormat>{}</md:NameIDFormat>\n",
+            "    <md:NameIDFormat>{}</md:NameIDFormat>\n",
+            "    <md:AssertionConsumerService Binding=\"{}\" Location=\"{}\" index=\"0\" isDefault=\"true\"/>\n",
+            "  </md:SPSSODescriptor>\n",
+            "</md:EntityDescriptor>\n",
+        ),
+        METADATA,
+        escape(&provider.client_id),
+        PROTOCOL,
+        UNSPECIFIED,
+        EMAIL_ADDRESS,
+        HTTP_POST,
+        escape(&acs_uri(&issuer, &provider)),
+    );
+
+    HttpResponse::Ok()
+        .content_type("application/samlmetadata+xml")
+        .body(metadata)
+}
+
+/// Send the user to the provider with an AuthnRequest over the redirect
+/// binding, the request id is the nonce of the login in flight
+pub async fn start(
+    db: &DatabaseConnection,
+    issuer: String,
+    provider: identity_providers::Model,
+) -> HttpResponse {
+    let request_id = format!("_{}", secret());
+    let expires_at = time::now() + chrono::Duration::seconds(LOGIN_SECONDS);
+
+    let login = match dao::identity_provider::start(db, &provider, &request_id, "", expires_at).await {
+        Err(e) => {
+            log::error!(services::saml::start, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(login) => login,
+    };
+
+    let sso_url = provider.sso_url.clone().unwrap_or_default();
+    let request = format!(
+        concat!(
+            "<samlp:AuthnRequest xmlns:samlp=\"{}\" xmlns:saml=\"{}\" ID=\"{}\" Version=\"2.0\" IssueInstant=\"{}\" ",
+            "Destination=\"{}\" AssertionConsumerServiceURL=\"{}\" ProtocolBinding=\"{}\">",
+            "<saml:Issuer>{}</saml:Issuer>",
+            "<samlp:NameIDPolicy Format=\"{}\" AllowCreate=\"true\"/>",
+            "</samlp:AuthnRequest>",
+        ),
+        PROTOCOL,
+        ASSERTION,
+        request_id,
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        escape(&sso_url),
+        escape(&acs_uri(&issuer, &provider)),
+        HTTP_POST,
+        escape(&provider.client_id),
+        UNSPECIFIED,
+    );
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    let deflated = encoder.write_all(request.as_bytes()).and_then(|_| encoder.finish());
+
+    match deflated {
+        Err(e) => {
+            log::error!(services::saml::start, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(deflated) => {
+            let request = STANDARD.encode(deflated);
+            let state = base58::to_string(login.id.as_bytes());
+
+            services::oauth::redirect(&sso_url, &[
+                ("SAMLRequest", request.as_str()),
+                ("RelayState", state.as_str()),
+            ])
+        },
+    }
+}
+
+/// Assertion consumer service, HTTP-POST binding. Only answers to our own
+/// requests are accepted, unsolicited responses are rejected
+pub async fn acs<C: ToString>(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    issuer: String,
+    code: C,
+    request: SamlResponseRequest,
+) -> HttpResponse {
+    let provider = match provider(db, code).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(provider) => provider,
+    };
+
+    let login = match request.relay_state.map(services::auth::decode) {
+        Some(Ok(id)) => dao::identity_provider::finish(db, id).await,
+        _ => None,
+    };
+
+    let login = match login {
+        Some(login) if login.provider_id.eq(&provider.id) && login.expires_at > time::now() => login,
+        _ => return unauthorized("Login has been expired, please try again"),
+    };
+
+    let xml = STANDARD.decode(request.saml_response.split_whitespace().collect::<String>())
+        .map_err(|e| e.to_string())
+        .and_then(|xml| String::from_utf8(xml).map_err(|e| e.to_string()));
+
+    let assertion = xml.and_then(|xml| {
+        let certificate = certificate(provider.certificate.as_deref().unwrap_or_default())?;
+
+        verify(&provider, &certificate, &xml, &acs_uri(&issuer, &provider), &login.nonce)
+    });
+
+    let assertion = match assertion {
+        Err(e) => {
+            log::error!(services::saml::acs, "{} {}", provider.code, e);
+
+            return unauthorized(format!("Failed to sign in with {}", provider.name))
+        },
+        Ok(assertion) => assertion,
+    };
+
+    let attribute = |configured: &Option<String>, defaults: &[&str]| {
+        let names = match configured {
+            Some(configured) => vec![configured.as_str()],
+            None => defaults.to_vec(),
+        };
+
+        names.into_iter().find_map(|name| match assertion.attributes.get(name) {
+            Some(Value::String(value)) => Some(value.clone()),
+            Some(Value::Array(values)) => values.first().and_then(Value::as_str).map(String::from),
+            _ => None,
+        })
+    };
+
+    let email = attribute(&provider.email_attribute, &EMAIL_ATTRIBUTES)
+        .or(assertion.format.as_deref()
+            .filter(|format| format.eq(&EMAIL_ADDRESS))
+            .map(|_| assertion.subject.clone()))
+        .map(|email| email.trim().to_lowercase());
+
+    // provisioning reads the same claims as openid connect
+    let mut claims = assertion.attributes.clone();
+
+    if let Some(name) = attribute(&provider.name_attribute, &NAME_ATTRIBUTES) {
+        claims.insert("name".to_string(), json!(name));
+    }
+
+    if let Some(username) = attribute(&provider.username_attribute, &USERNAME_ATTRIBUTES) {
+        claims.insert("preferred_username".to_string(), json!(username));
+    }
+
+    services::federation::sign_in(db, audit, &provider, assertion.subject.clone(), email, &claims).await
+}
+
+/// Check the response is ours, successful and signed by the provider, then
+/// read the single assertion it carries
+fn verify(
+    provider: &identity_providers::Model,
+    certificate: &X509,
+    xml: &str,
+    recipient: &str,
+    request_id: &str,
+) -> Result<Assertion, String> {
+    let document = Document::parse(xml).map_err(|e| e.to_string())?;
+    let response = document.root_element();
+    let now = time::now().timestamp();
+
+    if !response.tag_name().namespace().eq(&Some(PROTOCOL)) || !response.tag_name().name().eq("Response") {
+        return Err("document isn't a saml response".to_string())
+    }
+
+    if response.attribute("Destination").is_some_and(|destination| !destination.eq(recipient)) {
+        return Err("response is meant for another destination".to_string())
+    }
+
+    if !response.attribute("InResponseTo").eq(&Some(request_id)) {
+        return Err("response doesn't answer our request".to_string())
+    }
+
+    let status = child(response, PROTOCOL, "Status")
+        .and_then(|status| child(status, PROTOCOL, "StatusCode"))
+        .and_then(|code| code.attribute("Value"));
+
+    if !status.eq(&Some(SUCCESS)) {
+        return Err(format!("identity provider answered {}", status.unwrap_or("without a status")))
+    }
+
+    if child(response, ASSERTION, "EncryptedAssertion").is_some() {
+        return Err("encrypted assertions aren't supported".to_string())
+    }
+
+    let assertions = children(response, ASSERTION, "Assertion").collect::<Vec<Node>>();
+    let assertion = match assertions.as_slice() {
+        [assertion] => *assertion,
+        _ => return Err("response must carry exactly one assertion".to_string()),
+    };
+
+    // either the whole response or the assertion itself has to be signed,
+    // whichever signature is present has to hold
+    let response_signed = child(response, xmldsig::NAMESPACE, "Signature").is_some();
+    let assertion_signed = child(assertion, xmldsig::NAMESPACE, "Signature").is_some();
+
+    if !response_signed && !assertion_signed {
+        return Err("neither the response nor the assertion is signed".to_string())
+    }
+
+    if response_signed {
+        xmldsig::verify(response, certificate)?;
+    }
+
+    if assertion_signed {
+        xmldsig::verify(assertion, certificate)?;
+    }
+
+    if !text(child(assertion, ASSERTION, "Issuer"))?.eq(&Some(provider.issuer.clone())) {
+        return Err("assertion is issued by another provider".to_string())
+    }
+
+    let conditions = child(assertion, ASSERTION, "Conditions").ok_or("assertion has no conditions")?;
+
+    if let Some(not_before) = conditions.attribute("NotBefore") {
+        if now + CLOCK_SKEW_SECONDS < timestamp(not_before)? {
+            return Err("assertion isn't valid yet".to_string())
+        }
+    }
+
+    if let Some(not_on_or_after) = conditions.attribute("NotOnOrAfter") {
+        if now - CLOCK_SKEW_SECONDS >= timestamp(not_on_or_after)? {
+            return Err("assertion has expired".to_string())
+        }
+    }
+
+    // every audience restriction has to name us
+    let restrictions = children(conditions, ASSERTION, "AudienceRestriction").collect::<Vec<Node>>();
+
+    if restrictions.is_empty() {
+        return Err("assertion is meant for another audience".to_string())
+    }
+
+    for restriction in restrictions {
+        let audiences = children(restriction, ASSERTION, "Audience")
+            .map(|audience| text(Some(audience)))
+            .collect::<Result<Vec<Option<String>>, String>>()?;
+
+        if !audiences.contains(&Some(provider.client_id.clone())) {
+            return Err("assertion is meant for another audience".to_string())
+        }
+    }
+
+    let subject = child(assertion, ASSERTION, "Subject").ok_or("assertion has no subject")?;
+    let name_id = child(subject, ASSERTION, "NameID").ok_or("assertion has no name id")?;
+
+    let confirmed = children(subject, ASSERTION, "SubjectConfirmation")
+        .filter(|confirmation| confirmation.attribute("Method").eq(&Some(BEARER)))
+        .filter_map(|confirmation| child(confirmation, ASSERTION, "SubjectConfirmationData"))
+        .any(|data| {
+            data.attribute("Recipient").eq(&Some(recipient))
+                && data.attribute("InResponseTo").unwrap_or(request_id).eq(request_id)
+                && data.attribute("NotOnOrAfter")
+                    .and_then(|at| timestamp(at).ok())
+                    .is_some_and(|at| now - CLOCK_SKEW_SECONDS < at)
+        });
+
+    if !confirmed {
+        return Err("assertion has no usable bearer confirmation".to_string())
+    }
+
+    let mut attributes = Map::new();
+
+    for statement in children(assertion, ASSERTION, "AttributeStatement") {
+        for attribute in children(statement, ASSERTION, "Attribute") {
+            let values = children(attribute, ASSERTION, "AttributeValue")
+                .map(|value| text(Some(value)))
+                .collect::<Result<Vec<Option<String>>, String>>()?
+                .into_iter()
+                .flatten()
+                .map(Value::String)
+                .collect::<Vec<Value>>();
+
+            let value = match values.as_slice() {
+                [value] => value.clone(),
+                _ => Value::Array(values),
+            };
+
+            for name in [attribute.attribute("Name"), attribute.attribute("FriendlyName")].into_iter().flatten() {
+                attributes.insert(name.to_string(), value.clone());
+            }
+        }
+    }
+
+    Ok(Assertion {
+        subject: text(Some(name_id))?.ok_or("assertion has an empty name id")?,
+        format: name_id.attribute("Format").map(String::from),
+        attributes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::asn1::Asn1Time;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::{PKey, Private};
+    use openssl::rsa::Rsa;
+    use openssl::sign::Signer;
+    use openssl::x509::{X509Builder, X509NameBuilder};
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+
+    const IDP: &str = "https://idp.nightmare.local/saml";
+    const SP: &str = "nightmare";
+    const RECIPIENT: &str = "https://auth.nightmare.local/saml/mock/acs";
+    const REQUEST_ID: &str = "_request";
+
+    /// Identity provider with a self signed certificate of its own
+    struct MockIdp {
+        key: PKey<Private>,
+        certificate: X509,
+    }
+
+    /// What the identity provider puts in the assertion
+    struct Claims {
+        id: &'static str,
+        name_id: &'static str,
+        audience: &'static str,
+        not_on_or_after: i64,
+    }
+
+    impl Default for Claims {
+        fn default() -> Self {
+            Self {
+                id: "_assertion",
+                name_id: "alice@example.com",
+                audience: SP,
+                not_on_or_after: 300,
+            }
+        }
+    }
+
+    fn instant(offset: i64) -> String {
+        (chrono::Utc::now() + chrono::Duration::seconds(offset)).format("%Y-%m-%dT%H:%M:%SZ").to_string()
+    }
+
+    fn signature(id: &str, digest: &str, value: &str) -> String {
+        format!(
+            concat!(
+                "<ds:Signature xmlns:ds=\"http://www.w3.org/2000/09/xmldsig#\"><ds:SignedInfo>",
+                "<ds:CanonicalizationMethod Algorithm=\"http://www.w3.org/2001/10/xml-exc-c14n#\"/>",
+                "<ds:SignatureMethod Algorithm=\"http://www.w3.org/2001/04/xmldsig-more#rsa-sha256\"/>",
+                "<ds:Reference URI=\"#{}\"><ds:Transforms>",
+                "<ds:Transform Algorithm=\"http://www.w3.org/2000/09/xmldsig#enveloped-signature\"/>",
+                "<ds:Transform Algorithm=\"http://www.w3.org/2001/10/xml-exc-c14n#\"/>",
+                "</ds:Transforms><ds:DigestMethod Algorithm=\"http://www.w3.org/2001/04/xmlenc#sha256\"/>",
+                "<ds:DigestValue>{}</ds:DigestValue></ds:Reference></ds:SignedInfo>",
+                "<ds:SignatureValue>{}</ds:SignatureValue></ds:Signature>",
+            ),
+            id, digest, value,
+        )
+    }
+
+    fn response(assertion: &str) -> String {
+        format!(
+            concat!(
+                "<samlp:Response xmlns:samlp=\"{}\" xmlns:saml=\"{}\" ID=\"_response\" Version=\"2.0\" IssueInstant=\"{}\" ",
+                "Destination=\"{}\" InResponseTo=\"{}\">",
+                "<saml:Issuer>{}</saml:Issuer>",
+                "<samlp:Status><samlp:StatusCode Value=\"{}\"/></samlp:Status>",
+                "{}",
+                "</samlp:Response>",
+            ),
+            PROTOCOL, ASSERTION, instant(0), RECIPIENT, REQUEST_ID, IDP, SUCCESS, assertion,
+        )
+    }
+
+    impl MockIdp {
+        fn new() -> Self {
+            let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+            let mut name = X509NameBuilder::new().unwrap();
+
+            name.append_entry_by_text("CN", "idp.nightmare.local").unwrap();
+
+            let name = name.build();
+            let mut builder = X509Builder::new().unwrap();
+
+            builder.set_version(2).unwrap();
+            builder.set_subject_name(&name).unwrap();
+            builder.set_issuer_name(&name).unwrap();
+            builder.set_pubkey(&key).unwrap();
+            builder.set_not_before(&Asn1Time::days_from_now(0).unwrap()).unwrap();
+            builder.set_not_after(&Asn1Time::days_from_now(1).unwrap()).unwrap();
+            builder.sign(&key, MessageDigest::sha256()).unwrap();
+
+            Self { key, certificate: builder.build() }
+        }
+
+        fn assertion(claims: &Claims, signature: &str) -> String {
+            format!(
+                concat!(
+                    "<saml:Assertion xmlns:saml=\"{}\" ID=\"{}\" Version=\"2.0\" IssueInstant=\"{}\">",
+                    "<saml:Issuer>{}</saml:Issuer>",
+                    "{}",
+                    "<saml:Subject><saml:NameID Format=\"{}\">{}</saml:NameID>",
+                    "<saml:SubjectConfirmation Method=\"{}\">",
+                    "<saml:SubjectConfirmationData InResponseTo=\"{}\" Recipient=\"{}\" NotOnOrAfter=\"{}\"/>",
+                    "</saml:SubjectConfirmation></saml:Subject>",
+                    "<saml:Conditions NotBefore=\"{}\" NotOnOrAfter=\"{}\">",
+                    "<saml:AudienceRestriction><saml:Audience>{}</saml:Audience></saml:AudienceRestriction>",
+                    "</saml:Conditions>",
+                    "<saml:AttributeStatement><saml:Attribute Name=\"groups\">",
+                    "<saml:AttributeValue>admins</saml:AttributeValue>",
+                    "</saml:Attribute></saml:AttributeStatement>",
+                    "</saml:Assertion>",
+                ),
+                ASSERTION, claims.id, instant(0), IDP, signature, EMAIL_ADDRESS, claims.name_id,
+                BEARER, REQUEST_ID, RECIPIENT, instant(claims.not_on_or_after),
+                instant(-60), instant(claims.not_on_or_after), claims.audience,
+            )
+        }
+
+        /// Assertion with an enveloped signature over it
+        fn sign(&self, claims: &Claims) -> String {
+            let unsigned = Self::assertion(claims, &signature(claims.id, "", ""));
+            let document = Document::parse(&unsigned).unwrap();
+            let assertion = document.root_element();
+            let digest = STANDARD.encode(Sha256::digest(
+                xmldsig::canonicalize(assertion, child(assertion, xmldsig::NAMESPACE, "Signature"), &[]).as_bytes()
+            ));
+
+            let digested = Self::assertion(claims, &signature(claims.id, &digest, ""));
+            let document = Document::parse(&digested).unwrap();
+            let signed_info = child(document.root_element(), xmldsig::NAMESPACE, "Signature")
+                .and_then(|signature| child(signature, xmldsig::NAMESPACE, "SignedInfo"))
+                .unwrap();
+            let mut signer = Signer::new(MessageDigest::sha256(), &self.key).unwrap();
+
+            signer.update(xmldsig::canonicalize(signed_info, None, &[]).as_bytes()).unwrap();
+
+            let value = STANDARD.encode(signer.sign_to_vec().unwrap());
+
+            Self::assertion(claims, &signature(claims.id, &digest, &value))
+        }
+    }
+
+    fn provider() -> identity_providers::Model {
+        identity_providers::Model {
+            id: Uuid::new_v4().into(),
+            code: "mock".to_string(),
+            name: "Mock".to_string(),
+            issuer: IDP.to_string(),
+            client_id: SP.to_string(),
+            client_secret: None,
+            scopes: String::new(),
+            jit_provisioning: false,
+            enabled: true,
+            protocol: dao::identity_provider::SAML.to_string(),
+            sso_url: Some("https://idp.nightmare.local/saml/sso".to_string()),
+            certificate: None,
+            email_attribute: None,
+            name_attribute: None,
+            username_attribute: None,
+            algorithm: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+        }
+    }
+
+    fn check(idp: &MockIdp, xml: &str) -> Result<Assertion, String> {
+        verify(&provider(), &idp.certificate, xml, RECIPIENT, REQUEST_ID)
+    }
+
+    #[test]
+    fn accepts_a_signed_assertion() {
+        let idp = MockIdp::new();
+        let assertion = check(&idp, &response(&idp.sign(&Claims::default()))).unwrap();
+
+        assert_eq!(assertion.subject, "alice@example.com");
+        assert_eq!(assertion.format.as_deref(), Some(EMAIL_ADDRESS));
+        assert_eq!(assertion.attributes.get("groups"), Some(&json!("admins")));
+    }
+
+    #[test]
+    fn rejects_a_tampered_assertion() {
+        let idp = MockIdp::new();
+        let tampered = idp.sign(&Claims::default()).replace("alice@example.com", "mallory@example.com");
+
+        assert!(check(&idp, &response(&tampered)).is_err());
+    }
+
+    #[test]
+    fn rejects_an_assertion_signed_by_another_key() {
+        let idp = MockIdp::new();
+        let xml = response(&MockIdp::new().sign(&Claims::default()));
+
+        assert!(check(&idp, &xml).is_err());
+    }
+
+    #[test]
+    fn rejects_an_unsigned_assertion() {
+        let idp = MockIdp::new();
+        let xml = response(&MockIdp::assertion(&Claims::default(), ""));
+
+        assert!(check(&idp, &xml).is_err());
+    }
+
+    #[test]
+    fn rejects_comment_injected_into_a_signed_name_id() {
+        let idp = MockIdp::new();
+        let claims = Claims {
+            name_id: "alice@example.com.evil.com",
+            ..Claims::default()
+        };
+        let injected = idp.sign(&claims).replace("alice@example.com.evil.com", "alice@example.com<!---->.evil.com");
+
+        assert!(check(&idp, &response(&injected)).is_err());
+    }
+
+    #[test]
+    fn rejects_a_forged_assertion_wrapped_around_the_signed_one() {
+        let idp = MockIdp::new();
+        let signed = idp.sign(&Claims::default());
+        let original = Document::parse(&signed).unwrap();
+        let copied = &signed[child(original.root_element(), xmldsig::NAMESPACE, "Signature").unwrap().range()];
+
+        // the forged assertion carries the copied signature, the signed one
+        // is tucked away where a naive verifier would still find its id
+        let forged = MockIdp::assertion(&Claims {
+            id: "_forged",
+            name_id: "admin@example.com",
+            ..Claims::default()
+        }, copied);
+        let wrapped = response(&format!("<samlp:Extensions>{}</samlp:Extensions>{}", signed, forged));
+
+        assert!(check(&idp, &wrapped).is_err());
+    }
+
+    #[test]
+    fn rejects_a_forged_assertion_reusing_the_signed_id() {
+        let idp = MockIdp::new();
+        let signed = idp.sign(&Claims::default());
+        let original = Document::parse(&signed).unwrap();
+        let copied = &signed[child(original.root_element(), xmldsig::NAMESPACE, "Signature").unwrap().range()];
+        let forged = MockIdp::assertion(&Claims {
+            name_id: "admin@example.com",
+            ..Claims::default()
+        }, copied);
+        let wrapped = response(&format!("<samlp:Extensions>{}</samlp:Extensions>{}", signed, forged));
+
+        assert!(check(&idp, &wrapped).is_err());
+    }
+
+    #[test]
+    fn rejects_an_assertion_for_another_audience() {
+        let idp = MockIdp::new();
+        let xml = response(&idp.sign(&Claims {
+            audience: "someone-else",
+            ..Claims::default()
+        }));
+
+        assert!(check(&idp, &xml).is_err());
+    }
+
+    #[test]
+    fn rejects_an_expired_assertion() {
+        let idp = MockIdp::new();
+        let xml = response(&idp.sign(&Claims {
+            not_on_or_after: -3600,
+            ..Claims::default()
+        }));
+
+        assert!(check(&idp, &xml).is_err());
+    }
+
+    #[test]
+    fn text_refuses_anything_but_text() {
+        let document = Document::parse("<a><b>one<!---->two</b><c>  three </c><d/></a>").unwrap();
+        let root = document.root_element();
+        let node = |name: &str| root.children().find(|node| node.tag_name().name().eq(name));
+
+        assert!(text(node("b")).is_err());
+        assert_eq!(text(node("c")), Ok(Some("three".to_string())));
+        assert_eq!(text(node("d")), Ok(None));
+    }
+}
@@ -0,0 +1,1249 @@
+use actix_web::HttpResponse;
+use actix_web::http::StatusCode;
+use actix_web::http::header::LOCATION;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::models::{roles, users, Id};
+use sea_orm::{ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select, TransactionTrait};
+use sea_orm::sea_query::SimpleExpr;
+use serde::Serialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{dao, events};
+use crate::requests::scim::{ScimEmail, ScimGroupRequest, ScimListRequest, ScimName, ScimPatchOperation, ScimPatchRequest, ScimUserRequest};
+use crate::responses::scim::{ScimGroupList, ScimGroupOAS, ScimUserList, ScimUserOAS, GROUP_SCHEMA, LIST_SCHEMA, USER_SCHEMA};
+use crate::services;
+
+/// Permission the provisioning client's account must hold
+pub const PROVISIONING_PERMISSION: &str = "SCIM_PROVISIONING";
+
+pub const CONTENT_TYPE: &str = "application/scim+json";
+
+const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+const CONFIG_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:ServiceProviderConfig";
+const RESOURCE_TYPE_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:ResourceType";
+const SCHEMA_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:Schema";
+
+const DEFAULT_COUNT: u64 = 100;
+const MAX_COUNT: u64 = 200;
+
+fn respond<T: Serialize>(status: StatusCode, body: T) -> HttpResponse {
+    HttpResponse::build(status)
+        .content_type(CONTENT_TYPE)
+        .json(body)
+}
+
+fn error<D: ToString>(status: StatusCode, kind: Option<&str>, detail: D) -> HttpResponse {
+    let mut body = json!({
+        "schemas": [ERROR_SCHEMA],
+        "status": status.as_str(),
+        "detail": detail.to_string(),
+    });
+
+    if let Some(kind) = kind {
+        body["scimType"] = json!(kind);
+    }
+
+    respond(status, body)
+}
+
+fn internal<E: ToString>(target: &str, e: E) -> HttpResponse {
+    log::error!(services::scim, "{} {}", target, e.to_string());
+
+    error(StatusCode::INTERNAL_SERVER_ERROR, None, e)
+}
+
+/// Base url resources are located under
+pub fn base() -> String {
+    format!("{}/scim/v2", services::oidc::issuer())
+}
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(PROVISIONING_PERMISSION)) {
+        return None
+    }
+
+    Some(error(StatusCode::FORBIDDEN, None, "you are not allowed to provision users and groups"))
+}
+
+fn secret() -> String {
+    base58::to_string(Uuid::new_v4().as_bytes())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Eq,
+    Ne,
+    Co,
+    Sw,
+    Ew,
+    Pr,
+}
+
+/// A single attribute comparison, the only filter shape provisioning
+/// clients send in practice
+#[derive(Clone, Debug)]
+struct Filter {
+    attribute: String,
+    operator: Operator,
+    value: String,
+}
+
+fn parse(filter: &str) -> Result<Filter, String> {
+    let filter = filter.trim();
+    let (attribute, rest) = filter.split_once(char::is_whitespace)
+        .unwrap_or((filter, ""));
+    let rest = rest.trim_start();
+    let (operator, value) = rest.split_once(char::is_whitespace)
+        .unwrap_or((rest, ""));
+    let value = value.trim();
+
+    let operator = match operator.to_lowercase().as_str() {
+        "eq" => Operator::Eq,
+        "ne" => Operator::Ne,
+        "co" => Operator::Co,
+        "sw" => Operator::Sw,
+        "ew" => Operator::Ew,
+        "pr" => Operator::Pr,
+        operator => return Err(format!("unsupported operator {}", operator)),
+    };
+
+    if operator.eq(&Operator::Pr) {
+        if !value.is_empty() {
+            return Err("presence filter takes no value".to_string())
+        }
+
+        return Ok(Filter { attribute: attribute.to_lowercase(), operator, value: String::new() })
+    }
+
+    let value = value.strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or("filter value must be a quoted string".to_string())?
+        .replace("\\\"", "\"")
+        .replace("\\\\", "\\");
+
+    Ok(Filter { attribute: attribute.to_lowercase(), operator, value })
+}
+
+fn compare<C: ColumnTrait>(column: C, filter: &Filter) -> SimpleExpr {
+    match filter.operator {
+        Operator::Eq => column.eq(filter.value.as_str()),
+        Operator::Ne => column.ne(filter.value.as_str()),
+        Operator::Co => column.contains(filter.value.as_str()),
+        Operator::Sw => column.starts_with(filter.value.as_str()),
+        Operator::Ew => column.ends_with(filter.value.as_str()),
+        Operator::Pr => column.is_not_null(),
+    }
+}
+
+/// Ids only support equality, an id that isn't a uuid matches nothing
+fn compare_id<C: ColumnTrait>(column: C, filter: &Filter) -> Result<SimpleExpr, String> {
+    let ids = Uuid::parse_str(&filter.value).map(|id| vec![Id::from(id)]).unwrap_or_default();
+
+    match filter.operator {
+        Operator::Eq => Ok(column.is_in(ids)),
+        Operator::Ne => Ok(column.is_not_in(ids)),
+        Operator::Pr => Ok(column.is_not_null()),
+        _ => Err("id only supports eq and ne".to_string()),
+    }
+}
+
+fn user_filter(filter: &str) -> Result<SimpleExpr, String> {
+    let mut filter = parse(filter)?;
+
+    match filter.attribute.as_str() {
+        // usernames and emails are stored lowercase
+        "username" => {
+            filter.value = filter.value.to_lowercase();
+
+            Ok(compare(users::Column::Username, &filter))
+        },
+        "emails" | "emails.value" | "emails[type eq \"work\"].value" => {
+            filter.value = filter.value.to_lowercase();
+
+            Ok(compare(users::Column::Email, &filter))
+        },
+        "displayname" | "name.formatted" => Ok(compare(users::Column::Name, &filter)),
+        "id" => compare_id(users::Column::Id, &filter),
+        attribute => Err(format!("unsupported filter attribute {}", attribute)),
+    }
+}
+
+fn group_filter(filter: &str) -> Result<SimpleExpr, String> {
+    let filter = parse(filter)?;
+
+    match filter.attribute.as_str() {
+        "displayname" => Ok(compare(roles::Column::Name, &filter)),
+        "id" => compare_id(roles::Column::Id, &filter),
+        attribute => Err(format!("unsupported filter attribute {}", attribute)),
+    }
+}
+
+/// One based start index and page size clamped to what's advertised
+fn window(request: &ScimListRequest) -> (u64, u64) {
+    let start_index = request.start_index.unwrap_or(1).max(1);
+    let count = request.count.unwrap_or(DEFAULT_COUNT).min(MAX_COUNT);
+
+    (start_index, count)
+}
+
+async fn page<E: EntityTrait>(
+    db: &DatabaseConnection,
+    query: Select<E>,
+    start_index: u64,
+    count: u64,
+) -> Result<(u64, Vec<E::Model>), DbErr>
+where
+    E::Model: Send + Sync,
+{
+    let total = query.clone().count(db).await?;
+
+    if count == 0 {
+        return Ok((total, vec![]))
+    }
+
+    let models = query.offset(start_index - 1)
+        .limit(count)
+        .all(db)
+        .await?;
+
+    Ok((total, models))
+}
+
+/// Attributes a provisioning client manages on a user
+#[derive(Clone, Debug)]
+struct Draft {
+    username: String,
+    name: String,
+    email: String,
+    password: Option<String>,
+    active: bool,
+}
+
+impl Draft {
+    fn from_request(request: &ScimUserRequest) -> Self {
+        let username = request.user_name.trim().to_lowercase();
+
+        Self {
+            name: request.display_name.clone()
+                .or_else(|| request.name.as_ref().and_then(formatted))
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or(username.clone()),
+            email: primary(&request.emails).unwrap_or_default(),
+            password: request.password.clone().filter(|password| !password.is_empty()),
+            active: request.active.unwrap_or(true),
+            username,
+        }
+    }
+
+    fn from_user(user: &users::Model) -> Self {
+        Self {
+            username: user.username.clone(),
+            name: user.name.clone(),
+            email: user.email.clone(),
+            password: None,
+            active: user.deleted_at.is_none(),
+        }
+    }
+
+    /// Apply a patch value at the path, a missing path means the value is
+    /// an object of attributes
+    fn patch(&mut self, path: Option<&str>, value: &Value) -> Result<(), HttpResponse> {
+        let path = match path {
+            None => {
+                let attributes = value.as_object()
+                    .ok_or_else(|| error(StatusCode::BAD_REQUEST, Some("invalidValue"), "value must be an object when path is omitted"))?;
+
+                for (path, value) in attributes {
+                    self.patch(Some(path), value)?;
+                }
+
+                return Ok(())
+            },
+            Some(path) => path.trim().to_lowercase(),
+        };
+
+        let path = path.strip_prefix(&format!("{}:", USER_SCHEMA.to_lowercase()))
+            .unwrap_or(&path);
+
+        match path {
+            "active" => self.active = boolean(value)?,
+            "username" => self.username = string(value)?.trim().to_lowercase(),
+            "displayname" | "name.formatted" => self.name = string(value)?.trim().to_string(),
+            "password" => self.password = Some(string(value)?),
+            "name" => {
+                let name = serde_json::from_value::<ScimName>(value.clone())
+                    .map_err(|e| error(StatusCode::BAD_REQUEST, Some("invalidValue"), e))?;
+
+                if let Some(name) = formatted(&name) {
+                    self.name = name;
+                }
+            },
+            "emails" => {
+                let emails = serde_json::from_value::<Vec<ScimEmail>>(value.clone())
+                    .map_err(|e| error(StatusCode::BAD_REQUEST, Some("invalidValue"), e))?;
+
+                if let Some(email) = primary(&emails) {
+                    self.email = email;
+                }
+            },
+            path if path.starts_with("emails") && path.ends_with("value") => {
+                self.email = string(value)?.trim().to_lowercase();
+            },
+            // read only or unsupported attributes are ignored like the
+            // rest of a replaced resource
+            "id" | "schemas" | "meta" | "groups" | "externalid" => {},
+            path => return Err(error(StatusCode::BAD_REQUEST, Some("invalidPath"), format!("unsupported path {}", path))),
+        }
+
+        Ok(())
+    }
+}
+
+fn formatted(name: &ScimName) -> Option<String> {
+    name.formatted.clone()
+        .or_else(|| {
+            let parts = [name.given_name.clone(), name.family_name.clone()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>();
+
+            Some(parts.join(" ")).filter(|name| !name.is_empty())
+        })
+}
+
+fn primary(emails: &[ScimEmail]) -> Option<String> {
+    emails.iter()
+        .find(|email| email.primary.unwrap_or(false))
+        .or(emails.first())
+        .map(|email| email.value.trim().to_lowercase())
+}
+
+fn string(value: &Value) -> Result<String, HttpResponse> {
+    value.as_str()
+        .map(str::to_string)
+        .ok_or_else(|| error(StatusCode::BAD_REQUEST, Some("invalidValue"), "value must be a string"))
+}
+
+/// Some clients send booleans as strings
+fn boolean(value: &Value) -> Result<bool, HttpResponse> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        Value::String(value) if value.eq_ignore_ascii_case("true") => Ok(true),
+        Value::String(value) if value.eq_ignore_ascii_case("false") => Ok(false),
+        _ => Err(error(StatusCode::BAD_REQUEST, Some("invalidValue"), "value must be a boolean")),
+    }
+}
+
+async fn validate(
+    db: &DatabaseConnection,
+    draft: &Draft,
+    except: Option<&Id>,
+) -> Result<(), HttpResponse> {
+    if draft.username.is_empty() {
+        return Err(error(StatusCode::BAD_REQUEST, Some("invalidValue"), "userName is required"))
+    }
+
+    if !draft.email.contains('@') {
+        return Err(error(StatusCode::BAD_REQUEST, Some("invalidValue"), "a valid email is required"))
+    }
+
+    if let Some(password) = &draft.password {
+        let refused = services::password::policy().check(password, &draft.username, &draft.email);
+
+        if !refused.is_empty() {
+            return Err(error(StatusCode::BAD_REQUEST, Some("invalidValue"), refused.join(", ")))
+        }
+
+        // scim has no place for warnings in its responses
+        for warning in services::password::policy().warnings(password) {
+            log::info!(services::scim::validate, "{} {}", draft.username, warning);
+        }
+    }
+
+    let (username, email) = match except {
+        None => (
+            dao::user::username_exist(db, &draft.username).await,
+            dao::user::email_exist(db, &draft.email).await,
+        ),
+        Some(id) => (
+            dao::user::username_exist_except(db, id, &draft.username).await,
+            dao::user::email_exist_except(db, id, &draft.email).await,
+        ),
+    };
+
+    if username {
+        return Err(error(StatusCode::CONFLICT, Some("uniqueness"), "userName already used"))
+    }
+
+    if email {
+        return Err(error(StatusCode::CONFLICT, Some("uniqueness"), "email already used"))
+    }
+
+    Ok(())
+}
+
+async fn user_resource(
+    db: &DatabaseConnection,
+    user: &users::Model,
+    base: &str,
+) -> Result<ScimUserOAS, DbErr> {
+    let roles = dao::role::assigned(db, user).await?;
+
+    Ok(ScimUserOAS::new(user, &roles, base))
+}
+
+pub async fn users(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    request: ScimListRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = users::Entity::find()
+        .order_by_asc(users::Column::CreatedAt);
+
+    if let Some(filter) = request.filter.as_ref().filter(|filter| !filter.trim().is_empty()) {
+        match user_filter(filter) {
+            Err(e) => return error(StatusCode::BAD_REQUEST, Some("invalidFilter"), e),
+            Ok(condition) => query = query.filter(condition),
+        }
+    }
+
+    let (start_index, count) = window(&request);
+    let (total, users) = match page(db, query, start_index, count).await {
+        Err(e) => return internal("users", e),
+        Ok(page) => page,
+    };
+
+    let mut resources = vec![];
+
+    for user in &users {
+        match user_resource(db, user, &base).await {
+            Err(e) => return internal("users", e),
+            Ok(resource) => resources.push(resource),
+        }
+    }
+
+    respond(StatusCode::OK, ScimUserList {
+        schemas: vec![LIST_SCHEMA.to_string()],
+        total_results: total,
+        start_index,
+        items_per_page: resources.len() as u64,
+        resources,
+    })
+}
+
+pub async fn user<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::user::find(db, id).await {
+        None => error(StatusCode::NOT_FOUND, None, "user not found"),
+        Some(user) => match user_resource(db, &user, &base).await {
+            Err(e) => internal("user", e),
+            Ok(resource) => respond(StatusCode::OK, resource),
+        },
+    }
+}
+
+pub async fn store_user(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    request: ScimUserRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let draft = Draft::from_request(&request);
+
+    if let Err(response) = validate(db, &draft, None).await {
+        return response
+    }
+
+    let id = Uuid::new_v4();
+    let user = async {
+        let txn = db.begin().await?;
+        let user = dao::user::store(&txn, users::Model {
+            id: id.into(),
+            name: draft.name,
+            email: draft.email,
+            username: draft.username,
+            // the identity provider owns the address
+            email_verified_at: Some(time::now()),
+            password: hash::make(id, draft.password.unwrap_or_else(secret)).to_string(),
+            profile_photo_id: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+            deleted_at: (!draft.active).then(time::now),
+        }).await?;
+
+        dao::password::changed(&txn, &user, services::password::policy().history).await?;
+
+        events::emit(&txn, events::USER_CREATED, json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "username": user.username,
+            "provisioned": true,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(user)
+    };
+
+    match user.await {
+        Err(e) => internal("store_user", e),
+        Ok(user) => {
+            log::debug!(services::scim::store_user, "provisioned {}", user.id);
+
+            let resource = ScimUserOAS::new(&user, &[], &base);
+
+            HttpResponse::Created()
+                .content_type(CONTENT_TYPE)
+                .insert_header((LOCATION, resource.meta.location.clone()))
+                .json(resource)
+        },
+    }
+}
+
+/// Persist the draft onto the user, deactivation goes through the regular
+/// soft delete and activation restores the user
+async fn save(
+    db: &DatabaseConnection,
+    base: String,
+    mut user: users::Model,
+    draft: Draft,
+) -> HttpResponse {
+    if let Err(response) = validate(db, &draft, Some(&user.id)).await {
+        return response
+    }
+
+    let deactivated = user.deleted_at.is_none() && !draft.active;
+
+    user.username = draft.username;
+    user.name = draft.name;
+    user.email = draft.email;
+
+    let password_changed = draft.password.is_some();
+
+    if let Some(password) = draft.password {
+        match services::password::policy().reused(db, &user, &password).await {
+            Err(e) => return internal("save", e),
+            Ok(true) => return error(StatusCode::BAD_REQUEST, Some("invalidValue"), "password was used recently, choose another one"),
+            Ok(false) => {},
+        }
+
+        user.password = hash::make(user.id.clone(), password).to_string();
+    }
+
+    if draft.active {
+        user.deleted_at = None;
+    }
+
+    let saved = async {
+        let txn = db.begin().await?;
+        let mut user = dao::user::update(&txn, &user).await?;
+
+        if password_changed {
+            dao::password::changed(&txn, &user, services::password::policy().history).await?;
+        }
+
+        events::emit(&txn, events::USER_UPDATED, json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "username": user.username,
+            "profile_photo_id": user.profile_photo_id,
+        })).await?;
+
+        if deactivated {
+            user = dao::user::delete(&txn, &user).await?;
+
+            events::emit(&txn, events::USER_DELETED, json!({
+                "id": user.id,
+                "deleted_at": user.deleted_at,
+            })).await?;
+        }
+
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(user)
+    };
+
+    let user = match saved.await {
+        Err(e) => return internal("save", e),
+        Ok(user) => user,
+    };
+
+    if deactivated {
+        log::debug!(services::scim::save, "deactivated {}", user.id);
+    }
+
+    match user_resource(db, &user, &base).await {
+        Err(e) => internal("save", e),
+        Ok(resource) => respond(StatusCode::OK, resource),
+    }
+}
+
+pub async fn replace_user<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    id: I,
+    request: ScimUserRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::user::find(db, id).await {
+        None => error(StatusCode::NOT_FOUND, None, "user not found"),
+        Some(user) => save(db, base, user, Draft::from_request(&request)).await,
+    }
+}
+
+pub async fn patch_user<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    id: I,
+    request: ScimPatchRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let user = match dao::user::find(db, id).await {
+        None => return error(StatusCode::NOT_FOUND, None, "user not found"),
+        Some(user) => user,
+    };
+
+    let mut draft = Draft::from_user(&user);
+
+    for operation in &request.operations {
+        let value = operation.value.clone().unwrap_or(Value::Null);
+
+        let patched = match operation.op.to_lowercase().as_str() {
+            "add" | "replace" => draft.patch(operation.path.as_deref(), &value),
+            "remove" => Err(error(StatusCode::BAD_REQUEST, Some("mutability"), "user attributes can't be removed")),
+            op => Err(error(StatusCode::BAD_REQUEST, Some("invalidSyntax"), format!("unsupported operation {}", op))),
+        };
+
+        if let Err(response) = patched {
+            return response
+        }
+    }
+
+    save(db, base, user, draft).await
+}
+
+pub async fn delete_user<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let user = match dao::user::find(db, id).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return error(StatusCode::NOT_FOUND, None, "user not found"),
+    };
+
+    let deleted = async {
+        let txn = db.begin().await?;
+        let user = dao::user::delete(&txn, &user).await?;
+
+        events::emit(&txn, events::USER_DELETED, json!({
+            "id": user.id,
+            "deleted_at": user.deleted_at,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(user)
+    };
+
+    match deleted.await {
+        Err(e) => internal("delete_user", e),
+        Ok(user) => {
+            log::debug!(services::scim::delete_user, "user.deleted {}", user.id);
+
+            HttpResponse::NoContent().finish()
+        },
+    }
+}
+
+/// Role code derived from the display name, `Site Admins` becomes
+/// `SITE_ADMINS`
+fn code(display_name: &str) -> String {
+    display_name.to_uppercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("_")
+}
+
+async fn group_resource(
+    db: &DatabaseConnection,
+    role: &roles::Model,
+    base: &str,
+) -> Result<ScimGroupOAS, DbErr> {
+    let members = dao::role::members(db, role).await?;
+
+    Ok(ScimGroupOAS::new(role, &members, base))
+}
+
+/// Every member must reference an existing user
+async fn verify_members(
+    db: &DatabaseConnection,
+    values: Vec<Id>,
+) -> Result<Vec<Id>, HttpResponse> {
+    for value in &values {
+        if dao::user::find(db, value.clone()).await.is_none() {
+            return Err(error(StatusCode::BAD_REQUEST, Some("invalidValue"), format!("member {} doesn't exist", value)))
+        }
+    }
+
+    Ok(values)
+}
+
+/// Make the members of the role match exactly
+async fn sync_members<D: ConnectionTrait>(
+    db: &D,
+    role: &roles::Model,
+    members: Vec<Id>,
+) -> Result<(), DbErr> {
+    let current = dao::role::members(db, role).await?
+        .into_iter()
+        .map(|user| user.id)
+        .collect::<Vec<Id>>();
+
+    let detached = current.iter()
+        .filter(|user| !members.contains(user))
+        .cloned()
+        .collect::<Vec<Id>>();
+
+    if !detached.is_empty() {
+        dao::role::detach(db, role, detached).await?;
+    }
+
+    dao::role::attach(db, role, members).await
+}
+
+/// Rename the role when the display name changed and sync its members in
+/// one transaction with the update event
+async fn save_group(
+    db: &DatabaseConnection,
+    mut role: roles::Model,
+    name: String,
+    members: Vec<Id>,
+) -> Result<roles::Model, DbErr> {
+    let txn = db.begin().await?;
+
+    if !role.name.eq(&name) {
+        role = dao::role::update(&txn, role.id.clone(), name).await?;
+    }
+
+    sync_members(&txn, &role, members.clone()).await?;
+
+    events::emit(&txn, events::ROLE_UPDATED, json!({
+        "id": role.id,
+        "name": role.name,
+        "members": members,
+    })).await?;
+    txn.commit().await?;
+
+    Ok(role)
+}
+
+pub async fn groups(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    request: ScimListRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = roles::Entity::find()
+        .order_by_asc(roles::Column::Code);
+
+    if let Some(filter) = request.filter.as_ref().filter(|filter| !filter.trim().is_empty()) {
+        match group_filter(filter) {
+            Err(e) => return error(StatusCode::BAD_REQUEST, Some("invalidFilter"), e),
+            Ok(condition) => query = query.filter(condition),
+        }
+    }
+
+    let (start_index, count) = window(&request);
+    let (total, roles) = match page(db, query, start_index, count).await {
+        Err(e) => return internal("groups", e),
+        Ok(page) => page,
+    };
+
+    let mut resources = vec![];
+
+    for role in &roles {
+        match group_resource(db, role, &base).await {
+            Err(e) => return internal("groups", e),
+            Ok(resource) => resources.push(resource),
+        }
+    }
+
+    respond(StatusCode::OK, ScimGroupList {
+        schemas: vec![LIST_SCHEMA.to_string()],
+        total_results: total,
+        start_index,
+        items_per_page: resources.len() as u64,
+        resources,
+    })
+}
+
+pub async fn group<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::role::find(db, id).await {
+        None => error(StatusCode::NOT_FOUND, None, "group not found"),
+        Some(role) => match group_resource(db, &role, &base).await {
+            Err(e) => internal("group", e),
+            Ok(resource) => respond(StatusCode::OK, resource),
+        },
+    }
+}
+
+pub async fn store_group(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    request: ScimGroupRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let name = request.display_name.trim().to_string();
+    let code = code(&name);
+
+    if code.is_empty() {
+        return error(StatusCode::BAD_REQUEST, Some("invalidValue"), "displayName is required")
+    }
+
+    if dao::role::exist(db, &code).await {
+        return error(StatusCode::CONFLICT, Some("uniqueness"), format!("role {} already exists", code))
+    }
+
+    let members = match verify_members(db, request.members.into_iter().map(|member| member.value).collect()).await {
+        Err(response) => return response,
+        Ok(members) => members,
+    };
+
+    let stored = async {
+        let txn = db.begin().await?;
+        let role = dao::role::store(&txn, code, name).await?;
+
+        dao::role::attach(&txn, &role, members.clone()).await?;
+        events::emit(&txn, events::ROLE_CREATED, json!({
+            "id": role.id,
+            "code": role.code,
+            "name": role.name,
+            "members": members,
+        })).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(role)
+    };
+
+    let role = match stored.await {
+        Err(e) => return internal("store_group", e),
+        Ok(role) => role,
+    };
+
+    log::debug!(services::scim::store_group, "provisioned {}", role.id);
+
+    match group_resource(db, &role, &base).await {
+        Err(e) => internal("store_group", e),
+        Ok(resource) => HttpResponse::Created()
+            .content_type(CONTENT_TYPE)
+            .insert_header((LOCATION, resource.meta.location.clone()))
+            .json(resource),
+    }
+}
+
+pub async fn replace_group<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    id: I,
+    request: ScimGroupRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let role = match dao::role::find(db, id).await {
+        None => return error(StatusCode::NOT_FOUND, None, "group not found"),
+        Some(role) => role,
+    };
+
+    let name = request.display_name.trim().to_string();
+
+    if name.is_empty() {
+        return error(StatusCode::BAD_REQUEST, Some("invalidValue"), "displayName is required")
+    }
+
+    let members = match verify_members(db, request.members.into_iter().map(|member| member.value).collect()).await {
+        Err(response) => return response,
+        Ok(members) => members,
+    };
+
+    let role = match save_group(db, role, name, members).await {
+        Err(e) => return internal("replace_group", e),
+        Ok(role) => role,
+    };
+
+    match group_resource(db, &role, &base).await {
+        Err(e) => internal("replace_group", e),
+        Ok(resource) => respond(StatusCode::OK, resource),
+    }
+}
+
+/// Member ids in a patch value, either a list of members or a single one
+fn member_values(value: &Value) -> Result<Vec<Id>, HttpResponse> {
+    let values = match value {
+        Value::Array(values) => values.clone(),
+        Value::Null => vec![],
+        value => vec![value.clone()],
+    };
+
+    values.into_iter()
+        .map(|value| value.get("value")
+            .and_then(Value::as_str)
+            .and_then(|value| Uuid::parse_str(value).ok())
+            .map(Id::from)
+            .ok_or_else(|| error(StatusCode::BAD_REQUEST, Some("invalidValue"), "member value must be a user id")))
+        .collect()
+}
+
+/// Member id selected by a `members[value eq "..."]` path
+fn member_path(path: &str) -> Result<Id, HttpResponse> {
+    let filter = path.get(..8)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("members["))
+        .and_then(|_| path[8..].strip_suffix(']'))
+        .ok_or_else(|| error(StatusCode::BAD_REQUEST, Some("invalidPath"), format!("unsupported path {}", path)))?;
+
+    let filter = parse(filter)
+        .ok()
+        .filter(|filter| filter.attribute.eq("value") && filter.operator.eq(&Operator::Eq))
+        .ok_or_else(|| error(StatusCode::BAD_REQUEST, Some("invalidFilter"), "members can only be selected by value eq"))?;
+
+    Uuid::parse_str(&filter.value)
+        .map(Id::from)
+        .map_err(|_| error(StatusCode::BAD_REQUEST, Some("noTarget"), "member value must be a user id"))
+}
+
+/// Apply a group patch operation onto the name and member list
+fn patch_group_operation(
+    operation: &ScimPatchOperation,
+    name: &mut String,
+    members: &mut Vec<Id>,
+) -> Result<(), HttpResponse> {
+    let op = operation.op.to_lowercase();
+    let value = operation.value.clone().unwrap_or(Value::Null);
+    let path = operation.path.as_ref().map(|path| path.trim().to_string());
+    let lowered = path.as_ref().map(|path| path.to_lowercase());
+
+    match (op.as_str(), lowered.as_deref()) {
+        ("add" | "replace", None) => {
+            let attributes = value.as_object()
+                .ok_or_else(|| error(StatusCode::BAD_REQUEST, Some("invalidValue"), "value must be an object when path is omitted"))?;
+
+            for (attribute, value) in attributes {
+                patch_group_operation(&ScimPatchOperation {
+                    op: op.clone(),
+                    path: Some(attribute.clone()),
+                    value: Some(value.clone()),
+                }, name, members)?;
+            }
+        },
+        ("add" | "replace", Some("displayname")) => {
+            *name = string(&value)?.trim().to_string();
+        },
+        ("add", Some("members")) => {
+            for member in member_values(&value)? {
+                if !members.contains(&member) {
+                    members.push(member);
+                }
+            }
+        },
+        ("replace", Some("members")) => *members = member_values(&value)?,
+        ("remove", Some("members")) => match member_values(&value)? {
+            // no value removes every member
+            removed if removed.is_empty() => members.clear(),
+            removed => members.retain(|member| !removed.contains(member)),
+        },
+        ("remove", Some(lowered)) if lowered.starts_with("members[") => {
+            let selected = member_path(path.as_deref().unwrap_or_default())?;
+
+            members.retain(|member| member.ne(&selected));
+        },
+        ("add" | "replace" | "remove", Some("id" | "schemas" | "meta" | "externalid")) => {},
+        ("add" | "replace" | "remove", Some(path)) => {
+            return Err(error(StatusCode::BAD_REQUEST, Some("invalidPath"), format!("unsupported path {}", path)))
+        },
+        (op, _) => {
+            return Err(error(StatusCode::BAD_REQUEST, Some("invalidSyntax"), format!("unsupported operation {}", op)))
+        },
+    }
+
+    Ok(())
+}
+
+pub async fn patch_group<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    base: String,
+    id: I,
+    request: ScimPatchRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let role = match dao::role::find(db, id).await {
+        None => return error(StatusCode::NOT_FOUND, None, "group not found"),
+        Some(role) => role,
+    };
+
+    let current = match dao::role::members(db, &role).await {
+        Err(e) => return internal("patch_group", e),
+        Ok(members) => members.into_iter().map(|user| user.id).collect::<Vec<Id>>(),
+    };
+
+    let mut name = role.name.clone();
+    let mut patched = current.clone();
+
+    for operation in &request.operations {
+        if let Err(response) = patch_group_operation(operation, &mut name, &mut patched) {
+            return response
+        }
+    }
+
+    if name.is_empty() {
+        return error(StatusCode::BAD_REQUEST, Some("invalidValue"), "displayName is required")
+    }
+
+    let added = patched.iter()
+        .filter(|member| !current.contains(member))
+        .cloned()
+        .collect::<Vec<Id>>();
+
+    if let Err(response) = verify_members(db, added).await {
+        return response
+    }
+
+    let role = match save_group(db, role, name, patched).await {
+        Err(e) => return internal("patch_group", e),
+        Ok(role) => role,
+    };
+
+    match group_resource(db, &role, &base).await {
+        Err(e) => internal("patch_group", e),
+        Ok(resource) => respond(StatusCode::OK, resource),
+    }
+}
+
+pub async fn delete_group<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let role = match dao::role::find(db, id).await {
+        None => return error(StatusCode::NOT_FOUND, None, "group not found"),
+        Some(role) => role,
+    };
+
+    let deleted = async {
+        let txn = db.begin().await?;
+
+        dao::role::delete(&txn, role.id.clone()).await?;
+        events::emit(&txn, events::ROLE_DELETED, json!({
+            "id": role.id,
+            "code": role.code,
+        })).await?;
+
+        txn.commit().await
+    };
+
+    match deleted.await {
+        Err(e) => internal("delete_group", e),
+        Ok(_) => {
+            log::debug!(services::scim::delete_group, "role.deleted {}", role.id);
+
+            HttpResponse::NoContent().finish()
+        },
+    }
+}
+
+pub fn service_provider_config(base: String) -> HttpResponse {
+    respond(StatusCode::OK, json!({
+        "schemas": [CONFIG_SCHEMA],
+        "documentationUri": format!("{}/ServiceProviderConfig", base),
+        "patch": { "supported": true },
+        "bulk": { "supported": false, "maxOperations": 0, "maxPayloadSize": 0 },
+        "filter": { "supported": true, "maxResults": MAX_COUNT },
+        "changePassword": { "supported": true },
+        "sort": { "supported": false },
+        "etag": { "supported": false },
+        "authenticationSchemes": [{
+            "type": "oauthbearertoken",
+            "name": "Service account API key",
+            "description": "Bearer token of a service account holding the SCIM_PROVISIONING permission",
+            "primary": true,
+        }],
+        "meta": {
+            "resourceType": "ServiceProviderConfig",
+            "location": format!("{}/ServiceProviderConfig", base),
+        },
+    }))
+}
+
+fn resource_type(base: &str, name: &str, endpoint: &str, schema: &str) -> Value {
+    json!({
+        "schemas": [RESOURCE_TYPE_SCHEMA],
+        "id": name,
+        "name": name,
+        "endpoint": endpoint,
+        "schema": schema,
+        "meta": {
+            "resourceType": "ResourceType",
+            "location": format!("{}/ResourceTypes/{}", base, name),
+        },
+    })
+}
+
+pub fn resource_types(base: String) -> HttpResponse {
+    let resources = vec![
+        resource_type(&base, "User", "/Users", USER_SCHEMA),
+        resource_type(&base, "Group", "/Groups", GROUP_SCHEMA),
+    ];
+
+    respond(StatusCode::OK, json!({
+        "schemas": [LIST_SCHEMA],
+        "totalResults": resources.len(),
+        "startIndex": 1,
+        "itemsPerPage": resources.len(),
+        "Resources": resources,
+    }))
+}
+
+fn attribute(name: &str, kind: &str, required: bool, mutability: &str, uniqueness: &str) -> Value {
+    json!({
+        "name": name,
+        "type": kind,
+        "multiValued": false,
+        "required": required,
+        "caseExact": false,
+        "mutability": mutability,
+        "returned": if name.eq("password") { "never" } else { "default" },
+        "uniqueness": uniqueness,
+    })
+}
+
+fn multi(name: &str, mutability: &str, sub_attributes: Vec<Value>) -> Value {
+    json!({
+        "name": name,
+        "type": "complex",
+        "multiValued": true,
+        "required": false,
+        "mutability": mutability,
+        "returned": "default",
+        "subAttributes": sub_attributes,
+    })
+}
+
+fn schema(base: &str, id: &str, name: &str, attributes: Vec<Value>) -> Value {
+    json!({
+        "schemas": [SCHEMA_SCHEMA],
+        "id": id,
+        "name": name,
+        "attributes": attributes,
+        "meta": {
+            "resourceType": "Schema",
+            "location": format!("{}/Schemas/{}", base, id),
+        },
+    })
+}
+
+pub fn schemas(base: String) -> HttpResponse {
+    let member = vec![
+        attribute("value", "string", true, "immutable", "none"),
+        attribute("display", "string", false, "readOnly", "none"),
+    ];
+
+    let resources = vec![
+        schema(&base, USER_SCHEMA, "User", vec![
+            attribute("userName", "string", true, "readWrite", "server"),
+            json!({
+                "name": "name",
+                "type": "complex",
+                "multiValued": false,
+                "required": false,
+                "mutability": "readWrite",
+                "returned": "default",
+                "subAttributes": [
+                    attribute("formatted", "string", false, "readWrite", "none"),
+                    attribute("givenName", "string", false, "writeOnly", "none"),
+                    attribute("familyName", "string", false, "writeOnly", "none"),
+                ],
+            }),
+            attribute("displayName", "string", false, "readWrite", "none"),
+            multi("emails", "readWrite", vec![
+                attribute("value", "string", true, "readWrite", "server"),
+                attribute("type", "string", false, "readWrite", "none"),
+                attribute("primary", "boolean", false, "readWrite", "none"),
+            ]),
+            attribute("password", "string", false, "writeOnly", "none"),
+            attribute("active", "boolean", false, "readWrite", "none"),
+            multi("groups", "readOnly", member.clone()),
+        ]),
+        schema(&base, GROUP_SCHEMA, "Group", vec![
+            attribute("displayName", "string", true, "readWrite", "server"),
+            multi("members", "readWrite", member),
+        ]),
+    ];
+
+    respond(StatusCode::OK, json!({
+        "schemas": [LIST_SCHEMA],
+        "totalResults": resources.len(),
+        "startIndex": 1,
+        "itemsPerPage": resources.len(),
+        "Resources": resources,
+    }))
+}
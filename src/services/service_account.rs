@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+
+use actix_web::HttpResponse;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{base58, hash, log, time};
+use nightmare_common::hash::Hash;
+use nightmare_common::models::{users, Id};
+use nightmare_common::request::pagination::PaginationRequest;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait, TransactionTrait};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::{dao, events};
+use crate::dao::organization::Scope;
+use crate::middleware::audit::Audit;
+use crate::models::service_accounts;
+use crate::requests::permission::PermissionBulkRequest;
+use crate::requests::role::RoleBulkRequest;
+use crate::requests::service_account::{ServiceAccountOrderByColumn, ServiceAccountStoreRequest, ServiceAccountUpdateRequest};
+use crate::responses::service_account::{ServiceAccountCredentials, ServiceAccountOAS};
+use crate::services;
+
+/// API keys look like `sak_<id>_<secret>`
+pub const API_KEY_PREFIX: &str = "sak_";
+
+/// Permission required to manage service accounts and their credentials
+pub const MANAGE_PERMISSION: &str = "MANAGE_SERVICE_ACCOUNTS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage service accounts",
+    })))
+}
+
+fn secret() -> String {
+    base58::to_string(Uuid::new_v4().as_bytes())
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<ServiceAccountOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = service_accounts::Entity::find()
+        .order_by(match request.order(ServiceAccountOrderByColumn::Code) {
+            ServiceAccountOrderByColumn::Code => service_accounts::Column::Code,
+            ServiceAccountOrderByColumn::CreatedAt => service_accounts::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(service_accounts::Column::Code.like(request.search()))
+                .add(service_accounts::Column::ClientId.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    let accounts = match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(accounts) => accounts,
+    };
+
+    let users = users::Entity::find()
+        .filter(users::Column::Id.is_in(
+            accounts.iter().map(|account| account.user_id.clone()).collect::<Vec<Id>>()
+        ))
+        .all(db)
+        .await;
+
+    match users {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(users) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": accounts.iter()
+                    .filter_map(|account| {
+                        users.iter()
+                            .find(|user| user.id.eq(&account.user_id))
+                            .map(|user| ServiceAccountOAS::from((account, user)))
+                    })
+                    .collect::<Vec<ServiceAccountOAS>>(),
+            }))
+        },
+    }
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    audit: &Audit,
+    request: ServiceAccountStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let code = request.code.trim().to_lowercase();
+    let name = request.name.trim().to_string();
+    let description = request.description
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty());
+
+    if code.is_empty() {
+        validation.insert("code", vec!["field code is required"]);
+    } else if !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        validation.insert("code", vec!["code may only contain letters, numbers, dash and underscore"]);
+    } else if dao::service_account::exist(db, &code).await || dao::user::find_by_email_or_username(db, code.clone()).await.is_some() {
+        validation.insert("code", vec!["code already exists"]);
+    }
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if let Some(organization_id) = request.organization_id.clone() {
+        if dao::organization::find(db, organization_id).await.is_none() {
+            validation.insert("organization_id", vec!["organization doesn't exist"]);
+        }
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    // service accounts are backed by a user so roles and permissions work
+    // the same way, the password is random and never handed out
+    let user_id = Uuid::new_v4();
+    let id: Id = Uuid::new_v4().into();
+    let client_secret = secret();
+    let account = async {
+        let txn = db.begin().await?;
+        let user = dao::user::store(&txn, users::Model {
+            id: user_id.into(),
+            name,
+            email: format!("{}@service-account.local", code),
+            username: code.clone(),
+            email_verified_at: None,
+            password: hash::make(user_id, secret()).to_string(),
+            profile_photo_id: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+            deleted_at: None,
+        }).await?;
+        let account = dao::service_account::store(&txn, service_accounts::Model {
+            id: id.clone(),
+            user_id: user.id.clone(),
+            organization_id: request.organization_id.clone(),
+            code,
+            description,
+            client_id: secret(),
+            client_secret: hash::make(id, client_secret.clone()).to_string(),
+            api_key: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+        }).await?;
+
+        if let Some(organization_id) = &account.organization_id {
+            dao::organization::join(&txn, organization_id, &user.id).await?;
+        }
+
+        events::emit(&txn, events::USER_CREATED, json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "username": user.username,
+            "service_account_id": account.id,
+            "organization_id": account.organization_id,
+        })).await?;
+        services::audit::record(&txn, audit, events::USER_CREATED, Some(("user", &user.id)), Value::Null, services::user::snapshot(&user)).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(account)
+    };
+
+    match account.await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(account) => {
+            HttpResponse::Created().json(ServiceAccountCredentials {
+                id: account.id,
+                client_id: account.client_id,
+                client_secret: Some(client_secret),
+                api_key: None,
+                message: "Copy the credentials now since they won't be shown again".to_string(),
+            })
+        },
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let account = match dao::service_account::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(account) => account,
+    };
+
+    match dao::user::find(db, account.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => HttpResponse::Ok().json(ServiceAccountOAS::from((&account, &user))),
+        _ => HttpResponse::NotFound().finish(),
+    }
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: ServiceAccountUpdateRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let account = match dao::service_account::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(account) => account,
+    };
+
+    let mut user = match dao::user::find(db, account.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    let mut validation = HashMap::new();
+    let name = request.name.trim().to_string();
+    let description = request.description
+        .map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty());
+
+    if name.is_empty() {
+        validation.insert("name", vec!["field name is required"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    user.name = name;
+
+    if let Err(e) = dao::user::update(db, &user).await {
+        log::error!(update, "{}", e);
+
+        return HttpResponse::InternalServerError().json(json!({
+            "message": e.to_string(),
+        }))
+    }
+
+    match dao::service_account::update(db, account.id.clone(), description).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(account) => {
+            HttpResponse::Ok().json(json!({
+                "id": account.id,
+                "message": "Service account has been updated",
+            }))
+        },
+    }
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let account = match dao::service_account::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(account) => account,
+    };
+
+    if let Some(user) = dao::user::find(db, account.user_id.clone()).await {
+        if let Err(e) = dao::user::delete(db, &user).await {
+            log::error!(delete, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        }
+
+        if let Err(e) = dao::auth::delete(db, user.id).await {
+            log::error!(delete, "{}", e);
+        }
+    }
+
+    match dao::service_account::delete(db, account.id.clone()).await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": account.id,
+                "message": "Service account has been deleted",
+            }))
+        },
+    }
+}
+
+pub async fn rotate_secret<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let account = match dao::service_account::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(account) => account,
+    };
+
+    let client_secret = secret();
+    let hashed = hash::make(account.id.clone(), client_secret.clone()).to_string();
+
+    match dao::service_account::rotate_secret(db, &account, hashed).await {
+        Err(e) => {
+            log::error!(rotate_secret, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(account) => {
+            HttpResponse::Created().json(ServiceAccountCredentials {
+                id: account.id,
+                client_id: account.client_id,
+                client_secret: Some(client_secret),
+                api_key: None,
+                message: "Copy the credentials now since they won't be shown again".to_string(),
+            })
+        },
+    }
+}
+
+pub async fn rotate_api_key<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let account = match dao::service_account::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(account) => account,
+    };
+
+    let key = secret();
+    let hashed = hash::make(account.id.clone(), key.clone()).to_string();
+
+    match dao::service_account::rotate_api_key(db, &account, hashed).await {
+        Err(e) => {
+            log::error!(rotate_api_key, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(account) => {
+            HttpResponse::Created().json(ServiceAccountCredentials {
+                id: account.id.clone(),
+                client_id: account.client_id,
+                client_secret: None,
+                api_key: Some(format!("{}{}_{}", API_KEY_PREFIX, base58::to_string(account.id.as_bytes()), key)),
+                message: "Copy the credentials now since they won't be shown again".to_string(),
+            })
+        },
+    }
+}
+
+/// Accounts bound to an organization only reach its roles and permissions
+fn scope(account: &service_accounts::Model) -> Scope {
+    account.organization_id.clone().map_or(Scope::Platform, Scope::Organization)
+}
+
+pub async fn sync_roles<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    audit: &Audit,
+    id: I,
+    request: RoleBulkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::service_account::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(account) => services::user::sync_roles(db, audit, scope(&account), account.user_id, request).await,
+    }
+}
+
+pub async fn sync_permissions<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    audit: &Audit,
+    id: I,
+    request: PermissionBulkRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::service_account::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(account) => services::user::sync_permissions(db, audit, scope(&account), account.user_id, request).await,
+    }
+}
+
+/// Service account the client credentials belong to
+pub async fn by_credentials(
+    db: &DatabaseConnection,
+    client_id: &str,
+    client_secret: &str,
+) -> Option<(service_accounts::Model, users::Model)> {
+    let account = dao::service_account::find_by_client_id(db, client_id).await?;
+
+    if !hash::verify(Hash::from(account.client_secret.clone()), account.id.clone(), client_secret.to_string()) {
+        return None
+    }
+
+    match dao::user::find(db, account.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => Some((account, user)),
+        _ => None,
+    }
+}
+
+pub async fn user_by_api_key(
+    db: &DatabaseConnection,
+    key: String,
+) -> Result<(users::Model, service_accounts::Model), String> {
+    let (id, secret) = key.strip_prefix(API_KEY_PREFIX)
+        .and_then(|key| key.split_once('_'))
+        .ok_or("Invalid token, malformed api key".to_string())?;
+
+    let id = services::auth::decode(id.to_string())?;
+    let account = dao::service_account::find(db, id).await
+        .ok_or("Invalid token, record not found".to_string())?;
+
+    let hashed = account.api_key.clone()
+        .ok_or("Invalid token, record not found".to_string())?;
+
+    if !hash::verify(Hash::from(hashed), account.id.clone(), secret.to_string()) {
+        return Err("Invalid token, record not found".to_string())
+    }
+
+    match dao::user::find(db, account.user_id.clone()).await {
+        Some(user) if user.deleted_at.is_none() => Ok((user, account)),
+        _ => Err("Invalid token, user not found".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+    use nightmare_common::models::permissions;
+
+    use super::*;
+    use crate::testing;
+
+    fn auth(permissions: Vec<&str>) -> Auth {
+        Auth {
+            user: testing::user("alice", "secret"),
+            permissions: permissions.into_iter()
+                .map(|code| permissions::Model {
+                    id: Uuid::new_v4().into(),
+                    code: code.to_string(),
+                    name: code.to_lowercase(),
+                })
+                .collect(),
+            roles: vec![],
+        }
+    }
+
+    #[actix_web::test]
+    async fn refuses_callers_without_the_permission() {
+        let db = testing::database(vec![testing::table(service_accounts::Entity)]).await;
+        let audit = Audit::default();
+        let auth = auth(vec!["VIEW_AUDIT_LOG"]);
+        let id: Id = Uuid::new_v4().into();
+
+        let responses = vec![
+            store(&db, &auth, &audit, ServiceAccountStoreRequest {
+                code: "billing-worker".to_string(),
+                name: "billing worker".to_string(),
+                description: None,
+                organization_id: None,
+            }).await,
+            show(&db, &auth, id.clone()).await,
+            delete(&db, &auth, id.clone()).await,
+            rotate_secret(&db, &auth, id.clone()).await,
+            rotate_api_key(&db, &auth, id).await,
+        ];
+
+        for response in responses {
+            assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        }
+
+        assert!(service_accounts::Entity::find().all(&db).await.unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn lets_callers_with_the_permission_through() {
+        let db = testing::database(vec![testing::table(service_accounts::Entity)]).await;
+
+        let response = show(&db, &auth(vec![MANAGE_PERMISSION]), Uuid::new_v4()).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
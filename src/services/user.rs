@@ -2,25 +2,50 @@ use std::collections::HashMap;
 
 use actix_web::HttpResponse;
 use nightmare_common::hash::Hash;
-use nightmare_common::{log, hash, time};
-use nightmare_common::models::{users, permissions, roles, Id};
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{base58, log, hash, time};
+use nightmare_common::models::{users, permissions, roles, Id, Timestamp};
 use nightmare_common::request::pagination::PaginationRequest;
-use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, QuerySelect, PaginatorTrait, ConnectionTrait, QueryTrait};
-use serde_json::json;
+use sea_orm::{DatabaseConnection, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, QuerySelect, PaginatorTrait, ConnectionTrait, QueryTrait, DbErr, TransactionTrait};
+use serde_json::{json, Value};
 use uuid::Uuid;
 
-use crate::dao;
+use crate::{dao, services};
+use crate::dao::authorization::{Resource, Window};
+use crate::events;
+use crate::dao::organization::Scope;
+use crate::middleware::audit::Audit;
+use crate::middleware::tenant::Tenant;
 use crate::requests::permission::PermissionBulkRequest;
 use crate::requests::role::RoleBulkRequest;
 use crate::requests::user::{UserUpdateGeneralInformationRequest, UserUpdatePasswordRequest, UserOrderByColumn, UserStoreRequest};
 use crate::responses::user::UserOAS;
 
+/// Permission required to impersonate other users
+pub const IMPERSONATE_PERMISSION: &str = "IMPERSONATE_USER";
+
+/// Impersonation tokens are short lived
+const IMPERSONATION_MINUTES: i64 = 15;
+
+/// Audited fields of the user, the password never leaves the service
+pub fn snapshot(user: &users::Model) -> Value {
+    json!({
+        "name": user.name,
+        "email": user.email,
+        "username": user.username,
+        "profile_photo_id": user.profile_photo_id,
+        "deleted_at": user.deleted_at,
+    })
+}
+
 pub async fn paginate(
     db: &DatabaseConnection,
+    scope: Scope,
     request: PaginationRequest<UserOrderByColumn>,
 ) -> HttpResponse {
     let mut query = users::Entity::find()
-        .filter(users::Column::DeletedAt.is_null());
+        .filter(users::Column::DeletedAt.is_null())
+        .filter(dao::organization::members(scope));
 
     if request.search.is_some() {
         query = query.filter(
@@ -66,14 +91,22 @@ pub async fn paginate(
 
 pub async fn store(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     request: UserStoreRequest,
 ) -> HttpResponse {
-    let mut validation = HashMap::new();
+    if scope == Scope::Nowhere {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "users can only be created within an organization",
+        }))
+    }
 
     let name = request.name.trim().to_lowercase();
     let email = request.email.trim().to_lowercase();
     let username = request.username.trim().to_lowercase();
     let password = request.password.trim();
+    let refused = services::password::policy().check(password, &username, &email);
+    let mut validation = HashMap::new();
 
     if name.is_empty() {
         validation.insert("name", vec!["field name is required"]);
@@ -97,28 +130,8 @@ pub async fn store(
 
     if password.is_empty() {
         validation.insert("password", vec!["field password is required"]);
-    } else {
-        let mut errors = vec![];
-
-        if password.len() < 6 {
-            errors.push("min length for password is 6");
-        }
-
-        if !password.chars().any(|c| c.is_numeric()) {
-            errors.push("password must have numeric value");
-        }
-
-        if !password.chars().any(|c|c.is_alphabetic()) {
-            errors.push("password must have alphabetic value");
-        }
-
-        if password.to_lowercase().eq(password) || password.to_uppercase().eq(password) {
-            errors.push("password must have lower and upper case");
-        }
-
-        if !errors.is_empty() {
-            validation.insert("password", errors);
-        }
+    } else if !refused.is_empty() {
+        validation.insert("password", refused.iter().map(String::as_str).collect());
     }
 
     if !validation.is_empty() {
@@ -128,19 +141,43 @@ pub async fn store(
     }
 
     let id = Uuid::new_v4();
+    let warnings = services::password::policy().warnings(password);
     let password = hash::make(id, password);
-    let user = dao::user::store(db, users::Model {
-        id: id.into(),
-        name,
-        email,
-        username,
-        email_verified_at: None,
-        password: password.to_string(),
-        profile_photo_id: None,
-        created_at: time::now(),
-        updated_at: time::now(),
-        deleted_at: None,
-    });
+    let user = async {
+        let txn = db.begin().await?;
+        let user = dao::user::store(&txn, users::Model {
+            id: id.into(),
+            name,
+            email,
+            username,
+            email_verified_at: None,
+            password: password.to_string(),
+            profile_photo_id: None,
+            created_at: time::now(),
+            updated_at: time::now(),
+            deleted_at: None,
+        }).await?;
+
+        dao::password::changed(&txn, &user, services::password::policy().history).await?;
+
+        let organization_id = scope.organization_id();
+
+        if let Some(organization_id) = &organization_id {
+            dao::organization::join(&txn, organization_id, &user.id).await?;
+        }
+
+        events::emit(&txn, events::USER_CREATED, json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "username": user.username,
+            "organization_id": organization_id,
+        })).await?;
+        services::audit::record(&txn, audit, events::USER_CREATED, Some(("user", &user.id)), Value::Null, snapshot(&user)).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(user)
+    };
 
     match user.await {
         Err(e) => {
@@ -156,6 +193,7 @@ pub async fn store(
             HttpResponse::Created().json(json!({
                 "id": user.id,
                 "message": "User has been created",
+                "warnings": warnings,
             }))
         },
     }
@@ -163,9 +201,10 @@ pub async fn store(
 
 pub async fn show<I: Into<Id>>(
     db: &DatabaseConnection,
+    scope: Scope,
     id: I,
 ) -> HttpResponse {
-    match dao::user::find(db, id).await {
+    match dao::user::find_within(db, id, dao::organization::members(scope)).await {
         None => HttpResponse::NotFound().finish(),
         Some(user) => HttpResponse::Ok().json(UserOAS::from(&user)),
     }
@@ -173,10 +212,12 @@ pub async fn show<I: Into<Id>>(
 
 pub async fn update_general_information<I: Into<Id>>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
     request: UserUpdateGeneralInformationRequest,
 ) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    let user = dao::user::find_within(db, id, dao::organization::members(scope)).await;
 
     if user.is_none() {
         return HttpResponse::NotFound().finish()
@@ -215,12 +256,30 @@ pub async fn update_general_information<I: Into<Id>>(
         }))
     }
 
+    let before = snapshot(&user);
+
     user.name = name;
     user.email = email;
     user.username = username;
     user.profile_photo_id = profile_photo_id;
 
-    match dao::user::update(db, &user).await {
+    let updated = async {
+        let txn = db.begin().await?;
+        let user = dao::user::update(&txn, &user).await?;
+
+        events::emit(&txn, events::USER_UPDATED, json!({
+            "id": user.id,
+            "name": user.name,
+            "email": user.email,
+            "username": user.username,
+            "profile_photo_id": user.profile_photo_id,
+        })).await?;
+        services::audit::record(&txn, audit, events::USER_UPDATED, Some(("user", &user.id)), before, snapshot(&user)).await?;
+
+        txn.commit().await
+    };
+
+    match updated.await {
         Err(e) => {
             log::error!(update_general_information, "{}", e);
 
@@ -240,10 +299,12 @@ pub async fn update_general_information<I: Into<Id>>(
 
 pub async fn update_password<I: Into<Id> + Clone>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
     request: UserUpdatePasswordRequest,
 ) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    let user = dao::user::find_within(db, id, dao::organization::members(scope)).await;
 
     if user.is_none() {
         return HttpResponse::NotFound().finish()
@@ -253,54 +314,19 @@ pub async fn update_password<I: Into<Id> + Clone>(
     let current = request.current_password;
     let new = request.new_password;
     let confirmation = request.password_confirmation;
+    // the current password is only compared, rules may have changed since
+    // it was set
+    let refused = services::password::policy().check(&new, &user.username, &user.email);
     let mut validation = HashMap::new();
 
     if current.is_empty() {
         validation.insert("current", vec!["field current password is required"]);
-    } else {
-        let mut errors = vec![];
-
-        if current.len() < 6 {
-            errors.push("min length for current password is 6");
-        }
-
-        if !current.chars().any(|c| c.is_numeric()) {
-            errors.push("current must have numeric value");
-        }
-
-        if !current.chars().any(|c|c.is_alphabetic()) {
-            errors.push("current password must have alphabetic value");
-        }
-
-        if current.to_lowercase().eq(&current) || current.to_uppercase().eq(&current) {
-            errors.push("current password must have lower and upper case");
-        }
-
-        if !errors.is_empty() {
-            validation.insert("currentPassword", errors);
-        }
     }
 
     if new.is_empty() {
         validation.insert("new", vec!["field new password is required"]);
     } else {
-        let mut errors = vec![];
-
-        if new.len() < 6 {
-            errors.push("min length for new password is 6");
-        }
-
-        if !new.chars().any(|c| c.is_numeric()) {
-            errors.push("new must have numeric value");
-        }
-
-        if !new.chars().any(|c|c.is_alphabetic()) {
-            errors.push("new password must have alphabetic value");
-        }
-
-        if new.to_lowercase().eq(&new) || new.to_uppercase().eq(&new) {
-            errors.push("new password must have lower and upper case");
-        }
+        let mut errors = refused.iter().map(String::as_str).collect::<Vec<&str>>();
 
         if current.eq(&new) {
             errors.push("can't update new password with old password");
@@ -310,35 +336,11 @@ pub async fn update_password<I: Into<Id> + Clone>(
             validation.insert("newPassword", errors);
         }
     }
-    
+
     if confirmation.is_empty() {
         validation.insert("confirmation", vec!["field password confirmation is required"]);
-    } else {
-        let mut errors = vec![];
-
-        if confirmation.len() < 6 {
-            errors.push("min length for password confirmation is 6");
-        }
-
-        if !confirmation.chars().any(|c| c.is_numeric()) {
-            errors.push("confirmation must have numeric value");
-        }
-
-        if !confirmation.chars().any(|c|c.is_alphabetic()) {
-            errors.push("password confirmation must have alphabetic value");
-        }
-
-        if confirmation.to_lowercase().eq(&confirmation) || confirmation.to_uppercase().eq(&confirmation) {
-            errors.push("password confirmation must have lower and upper case");
-        }
-
-        if confirmation.ne(&new) {
-            errors.push("password confirmation does't match with new password");
-        }
-
-        if !errors.is_empty() {
-            validation.insert("passwordConfirmation", errors);
-        }
+    } else if confirmation.ne(&new) {
+        validation.insert("passwordConfirmation", vec!["password confirmation does't match with new password"]);
     }
 
     if !validation.is_empty() {
@@ -359,9 +361,42 @@ pub async fn update_password<I: Into<Id> + Clone>(
         }))
     }
 
+    match services::password::policy().reused(db, &user, &new).await {
+        Err(e) => {
+            log::error!(update_password, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(true) => return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "newPassword": [
+                    "password was used recently, choose another one"
+                ],
+            }
+        })),
+        Ok(false) => {},
+    }
+
+    let warnings = services::password::policy().warnings(&new);
+
     user.password = hash::make(user.id.clone(), new).to_string();
 
-    match dao::user::update(db, &user).await {
+    let updated = async {
+        let txn = db.begin().await?;
+
+        dao::user::update(&txn, &user).await?;
+        dao::password::changed(&txn, &user, services::password::policy().history).await?;
+        events::emit(&txn, events::USER_PASSWORD_CHANGED, json!({
+            "id": user.id,
+        })).await?;
+        services::audit::record(&txn, audit, events::USER_PASSWORD_CHANGED, Some(("user", &user.id)), Value::Null, Value::Null).await?;
+
+        txn.commit().await
+    };
+
+    match updated.await {
         Err(e) => {
             log::error!(update_password, "{}", e);
 
@@ -371,21 +406,40 @@ pub async fn update_password<I: Into<Id> + Clone>(
         },
         Ok(_) => HttpResponse::Ok().json(json!({
             "message": "user password has been updated",
+            "warnings": warnings,
         })),
     }
 }
 
 pub async fn delete<I: Into<Id> + Clone>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
 ) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    let user = dao::user::find_within(db, id, dao::organization::members(scope)).await;
 
     if user.is_none() {
         return HttpResponse::NotFound().finish()
     }
 
-    match dao::user::delete(db, &user.unwrap()).await {
+    let user = user.unwrap();
+    let before = snapshot(&user);
+    let deleted = async {
+        let txn = db.begin().await?;
+        let user = dao::user::delete(&txn, &user).await?;
+
+        events::emit(&txn, events::USER_DELETED, json!({
+            "id": user.id,
+            "deleted_at": user.deleted_at,
+        })).await?;
+        services::audit::record(&txn, audit, events::USER_DELETED, Some(("user", &user.id)), before, snapshot(&user)).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(user)
+    };
+
+    match deleted.await {
         Err(e) => {
             log::error!(delete, "{}", e);
 
@@ -405,18 +459,39 @@ pub async fn delete<I: Into<Id> + Clone>(
 
 pub async fn sync_permissions<I: Into<Id>>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
     request: PermissionBulkRequest,
 ) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    let user = dao::user::find_within(db, id, dao::organization::members(scope.clone())).await;
 
     if user.is_none() {
         return HttpResponse::NotFound().finish()
     }
 
+    let mut validation = HashMap::new();
+
+    if request.assignments.iter().any(|assignment| !window(assignment.starts_at, assignment.expires_at)) {
+        validation.insert("assignments", vec!["expires at must be after starts at"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
     let user = user.unwrap();
     let permissions = permissions::Entity::find()
-        .filter(permissions::Column::Id.is_in(request.permissions));
+        .filter(permissions::Column::Id.is_in(
+            request.permissions.iter()
+                .chain(request.assignments.iter().map(|assignment| &assignment.permission))
+                .cloned()
+                .collect::<Vec<Id>>()
+        ))
+        // another organization's permissions count as unknown
+        .filter(dao::organization::visible_permissions(scope));
 
     log::debug!(sync_permissions, "{}", permissions.build(db.get_database_backend()).to_string());
 
@@ -430,7 +505,65 @@ pub async fn sync_permissions<I: Into<Id>>(
         }))
     }
 
-    match dao::user::sync_permissions(db, &user, permissions.unwrap()).await {
+    let permissions = permissions.unwrap();
+    let mut grants = vec![];
+
+    for permission in permissions.iter().filter(|permission| request.permissions.contains(&permission.id)) {
+        grants.push((permission.clone(), Window::default()));
+    }
+
+    for assignment in &request.assignments {
+        if let Some(permission) = permissions.iter().find(|permission| permission.id.eq(&assignment.permission)) {
+            grants.push((permission.clone(), Window {
+                starts_at: assignment.starts_at,
+                expires_at: assignment.expires_at,
+            }));
+        }
+    }
+
+    let payload = json!({
+        "user_id": user.id,
+        "permissions": grants.iter()
+            .map(|(permission, window)| json!({
+                "id": permission.id,
+                "code": permission.code,
+                "starts_at": window.starts_at,
+                "expires_at": window.expires_at,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let after = json!({
+        "permissions": grants.iter()
+            .map(|(permission, window)| json!({
+                "id": permission.id,
+                "starts_at": window.starts_at,
+                "expires_at": window.expires_at,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let synced = async {
+        let txn = db.begin().await?;
+        let before = json!({
+            "permissions": dao::user::permission_grants(&txn, &user).await?
+                .iter()
+                .map(|grant| json!({
+                    "id": grant.permission_id,
+                    "starts_at": grant.starts_at,
+                    "expires_at": grant.expires_at,
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        dao::user::sync_permissions(&txn, &user, grants).await?;
+        events::emit(&txn, events::PERMISSION_SYNCED, payload).await?;
+        services::audit::record(&txn, audit, events::PERMISSION_SYNCED, Some(("user", &user.id)), before, after).await?;
+
+        txn.commit().await
+    };
+
+    match synced.await {
         Err(e) => {
             log::error!(sync_permissions, "{}", e);
 
@@ -444,18 +577,54 @@ pub async fn sync_permissions<I: Into<Id>>(
 
 pub async fn sync_roles<I: Into<Id>>(
     db: &DatabaseConnection,
+    audit: &Audit,
+    scope: Scope,
     id: I,
     request: RoleBulkRequest,
 ) -> HttpResponse {
-    let user = dao::user::find(db, id).await;
+    let user = dao::user::find_within(db, id, dao::organization::members(scope.clone())).await;
 
     if user.is_none() {
         return HttpResponse::NotFound().finish()
     }
 
+    let mut validation = HashMap::new();
+    let mut errors = vec![];
+
+    for assignment in &request.assignments {
+        let resource_type = assignment.resource_type.as_deref().map(str::trim).unwrap_or_default();
+        let resource_id = assignment.resource_id.as_deref().map(str::trim).unwrap_or_default();
+
+        if resource_type.is_empty() != resource_id.is_empty() {
+            errors.push("field resource type and resource id must be filled together");
+        }
+
+        if !window(assignment.starts_at, assignment.expires_at) {
+            errors.push("expires at must be after starts at");
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.dedup();
+        validation.insert("assignments", errors);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
     let user = user.unwrap();
     let roles = roles::Entity::find()
-        .filter(roles::Column::Id.is_in(request.roles));
+        .filter(roles::Column::Id.is_in(
+            request.roles.iter()
+                .chain(request.assignments.iter().map(|assignment| &assignment.role))
+                .cloned()
+                .collect::<Vec<Id>>()
+        ))
+        // another organization's roles count as unknown
+        .filter(dao::organization::visible_roles(scope));
 
     log::debug!(sync_roles, "{}", roles.build(db.get_database_backend()).to_string());
 
@@ -469,7 +638,79 @@ pub async fn sync_roles<I: Into<Id>>(
         }))
     }
 
-    match dao::user::sync_roles(db, &user, roles.unwrap()).await {
+    let roles = roles.unwrap();
+    let mut assignments = vec![];
+
+    for role in roles.iter().filter(|role| request.roles.contains(&role.id)) {
+        assignments.push((role.clone(), None, Window::default()));
+    }
+
+    for assignment in &request.assignments {
+        if let Some(role) = roles.iter().find(|role| role.id.eq(&assignment.role)) {
+            let resource = match (&assignment.resource_type, &assignment.resource_id) {
+                (Some(kind), Some(id)) if !kind.trim().is_empty() => Some(Resource {
+                    kind: kind.trim().to_lowercase(),
+                    id: id.trim().to_string(),
+                }),
+                _ => None,
+            };
+
+            assignments.push((role.clone(), resource, Window {
+                starts_at: assignment.starts_at,
+                expires_at: assignment.expires_at,
+            }));
+        }
+    }
+
+    let payload = json!({
+        "user_id": user.id,
+        "roles": assignments.iter()
+            .map(|(role, resource, window)| json!({
+                "id": role.id,
+                "code": role.code,
+                "resource_type": resource.as_ref().map(|resource| &resource.kind),
+                "resource_id": resource.as_ref().map(|resource| &resource.id),
+                "starts_at": window.starts_at,
+                "expires_at": window.expires_at,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let after = json!({
+        "roles": assignments.iter()
+            .map(|(role, resource, window)| json!({
+                "id": role.id,
+                "resource_type": resource.as_ref().map(|resource| &resource.kind),
+                "resource_id": resource.as_ref().map(|resource| &resource.id),
+                "starts_at": window.starts_at,
+                "expires_at": window.expires_at,
+            }))
+            .collect::<Vec<_>>(),
+    });
+
+    let synced = async {
+        let txn = db.begin().await?;
+        let before = json!({
+            "roles": dao::user::role_assignments(&txn, &user).await?
+                .iter()
+                .map(|assignment| json!({
+                    "id": assignment.role_id,
+                    "resource_type": assignment.resource_type,
+                    "resource_id": assignment.resource_id,
+                    "starts_at": assignment.starts_at,
+                    "expires_at": assignment.expires_at,
+                }))
+                .collect::<Vec<_>>(),
+        });
+
+        dao::user::sync_roles(&txn, &user, assignments).await?;
+        events::emit(&txn, events::ROLE_SYNCED, payload).await?;
+        services::audit::record(&txn, audit, events::ROLE_SYNCED, Some(("user", &user.id)), before, after).await?;
+
+        txn.commit().await
+    };
+
+    match synced.await {
         Err(e) => {
             log::error!(sync_roles, "{}", e);
 
@@ -480,3 +721,117 @@ pub async fn sync_roles<I: Into<Id>>(
         _ => HttpResponse::Ok().finish(),
     }
 }
+
+pub fn window(starts_at: Option<Timestamp>, expires_at: Option<Timestamp>) -> bool {
+    match (starts_at, expires_at) {
+        (Some(starts_at), Some(expires_at)) => starts_at < expires_at,
+        _ => true,
+    }
+}
+
+pub async fn impersonate<I: Into<Id>>(
+    db: &DatabaseConnection,
+    audit: &Audit,
+    auth: Auth,
+    tenant: Tenant,
+    id: I,
+) -> HttpResponse {
+    if !auth.permissions.iter().any(|permission| permission.code.eq(IMPERSONATE_PERMISSION)) {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "you are not allowed to impersonate users",
+        }))
+    }
+
+    if tenant.token.as_ref().is_some_and(|token| token.impersonator_id.is_some()) {
+        return HttpResponse::Forbidden().json(json!({
+            "message": "impersonation can't be nested",
+        }))
+    }
+
+    let user = match dao::user::find_within(db, id, dao::organization::members(tenant.scope(&auth))).await {
+        Some(user) if user.deleted_at.is_none() => user,
+        _ => return HttpResponse::NotFound().finish(),
+    };
+
+    let mut validation = HashMap::new();
+
+    if user.id.eq(&auth.user.id) {
+        validation.insert("id", vec!["you can't impersonate yourself"]);
+    }
+
+    let grants = dao::authorization::grants(db, &user, None).await;
+    let own = dao::authorization::grants(db, &auth.user, None).await;
+
+    let (grants, own) = match (grants, own) {
+        (Err(e), _) | (_, Err(e)) => {
+            log::error!(impersonate, "{}", e);
+
+            return HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        (Ok(grants), Ok(own)) => (grants, own),
+    };
+
+    // roles count on their own, a role like SUPERUSER may carry no
+    // permission rows and scoped roles only match the same resource
+    let holds = |role: &roles::Model| own.roles.iter().any(|exist| exist.id.eq(&role.id));
+    let escalates = grants.roles.iter().any(|role| !holds(role))
+        || grants.scoped.iter().any(|scoped| {
+            !holds(&scoped.role) && !own.scoped.iter().any(|exist| {
+                exist.role.id.eq(&scoped.role.id) && exist.resource.eq(&scoped.resource)
+            })
+        })
+        || grants.grants.iter().any(|grant| own.find(&grant.permission.code, grant.resource.as_ref()).is_empty());
+
+    if escalates {
+        validation.insert("id", vec!["user holds roles or permissions you don't have"]);
+    }
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    let organization_id = dao::organization::memberships(db, &user).await
+        .unwrap_or_default()
+        .first()
+        .map(|organization| organization.id.clone());
+
+    let expired_at = time::now() + chrono::Duration::minutes(IMPERSONATION_MINUTES);
+
+    let impersonated = async {
+        let txn = db.begin().await?;
+        let token = dao::auth::impersonate(&txn, &user, &auth.user, expired_at, organization_id).await?;
+
+        let impersonation = json!({
+            "user_id": user.id,
+            "impersonator_id": auth.user.id,
+            "expired_at": expired_at,
+        });
+
+        events::emit(&txn, events::IMPERSONATION_STARTED, impersonation.clone()).await?;
+        services::audit::record(&txn, audit, events::IMPERSONATION_STARTED, Some(("user", &user.id)), Value::Null, impersonation).await?;
+        txn.commit().await?;
+
+        Ok::<_, DbErr>(token)
+    };
+
+    match impersonated.await {
+        Err(e) => {
+            log::error!(impersonate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(token) => {
+            HttpResponse::Ok().json(json!({
+                "token": base58::to_string(token.id.as_bytes()),
+                "user": UserOAS::from(user),
+                "expired_at": expired_at,
+            }))
+        },
+    }
+}
@@ -0,0 +1,922 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::time::Duration;
+
+use actix_tls::connect::{Connector, Resolve, Resolver};
+use actix_web::HttpResponse;
+use actix_web::http::Uri;
+use actix_web::http::header::CONTENT_TYPE;
+use nightmare_common::middleware::auth::Auth;
+use nightmare_common::{base58, log, time};
+use nightmare_common::models::Id;
+use nightmare_common::request::pagination::PaginationRequest;
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use sea_orm::{DatabaseConnection, DbErr, EntityTrait, QueryOrder, QueryFilter, Condition, ColumnTrait, PaginatorTrait, QuerySelect, QueryTrait, ConnectionTrait};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{dao, events};
+use crate::models::{webhook_deliveries, webhooks};
+use crate::requests::webhook::{WebhookDeliveryOrderByColumn, WebhookOrderByColumn, WebhookStoreRequest, WebhookUpdateRequest};
+use crate::responses::webhook::{WebhookOAS, WebhookSecret};
+use crate::responses::webhook_delivery::WebhookDeliveryOAS;
+
+/// Permission required to manage webhooks and their deliveries
+pub const MANAGE_PERMISSION: &str = "MANAGE_WEBHOOKS";
+
+fn forbidden(auth: &Auth) -> Option<HttpResponse> {
+    if auth.permissions.iter().any(|permission| permission.code.eq(MANAGE_PERMISSION)) {
+        return None
+    }
+
+    Some(HttpResponse::Forbidden().json(json!({
+        "message": "you are not allowed to manage webhooks",
+    })))
+}
+
+pub const EVENT_HEADER: &str = "X-Nightmare-Event";
+pub const DELIVERY_HEADER: &str = "X-Nightmare-Delivery";
+
+/// `t=<unix timestamp>,v1=<hex hmac sha256 of "<timestamp>.<body>">`
+pub const SIGNATURE_HEADER: &str = "X-Nightmare-Signature";
+
+/// Attempts before a delivery is dead
+pub const MAX_ATTEMPTS: i32 = 10;
+
+/// First retry delay, doubled on every failed attempt
+const BACKOFF_SECONDS: i64 = 30;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Characters of the receiver's response kept in the delivery log
+const RESPONSE_LIMIT: usize = 1024;
+
+fn secret() -> String {
+    format!("whsec_{}", base58::to_string(Uuid::new_v4().as_bytes()))
+}
+
+/// Hex encoded hmac sha256 of `<timestamp>.<payload>`, receivers recompute
+/// it with their copy of the secret and reject stale timestamps
+pub fn sign(secret: &str, timestamp: i64, payload: &str) -> Result<String, ErrorStack> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+
+    signer.update(format!("{}.{}", timestamp, payload).as_bytes())?;
+
+    Ok(signer.sign_to_vec()?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Delay after the given number of failed attempts, 30 seconds doubling up
+/// to about two hours before the last attempt
+fn backoff(attempts: i32) -> chrono::Duration {
+    chrono::Duration::seconds(BACKOFF_SECONDS << (attempts - 1).clamp(0, 16))
+}
+
+/// Answers every lookup with the addresses `target` already checked, a
+/// second dns answer can't send the attempt somewhere else
+struct Pinned(Vec<SocketAddr>);
+
+impl Resolve for Pinned {
+    fn lookup<'a>(
+        &'a self,
+        _: &'a str,
+        _: u16,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<SocketAddr>, Box<dyn Error>>> + 'a>> {
+        Box::pin(async move { Ok(self.0.clone()) })
+    }
+}
+
+/// Post the delivery to the checked addresses, returning the receiver's
+/// status and response. Redirects aren't followed, a 3xx is a failed attempt
+async fn send(
+    webhook: &webhooks::Model,
+    delivery: &webhook_deliveries::Model,
+    addresses: Vec<SocketAddr>,
+) -> Result<(u16, String), String> {
+    let timestamp = chrono::Utc::now().timestamp();
+    let signature = sign(&webhook.secret, timestamp, &delivery.payload).map_err(|e| e.to_string())?;
+
+    let connector = Connector::new(Resolver::custom(Pinned(addresses))).service();
+
+    let mut response = awc::Client::builder()
+        .connector(awc::Connector::new().connector(connector))
+        .disable_redirects()
+        .timeout(TIMEOUT)
+        .finish()
+        .post(&webhook.url)
+        .insert_header((CONTENT_TYPE, "application/json"))
+        .insert_header((EVENT_HEADER, delivery.event.clone()))
+        .insert_header((DELIVERY_HEADER, delivery.id.to_string()))
+        .insert_header((SIGNATURE_HEADER, format!("t={},v1={}", timestamp, signature)))
+        .send_body(delivery.payload.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let body = response.body()
+        .await
+        .map(|body| String::from_utf8_lossy(&body).chars().take(RESPONSE_LIMIT).collect())
+        .unwrap_or_default();
+
+    Ok((response.status().as_u16(), body))
+}
+
+/// Whether the address is reachable from the internet, webhooks may not
+/// target the host itself or the networks it sits in
+fn public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            // 100.64.0.0/10, carrier grade nat
+            let shared = first == 100 && (second & 0xc0) == 64;
+
+            !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+                || ip.is_broadcast() || ip.is_documentation() || shared)
+        },
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => public(IpAddr::V4(ip)),
+            None => {
+                let segment = ip.segments()[0];
+                // fc00::/7 unique local and fe80::/10 link local
+                let local = (segment & 0xfe00) == 0xfc00 || (segment & 0xffc0) == 0xfe80;
+
+                !(ip.is_loopback() || ip.is_unspecified() || local)
+            },
+        },
+    }
+}
+
+/// `WEBHOOK_ALLOW_PRIVATE_TARGETS=true` lets webhooks reach private
+/// addresses, only meant for local receivers like the compose sink
+pub fn private_allowed() -> bool {
+    std::env::var("WEBHOOK_ALLOW_PRIVATE_TARGETS")
+        .map(|allowed| allowed.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Resolve the url's host, refusing urls that aren't http or https or whose
+/// host resolves to an address that isn't public unless `allow_private`.
+/// Checked when the webhook is saved and again before every attempt since
+/// dns may have changed in between, the attempt connects to these addresses
+pub async fn target(url: &str, allow_private: bool) -> Result<Vec<SocketAddr>, &'static str> {
+    let uri = url.parse::<Uri>().map_err(|_| "url must be an http or https url")?;
+    let port = match uri.scheme_str() {
+        Some("http") => uri.port_u16().unwrap_or(80),
+        Some("https") => uri.port_u16().unwrap_or(443),
+        _ => return Err("url must be an http or https url"),
+    };
+    let host = uri.host()
+        .map(|host| host.trim_start_matches('[').trim_end_matches(']').to_string())
+        .filter(|host| !host.is_empty())
+        .ok_or("url must be an http or https url")?;
+
+    let addresses: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => actix_web::rt::task::spawn_blocking(move || (host.as_str(), port).to_socket_addrs())
+            .await
+            .map_err(|_| "url host can't be resolved")?
+            .map_err(|_| "url host can't be resolved")?
+            .collect(),
+    };
+
+    if addresses.is_empty() {
+        return Err("url host can't be resolved")
+    }
+
+    if !allow_private && !addresses.iter().all(|address| public(address.ip())) {
+        return Err("url must not point to a private, loopback or link local address")
+    }
+
+    Ok(addresses)
+}
+
+/// Make one attempt and record its outcome, failures are retried with
+/// exponential backoff until the delivery is dead
+pub async fn deliver(
+    db: &DatabaseConnection,
+    webhook: &webhooks::Model,
+    delivery: &webhook_deliveries::Model,
+    allow_private: bool,
+) -> Result<webhook_deliveries::Model, DbErr> {
+    if !webhook.enabled {
+        return dao::webhook::failed(db, delivery, None, None, "webhook is disabled", None).await
+    }
+
+    let outcome = match target(&webhook.url, allow_private).await {
+        Err(e) => Err(e.to_string()),
+        Ok(addresses) => send(webhook, delivery, addresses).await,
+    };
+
+    record(db, webhook, delivery, outcome).await
+}
+
+/// Store the outcome of an attempt, scheduling the next one on failure
+async fn record(
+    db: &DatabaseConnection,
+    webhook: &webhooks::Model,
+    delivery: &webhook_deliveries::Model,
+    outcome: Result<(u16, String), String>,
+) -> Result<webhook_deliveries::Model, DbErr> {
+    let attempts = delivery.attempts + 1;
+    let next_attempt_at = (attempts < MAX_ATTEMPTS).then(|| time::now() + backoff(attempts));
+
+    let delivery = match outcome {
+        Ok((status, body)) if (200..300).contains(&status) => {
+            dao::webhook::delivered(db, delivery, status.into(), Some(body)).await?
+        },
+        Ok((status, body)) => {
+            let error = format!("receiver responded with status {}", status);
+
+            dao::webhook::failed(db, delivery, Some(status.into()), Some(body), error, next_attempt_at).await?
+        },
+        Err(e) => dao::webhook::failed(db, delivery, None, None, e, next_attempt_at).await?,
+    };
+
+    log::debug!(services::webhook::deliver, "{} {} to {} is {} after {} attempts", delivery.event, delivery.id, webhook.url, delivery.status, delivery.attempts);
+
+    Ok(delivery)
+}
+
+pub async fn paginate(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: PaginationRequest<WebhookOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut query = webhooks::Entity::find()
+        .order_by(match request.order(WebhookOrderByColumn::CreatedAt) {
+            WebhookOrderByColumn::Url => webhooks::Column::Url,
+            WebhookOrderByColumn::CreatedAt => webhooks::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(webhooks::Column::Url.like(request.search()))
+                .add(webhooks::Column::Description.like(request.search()))
+                .add(webhooks::Column::Events.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(paginate, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(paginate, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                    .map(WebhookOAS::from)
+                    .collect::<Vec<WebhookOAS>>(),
+            }))
+        },
+    }
+}
+
+/// Known event types or wildcards matching at least one of them, stored
+/// comma separated without duplicates
+fn subscription(requested: Vec<String>) -> Result<String, &'static str> {
+    let mut subscribed: Vec<String> = vec![];
+
+    for event in requested {
+        let event = event.trim().to_lowercase();
+        let known = match event.strip_suffix('*') {
+            None => events::TYPES.contains(&event.as_str()),
+            Some(prefix) => events::TYPES.iter().any(|known| known.starts_with(prefix)),
+        };
+
+        if !known {
+            return Err("events must be known event types, * or a prefix like user.*")
+        }
+
+        if !subscribed.contains(&event) {
+            subscribed.push(event);
+        }
+    }
+
+    if subscribed.is_empty() {
+        return Err("field events is required")
+    }
+
+    Ok(subscribed.join(","))
+}
+
+async fn validate<'a>(
+    validation: &mut HashMap<&'a str, Vec<&'a str>>,
+    webhook: &webhooks::Model,
+) {
+    if let Err(e) = target(&webhook.url, private_allowed()).await {
+        validation.insert("url", vec![e]);
+    }
+
+    if webhook.secret.len() < 16 {
+        validation.insert("secret", vec!["secret must be at least 16 characters"]);
+    }
+}
+
+/// Blank descriptions are stored as null
+fn description(description: Option<String>) -> Option<String> {
+    description.map(|description| description.trim().to_string())
+        .filter(|description| !description.is_empty())
+}
+
+pub async fn store(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    request: WebhookStoreRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut validation = HashMap::new();
+    let events = subscription(request.events).unwrap_or_else(|e| {
+        validation.insert("events", vec![e]);
+
+        String::new()
+    });
+
+    let webhook = webhooks::Model {
+        id: Uuid::new_v4().into(),
+        url: request.url.trim().to_string(),
+        description: description(request.description),
+        events,
+        secret: request.secret.filter(|secret| !secret.is_empty()).unwrap_or_else(secret),
+        enabled: request.enabled,
+        created_at: time::now(),
+        updated_at: time::now(),
+    };
+
+    validate(&mut validation, &webhook).await;
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::webhook::store(db, webhook).await {
+        Err(e) => {
+            log::error!(store, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(webhook) => {
+            HttpResponse::Created().json(WebhookSecret {
+                id: webhook.id,
+                secret: webhook.secret,
+                message: "Copy the secret now since it won't be shown again".to_string(),
+            })
+        },
+    }
+}
+
+pub async fn show<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    match dao::webhook::find(db, id).await {
+        None => HttpResponse::NotFound().finish(),
+        Some(webhook) => HttpResponse::Ok().json(WebhookOAS::from(&webhook)),
+    }
+}
+
+pub async fn update<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: WebhookUpdateRequest,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let mut webhook = match dao::webhook::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(webhook) => webhook,
+    };
+
+    let mut validation = HashMap::new();
+
+    match subscription(request.events) {
+        Err(e) => {
+            validation.insert("events", vec![e]);
+        },
+        Ok(events) => webhook.events = events,
+    }
+
+    webhook.url = request.url.trim().to_string();
+    webhook.description = description(request.description);
+    webhook.enabled = request.enabled;
+
+    if let Some(secret) = request.secret.filter(|secret| !secret.is_empty()) {
+        webhook.secret = secret;
+    }
+
+    validate(&mut validation, &webhook).await;
+
+    if !validation.is_empty() {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": validation,
+        }))
+    }
+
+    match dao::webhook::update(db, webhook).await {
+        Err(e) => {
+            log::error!(update, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(webhook) => {
+            HttpResponse::Ok().json(json!({
+                "id": webhook.id,
+                "message": "Webhook has been updated",
+            }))
+        },
+    }
+}
+
+pub async fn delete<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let webhook = match dao::webhook::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(webhook) => webhook,
+    };
+
+    match dao::webhook::delete(db, webhook.id.clone()).await {
+        Err(e) => {
+            log::error!(delete, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        _ => {
+            HttpResponse::Ok().json(json!({
+                "id": webhook.id,
+                "message": "Webhook has been deleted",
+            }))
+        },
+    }
+}
+
+pub async fn deliveries<I: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    request: PaginationRequest<WebhookDeliveryOrderByColumn>,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let webhook = match dao::webhook::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(webhook) => webhook,
+    };
+
+    let mut query = webhook_deliveries::Entity::find()
+        .filter(webhook_deliveries::Column::WebhookId.eq(webhook.id.clone()))
+        .order_by(match request.order(WebhookDeliveryOrderByColumn::CreatedAt) {
+            WebhookDeliveryOrderByColumn::Event => webhook_deliveries::Column::Event,
+            WebhookDeliveryOrderByColumn::Status => webhook_deliveries::Column::Status,
+            WebhookDeliveryOrderByColumn::CreatedAt => webhook_deliveries::Column::CreatedAt,
+        }, request.sort());
+
+    if request.search.is_some() {
+        query = query.filter(
+            Condition::any()
+                .add(webhook_deliveries::Column::Event.like(request.search()))
+                .add(webhook_deliveries::Column::Status.like(request.search()))
+        )
+    }
+
+    let count = query.clone().count(db).await.unwrap();
+    let query = query.limit(Some(request.limit().into()))
+        .offset(Some(request.limit() as u64 * (request.page() as u64 - 1)));
+
+    log::debug!(deliveries, "{}", query.build(db.get_database_backend()).to_string());
+
+    match query.all(db).await {
+        Err(e) => {
+            log::error!(deliveries, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(data) => {
+            HttpResponse::Ok().json(json!({
+                "total": {
+                    "data": count,
+                    "page": count / request.page(),
+                },
+                "data": data.iter()
+                    .map(WebhookDeliveryOAS::from)
+                    .collect::<Vec<WebhookDeliveryOAS>>(),
+            }))
+        },
+    }
+}
+
+/// Send a delivery again regardless of its state, a failed attempt starts
+/// a fresh retry schedule
+pub async fn redeliver<I: Into<Id>, D: Into<Id>>(
+    db: &DatabaseConnection,
+    auth: &Auth,
+    id: I,
+    delivery: D,
+) -> HttpResponse {
+    if let Some(response) = forbidden(auth) {
+        return response
+    }
+
+    let webhook = match dao::webhook::find(db, id).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(webhook) => webhook,
+    };
+
+    let delivery = match dao::webhook::find_delivery(db, &webhook, delivery).await {
+        None => return HttpResponse::NotFound().finish(),
+        Some(delivery) => delivery,
+    };
+
+    if !webhook.enabled {
+        return HttpResponse::UnprocessableEntity().json(json!({
+            "errors": {
+                "webhook": ["webhook is disabled"],
+            },
+        }))
+    }
+
+    let delivered = async {
+        let delivery = dao::webhook::redeliver(db, &delivery).await?;
+
+        deliver(db, &webhook, &delivery, private_allowed()).await
+    };
+
+    match delivered.await {
+        Err(e) => {
+            log::error!(redeliver, "{}", e);
+
+            HttpResponse::InternalServerError().json(json!({
+                "message": e.to_string(),
+            }))
+        },
+        Ok(delivery) => HttpResponse::Ok().json(WebhookDeliveryOAS::from(&delivery)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::{web, App, HttpRequest, HttpServer};
+    use actix_web::http::StatusCode;
+    use actix_web::http::header::LOCATION;
+
+    use super::*;
+    use crate::testing;
+
+    #[derive(Clone, Debug)]
+    struct Received {
+        event: String,
+        delivery: String,
+        signature: String,
+        body: String,
+    }
+
+    /// Local http receiver answering every request with the given status
+    fn sink(status: u16) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let received = Arc::new(Mutex::new(vec![]));
+        let inbox = received.clone();
+        let server = HttpServer::new(move || {
+            let inbox = inbox.clone();
+
+            App::new().default_service(web::to(move |request: HttpRequest, body: String| {
+                let inbox = inbox.clone();
+
+                async move {
+                    let header = |name: &str| request.headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                        .unwrap_or_default()
+                        .to_string();
+
+                    inbox.lock().unwrap().push(Received {
+                        event: header(EVENT_HEADER),
+                        delivery: header(DELIVERY_HEADER),
+                        signature: header(SIGNATURE_HEADER),
+                        body,
+                    });
+
+                    HttpResponse::build(StatusCode::from_u16(status).unwrap()).body("received")
+                }
+            }))
+        })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let address = server.addrs()[0];
+
+        actix_web::rt::spawn(server.run());
+
+        (format!("http://{}/hook", address), received)
+    }
+
+    /// Local http receiver redirecting every request to the location
+    fn redirect(location: &str) -> String {
+        let location = location.to_string();
+        let server = HttpServer::new(move || {
+            let location = location.clone();
+
+            App::new().default_service(web::to(move || {
+                let location = location.clone();
+
+                async move { HttpResponse::Found().insert_header((LOCATION, location)).finish() }
+            }))
+        })
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let address = server.addrs()[0];
+
+        actix_web::rt::spawn(server.run());
+
+        format!("http://{}/hook", address)
+    }
+
+    fn webhook(url: &str) -> webhooks::Model {
+        webhooks::Model {
+            id: Uuid::new_v4().into(),
+            url: url.to_string(),
+            description: None,
+            events: "*".to_string(),
+            secret: "whsec_test_secret_key".to_string(),
+            enabled: true,
+            created_at: time::now(),
+            updated_at: time::now(),
+        }
+    }
+
+    async fn delivery(db: &DatabaseConnection, webhook: &webhooks::Model, attempts: i32) -> webhook_deliveries::Model {
+        let delivery = dao::webhook::store_delivery(db, webhook, Uuid::new_v4().into(), events::USER_CREATED, r#"{"type":"user.created"}"#)
+            .await
+            .unwrap();
+
+        webhook_deliveries::Model { attempts, ..delivery }
+    }
+
+    #[test]
+    fn sign_is_the_hmac_of_the_timestamp_and_payload() {
+        let signature = sign("whsec_test_secret_key", 1700000000, r#"{"type":"user.created"}"#).unwrap();
+
+        assert_eq!(signature, "037dc1c62a37adf7f22166c9bfa150a7d46b370d1bebf892ede03ddd8cb0e452");
+        assert_ne!(signature, sign("whsec_other_secret_key", 1700000000, r#"{"type":"user.created"}"#).unwrap());
+        assert_ne!(signature, sign("whsec_test_secret_key", 1700000001, r#"{"type":"user.created"}"#).unwrap());
+    }
+
+    #[test]
+    fn backoff_doubles_from_thirty_seconds() {
+        assert_eq!(backoff(1).num_seconds(), 30);
+        assert_eq!(backoff(2).num_seconds(), 60);
+        assert_eq!(backoff(4).num_seconds(), 240);
+        assert_eq!(backoff(MAX_ATTEMPTS - 1).num_seconds(), 30i64 << (MAX_ATTEMPTS - 2));
+    }
+
+    #[actix_web::test]
+    async fn send_posts_the_signed_payload() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (url, received) = sink(200);
+        let webhook = webhook(&url);
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let addresses = target(&url, true).await.unwrap();
+        let (status, body) = send(&webhook, &delivery, addresses).await.unwrap();
+
+        assert_eq!(status, 200);
+        assert_eq!(body, "received");
+
+        let received = received.lock().unwrap().clone();
+
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event, events::USER_CREATED);
+        assert_eq!(received[0].delivery, delivery.id.to_string());
+        assert_eq!(received[0].body, delivery.payload);
+
+        let (timestamp, signature) = received[0].signature
+            .strip_prefix("t=")
+            .and_then(|signature| signature.split_once(",v1="))
+            .unwrap();
+
+        assert_eq!(signature, sign(&webhook.secret, timestamp.parse().unwrap(), &received[0].body).unwrap());
+    }
+
+    #[actix_web::test]
+    async fn send_returns_the_receiver_status() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (url, _) = sink(503);
+        let webhook = webhook(&url);
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let addresses = target(&url, true).await.unwrap();
+
+        assert_eq!(send(&webhook, &delivery, addresses).await.unwrap().0, 503);
+    }
+
+    #[actix_web::test]
+    async fn accepted_attempts_are_delivered() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let webhook = webhook("https://hooks.example.com");
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let delivered = record(&db, &webhook, &delivery, Ok((204, String::new()))).await.unwrap();
+
+        assert_eq!(delivered.status, dao::webhook::DELIVERED);
+        assert_eq!(delivered.attempts, 1);
+        assert_eq!(delivered.response_status, Some(204));
+        assert!(delivered.delivered_at.is_some());
+        assert!(delivered.next_attempt_at.is_none());
+    }
+
+    #[actix_web::test]
+    async fn failed_attempts_are_retried_with_backoff() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let webhook = webhook("https://hooks.example.com");
+        let delivery = delivery(&db, &webhook, 2).await;
+        let before = time::now();
+
+        let failed = record(&db, &webhook, &delivery, Ok((500, "oops".to_string()))).await.unwrap();
+        let next_attempt_at = failed.next_attempt_at.unwrap();
+
+        assert_eq!(failed.status, dao::webhook::PENDING);
+        assert_eq!(failed.attempts, 3);
+        assert_eq!(failed.response_status, Some(500));
+        assert_eq!(failed.last_error.as_deref(), Some("receiver responded with status 500"));
+        assert!(next_attempt_at >= before + backoff(3));
+        assert!(next_attempt_at <= time::now() + backoff(3));
+    }
+
+    #[actix_web::test]
+    async fn deliveries_are_dead_after_the_last_attempt() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let webhook = webhook("https://hooks.example.com");
+        let delivery = delivery(&db, &webhook, MAX_ATTEMPTS - 1).await;
+
+        let dead = record(&db, &webhook, &delivery, Err("connection refused".to_string())).await.unwrap();
+
+        assert_eq!(dead.status, dao::webhook::DEAD);
+        assert_eq!(dead.attempts, MAX_ATTEMPTS);
+        assert_eq!(dead.last_error.as_deref(), Some("connection refused"));
+        assert!(dead.next_attempt_at.is_none());
+    }
+
+    #[actix_web::test]
+    async fn disabled_webhooks_kill_their_deliveries() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (url, received) = sink(200);
+        let webhook = webhooks::Model { enabled: false, ..webhook(&url) };
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let dead = deliver(&db, &webhook, &delivery, true).await.unwrap();
+
+        assert_eq!(dead.status, dao::webhook::DEAD);
+        assert_eq!(dead.last_error.as_deref(), Some("webhook is disabled"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn deliver_refuses_private_targets() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (url, received) = sink(200);
+        let webhook = webhook(&url);
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let failed = deliver(&db, &webhook, &delivery, false).await.unwrap();
+
+        assert_eq!(failed.status, dao::webhook::PENDING);
+        assert_eq!(failed.last_error.as_deref(), Some("url must not point to a private, loopback or link local address"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn target_refuses_internal_addresses() {
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://localhost:8082/hook",
+            "http://10.0.0.1/hook",
+            "http://172.16.0.1/hook",
+            "http://192.168.1.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://[::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert_eq!(target(url, false).await, Err("url must not point to a private, loopback or link local address"), "{}", url);
+        }
+    }
+
+    #[actix_web::test]
+    async fn target_refuses_other_schemes() {
+        for url in ["ftp://93.184.216.34/hook", "93.184.216.34/hook", "file:///etc/passwd", ""] {
+            assert_eq!(target(url, false).await, Err("url must be an http or https url"), "{}", url);
+        }
+    }
+
+    #[actix_web::test]
+    async fn target_accepts_public_addresses() {
+        assert_eq!(target("https://93.184.216.34/hook", false).await, Ok(vec!["93.184.216.34:443".parse().unwrap()]));
+        assert_eq!(target("http://[2606:2800:220:1:248:1893:25c8:1946]:8443/hook", false).await, Ok(vec!["[2606:2800:220:1:248:1893:25c8:1946]:8443".parse().unwrap()]));
+    }
+
+    #[actix_web::test]
+    async fn target_allows_internal_addresses_when_told_to() {
+        assert_eq!(target("http://127.0.0.1:8082/hook", true).await, Ok(vec!["127.0.0.1:8082".parse().unwrap()]));
+        assert_eq!(target("ftp://127.0.0.1/hook", true).await, Err("url must be an http or https url"));
+    }
+
+    #[actix_web::test]
+    async fn deliver_reaches_private_targets_when_allowed() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (url, received) = sink(200);
+        let webhook = webhook(&url);
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let delivered = deliver(&db, &webhook, &delivery, true).await.unwrap();
+
+        assert_eq!(delivered.status, dao::webhook::DELIVERED);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn send_connects_to_the_checked_addresses() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (url, received) = sink(200);
+        let addresses = target(&url, true).await.unwrap();
+        // the host never resolves, only the pinned address can be reached
+        let webhook = webhook(&url.replace("127.0.0.1", "hooks.invalid"));
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        assert_eq!(send(&webhook, &delivery, addresses).await.unwrap().0, 200);
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+
+    #[actix_web::test]
+    async fn redirects_are_failed_attempts() {
+        let db = testing::database(vec![testing::table(webhook_deliveries::Entity)]).await;
+        let (elsewhere, received) = sink(200);
+        let url = redirect(&elsewhere);
+        let webhook = webhook(&url);
+        let delivery = delivery(&db, &webhook, 0).await;
+
+        let failed = deliver(&db, &webhook, &delivery, true).await.unwrap();
+
+        assert_eq!(failed.status, dao::webhook::PENDING);
+        assert_eq!(failed.response_status, Some(302));
+        assert_eq!(failed.last_error.as_deref(), Some("receiver responded with status 302"));
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use openssl::hash::MessageDigest;
+use openssl::sign::Verifier;
+use openssl::x509::X509;
+use roxmltree::{Node, NodeType};
+use sha2::{Digest, Sha256};
+
+pub const NAMESPACE: &str = "http://www.w3.org/2000/09/xmldsig#";
+
+const EXCLUSIVE_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+
+pub fn child<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Option<Node<'a, 'input>> {
+    children(node, namespace, name).next()
+}
+
+pub fn children<'a: 'b, 'input: 'a, 'b>(
+    node: Node<'a, 'input>,
+    namespace: &'b str,
+    name: &'b str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'b {
+    node.children().filter(move |child| {
+        child.is_element()
+            && child.tag_name().namespace().eq(&Some(namespace))
+            && child.tag_name().name().eq(name)
+    })
+}
+
+fn decode(node: Option<Node>) -> Result<Vec<u8>, String> {
+    let value = node.and_then(|node| node.text())
+        .unwrap_or_default()
+        .split_whitespace()
+        .collect::<String>();
+
+    STANDARD.decode(value).map_err(|e| e.to_string())
+}
+
+/// Prefixes of an InclusiveNamespaces element, rendered like inclusive
+/// canonicalization would
+fn inclusive(method: Node) -> Vec<String> {
+    child(method, EXCLUSIVE_C14N, "InclusiveNamespaces")
+        .and_then(|namespaces| namespaces.attribute("PrefixList"))
+        .map(|prefixes| prefixes.split_whitespace()
+            .map(|prefix| match prefix {
+                "#default" => String::new(),
+                prefix => prefix.to_string(),
+            })
+            .collect())
+        .unwrap_or_default()
+}
+
+/// Check the enveloped signature of the element against the certificate.
+/// Only a signature that is a direct child and references the element by
+/// its id counts, so nothing else in the document is trusted by accident
+pub fn verify(element: Node, certificate: &X509) -> Result<(), String> {
+    let signature = child(element, NAMESPACE, "Signature").ok_or("element isn't signed")?;
+    let signed_info = child(signature, NAMESPACE, "SignedInfo").ok_or("signature has no signed info")?;
+    let method = child(signed_info, NAMESPACE, "CanonicalizationMethod")
+        .filter(|method| method.attribute("Algorithm").eq(&Some(EXCLUSIVE_C14N)))
+        .ok_or("signature must use exclusive canonicalization")?;
+
+    if child(signed_info, NAMESPACE, "SignatureMethod").and_then(|method| method.attribute("Algorithm")).ne(&Some(RSA_SHA256)) {
+        return Err("signature must use rsa-sha256".to_string())
+    }
+
+    let references = children(signed_info, NAMESPACE, "Reference").collect::<Vec<Node>>();
+    let reference = match references.as_slice() {
+        [reference] => *reference,
+        _ => return Err("signature must have exactly one reference".to_string()),
+    };
+
+    let id = element.attribute("ID").ok_or("signed element has no id")?;
+
+    if reference.attribute("URI").ne(&Some(format!("#{}", id).as_str())) {
+        return Err("signature doesn't reference the signed element".to_string())
+    }
+
+    // canonical form leaves comments out, text split by one would read
+    // differently than what was signed
+    if element.descendants().any(|node| node.is_comment()) {
+        return Err("signed element must not contain comments".to_string())
+    }
+
+    // a duplicated id would let the reference resolve to another element
+    if element.document().descendants().filter(|node| node.attribute("ID").eq(&Some(id))).count() != 1 {
+        return Err("signed element id isn't unique".to_string())
+    }
+
+    let mut enveloped = false;
+    let mut prefixes = vec![];
+
+    if let Some(transforms) = child(reference, NAMESPACE, "Transforms") {
+        for transform in children(transforms, NAMESPACE, "Transform") {
+            match transform.attribute("Algorithm") {
+                Some(ENVELOPED_SIGNATURE) => enveloped = true,
+                Some(EXCLUSIVE_C14N) => prefixes = inclusive(transform),
+                algorithm => return Err(format!("unsupported transform {}", algorithm.unwrap_or_default())),
+            }
+        }
+    }
+
+    if !enveloped {
+        return Err("signature must be enveloped".to_string())
+    }
+
+    if child(reference, NAMESPACE, "DigestMethod").and_then(|method| method.attribute("Algorithm")).ne(&Some(SHA256)) {
+        return Err("digest must use sha256".to_string())
+    }
+
+    let digest = Sha256::digest(canonicalize(element, Some(signature), &prefixes).as_bytes());
+
+    if digest.as_slice().ne(decode(child(reference, NAMESPACE, "DigestValue"))?.as_slice()) {
+        return Err("digest doesn't match the signed element".to_string())
+    }
+
+    let value = decode(child(signature, NAMESPACE, "SignatureValue"))?;
+    let key = certificate.public_key().map_err(|e| e.to_string())?;
+    let mut verifier = Verifier::new(MessageDigest::sha256(), &key).map_err(|e| e.to_string())?;
+
+    verifier.update(canonicalize(signed_info, None, &inclusive(method)).as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    match verifier.verify(&value) {
+        Ok(true) => Ok(()),
+        _ => Err("signature doesn't match the certificate".to_string()),
+    }
+}
+
+/// Exclusive xml canonicalization without comments of the element and its
+/// descendants, leaving out the excluded subtree
+pub fn canonicalize(element: Node, exclude: Option<Node>, inclusive: &[String]) -> String {
+    let mut output = String::new();
+
+    write(&mut output, element, exclude, inclusive, &[]);
+
+    output
+}
+
+/// Qualified name as written in the source, roxmltree only keeps the
+/// resolved namespace
+fn qname<'input>(input: &'input str, position: usize) -> &'input str {
+    let rest = &input[position..];
+    let end = rest.find(|c: char| c.is_whitespace() || c == '=' || c == '/' || c == '>')
+        .unwrap_or(rest.len());
+
+    &rest[..end]
+}
+
+fn prefix(qname: &str) -> &str {
+    qname.split_once(':').map(|(prefix, _)| prefix).unwrap_or_default()
+}
+
+fn escape_text(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\r', "&#xD;")
+}
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+        .replace('\t', "&#x9;")
+        .replace('\n', "&#xA;")
+        .replace('\r', "&#xD;")
+}
+
+fn write(
+    output: &mut String,
+    node: Node,
+    exclude: Option<Node>,
+    inclusive: &[String],
+    rendered: &[(String, String)],
+) {
+    if exclude.is_some_and(|exclude| exclude.eq(&node)) {
+        return
+    }
+
+    match node.node_type() {
+        NodeType::Text => output.push_str(&escape_text(node.text().unwrap_or_default())),
+        NodeType::PI => if let Some(pi) = node.pi() {
+            output.push_str(&format!("<?{}", pi.target));
+
+            if let Some(value) = pi.value {
+                output.push_str(&format!(" {}", value));
+            }
+
+            output.push_str("?>");
+        },
+        NodeType::Element => {
+            let input = node.document().input_text();
+            let name = qname(input, node.range().start + 1);
+            let mut attributes = node.attributes()
+                .map(|attribute| (qname(input, attribute.position()), attribute))
+                .collect::<Vec<_>>();
+
+            attributes.sort_by_key(|(_, attribute)| (attribute.namespace().unwrap_or_default(), attribute.name()));
+
+            // namespaces are only rendered where they're visibly utilized
+            // or listed as inclusive, and not yet in scope of the output
+            let mut utilized = vec![prefix(name).to_string()];
+
+            utilized.extend(attributes.iter()
+                .filter(|(_, attribute)| attribute.namespace().is_some())
+                .map(|(qname, _)| prefix(qname).to_string()));
+            utilized.extend(inclusive.iter().cloned());
+            utilized.sort();
+            utilized.dedup();
+
+            let mut declarations = vec![];
+
+            for prefix in utilized.into_iter().filter(|prefix| !prefix.eq("xml")) {
+                let uri = node.lookup_namespace_uri(Some(prefix.as_str()).filter(|prefix| !prefix.is_empty()))
+                    .unwrap_or_default();
+                let current = rendered.iter()
+                    .rev()
+                    .find(|(rendered, _)| rendered.eq(&prefix))
+                    .map(|(_, uri)| uri.as_str())
+                    .unwrap_or_default();
+
+                if !uri.eq(current) && (prefix.is_empty() || !uri.is_empty()) {
+                    declarations.push((prefix, uri.to_string()));
+                }
+            }
+
+            output.push('<');
+            output.push_str(name);
+
+            for (prefix, uri) in &declarations {
+                match prefix.as_str() {
+                    "" => output.push_str(&format!(" xmlns=\"{}\"", escape_attribute(uri))),
+                    prefix => output.push_str(&format!(" xmlns:{}=\"{}\"", prefix, escape_attribute(uri))),
+                }
+            }
+
+            for (qname, attribute) in &attributes {
+                output.push_str(&format!(" {}=\"{}\"", qname, escape_attribute(attribute.value())));
+            }
+
+            output.push('>');
+
+            let mut scope = rendered.to_vec();
+
+            scope.extend(declarations);
+
+            for child in node.children() {
+                write(output, child, exclude, inclusive, &scope);
+            }
+
+            output.push_str(&format!("</{}>", name));
+        },
+        // canonicalization without comments, verify refuses signed
+        // elements that carry any
+        NodeType::Comment => {},
+        NodeType::Root => {},
+    }
+}
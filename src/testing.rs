@@ -0,0 +1,40 @@
+use nightmare_common::hash;
+use nightmare_common::models::users;
+use nightmare_common::time;
+use sea_orm::{ConnectionTrait, Database, DatabaseConnection, DbBackend, EntityTrait, Schema};
+use sea_orm::sea_query::TableCreateStatement;
+use uuid::Uuid;
+
+/// Create table statement of the entity for sqlite
+pub fn table<E: EntityTrait>(entity: E) -> TableCreateStatement {
+    Schema::new(DbBackend::Sqlite).create_table_from_entity(entity)
+}
+
+/// Fresh in memory database with the given tables
+pub async fn database(tables: Vec<TableCreateStatement>) -> DatabaseConnection {
+    let db = Database::connect("sqlite::memory:").await.expect("failed to open sqlite");
+
+    for table in tables {
+        db.execute(db.get_database_backend().build(&table)).await.expect("failed to create table");
+    }
+
+    db
+}
+
+/// User with the given username whose password is `password`
+pub fn user(username: &str, password: &str) -> users::Model {
+    let id = Uuid::new_v4();
+
+    users::Model {
+        id: id.into(),
+        name: username.to_string(),
+        email: format!("{}@nightmare.local", username),
+        username: username.to_string(),
+        email_verified_at: None,
+        password: hash::make(id, password.to_string()).to_string(),
+        profile_photo_id: None,
+        created_at: time::now(),
+        updated_at: time::now(),
+        deleted_at: None,
+    }
+}